@@ -0,0 +1,104 @@
+//! Serde `with` helpers for (de)serializing Rust integer fields from/to a specific TTLV type.
+//!
+//! By default the Rust type of a field determines the TTLV type that it is serialized as, e.g. an `i64` serializes to a
+//! TTLV Long Integer. Some KMIP fields are however best represented in Rust by a type that would otherwise serialize
+//! as a different TTLV type, e.g. a Date-Time as an `i64` number of seconds since the epoch or an Interval as an `i32`.
+//! Annotating such a field with `#[serde(with = "kmip_ttlv::as_type::date_time")]`,
+//! `#[serde(with = "kmip_ttlv::as_type::enumeration")]` or `#[serde(with = "kmip_ttlv::as_type::interval")]`
+//! (de)serializes an integer of any width from/to that TTLV type instead:
+//!
+//! ```ignore
+//! #[derive(Deserialize, Serialize)]
+//! #[serde(rename = "Transparent:0x420001")]
+//! struct ActivationDate(#[serde(with = "kmip_ttlv::as_type::date_time")] i64);
+//! ```
+//!
+//! Serialization fails if the value is out of range for the TTLV type, e.g. a negative value for an Enumeration, and
+//! deserialization fails if the value is out of range for the Rust type. To do the same for every field of a newtype
+//! struct rename the struct instead, e.g. `#[serde(rename = "Transparent(DateTime):0x420001")]`.
+
+use std::convert::TryFrom;
+
+use serde::{de::Unexpected, Deserialize, Deserializer, Serialize, Serializer};
+
+/// (De)serialize an integer from/to a TTLV Date-Time.
+pub mod date_time {
+    use super::*;
+
+    use crate::types::TTLV_DATE_TIME_NAME;
+
+    /// Serialize an integer as a TTLV Date-Time.
+    pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TTLV_DATE_TIME_NAME, v)
+    }
+
+    /// Deserialize a TTLV Date-Time into an integer.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<i64>,
+        D: Deserializer<'de>,
+    {
+        let v = i64::deserialize(deserializer)?;
+        T::try_from(v).map_err(|_| serde::de::Error::invalid_value(Unexpected::Signed(v), &"a smaller TTLV Date-Time"))
+    }
+}
+
+/// (De)serialize an integer from/to a TTLV Enumeration.
+pub mod enumeration {
+    use super::*;
+
+    use crate::types::TTLV_ENUMERATION_NAME;
+
+    /// Serialize an integer as a TTLV Enumeration.
+    pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TTLV_ENUMERATION_NAME, v)
+    }
+
+    /// Deserialize a TTLV Enumeration into an integer.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<u32>,
+        D: Deserializer<'de>,
+    {
+        let v = u32::deserialize(deserializer)?;
+        T::try_from(v)
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Unsigned(v.into()), &"a smaller TTLV Enumeration"))
+    }
+}
+
+/// (De)serialize an integer from/to a TTLV Interval.
+pub mod interval {
+    use super::*;
+
+    use crate::types::{TtlvInterval, TTLV_INTERVAL_NAME};
+
+    /// Serialize an integer as a TTLV Interval.
+    pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TTLV_INTERVAL_NAME, v)
+    }
+
+    /// Deserialize a TTLV Interval into an integer.
+    ///
+    /// Unlike deserializing into a plain `u32` this rejects other TTLV types, e.g. Enumeration.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<u32>,
+        D: Deserializer<'de>,
+    {
+        let v = *TtlvInterval::deserialize(deserializer)?;
+        T::try_from(v)
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Unsigned(v.into()), &"a smaller TTLV Interval"))
+    }
+}
@@ -0,0 +1,74 @@
+//! Serde `with` helpers for (de)serializing TTLV Big Integer values from/to `num_bigint::BigInt` and
+//! `num_bigint::BigUint` fields.
+//!
+//! As with other primitive types, serializing requires the field to be wrapped in a `Transparent:0xNNNNNN` newtype
+//! struct in order to associate a TTLV tag with it:
+//!
+//! ```ignore
+//! #[derive(Deserialize, Serialize)]
+//! #[serde(rename = "Transparent:0x420052")]
+//! struct Modulus(#[serde(with = "kmip_ttlv::bigint")] BigUint);
+//! ```
+//!
+//! Values are serialized in two's complement form, sign-extended to a multiple of eight bytes as required by the KMIP
+//! specification, see [TtlvBigInteger].
+
+use std::convert::TryFrom;
+
+use serde::{
+    de::{Unexpected, Visitor},
+    Deserializer, Serialize, Serializer,
+};
+
+use crate::types::TtlvBigInteger;
+
+/// Serialize a `num_bigint::BigInt` or `num_bigint::BigUint` as a TTLV Big Integer.
+pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Clone,
+    TtlvBigInteger: From<T>,
+    S: Serializer,
+{
+    TtlvBigInteger::from(v.clone()).serialize(serializer)
+}
+
+/// Deserialize a TTLV Big Integer into a `num_bigint::BigInt` or `num_bigint::BigUint`.
+///
+/// Deserializing into a `BigUint` fails with a [crate::error::SerdeError] if the value is negative.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: TryFrom<TtlvBigInteger>,
+    D: Deserializer<'de>,
+{
+    struct BigIntegerVisitor;
+
+    impl<'de> Visitor<'de> for BigIntegerVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a TTLV Big Integer")
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
+    }
+
+    let bytes = deserializer.deserialize_byte_buf(BigIntegerVisitor)?;
+    T::try_from(TtlvBigInteger(bytes)).map_err(|_| {
+        serde::de::Error::invalid_value(
+            Unexpected::Other("negative Big Integer"),
+            &"a non-negative TTLV Big Integer",
+        )
+    })
+}
@@ -0,0 +1,252 @@
+//! A `tokio_util::codec::Decoder` and `Encoder` for framing TTLV messages sent and received over a byte stream.
+//!
+//! The length of a TTLV message is only known once the 8 byte header (3-byte tag, 1-byte type, 4-byte length) of the
+//! outermost TTLV Structure has been received. [TtlvCodec] waits for the header, then for the value bytes that it
+//! announces, and yields each complete message as a `BytesMut` frame. [TypedTtlvCodec] additionally deserializes each
+//! frame into a Rust type:
+//!
+//! ```ignore
+//! let mut responses = FramedRead::new(tls_stream, TypedTtlvCodec::<ResponseMessage>::new(&Config::default()));
+//! while let Some(response) = responses.next().await {
+//!     let response = response?;
+//!     ...
+//! }
+//! ```
+//!
+//! The header is validated as it arrives, so a stream that does not start with a TTLV Structure is rejected without
+//! waiting for the (potentially gigantic) number of bytes its header appears to announce. As with [from_reader()] the
+//! [Config::max_bytes()] setting, if set, limits the size of a message that will be buffered. If not set messages
+//! larger than 8 MiB are rejected. Buffer space is reserved in step with the bytes that actually arrive, so a header
+//! that announces a large message does not by itself cause a large allocation.
+//!
+//! Both codecs can also serialize any Rust type that implements `Serialize` into an outgoing message, so that a single
+//! `Framed` transport can be used to both send requests and receive responses:
+//!
+//! ```ignore
+//! let mut transport = Framed::new(tls_stream, TypedTtlvCodec::<ResponseMessage>::new(&Config::default()));
+//! transport.send(&request).await?;
+//! let response = transport.next().await;
+//! ```
+//!
+//! Outgoing messages are serialized using the [ser::Config] given to [TtlvCodec::with_serializer_config()], if any.
+//!
+//! [from_reader()]: crate::de::from_reader
+use std::{io::Cursor, marker::PhantomData};
+
+use bytes::BytesMut;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{
+    de::{from_slice_with_config, Config, TtlvDeserializer},
+    error::{Error, ErrorKind, ErrorLocation, MalformedTtlvError, Result},
+    ser::{self, to_bytes_mut_with_config},
+    types::{TtlvStateMachine, TtlvStateMachineMode, TtlvType},
+};
+
+/// The number of bytes in the TTL header of a TTLV item.
+const HEADER_LEN: usize = 8;
+
+/// The maximum size of a message that will be buffered if [Config::max_bytes()] is not set.
+const DEFAULT_MAX_FRAME_LEN: u64 = 8 * 1024 * 1024;
+
+/// The maximum number of bytes to reserve space for in a single call to `decode()`.
+///
+/// The length announced by a header has not yet been backed up by any actual bytes so reserving space for all of it at
+/// once would let a peer cause large allocations cheaply.
+const MAX_RESERVE_LEN: usize = 64 * 1024;
+
+/// Splits a stream of bytes into complete TTLV messages.
+#[derive(Clone, Debug, Default)]
+pub struct TtlvCodec {
+    config: Config,
+    serializer_config: ser::Config,
+}
+
+impl TtlvCodec {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            serializer_config: ser::Config::default(),
+        }
+    }
+
+    /// Serialize outgoing messages using the given configuration.
+    pub fn with_serializer_config(self, serializer_config: &ser::Config) -> Self {
+        Self {
+            serializer_config: serializer_config.clone(),
+            ..self
+        }
+    }
+
+    /// The configuration used to frame and deserialize messages.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The configuration used to serialize messages.
+    pub fn serializer_config(&self) -> &ser::Config {
+        &self.serializer_config
+    }
+
+    /// Determine the length of the TTLV message at the start of the given bytes, if the header is complete.
+    fn frame_len(&self, src: &[u8]) -> Result<Option<usize>> {
+        if src.len() < HEADER_LEN {
+            return Ok(None);
+        }
+
+        let mut state = TtlvStateMachine::new(TtlvStateMachineMode::Deserializing);
+        let mut cursor = Cursor::new(&src[..HEADER_LEN]);
+
+        let pos = cursor.position();
+        let tag = TtlvDeserializer::read_tag(&mut cursor, Some(&mut state)).map_err(|err| pinpoint!(err, pos))?;
+
+        let pos = cursor.position();
+        let r#type =
+            TtlvDeserializer::read_type(&mut cursor, Some(&mut state)).map_err(|err| pinpoint!(err, pos, tag))?;
+
+        if r#type != TtlvType::Structure {
+            let error = MalformedTtlvError::UnexpectedType {
+                expected: TtlvType::Structure,
+                actual: r#type,
+            };
+            return Err(pinpoint!(error, pos, tag));
+        }
+
+        let pos = cursor.position();
+        let value_len = TtlvDeserializer::read_length(&mut cursor, Some(&mut state))
+            .map_err(|err| pinpoint!(err, pos, tag, r#type))?;
+
+        let frame_len = (HEADER_LEN as u64) + (value_len as u64);
+        let max_frame_len = self.config.max_bytes().map_or(DEFAULT_MAX_FRAME_LEN, u64::from);
+        if frame_len > max_frame_len {
+            let error = ErrorKind::ResponseSizeExceedsLimit(frame_len as usize);
+            let location = ErrorLocation::from(cursor).with_tag(tag).with_type(r#type);
+            return Err(Error::pinpoint(error, location));
+        }
+
+        Ok(Some(frame_len as usize))
+    }
+}
+
+impl Decoder for TtlvCodec {
+    type Item = BytesMut;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        match self.frame_len(src)? {
+            None => {
+                src.reserve(HEADER_LEN - src.len());
+                Ok(None)
+            }
+            Some(frame_len) if frame_len > src.len() => {
+                src.reserve((frame_len - src.len()).min(MAX_RESERVE_LEN));
+                Ok(None)
+            }
+            Some(frame_len) => Ok(Some(src.split_to(frame_len))),
+        }
+    }
+}
+
+impl<T> Encoder<T> for TtlvCodec
+where
+    T: Serialize,
+{
+    type Error = Error;
+
+    /// Serialize the given value and append it to the buffer. Nothing is appended if serialization fails.
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
+        let len = dst.len();
+        let res = to_bytes_mut_with_config(&item, dst, &self.serializer_config);
+        if res.is_err() {
+            dst.truncate(len);
+        }
+        res
+    }
+}
+
+/// Splits a stream of bytes into complete TTLV messages and deserializes each of them into a `T`.
+#[derive(Debug)]
+pub struct TypedTtlvCodec<T> {
+    inner: TtlvCodec,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedTtlvCodec<T> {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            inner: TtlvCodec::new(config),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Serialize outgoing messages using the given configuration.
+    pub fn with_serializer_config(self, serializer_config: &ser::Config) -> Self {
+        Self {
+            inner: self.inner.with_serializer_config(serializer_config),
+            phantom: PhantomData,
+        }
+    }
+
+    /// The configuration used to frame and deserialize messages.
+    pub fn config(&self) -> &Config {
+        self.inner.config()
+    }
+
+    /// The configuration used to serialize messages.
+    pub fn serializer_config(&self) -> &ser::Config {
+        self.inner.serializer_config()
+    }
+}
+
+impl<T> Clone for TypedTtlvCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for TypedTtlvCodec<T> {
+    fn default() -> Self {
+        Self::new(&Config::default())
+    }
+}
+
+impl<T> From<TtlvCodec> for TypedTtlvCodec<T> {
+    fn from(inner: TtlvCodec) -> Self {
+        Self {
+            inner,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Decoder for TypedTtlvCodec<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>> {
+        match self.inner.decode(src)? {
+            Some(frame) => from_slice_with_config(&frame, self.inner.config()).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Serializes messages of any type, not only of type `T`, as the messages sent are typically of a different type, e.g.
+/// requests, than those received, e.g. responses.
+impl<T, U> Encoder<U> for TypedTtlvCodec<T>
+where
+    U: Serialize,
+{
+    type Error = Error;
+
+    fn encode(&mut self, item: U, dst: &mut BytesMut) -> Result<()> {
+        self.inner.encode(item, dst)
+    }
+}
@@ -0,0 +1,68 @@
+//! Serde `with` helpers for (de)serializing TTLV Date-Time values from/to `chrono::DateTime<Utc>` fields.
+//!
+//! As with other primitive types, serializing requires the field to be wrapped in a `Transparent:0xNNNNNN` newtype
+//! struct in order to associate a TTLV tag with it:
+//!
+//! ```ignore
+//! #[derive(Deserialize, Serialize)]
+//! #[serde(rename = "Transparent:0x420008")]
+//! struct ActivationDate(#[serde(with = "kmip_ttlv::datetime")] DateTime<Utc>);
+//! ```
+//!
+//! TTLV Date-Time values have a resolution of one second, any fractional second is discarded when serializing. To
+//! instead fail serialization if this would lose precision use `#[serde(with = "kmip_ttlv::datetime::strict")]`.
+
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Utc};
+use serde::{de::Unexpected, Deserialize, Deserializer, Serializer};
+
+use crate::types::TtlvDateTime;
+
+/// Serialize a `chrono::DateTime<Utc>` as a TTLV Date-Time.
+pub fn serialize<S>(v: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    // The TTLV serializer writes a u64 as a TTLV Date-Time, see ser::TtlvSerializer::serialize_u64().
+    serializer.serialize_u64(TtlvDateTime::from(*v).0 as u64)
+}
+
+/// Deserialize a TTLV Date-Time into a `chrono::DateTime<Utc>`.
+///
+/// Fails with a [crate::error::SerdeError] if the value is outside the range supported by `chrono`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let v = i64::deserialize(deserializer)?;
+    DateTime::<Utc>::try_from(TtlvDateTime(v)).map_err(|_| {
+        serde::de::Error::invalid_value(
+            Unexpected::Signed(v),
+            &"a TTLV Date-Time in the range supported by chrono",
+        )
+    })
+}
+
+/// Like the parent module but serialization fails instead of discarding a fractional second.
+pub mod strict {
+    use super::*;
+
+    /// Serialize a `chrono::DateTime<Utc>` as a TTLV Date-Time.
+    ///
+    /// Fails with a [crate::error::SerdeError] if the value has a fractional second as TTLV Date-Time values have a
+    /// resolution of one second.
+    pub fn serialize<S>(v: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if v.timestamp_subsec_nanos() != 0 {
+            return Err(serde::ser::Error::custom(
+                "Date-Time has a fractional second which cannot be represented in TTLV",
+            ));
+        }
+        super::serialize(v, serializer)
+    }
+
+    pub use super::deserialize;
+}
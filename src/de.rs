@@ -1,13 +1,17 @@
 //! High-level Serde based deserialization of TTLV bytes to Rust data types.
 
 use std::{
-    cell::{RefCell, RefMut},
+    borrow::Cow,
+    cell::{Ref, RefCell, RefMut},
     cmp::Ordering,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
     io::{Cursor, Read},
-    ops::Deref,
+    marker::PhantomData,
+    ops::Range,
     rc::Rc,
     str::FromStr,
+    sync::Arc,
 };
 
 use serde::{
@@ -15,7 +19,6 @@ use serde::{
     Deserialize, Deserializer,
 };
 
-use crate::traits::AnySyncRead;
 use crate::{
     error::Error,
     error::{ErrorKind, ErrorLocation, MalformedTtlvError, Result, SerdeError},
@@ -23,210 +26,1606 @@ use crate::{
         self, FieldType, SerializableTtlvType, TtlvBoolean, TtlvDateTime, TtlvEnumeration, TtlvInteger, TtlvLength,
         TtlvLongInteger, TtlvStateMachine, TtlvStateMachineMode, TtlvTextString,
     },
-    types::{TtlvBigInteger, TtlvByteString, TtlvTag, TtlvType},
+    types::{
+        ByteOffset, Bytes, BytesRef, Event, TtlvBigInteger, TtlvByteString, TtlvInterval, TtlvItem, TtlvNode, TtlvTag,
+        TtlvTokenizer, TtlvType, TtlvValue, TTLV_INTERVAL_NAME,
+    },
 };
 
+#[cfg(any(
+    feature = "async-with-async-std",
+    feature = "async-with-tokio",
+    feature = "async-with-futures"
+))]
+pub mod asynch;
+#[cfg(feature = "sync")]
+pub mod sync;
+
 // --- Public interface ------------------------------------------------------------------------------------------------
 
-/// Configuration settings used by the deserializer.
-///
-/// May in future also be used by the serializer.
-#[derive(Debug, Default)]
-pub struct Config {
-    max_bytes: Option<u32>,
-    read_buf: Option<RefCell<Vec<u8>>>,
-}
+#[cfg(feature = "sync")]
+#[doc(inline)]
+pub use sync::{from_reader, from_reader_iter, TtlvReaderIter};
+
+/// A function that returns the human readable name, if known, of a TTLV tag.
+///
+/// See [Config::with_tag_name_resolver()].
+pub type TagNameResolver = fn(TtlvTag) -> Option<&'static str>;
+
+/// A function that returns the tag, if known, for the name of a Rust type or field.
+///
+/// See [Config::with_tag_provider()] and [crate::ser::Config::with_tag_provider()].
+pub type TagProvider = fn(&str) -> Option<TtlvTag>;
+
+/// A function that is shown the header of every TTLV item as it is deserialized.
+///
+/// Returning an error rejects the input. See [Config::with_item_observer()].
+pub type ItemObserver = Arc<dyn Fn(&ItemHeader) -> std::result::Result<(), String> + Send + Sync>;
+
+/// The tag, type and length of a TTLV item and where in the (de)serialized bytes it starts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ItemHeader {
+    offset: ByteOffset,
+    tag: TtlvTag,
+    r#type: TtlvType,
+    length: u32,
+}
+
+impl ItemHeader {
+    pub(crate) fn new(offset: ByteOffset, tag: TtlvTag, r#type: TtlvType, length: u32) -> Self {
+        Self {
+            offset,
+            tag,
+            r#type,
+            length,
+        }
+    }
+
+    /// The offset of the first byte of the TTLV item.
+    pub fn offset(&self) -> ByteOffset {
+        self.offset
+    }
+
+    pub fn tag(&self) -> TtlvTag {
+        self.tag
+    }
+
+    pub fn r#type(&self) -> TtlvType {
+        self.r#type
+    }
+
+    /// The length of the TTLV item value in bytes, excluding any padding.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+}
+
+/// A function that selects the enum variant to deserialize into, if it can, given the items seen so far.
+///
+/// See [Config::with_variant_selector()].
+pub type VariantSelector = Arc<dyn Fn(&MatcherContext<'_>) -> Option<&'static str> + Send + Sync>;
+
+/// What a [VariantSelector] knows about the enum being deserialized and the TTLV items that preceded it.
+pub struct MatcherContext<'a> {
+    enum_name: &'static str,
+    variants: &'static [&'static str],
+    item_tag: Option<TtlvTag>,
+    item_type: Option<TtlvType>,
+    tag_values: Ref<'a, HashMap<TtlvTag, String>>,
+    seen_tags: Ref<'a, HashSet<TtlvTag>>,
+    tag_path_values: Ref<'a, TagPathValueStore>,
+}
+
+impl<'a> MatcherContext<'a> {
+    /// The name of the Rust enum, or its `#[serde(rename = "...")]`.
+    pub fn enum_name(&self) -> &'static str {
+        self.enum_name
+    }
+
+    /// The names, or renames, of the variants of the Rust enum.
+    pub fn variants(&self) -> &'static [&'static str] {
+        self.variants
+    }
+
+    /// The tag of the TTLV item to be deserialized into the enum, if known.
+    pub fn item_tag(&self) -> Option<TtlvTag> {
+        self.item_tag
+    }
+
+    /// The type of the TTLV item to be deserialized into the enum, if known.
+    pub fn item_type(&self) -> Option<TtlvType> {
+        self.item_type
+    }
+
+    /// The last seen value of a TTLV Enumeration, Integer or Text String with the given tag.
+    ///
+    /// Enumeration and Integer values are given in the same `0xMMMMMMMM` form as used by the `if` matcher syntax.
+    pub fn tag_value(&self, tag: TtlvTag) -> Option<&str> {
+        self.tag_values.get(&tag).map(String::as_str)
+    }
+
+    /// Like [Self::tag_value()] but for the last seen item whose tag, and the tags of the structures enclosing it,
+    /// end with the given path of tags.
+    pub fn path_value(&self, path: &[TtlvTag]) -> Option<&str> {
+        self.tag_path_values.find(path, false)
+    }
+
+    /// Was a TTLV item, of any type, with the given tag seen?
+    pub fn is_tag_seen(&self, tag: TtlvTag) -> bool {
+        self.seen_tags.contains(&tag)
+    }
+}
+
+impl<'a> std::fmt::Debug for MatcherContext<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MatcherContext")
+            .field("enum_name", &self.enum_name)
+            .field("variants", &self.variants)
+            .field("item_tag", &self.item_tag)
+            .field("item_type", &self.item_type)
+            .finish()
+    }
+}
+
+/// What to do when a TTLV Structure contains more than one item with the tag of a Rust struct field.
+///
+/// See [Config::with_duplicate_tag_policy()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateTagPolicy {
+    /// Fail with [SerdeError::DuplicateTag] located at the second item with the tag. This is the default.
+    Error,
+
+    /// Deserialize the first item with the tag into the field and skip the others.
+    FirstWins,
+
+    /// Deserialize the last item with the tag into the field and skip the others.
+    LastWins,
+}
+
+// Not derived as #[default] on enum variants requires Rust 1.62.
+#[allow(clippy::derivable_impls)]
+impl Default for DuplicateTagPolicy {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// The maximum depth to which TTLV Structures may be nested when deserializing into Rust types, whatever the
+/// configured limit.
+///
+/// Serde deserializes nested data by recursing, one level per nested TTLV Structure, so without a limit deeply nested
+/// input could exhaust the call stack. KMIP messages are nowhere near this deeply nested.
+const MAX_NESTING_DEPTH: usize = 128;
+
+/// Configuration settings used by the deserializer.
+///
+/// See [crate::ser::Config] for the settings used by the serializer.
+#[derive(Default)]
+pub struct Config {
+    duplicate_tag_policy: DuplicateTagPolicy,
+    lossy_text_strings: bool,
+    max_big_integer_len: Option<u32>,
+    max_bytes: Option<u32>,
+    max_nesting_depth: Option<usize>,
+    opaque_extension_types: bool,
+    read_buf: Option<RefCell<Vec<u8>>>,
+    read_chunk_size: Option<usize>,
+    reject_trailing_bytes: bool,
+    item_observer: Option<ItemObserver>,
+    tag_name_resolver: Option<TagNameResolver>,
+    tag_provider: Option<TagProvider>,
+    tuple_structs: bool,
+    variant_selector: Option<VariantSelector>,
+    verify_padding: bool,
+}
+
+impl Clone for Config {
+    fn clone(&self) -> Self {
+        Self {
+            duplicate_tag_policy: self.duplicate_tag_policy,
+            lossy_text_strings: self.lossy_text_strings,
+            max_big_integer_len: self.max_big_integer_len,
+            max_bytes: self.max_bytes,
+            max_nesting_depth: self.max_nesting_depth,
+            opaque_extension_types: self.opaque_extension_types,
+            read_buf: if self.has_buf() {
+                Some(RefCell::new(Vec::new()))
+            } else {
+                None
+            },
+            read_chunk_size: self.read_chunk_size,
+            reject_trailing_bytes: self.reject_trailing_bytes,
+            item_observer: self.item_observer.clone(),
+            tag_name_resolver: self.tag_name_resolver,
+            tag_provider: self.tag_provider,
+            tuple_structs: self.tuple_structs,
+            variant_selector: self.variant_selector.clone(),
+            verify_padding: self.verify_padding,
+        }
+    }
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The configured functions cannot be shown, only whether or not they are set
+        f.debug_struct("Config")
+            .field("duplicate_tag_policy", &self.duplicate_tag_policy)
+            .field("lossy_text_strings", &self.lossy_text_strings)
+            .field("max_big_integer_len", &self.max_big_integer_len)
+            .field("max_bytes", &self.max_bytes)
+            .field("max_nesting_depth", &self.max_nesting_depth)
+            .field("opaque_extension_types", &self.opaque_extension_types)
+            .field("read_buf", &self.read_buf)
+            .field("read_chunk_size", &self.read_chunk_size)
+            .field("reject_trailing_bytes", &self.reject_trailing_bytes)
+            .field("item_observer", &self.item_observer.is_some())
+            .field("tag_name_resolver", &self.tag_name_resolver)
+            .field("tag_provider", &self.tag_provider)
+            .field("tuple_structs", &self.tuple_structs)
+            .field("variant_selector", &self.variant_selector.is_some())
+            .field("verify_padding", &self.verify_padding)
+            .finish()
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Config {
+    /// What should happen when a TTLV Structure contains more than one item with the tag of a Rust struct field?
+    pub fn duplicate_tag_policy(&self) -> DuplicateTagPolicy {
+        self.duplicate_tag_policy
+    }
+
+    /// What, if any, is the configured function for observing the TTLV items being deserialized?
+    pub fn item_observer(&self) -> Option<ItemObserver> {
+        self.item_observer.clone()
+    }
+
+    /// Should Text Strings that are not valid UTF-8 be decoded lossily rather than rejected?
+    pub fn lossy_text_strings(&self) -> bool {
+        self.lossy_text_strings
+    }
+
+    /// What, if any, is the configured maximum permitted length in bytes of a TTLV Big Integer value?
+    pub fn max_big_integer_len(&self) -> Option<u32> {
+        self.max_big_integer_len
+    }
+
+    /// What, if any, is the configured maximum permitted response size?
+    pub fn max_bytes(&self) -> Option<u32> {
+        self.max_bytes
+    }
+
+    /// What, if any, is the configured maximum permitted depth of nested TTLV Structures?
+    pub fn max_nesting_depth(&self) -> Option<usize> {
+        self.max_nesting_depth
+    }
+
+    /// Should items with types not defined by the KMIP specification be passed through rather than matched to fields?
+    pub fn opaque_extension_types(&self) -> bool {
+        self.opaque_extension_types
+    }
+
+    /// Has a persistent read buffer been configured for reading response bytes into?
+    pub fn has_buf(&self) -> bool {
+        self.read_buf.is_some()
+    }
+
+    /// Get mutable access to optional persistent response bytes buffer
+    pub fn read_buf(&self) -> Option<RefMut<'_, Vec<u8>>> {
+        self.read_buf.as_ref().map(|buf| buf.borrow_mut())
+    }
+
+    /// What, if any, is the configured maximum number of bytes to request from a reader at once?
+    pub fn read_chunk_size(&self) -> Option<usize> {
+        self.read_chunk_size
+    }
+
+    /// Should bytes that remain after the root TTLV item has been deserialized be rejected?
+    pub fn reject_trailing_bytes(&self) -> bool {
+        self.reject_trailing_bytes
+    }
+
+    /// What, if any, is the configured function for looking up the human readable names of tags?
+    pub fn tag_name_resolver(&self) -> Option<TagNameResolver> {
+        self.tag_name_resolver
+    }
+
+    /// What, if any, is the configured function for looking up the tags of Rust type and field names?
+    pub fn tag_provider(&self) -> Option<TagProvider> {
+        self.tag_provider
+    }
+
+    /// May TTLV Structures be deserialized into Rust tuple structs?
+    pub fn tuple_structs(&self) -> bool {
+        self.tuple_structs
+    }
+
+    /// What, if any, is the configured function for selecting enum variants?
+    pub fn variant_selector(&self) -> Option<VariantSelector> {
+        self.variant_selector.clone()
+    }
+
+    /// Should non-zero padding bytes be rejected?
+    pub fn verify_padding(&self) -> bool {
+        self.verify_padding
+    }
+}
+
+// Builder style interface
+impl Config {
+    /// Specify what to do when a TTLV Structure contains more than one item with the tag of a Rust struct field.
+    ///
+    /// A field that is not a sequence, e.g. not a `Vec`, can only take the value of a single TTLV item. By default a
+    /// second item with the same tag fails deserialization with [SerdeError::DuplicateTag], as the input does not
+    /// conform to the expected structure. Use [DuplicateTagPolicy::FirstWins] or [DuplicateTagPolicy::LastWins] to
+    /// instead deserialize the first or the last of the items into the field. The other items are skipped and are
+    /// listed by [DeserializationReport::ignored_items()] when using [from_slice_with_report].
+    ///
+    /// The consecutive items with the same tag and type that make up a sequence field are not duplicates, but any
+    /// further items with that tag that follow an item with a different tag are. [DuplicateTagPolicy::LastWins] is
+    /// treated as [DuplicateTagPolicy::FirstWins] for such items as the sequence has already been deserialized.
+    pub fn with_duplicate_tag_policy(self, policy: DuplicateTagPolicy) -> Self {
+        Self {
+            duplicate_tag_policy: policy,
+            ..self
+        }
+    }
+
+    /// Specify a function to be shown the header of every TTLV item that is deserialized.
+    ///
+    /// The given function is invoked with the offset, tag, type and length of each TTLV item as it is encountered by
+    /// [from_slice_with_config] and the functions built on it, e.g. to collect metrics about or to log the items
+    /// received without having to parse the bytes a second time. The function can also enforce policies, e.g. that
+    /// certain tags must not occur or that text values must not exceed a certain length, by returning an error which
+    /// causes deserialization to fail with [ErrorKind::RejectedByObserver] located at the rejected item.
+    ///
+    /// The function may be a closure, e.g. one that collects the headers, and is shared by clones of this config.
+    pub fn with_item_observer<F>(self, observer: F) -> Self
+    where
+        F: Fn(&ItemHeader) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            item_observer: Some(Arc::new(observer)),
+            ..self
+        }
+    }
+
+    /// Decode TTLV Text Strings that are not valid UTF-8 by replacing invalid sequences with U+FFFD.
+    ///
+    /// The KMIP specification requires Text Strings to be UTF-8 encoded but some devices emit Latin-1 or otherwise
+    /// invalid UTF-8 in free text fields. By default such a value fails deserialization with
+    /// [types::Error::InvalidTtlvValue]. With this setting the value is instead decoded as if by
+    /// [String::from_utf8_lossy] and [validate] accepts it. Each affected item is listed by
+    /// [DeserializationReport::lossy_text_strings()] when using [from_slice_with_report].
+    ///
+    /// A lossily decoded value cannot be borrowed from the input and so cannot be deserialized into a `&str` field, use
+    /// `String` or `Cow<str>` instead.
+    pub fn with_lossy_text_strings(self) -> Self {
+        Self {
+            lossy_text_strings: true,
+            ..self
+        }
+    }
+
+    /// Specify a maximum length in bytes of TTLV Big Integer values.
+    ///
+    /// Space for a Big Integer value is allocated before it is read and so a large value length, e.g. in input from an
+    /// untrusted source, could cause a large allocation. Longer values are rejected with
+    /// [ErrorKind::ValueLengthExceedsLimit] by [validate], [from_slice_with_config] and [from_reader]. If not specified
+    /// the limit is [TtlvBigInteger::DEFAULT_MAX_LEN].
+    ///
+    /// Big Integers deserialized into Rust byte types, e.g. `Vec<u8>`, are exempt as they are copied only once the
+    /// value bytes are known to be present.
+    pub fn with_max_big_integer_len(self, max_big_integer_len: u32) -> Self {
+        Self {
+            max_big_integer_len: Some(max_big_integer_len),
+            ..self
+        }
+    }
+
+    /// Specify a maximum number of response bytes to read.
+    ///
+    /// Use this if you are reading data from an untrusted source. If that source then sends a very large response we
+    /// will reject it rather than attempt to read it all and thus avoid possibly running out of memory.
+    pub fn with_max_bytes(self, max_bytes: u32) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+            ..self
+        }
+    }
+
+    /// Specify a maximum depth to which TTLV Structures may be nested.
+    ///
+    /// A root TTLV Structure containing only primitive items has a nesting depth of 1. Use this if you are processing
+    /// data from an untrusted source to reject pathologically deep inputs. Enforced by [validate],
+    /// [from_slice_with_config] and [from_reader].
+    ///
+    /// When deserializing into Rust types a limit of 128 applies if no lower limit is configured, as deeper nesting
+    /// could otherwise overflow the stack. [validate] and [crate::PrettyPrinter] do not recurse and so are not limited
+    /// by default.
+    pub fn with_max_nesting_depth(self, max_nesting_depth: usize) -> Self {
+        Self {
+            max_nesting_depth: Some(max_nesting_depth),
+            ..self
+        }
+    }
+
+    /// Pass through TTLV items whose type is not defined by the KMIP specification, i.e. [TtlvType::Extension] items.
+    ///
+    /// By default such an item is skipped if its tag has no corresponding field in the Rust struct being deserialized
+    /// into, but fails deserialization with [SerdeError::UnexpectedType] if it does, e.g. because a newer version of
+    /// KMIP changed the type of a known tag. With this setting such items are never matched to fields by tag. Instead
+    /// they are collected, along with any items that follow them, by the catch-all field of the structure if it has
+    /// one (see [TtlvItem]) or are otherwise skipped. [validate] then also accepts them. This allows a middlebox to
+    /// forward messages from newer KMIP versions unchanged.
+    pub fn with_opaque_extension_types(self) -> Self {
+        Self {
+            opaque_extension_types: true,
+            ..self
+        }
+    }
+
+    /// Save the read response bytes into a buffer for use later.
+    ///
+    /// Allocate a persistent buffer that can be used by a reader to store the read response bytes into. This could be
+    /// to avoid allocating a buffer for every response read, or to permit logging or storing or pretty printing of the
+    /// response bytes once they have been read from the source.
+    pub fn with_read_buf(self) -> Self {
+        Self {
+            read_buf: Some(RefCell::new(Vec::new())),
+            ..self
+        }
+    }
+
+    /// Read from readers in chunks of at most the given number of bytes.
+    ///
+    /// By default [from_reader] reads the TTL header of the response and then the rest of the response in a single
+    /// read, allocating space for however many bytes the header announces before any of them have arrived. With this
+    /// setting the rest of the response is instead read a chunk at a time, growing the buffer as the bytes arrive.
+    ///
+    /// `TtlvReaderIter` additionally reads ahead a chunk at a time, keeping any bytes read beyond the end of one
+    /// response for the next. When reading many small responses from an unbuffered stream, e.g. a TLS connection,
+    /// this replaces the two reads per response otherwise needed with roughly one read per chunk. A single call to
+    /// [from_reader] cannot read ahead as any bytes read beyond the end of the response would be lost.
+    ///
+    /// A chunk size of zero is treated as a chunk size of one.
+    pub fn with_read_chunk_size(self, read_chunk_size: usize) -> Self {
+        Self {
+            read_chunk_size: Some(read_chunk_size.max(1)),
+            ..self
+        }
+    }
+
+    /// Reject input that continues after the end of the root TTLV item.
+    ///
+    /// By default any bytes following the root TTLV item are ignored. With this setting [from_slice_with_config]
+    /// instead fails with [MalformedTtlvError::TrailingBytes] located at the offset of the first extra byte. Use this
+    /// to detect framing bugs, e.g. a length prefix that disagrees with the amount of data actually sent.
+    pub fn with_reject_trailing_bytes(self) -> Self {
+        Self {
+            reject_trailing_bytes: true,
+            ..self
+        }
+    }
+
+    /// Specify a function for looking up the human readable name of a tag.
+    ///
+    /// When reporting the location of a deserialization error the name, if any, returned by the given function for the
+    /// tag of the item at which the error occurred, and for the tags of the structures containing it, is included
+    /// alongside the hexadecimal tag value, e.g. `tag: RequestMessage (0x420078)`. See [ErrorLocation::tag_name()].
+    pub fn with_tag_name_resolver(self, resolver: TagNameResolver) -> Self {
+        Self {
+            tag_name_resolver: Some(resolver),
+            ..self
+        }
+    }
+
+    /// Specify a function for looking up the tag of a Rust struct or struct field by name.
+    ///
+    /// By default the tag of a Rust struct, and of each of its fields, must be given in hexadecimal form by its name,
+    /// typically by annotating it with `#[serde(rename = "0xNNNNNN")]`. With this setting a name that is not a tag in
+    /// hexadecimal form is passed to the given function instead, so that the tags can be defined in one place, e.g. a
+    /// registry of KMIP tags keyed by Rust type or field name, rather than by attributes spread over many types. A name
+    /// for which the function returns `None` matches no tag. The serializer can be configured to use the same function,
+    /// see [crate::ser::Config::with_tag_provider()].
+    pub fn with_tag_provider(self, provider: TagProvider) -> Self {
+        Self {
+            tag_provider: Some(provider),
+            ..self
+        }
+    }
+
+    /// Permit TTLV Structures to be deserialized into Rust tuple structs.
+    ///
+    /// By default only Rust brace structs can be deserialized into, as they name each field after the tag it expects.
+    /// With this setting the items of a TTLV Structure are instead deserialized into the fields of a tuple struct in
+    /// the order that they occur. Only the tag of the structure itself is checked, taken from the
+    /// `#[serde(rename = "0xNNNNNN")]` attribute of the tuple struct. Any items beyond the number of tuple struct
+    /// fields are ignored.
+    pub fn with_tuple_structs(self) -> Self {
+        Self {
+            tuple_structs: true,
+            ..self
+        }
+    }
+
+    /// Specify a function for selecting the enum variant to deserialize into.
+    ///
+    /// For cases that the `#[serde(rename = "if ...")]` matcher syntax cannot express the given function is consulted
+    /// before any matchers whenever an enum is deserialized. It is given a [MatcherContext] describing the enum and
+    /// the items seen so far and can return the name of the variant to select, or `None` to leave the choice to the
+    /// matchers as usual. As the function is consulted for every enum it should return `None` for enums that it does
+    /// not recognize, e.g. by checking [MatcherContext::enum_name()].
+    ///
+    /// The function may be a closure, e.g. one that captures the rules to apply, and is shared by clones of this
+    /// config.
+    pub fn with_variant_selector<F>(self, selector: F) -> Self
+    where
+        F: Fn(&MatcherContext<'_>) -> Option<&'static str> + Send + Sync + 'static,
+    {
+        Self {
+            variant_selector: Some(Arc::new(selector)),
+            ..self
+        }
+    }
+
+    /// Reject TTLV values whose padding bytes are not zero.
+    ///
+    /// The KMIP specification requires that the bytes used to pad TTLV values to a multiple of eight bytes are zero but
+    /// by default their content is ignored. With this setting [validate] and [from_slice_with_config] instead fail with
+    /// [MalformedTtlvError::InvalidPadding]. This is useful for conformance testing of KMIP implementations.
+    pub fn with_verify_padding(self) -> Self {
+        Self {
+            verify_padding: true,
+            ..self
+        }
+    }
+}
+
+/// Read and deserialize bytes from the given slice.
+pub fn from_slice<'de, T>(bytes: &'de [u8]) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    from_slice_with_config(bytes, &Config::default())
+}
+
+/// Read and deserialize bytes from the given slice using the given configuration.
+///
+/// Unlike [from_reader] the [Config::max_bytes] setting is not applied as the bytes have already been read.
+pub fn from_slice_with_config<'de, T>(bytes: &'de [u8], config: &Config) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    let (v, consumed) = deserialize_from_slice(bytes, config)?;
+    check_trailing_bytes(bytes, consumed, config)?;
+    Ok(v)
+}
+
+fn check_trailing_bytes(bytes: &[u8], consumed: usize, config: &Config) -> Result<()> {
+    if config.reject_trailing_bytes() && consumed < bytes.len() {
+        let error = MalformedTtlvError::TrailingBytes;
+        return Err(Error::pinpoint(error, ErrorLocation::at(ByteOffset(consumed as u64))));
+    }
+    Ok(())
+}
+
+/// Read and deserialize the first TTLV item in the given slice, returning also the number of bytes it occupied.
+///
+/// Any bytes following the first TTLV item are ignored, regardless of the [Config::reject_trailing_bytes] setting. Use
+/// the returned byte count to determine where the next item, if any, starts. See also [from_slice_iter].
+pub fn from_slice_partial<'de, T>(bytes: &'de [u8], config: &Config) -> Result<(T, usize)>
+where
+    T: Deserialize<'de>,
+{
+    deserialize_from_slice(bytes, config)
+}
+
+/// Read and deserialize bytes from the given slice, on failure keeping a record of what was decoded.
+///
+/// Behaves like [from_slice_with_config] except that the returned [PartialError] also contains the complete TTLV
+/// items that preceded the location of the error, parsed into [TtlvNode]s. As Serde discards any partially
+/// deserialized Rust value when an error occurs this is the closest available thing to a partial result.
+pub fn from_slice_with_partial<'de, T>(bytes: &'de [u8], config: &Config) -> std::result::Result<T, PartialError>
+where
+    T: Deserialize<'de>,
+{
+    from_slice_with_config(bytes, config).map_err(|error| {
+        let end = error
+            .location()
+            .offset()
+            .map_or(0, |offset| *offset as usize)
+            .min(bytes.len());
+        let (decoded, enclosing) = decode_complete_items(bytes, end);
+        let error = Box::new(error);
+        PartialError {
+            error,
+            decoded,
+            enclosing,
+        }
+    })
+}
+
+/// Parse the TTLV items that end at or before the given offset, returning them in the order in which they occur
+/// together with the tags of the Structures that were still open at that offset.
+fn decode_complete_items(bytes: &[u8], end: usize) -> (Vec<TtlvNode>, Vec<TtlvTag>) {
+    let mut decoded = Vec::new();
+    let mut open: Vec<(TtlvTag, Vec<TtlvNode>)> = Vec::new();
+
+    for event in TtlvTokenizer::new(bytes) {
+        let node = match event {
+            Ok(Event::StructStart { offset, tag, .. }) if offset < end => {
+                open.push((tag, Vec::new()));
+                continue;
+            }
+            Ok(Event::Primitive { offset, tag, value }) if offset + value.encoded_len() <= end => {
+                TtlvNode::new(tag, value.into())
+            }
+            Ok(Event::StructEnd { offset, .. }) if offset <= end => match open.pop() {
+                Some((tag, children)) => TtlvNode::new(tag, TtlvValue::Structure(children)),
+                None => break,
+            },
+            _ => break,
+        };
+        match open.last_mut() {
+            Some((_, children)) => children.push(node),
+            None => decoded.push(node),
+        }
+    }
+
+    // The Structures still open are incomplete so only the items within them are kept.
+    let mut enclosing = Vec::with_capacity(open.len());
+    for (tag, children) in open {
+        enclosing.push(tag);
+        decoded.extend(children);
+    }
+    (decoded, enclosing)
+}
+
+/// Read and deserialize bytes from the given slice, also reporting which TTLV items were ignored.
+///
+/// Behaves like [from_slice_with_config] except that it also returns a [DeserializationReport] listing the TTLV items
+/// that were skipped because the Rust type being deserialized into has no field for them, e.g. vendor extensions sent
+/// by a server that would otherwise be silently dropped.
+pub fn from_slice_with_report<'de, T>(bytes: &'de [u8], config: &Config) -> Result<(T, DeserializationReport)>
+where
+    T: Deserialize<'de>,
+{
+    let mut scratch = Scratch::default();
+    let (v, consumed) = deserialize_from_slice_with_scratch(bytes, config, &mut scratch)?;
+    check_trailing_bytes(bytes, consumed, config)?;
+    Ok((v, scratch.report))
+}
+
+/// Details of a successful deserialization that are not visible in the deserialized Rust value.
+///
+/// See [from_slice_with_report].
+#[derive(Clone, Debug, Default)]
+pub struct DeserializationReport {
+    ignored_items: Vec<IgnoredItem>,
+    lossy_text_strings: Vec<LossyTextString>,
+}
+
+impl DeserializationReport {
+    /// The TTLV items that were skipped rather than deserialized, in the order that they occurred.
+    pub fn ignored_items(&self) -> &[IgnoredItem] {
+        &self.ignored_items
+    }
+
+    /// The TTLV Text Strings that were not valid UTF-8, in the order that they occurred.
+    ///
+    /// Always empty unless [Config::with_lossy_text_strings()] is used.
+    pub fn lossy_text_strings(&self) -> &[LossyTextString] {
+        &self.lossy_text_strings
+    }
+
+    fn clear(&mut self) {
+        self.ignored_items.clear();
+        self.lossy_text_strings.clear();
+    }
+}
+
+/// A TTLV item that was skipped during deserialization, e.g. because it had a tag unknown to the Rust type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IgnoredItem {
+    tag: TtlvTag,
+    r#type: TtlvType,
+    byte_range: Range<usize>,
+}
+
+impl IgnoredItem {
+    pub fn tag(&self) -> TtlvTag {
+        self.tag
+    }
+
+    pub fn r#type(&self) -> TtlvType {
+        self.r#type
+    }
+
+    /// The bytes occupied by the entire TTLV item, from the first byte of its tag up to and including any padding.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_range.clone()
+    }
+}
+
+/// A TTLV Text String that was not valid UTF-8 and was decoded with invalid sequences replaced by U+FFFD.
+///
+/// See [Config::with_lossy_text_strings()].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LossyTextString {
+    tag: TtlvTag,
+    byte_range: Range<usize>,
+}
+
+impl LossyTextString {
+    pub fn tag(&self) -> TtlvTag {
+        self.tag
+    }
+
+    /// The bytes occupied by the entire TTLV item, from the first byte of its tag up to and including any padding.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_range.clone()
+    }
+}
+
+/// A deserialization error together with what was decoded before the error occurred.
+///
+/// See [from_slice_with_partial].
+#[derive(Debug)]
+pub struct PartialError {
+    error: Box<Error>,
+    decoded: Vec<TtlvNode>,
+    enclosing: Vec<TtlvTag>,
+}
+
+impl PartialError {
+    /// The error that caused deserialization to fail.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// The complete TTLV items preceding the error location, in the order in which they occurred.
+    ///
+    /// The Structures enclosing the error location are incomplete and so are not included, but the complete items
+    /// within them are, following any complete items that preceded the enclosing Structures. Empty if the error
+    /// location is not known.
+    pub fn decoded(&self) -> &[TtlvNode] {
+        &self.decoded
+    }
+
+    /// The tags of the TTLV Structures enclosing the error location, outermost first.
+    pub fn enclosing(&self) -> &[TtlvTag] {
+        &self.enclosing
+    }
+
+    /// Discard the decoded items and return just the error.
+    pub fn into_error(self) -> Error {
+        *self.error
+    }
+}
+
+impl std::fmt::Display for PartialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for PartialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+fn deserialize_from_slice<'de, T>(bytes: &'de [u8], config: &Config) -> Result<(T, usize)>
+where
+    T: Deserialize<'de>,
+{
+    deserialize_from_slice_with_scratch(bytes, config, &mut Scratch::default())
+}
+
+fn deserialize_from_slice_with_scratch<'de, T>(
+    bytes: &'de [u8],
+    config: &Config,
+    scratch: &mut Scratch,
+) -> Result<(T, usize)>
+where
+    T: Deserialize<'de>,
+{
+    let cursor = &mut Cursor::new(bytes);
+    let mut deserializer = TtlvDeserializer::from_slice(cursor);
+    deserializer.settings = Rc::new(Settings::from(config));
+
+    let tag_value_store = Rc::new(RefCell::new(std::mem::take(&mut scratch.tag_value_store)));
+    let seen_tags = Rc::new(RefCell::new(std::mem::take(&mut scratch.seen_tags)));
+    let tag_path_value_store = Rc::new(RefCell::new(std::mem::take(&mut scratch.tag_path_value_store)));
+    let tag_path = Rc::new(RefCell::new(std::mem::take(&mut scratch.tag_path)));
+    let report = Rc::new(RefCell::new(std::mem::take(&mut scratch.report)));
+    report.borrow_mut().clear();
+    deserializer.tag_value_store = tag_value_store.clone();
+    deserializer.seen_tags = seen_tags.clone();
+    deserializer.tag_path_value_store = tag_path_value_store.clone();
+    deserializer.tag_path = tag_path.clone();
+    deserializer.report = report.clone();
+
+    let res = T::deserialize(&mut deserializer).map(|v| (v, deserializer.pos() as usize));
+
+    // Hand the buffers back for use by the next call, keeping their allocated capacity. By now the deserializer and
+    // any descendent deserializers have been dropped and so these are the only remaining references.
+    drop(deserializer);
+    if let Ok(tag_value_store) = Rc::try_unwrap(tag_value_store) {
+        scratch.tag_value_store = tag_value_store.into_inner();
+        scratch.tag_value_store.clear();
+    }
+    if let Ok(seen_tags) = Rc::try_unwrap(seen_tags) {
+        scratch.seen_tags = seen_tags.into_inner();
+        scratch.seen_tags.clear();
+    }
+    if let Ok(tag_path_value_store) = Rc::try_unwrap(tag_path_value_store) {
+        scratch.tag_path_value_store = tag_path_value_store.into_inner();
+        scratch.tag_path_value_store.clear();
+    }
+    if let Ok(tag_path) = Rc::try_unwrap(tag_path) {
+        scratch.tag_path = tag_path.into_inner();
+        scratch.tag_path.clear();
+    }
+    if let Ok(report) = Rc::try_unwrap(report) {
+        // Not cleared until the next use so that it can be handed to the caller
+        scratch.report = report.into_inner();
+    }
+
+    res
+}
+
+/// Buffers used while deserializing that can be reused from one message to the next.
+#[derive(Debug, Default)]
+struct Scratch {
+    tag_value_store: HashMap<TtlvTag, String>,
+    seen_tags: HashSet<TtlvTag>,
+    tag_path_value_store: TagPathValueStore,
+    tag_path: Vec<TtlvTag>,
+    report: DeserializationReport,
+}
+
+/// A deserializer that can be used to deserialize many messages, one after the other.
+///
+/// Deserializing with [from_slice_with_config] allocates working memory afresh for every message. When deserializing
+/// large numbers of messages, e.g. in a KMIP proxy, construct a [ReusableDeserializer] once instead and use it for each
+/// message so that the working memory is reused. To also reuse the buffer that [from_reader] reads the message bytes
+/// into use [Config::with_read_buf()].
+///
+/// ```ignore
+/// let mut deserializer = ReusableDeserializer::new(Config::new().with_max_nesting_depth(16));
+/// for bytes in messages {
+///     let res: ResponseMessage = deserializer.from_slice(&bytes)?;
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ReusableDeserializer {
+    config: Config,
+    scratch: Scratch,
+}
+
+impl ReusableDeserializer {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// The configuration used by this deserializer.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Read and deserialize bytes from the given slice. Behaves like [from_slice_with_config].
+    pub fn from_slice<'de, T>(&mut self, bytes: &'de [u8]) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        let (v, consumed) = deserialize_from_slice_with_scratch(bytes, &self.config, &mut self.scratch)?;
+        check_trailing_bytes(bytes, consumed, &self.config)?;
+        Ok(v)
+    }
+}
+
+/// Deserialize from the bytes referenced by the given [bytes::Bytes] handle using the given configuration.
+///
+/// The bytes are read directly from the contiguous storage backing the handle, no copy is made. As with
+/// [from_slice_with_config] the [Config::max_bytes] setting is not applied as the bytes have already been read.
+#[cfg(feature = "bytes")]
+pub fn from_bytes<T>(buf: bytes::Bytes, config: &Config) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_slice_with_config(&buf, config)
+}
+
+/// Deserialize from the bytes remaining in the given [bytes::Buf] using the given configuration.
+///
+/// The deserializer needs random access to the input (it seeks back and forth while matching Structure fields) so
+/// the TTLV bytes must be available as a single contiguous slice. When the remaining bytes are already held in one
+/// chunk, e.g. a [bytes::Bytes] or a chain whose other segments are empty, they are read directly and no copy is
+/// made. Otherwise the segments are gathered exactly once into the [Config] read buffer, if one was configured with
+/// [Config::with_read_buf], or else into a temporary `Vec` sized to fit. This avoids the caller having to first
+/// collect a segmented buffer into a `Vec` of their own.
+///
+/// As with [from_slice_with_config] the [Config::max_bytes] setting is not applied as the bytes have already been
+/// read.
+#[cfg(feature = "bytes")]
+pub fn from_buf<T, B>(mut buf: B, config: &Config) -> Result<T>
+where
+    T: DeserializeOwned,
+    B: bytes::Buf,
+{
+    if buf.chunk().len() == buf.remaining() {
+        return from_slice_with_config(buf.chunk(), config);
+    }
+
+    let mut buf_bytes;
+    let mut config_buf = config.read_buf();
+    let gathered: &mut Vec<u8> = if let Some(ref mut config_buf) = config_buf {
+        config_buf.clear();
+        config_buf
+    } else {
+        buf_bytes = Vec::new();
+        &mut buf_bytes
+    };
+
+    gathered.reserve(buf.remaining());
+    while buf.has_remaining() {
+        let chunk = buf.chunk();
+        let chunk_len = chunk.len();
+        gathered.extend_from_slice(chunk);
+        buf.advance(chunk_len);
+    }
+
+    from_slice_with_config(gathered, config)
+}
+
+/// Read and deserialize zero or more back-to-back TTLV Structures from the given slice.
+///
+/// This is useful when a buffer may contain more than one complete response, e.g. when multiple responses were
+/// coalesced into a single read from a TCP stream. See [TtlvFrameIter] for details of how the buffer is split into
+/// individual responses.
+///
+/// # Errors
+///
+/// Deserialization stops at the first response that fails to deserialize and the error is returned. This includes
+/// the case where the buffer ends with an incomplete response.
+pub fn from_slice_multi<T>(bytes: &[u8], config: &Config) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    TtlvFrameIter::new(bytes, config).collect()
+}
+
+/// A lazy iterator over back-to-back TTLV Structures in a slice, deserializing each in turn.
+///
+/// Each step reads the TTL header of the next TTLV item, uses the length in the header to determine where the item
+/// ends and deserializes the item and its value bytes using [from_slice]. Iteration stops cleanly when fewer than 8
+/// bytes, i.e. less than a complete TTL header, remain.
+///
+/// An item whose length extends beyond the end of the slice results in an [ErrorKind::IoError] of kind
+/// [std::io::ErrorKind::UnexpectedEof]. An item whose length exceeds [Config::max_bytes], if set, results in an
+/// [ErrorKind::ResponseSizeExceedsLimit] error. Error offsets are relative to the start of the entire slice. No more
+/// items are returned after an error.
+///
+/// Use [TtlvFrameIter::position()] to determine how many bytes have been consumed, e.g. in order to retain a trailing
+/// incomplete response until more bytes have been received.
+pub struct TtlvFrameIter<'a, T> {
+    bytes: &'a [u8],
+    pos: usize,
+    config: Config,
+    failed: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T> TtlvFrameIter<'a, T> {
+    pub fn new(bytes: &'a [u8], config: &Config) -> Self {
+        Self {
+            bytes,
+            pos: 0,
+            config: config.clone(),
+            failed: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The offset of the first byte after the last successfully deserialized TTLV item.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a, T> Iterator for TtlvFrameIter<'a, T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = &self.bytes[self.pos..];
+        if self.failed || remaining.len() < 8 {
+            return None;
+        }
+
+        let res = self.next_frame(remaining);
+        self.failed = res.is_err();
+        Some(res)
+    }
+}
+
+impl<'a, T> TtlvFrameIter<'a, T>
+where
+    T: DeserializeOwned,
+{
+    fn next_frame(&mut self, remaining: &[u8]) -> Result<T> {
+        let start = self.pos as u64;
+        let tag = TtlvTag::from([remaining[0], remaining[1], remaining[2]]);
+        let value_len = u32::from_be_bytes([remaining[4], remaining[5], remaining[6], remaining[7]]);
+        let frame_len = 8 + value_len as u64;
+
+        if let Some(max_bytes) = self.config.max_bytes() {
+            if frame_len > (max_bytes as u64) {
+                let error = ErrorKind::ResponseSizeExceedsLimit(frame_len as usize);
+                return Err(pinpoint!(error, ByteOffset(start), tag));
+            }
+        }
+
+        if frame_len > remaining.len() as u64 {
+            let error = std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "TTLV item requires {} bytes but only {} bytes remain",
+                    frame_len,
+                    remaining.len()
+                ),
+            );
+            return Err(pinpoint!(error, ByteOffset(start), tag));
+        }
+
+        // Make the error offset relative to the start of the entire slice rather than to the start of the item
+        let v = from_slice_with_config(&remaining[..frame_len as usize], &self.config)
+            .map_err(|err| rebase_error(err, start))?;
+
+        self.pos += frame_len as usize;
+        Ok(v)
+    }
+}
+
+/// Offset the location of the given error, if known, by the given number of bytes.
+fn rebase_error(err: Error, start: u64) -> Error {
+    let (kind, location) = err.into_inner();
+    let location = match location.offset() {
+        Some(offset) => ErrorLocation::at(ByteOffset(start + *offset)).merge(location),
+        None => location,
+    };
+    Error::pinpoint(kind, location)
+}
+
+/// Create a lazy iterator that deserializes back-to-back TTLV Structures from the given slice.
+///
+/// See [TtlvFrameIter] for details.
+pub fn from_slice_iter<'a, T>(bytes: &'a [u8], config: &Config) -> TtlvFrameIter<'a, T>
+where
+    T: DeserializeOwned,
+{
+    TtlvFrameIter::new(bytes, config)
+}
+
+/// The number of bytes in the TTL header of a TTLV item, i.e. a 3 byte tag, 1 byte type and 4 byte length.
+pub(crate) const TTL_HEADER_LEN: usize = 8;
+
+/// Verify the TTL header of the first TTLV item of a response being read from a stream.
+///
+/// When reading from a stream we don't know how many bytes to read until we've read the L of the first TTLV in the
+/// response stream. As the deserializer jumps around in the response bytes while parsing (see calls to
+/// set_position()), and requiring the caller to provider a Seek capable stream would be quite onerous, and as we're
+/// not trying to be super efficient as HSMs are typically quite slow anyway, the readers read the bytes into a Vec and
+/// then parse it from there. They can't just call read_to_end() because that can cause the response reading to block
+/// if the server doesn't close the connection after writing the response bytes (e.g. PyKMIP behaves this way). We know
+/// from the TTLV specification that the initial TTL bytes must be 8 bytes long (3-byte tag, 1-byte type, 4-byte
+/// length) so the readers first read this "magic header" from the stream and pass it to this function.
+///
+/// Returns the size in bytes of the entire response, including the header, and the tag and type of the response.
+pub(crate) fn verify_response_header(header: &[u8], config: &Config) -> Result<(usize, TtlvTag, TtlvType)> {
+    // Greedy closure capturing:
+    // -------------------------
+    // Note: In the read_xxx() calls below we take the cursor.position() _before_ the read because otherwise, in Rust
+    // 2018 Edition, the closure captures the cursor causing compilation to fail due to multiple mutable borrows of fhe
+    // cursor. Rust 2021 Edition implements so-called "Disjoint capture in closures" which may eliminate this problem.
+    // See: https://doc.rust-lang.org/nightly/edition-guide/rust-2021/disjoint-capture-in-closures.html
+    let mut state = TtlvStateMachine::new(TtlvStateMachineMode::Deserializing);
+    let mut cursor = Cursor::new(header);
+
+    // Extract and verify the first T (tag)
+    let buf_len = cursor.position();
+    let tag = TtlvDeserializer::read_tag(&mut cursor, Some(&mut state)).map_err(|err| pinpoint!(err, buf_len))?;
+
+    // Extract and verify the second T (type)
+    let buf_len = cursor.position();
+    let r#type =
+        TtlvDeserializer::read_type(&mut cursor, Some(&mut state)).map_err(|err| pinpoint!(err, buf_len, tag))?;
+
+    // Extract and verify the L (value length)
+    let buf_len = cursor.position();
+    let additional_len = TtlvDeserializer::read_length(&mut cursor, Some(&mut state))
+        .map_err(|err| pinpoint!(err, buf_len, tag, r#type))?;
+
+    // The number of bytes to allocate is determined by the data being read. It could be a gazillion bytes and we'd
+    // panic trying to allocate it. The caller is therefore advised to define an upper bound if the source cannot be
+    // trusted.
+    let response_size = cursor.position() + (additional_len as u64);
+    if let Some(max_bytes) = config.max_bytes() {
+        if response_size > (max_bytes as u64) {
+            let error = ErrorKind::ResponseSizeExceedsLimit(response_size as usize);
+            let location = ErrorLocation::from(cursor).with_tag(tag).with_type(r#type);
+            return Err(Error::pinpoint(error, location));
+        }
+    }
+
+    Ok((response_size as usize, tag, r#type))
+}
+
+/// Determine which bytes of a response to read next, given that `buf_len` of `response_size` bytes have been read.
+///
+/// See [Config::with_read_chunk_size()].
+pub(crate) fn next_read_range(buf_len: usize, response_size: usize, config: &Config) -> std::ops::Range<usize> {
+    match config.read_chunk_size() {
+        Some(chunk_size) => buf_len..response_size.min(buf_len.saturating_add(chunk_size)),
+        None => buf_len..response_size,
+    }
+}
+
+/// Verify that the given bytes are structurally valid TTLV without deserializing them.
+///
+/// No Rust type is involved, the TTLV items are walked using the low-level [types] API and the following checks are
+/// made:
+///
+///   - Every type byte is a TTLV type defined by the KMIP specification, i.e. not a [TtlvType::Extension] type, unless
+///     [Config::opaque_extension_types] is set.
+///   - Every length is valid for the type of the item, e.g. an Integer must have length 4 and a Boolean length 8.
+///   - Every value is valid for its type, e.g. a Text String must be valid UTF-8, unless
+///     [Config::lossy_text_strings] is set, and a Boolean must be 0 or 1.
+///   - Every TTLV Structure length exactly spans the items it contains, and so is a multiple of 8 bytes.
+///   - The root TTLV item spans exactly `bytes.len()` bytes.
+///   - TTLV Structures are not nested more deeply than [Config::max_nesting_depth], if set.
+///   - The number of bytes does not exceed [Config::max_bytes], if set.
+///   - The padding bytes following each value are zero, if [Config::verify_padding] is set.
+///
+/// Use this to reject malformed input from an untrusted source before passing it to [from_slice], or to audit captured
+/// TTLV independently of any Rust model of it. On success a [ValidationReport] describes what was found.
+///
+/// # Errors
+///
+/// The first violation found is returned as an [Error] whose [ErrorLocation] gives the byte offset at which the
+/// problem was detected along with the tag and type of the offending item where known.
+pub fn validate(bytes: &[u8], config: &Config) -> Result<ValidationReport> {
+    if let Some(max_bytes) = config.max_bytes() {
+        if bytes.len() > max_bytes as usize {
+            let error = ErrorKind::ResponseSizeExceedsLimit(bytes.len());
+            return Err(Error::pinpoint(error, ErrorLocation::at(ByteOffset(0))));
+        }
+    }
+
+    let mut cursor = Cursor::new(bytes);
+
+    // The end offsets and tags of the TTLV Structures enclosing the current read position, innermost last. An explicit
+    // stack is used rather than recursion so that deeply nested input cannot exhaust the call stack.
+    let mut structure_ends: Vec<u64> = Vec::new();
+    let mut parent_tags: Vec<TtlvTag> = Vec::new();
+    let mut report = ValidationReport::default();
+
+    loop {
+        // Leave any TTLV Structures whose content has been fully read
+        while let Some(&structure_end) = structure_ends.last() {
+            if cursor.position() < structure_end {
+                break;
+            }
+            structure_ends.pop();
+            parent_tags.pop();
+        }
+
+        // Stop once the root TTLV item has been fully read
+        let item_start = cursor.position();
+        if item_start > 0 && structure_ends.is_empty() {
+            break;
+        }
+
+        let here = |pos: u64, parent_tags: &[TtlvTag]| ErrorLocation::at(ByteOffset(pos)).with_parent_tags(parent_tags);
+        let parent_end = structure_ends.last().copied().unwrap_or(bytes.len() as u64);
+
+        // Read the item header, which must fit within the enclosing TTLV Structure
+        if parent_end - item_start < 8 && !structure_ends.is_empty() {
+            let error = MalformedTtlvError::overflow(parent_end);
+            return Err(Error::pinpoint(error, here(item_start, &parent_tags)));
+        }
+        let tag = TtlvTag::read(&mut cursor).map_err(|err| pinpoint!(err, here(item_start, &parent_tags)))?;
+        let type_pos = cursor.position();
+        let r#type =
+            TtlvType::read(&mut cursor).map_err(|err| pinpoint!(err, here(type_pos, &parent_tags).with_tag(tag)))?;
+        if let (TtlvType::Extension(type_byte), false) = (r#type, config.opaque_extension_types()) {
+            let error = MalformedTtlvError::InvalidType(*type_byte);
+            return Err(Error::pinpoint(error, here(type_pos, &parent_tags).with_tag(tag)));
+        }
+        let len_pos = cursor.position();
+        let location = |pos: u64| here(pos, &parent_tags).with_tag(tag).with_type(r#type);
+        let value_len = *TtlvLength::read(&mut cursor).map_err(|err| pinpoint!(err, location(len_pos)))?;
+
+        // The value, including padding, must fit within the enclosing TTLV Structure
+        let value_start = cursor.position();
+        let value_end = value_start + value_len as u64;
+        let item_end = match r#type {
+            TtlvType::Structure => value_end,
+            _ => value_end + TtlvByteString::calc_pad_bytes(value_len) as u64,
+        };
+        if item_end > parent_end {
+            let error = MalformedTtlvError::overflow(parent_end);
+            return Err(Error::pinpoint(error, location(len_pos)));
+        }
+
+        let res = match r#type {
+            TtlvType::Structure => {
+                let depth = structure_ends.len() + 1;
+                if matches!(config.max_nesting_depth(), Some(max_depth) if depth > max_depth) {
+                    let error = ErrorKind::NestingDepthExceedsLimit(depth);
+                    return Err(Error::pinpoint(error, location(item_start)));
+                }
+                report.max_nesting_depth = report.max_nesting_depth.max(depth);
+                Ok(())
+            }
+            TtlvType::Integer => TtlvInteger::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::LongInteger => TtlvLongInteger::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::BigInteger if value_len % 8 != 0 => Err(types::Error::InvalidTtlvValueLength {
+                expected: value_len + TtlvBigInteger::calc_pad_bytes(value_len),
+                actual: value_len,
+                r#type,
+            }),
+            TtlvType::BigInteger => {
+                let max_len = config.max_big_integer_len().unwrap_or(TtlvBigInteger::DEFAULT_MAX_LEN);
+                TtlvBigInteger::read_value_with_max_len(&mut cursor, value_len, max_len).map(|_| ())
+            }
+            TtlvType::Enumeration => TtlvEnumeration::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::Boolean => TtlvBoolean::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::TextString if config.lossy_text_strings() => Ok(()), // any bytes are acceptable
+            TtlvType::TextString => TtlvTextString::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::ByteString => TtlvByteString::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::DateTime => TtlvDateTime::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::Interval => TtlvInterval::read_value(&mut cursor, value_len).map(|_| ()),
+            TtlvType::Extension(_) => Ok(()), // opaque, the value is skipped below
+        };
+        res.map_err(|err| pinpoint!(err, location(value_start)))?;
+        report.item_count += 1;
 
-impl Clone for Config {
-    fn clone(&self) -> Self {
-        Self {
-            max_bytes: self.max_bytes,
-            read_buf: if self.has_buf() {
-                Some(RefCell::new(Vec::new()))
-            } else {
-                None
-            },
+        if r#type == TtlvType::Structure {
+            // Descend into the TTLV Structure
+            structure_ends.push(value_end);
+            parent_tags.push(tag);
+        } else {
+            // Skip the padding bytes, if any, which are known from the check above to be present
+            if bytes[value_end as usize..item_end as usize].iter().any(|&b| b != 0) {
+                if config.verify_padding() {
+                    let error = MalformedTtlvError::InvalidPadding { r#type };
+                    return Err(Error::pinpoint(error, location(value_end)));
+                }
+                report.non_zero_padding.push(ByteOffset(value_end));
+            }
+            cursor.set_position(item_end);
         }
     }
-}
 
-impl Config {
-    pub fn new() -> Self {
-        Self::default()
+    if cursor.position() < bytes.len() as u64 {
+        let error = MalformedTtlvError::TrailingBytes;
+        return Err(Error::pinpoint(error, ErrorLocation::at(ByteOffset(cursor.position()))));
     }
+
+    Ok(report)
 }
 
-impl Config {
-    /// What, if any, is the configured maximum permitted response size?
-    pub fn max_bytes(&self) -> Option<u32> {
-        self.max_bytes
+/// Details of TTLV bytes that [validate] found to be structurally valid.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    item_count: usize,
+    max_nesting_depth: usize,
+    non_zero_padding: Vec<ByteOffset>,
+}
+
+impl ValidationReport {
+    /// The number of TTLV items, including TTLV Structures and the root item.
+    pub fn item_count(&self) -> usize {
+        self.item_count
     }
 
-    /// Has a persistent read buffer been configured for reading response bytes into?
-    pub fn has_buf(&self) -> bool {
-        self.read_buf.is_some()
+    /// The depth of the most deeply nested TTLV Structure, where the root TTLV Structure has depth 1.
+    ///
+    /// See [Config::with_max_nesting_depth()].
+    pub fn max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
     }
 
-    /// Get mutable access to optional persistent response bytes buffer
-    pub fn read_buf(&self) -> Option<RefMut<Vec<u8>>> {
-        self.read_buf.as_ref().map(|buf| buf.borrow_mut())
+    /// The byte offsets of the padding that is not all zero, in the order that it occurred.
+    ///
+    /// Always empty when [Config::with_verify_padding()] is used as such padding is then an error.
+    pub fn non_zero_padding(&self) -> &[ByteOffset] {
+        &self.non_zero_padding
     }
 }
 
-// Builder style interface
-impl Config {
-    /// Specify a maximum number of response bytes to read.
-    ///
-    /// Use this if you are reading data from an untrusted source. If that source then sends a very large response we
-    /// will reject it rather than attempt to read it all and thus avoid possibly running out of memory.
-    pub fn with_max_bytes(self, max_bytes: u32) -> Self {
-        Self {
-            max_bytes: Some(max_bytes),
-            ..self
+// --- Private implementation details ----------------------------------------------------------------------------------
+
+// Required for impl Deserializer below to use this type, but I don't really want arbitrary strings leaking out of the
+// deserializer as they could leak sensitive data
+impl serde::de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        pinpoint!(SerdeError::Other(msg.to_string()), ErrorLocation::unknown())
+    }
+}
+
+/// Deserialize a TTLV Byte String, rejecting other TTLV types.
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Bytes;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a TTLV Byte String")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Bytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Bytes(v))
+            }
         }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
     }
+}
 
-    /// Save the read response bytes into a buffer for use later.
-    ///
-    /// Allocate a persistent buffer that can be used by a reader to store the read response bytes into. This could be
-    /// to avoid allocating a buffer for every response read, or to permit logging or storing or pretty printing of the
-    /// response bytes once they have been read from the source.
-    pub fn with_read_buf(self) -> Self {
-        Self {
-            read_buf: Some(RefCell::new(Vec::new())),
-            ..self
+/// Deserialize a TTLV Byte String by borrowing it from the input, rejecting other TTLV types.
+impl<'de: 'a, 'a> Deserialize<'de> for BytesRef<'a> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BytesRefVisitor;
+
+        impl<'de> Visitor<'de> for BytesRefVisitor {
+            type Value = BytesRef<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a TTLV Byte String borrowed from the input")
+            }
+
+            fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(BytesRef(v))
+            }
         }
+
+        deserializer.deserialize_bytes(BytesRefVisitor)
     }
 }
 
-/// Read and deserialize bytes from the given slice.
-pub fn from_slice<'de, T>(bytes: &'de [u8]) -> Result<T>
-where
-    T: Deserialize<'de>,
-{
-    let cursor = &mut Cursor::new(bytes);
-    let mut deserializer = TtlvDeserializer::from_slice(cursor);
-    T::deserialize(&mut deserializer)
+/// Deserialize a TTLV Interval, rejecting other TTLV types.
+impl<'de> Deserialize<'de> for TtlvInterval {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TtlvIntervalVisitor;
+
+        impl<'de> Visitor<'de> for TtlvIntervalVisitor {
+            type Value = TtlvInterval;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a TTLV Interval")
+            }
+
+            fn visit_u32<E>(self, v: u32) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(TtlvInterval(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                u32::try_from(v)
+                    .map(TtlvInterval)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TTLV_INTERVAL_NAME, TtlvIntervalVisitor)
+    }
 }
 
-/// Read and deserialize bytes from the given reader.
+/// Announces the tag of a TTLV Structure member as a map key.
 ///
-/// Note: Also accepts a mut reference.
-///
-/// Attempting to process a stream whose initial TTL header length value is larger the config max_bytes, if any, will
-/// result in`Error::ResponseSizeExceedsLimit`.
-#[maybe_async::maybe_async]
-pub async fn from_reader<T, R>(mut reader: R, config: &Config) -> Result<T>
-where
-    T: DeserializeOwned,
-    R: AnySyncRead,
-{
-    // When reading from a stream we don't know how many bytes to read until we've read the L of the first TTLV in
-    // the response stream. As the current implementation jumps around in the response bytes while parsing (see
-    // calls to set_position()), and requiring the caller to provider a Seek capable stream would be quite onerous,
-    // and as we're not trying to be super efficient as HSMs are typically quite slow anywa, just read the bytes into a
-    // Vec and then parse it from there. We can't just call read_to_end() because that can cause the response reading to
-    // block if the server doesn't close the connection after writing the response bytes (e.g. PyKMIP behaves this way).
-    // We know from the TTLV specification that the initial TTL bytes must be 8 bytes long (3-byte tag, 1-byte type,
-    // 4-byte length) so we attempt read to this "magic header" from the given stream.
-
-    fn cur_pos(buf_len: u64) -> ErrorLocation {
-        ErrorLocation::from(buf_len)
-    }
-
-    let max_bytes = config.max_bytes();
-
-    // Interior mutability access dance
-    // --------------------------------
-    // The Config object can optionally have its own buffer which we will write the read bytes into. This then allows
-    // the caller to log or save or pretty print those bytes or avoid allocating a new buffer on every read, or
-    // whatever the caller wants to do with them.
-    //
-    // We could take a buffer as an argument but that would complicate the default use case. We could also take a
-    // mutable reference to the Config object but then we'd appear to have the ability to alter the configuration
-    // settings which is not right. So instead we use the interior mutability pattern to get a mutable reference to
-    // just the buffer owned by the Config object, not the entire Config object. However, to write to the Config object
-    // buffer if available or otherwise to a local buffer requires a bit of a dance to get satisfy the Rust compiler
-    // borrow checker.
-    let mut buf_bytes;
-    let mut config_buf = config.read_buf();
-    let mut buf: &mut Vec<u8> = if let Some(ref mut buf) = config_buf {
-        // Use the buffer provided by the Config object.
-        buf
-    } else {
-        // Create and use our own temporary buffer.
-        buf_bytes = Vec::new();
-        &mut buf_bytes
-    };
+/// The tag is offered in hex form, e.g. "0x42000A", unless the key type asks for an unsigned integer. This permits
+/// deserializing into maps keyed by [TtlvTag], `String`, `u32` or `u64`.
+struct TagKeyDeserializer(TtlvTag);
 
-    // Greedy closure capturing:
-    // -------------------------
-    // Note: In the read_xxx() calls below we take the cursor.position() _before_ the read because otherwise, in Rust
-    // 2018 Edition, the closure captures the cursor causing compilation to fail due to multiple mutable borrows of fhe
-    // cursor. Rust 2021 Edition implements so-called "Disjoint capture in closures" which may eliminate this problem.
-    // See: https://doc.rust-lang.org/nightly/edition-guide/rust-2021/disjoint-capture-in-closures.html
+impl<'de> Deserializer<'de> for TagKeyDeserializer {
+    type Error = Error;
 
-    // Read the bytes of the first TTL (3 byte tag, 1 byte type, 4 byte len)
-    buf.resize(8, 0);
-    let response_size;
-    let tag;
-    let r#type;
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.0.to_string())
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u32(*self.0)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
     {
-        let mut state = TtlvStateMachine::new(TtlvStateMachineMode::Deserializing);
-        reader.read_exact(buf).await.map_err(|err| pinpoint!(err, cur_pos(0)))?;
+        visitor.visit_u64(*self.0 as u64)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
 
-        // Extract and verify the first T (tag)
-        let mut cursor = Cursor::new(&mut buf);
-        let buf_len = cursor.position();
-        tag = TtlvDeserializer::read_tag(&mut cursor, Some(&mut state))
-            .map_err(|err| pinpoint!(err, cur_pos(buf_len)))?;
+/// The Serde field name of a struct field that collects all remaining TTLV items in the structure.
+const CATCH_ALL_FIELD_NAME: &str = "*";
 
-        // Extract and verify the second T (type)
-        let buf_len = cursor.position();
-        r#type = TtlvDeserializer::read_type(&mut cursor, Some(&mut state))
-            .map_err(|err| pinpoint!(err, cur_pos(buf_len), tag))?;
+/// The prefix of the Serde field name of a struct field that collects repeated TTLV Structures into a map. The prefix is
+/// followed by the tag of the structures and the tag of the child item whose value is the key, e.g.
+/// "keyed:0x420008/0x42000A".
+const KEYED_FIELD_PREFIX: &str = "keyed:";
 
-        // Extract and verify the L (value length)
-        let buf_len = cursor.position();
-        let additional_len = TtlvDeserializer::read_length(&mut cursor, Some(&mut state))
-            .map_err(|err| pinpoint!(err, cur_pos(buf_len), tag, r#type))?;
+/// Split the Serde field name of a keyed map field into the structure tag and the key tag, see [KEYED_FIELD_PREFIX].
+fn keyed_field_tags(field: &str) -> Option<(&str, &str)> {
+    field
+        .strip_prefix(KEYED_FIELD_PREFIX)
+        .and_then(|tags| split_once(tags, "/"))
+}
+
+/// Announces the value of a child item of a TTLV Structure as the key under which the structure is added to a map.
+///
+/// See [KeyedMapAccess].
+struct KeyItemDeserializer<'de> {
+    src: Cursor<&'de [u8]>, // positioned at the length of the key item
+    location: ErrorLocation,
+    r#type: TtlvType,
+}
 
-        // ------------------------------------------------------------------------------------------
-        // Now read the value bytes of the first TTLV item (i.e. the rest of the entire TTLV message)
-        // ------------------------------------------------------------------------------------------
+impl<'de> KeyItemDeserializer<'de> {
+    fn read<T: SerializableTtlvType>(&mut self) -> Result<T> {
+        T::read(&mut self.src).map_err(|err| Error::pinpoint(err, self.location.clone()))
+    }
+}
+
+impl<'de> Deserializer<'de> for KeyItemDeserializer<'de> {
+    type Error = Error;
 
-        // The number of bytes to allocate is determined by the data being read. It could be a gazillion bytes and we'd
-        // panic trying to allocate it. The caller is therefore advised to define an upper bound if the source cannot be
-        // trusted.
-        let buf_len = cursor.position();
-        response_size = buf_len + (additional_len as u64);
-        if let Some(max_bytes) = max_bytes {
-            if response_size > (max_bytes as u64) {
-                let error = ErrorKind::ResponseSizeExceedsLimit(response_size as usize);
-                let location = ErrorLocation::from(cursor).with_tag(tag).with_type(r#type);
-                return Err(Error::pinpoint(error, location));
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.r#type {
+            TtlvType::TextString => visitor.visit_string(self.read::<TtlvTextString>()?.0),
+            TtlvType::Integer => visitor.visit_i32(*self.read::<TtlvInteger>()?),
+            TtlvType::LongInteger => visitor.visit_i64(*self.read::<TtlvLongInteger>()?),
+            TtlvType::DateTime => visitor.visit_i64(*self.read::<TtlvDateTime>()?),
+            TtlvType::Enumeration => visitor.visit_u32(*self.read::<TtlvEnumeration>()?),
+            TtlvType::Interval => visitor.visit_u32(*self.read::<TtlvInterval>()?),
+            TtlvType::Boolean => visitor.visit_bool(*self.read::<TtlvBoolean>()?),
+            TtlvType::ByteString => visitor.visit_byte_buf(self.read::<TtlvByteString>()?.0),
+            other_type => {
+                let error = SerdeError::Other(format!("TTLV type {} cannot be used as a map key", other_type));
+                Err(Error::pinpoint(error, self.location))
             }
         }
     }
 
-    // Warning: this will panic if it fails to allocate the requested amount of memory, at least until try_reserve() is
-    // stabilized!
-    buf.resize(response_size as usize, 0);
-    reader
-        .read_exact(&mut buf[8..])
-        .await
-        .map_err(|err| Error::pinpoint(err, ErrorLocation::from(buf.len()).with_tag(tag).with_type(r#type)))?;
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
 
-    from_slice(buf)
+/// Deserializes repeated TTLV Structures into a Rust map, keyed by the value of one of their child items.
+///
+/// The structures are those with the same tag and type as the first, as for a Rust `Vec` (see `deserialize_seq()`),
+/// but each is offered to Serde as a map value under the key given by the value of its child item with `key_tag`.
+struct KeyedMapAccess<'de, 'c> {
+    items: TtlvDeserializer<'de, 'c>,
+    key_tag: TtlvTag,
 }
 
-// --- Private implementation details ----------------------------------------------------------------------------------
+impl<'de: 'c, 'c> MapAccess<'de> for KeyedMapAccess<'de, 'c> {
+    type Error = Error;
 
-// Required for impl Deserializer below to use this type, but I don't really want arbitrary strings leaking out of the
-// deserializer as they could leak sensitive data
-impl serde::de::Error for Error {
-    fn custom<T: std::fmt::Display>(msg: T) -> Self {
-        pinpoint!(SerdeError::Other(msg.to_string()), ErrorLocation::unknown())
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        if self.items.read_next_seq_item_key()? {
+            let key = self.items.find_key_item(self.key_tag)?;
+            seed.deserialize(key).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut self.items) // jumps to deserialize_xxx() in impl Deserializer above
+    }
+}
+
+/// The Serde newtype struct name used to request the raw bytes of the current TTLV item from the deserializer.
+const RAW_ITEM_NAME: &str = "Raw:TtlvItem";
+
+/// Deserialize the current TTLV item, whatever its tag and type, without interpreting its value.
+///
+/// The deserializer hands the item to the visitor as a byte buffer consisting of the 3 tag bytes, the 1 type byte and
+/// the value bytes, i.e. without the length and any padding bytes.
+impl<'de> Deserialize<'de> for TtlvItem {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TtlvItemVisitor;
+
+        impl<'de> Visitor<'de> for TtlvItemVisitor {
+            type Value = TtlvItem;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a raw TTLV item")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if v.len() < 4 {
+                    return Err(E::invalid_length(v.len(), &self));
+                }
+                let tag = TtlvTag::from([v[0], v[1], v[2]]);
+                let r#type = TtlvType::try_from(v[3]).map_err(|err| E::custom(format!("{:?}", err)))?;
+                Ok(TtlvItem::new(tag, r#type, v[4..].to_vec()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(RAW_ITEM_NAME, TtlvItemVisitor)
     }
 }
 
@@ -246,6 +1645,45 @@ trait ContextualErrorSupport {
     fn pos(&self) -> u64;
 }
 
+/// The settings that apply to an entire deserialization, shared by the parsers of all of the nested TTLV Structures.
+struct Settings {
+    duplicate_tag_policy: DuplicateTagPolicy,
+    item_observer: Option<ItemObserver>,
+    lossy_text_strings: bool,
+    max_big_integer_len: u32,
+    max_nesting_depth: Option<usize>,
+    opaque_extension_types: bool,
+    tag_name_resolver: Option<TagNameResolver>,
+    tag_provider: Option<TagProvider>,
+    tuple_structs: bool,
+    variant_selector: Option<VariantSelector>,
+    verify_padding: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::from(&Config::default())
+    }
+}
+
+impl From<&Config> for Settings {
+    fn from(config: &Config) -> Self {
+        Self {
+            duplicate_tag_policy: config.duplicate_tag_policy(),
+            item_observer: config.item_observer(),
+            lossy_text_strings: config.lossy_text_strings(),
+            max_big_integer_len: config.max_big_integer_len().unwrap_or(TtlvBigInteger::DEFAULT_MAX_LEN),
+            max_nesting_depth: config.max_nesting_depth(),
+            opaque_extension_types: config.opaque_extension_types(),
+            tag_name_resolver: config.tag_name_resolver(),
+            tag_provider: config.tag_provider(),
+            tuple_structs: config.tuple_structs(),
+            variant_selector: config.variant_selector(),
+            verify_padding: config.verify_padding(),
+        }
+    }
+}
+
 pub(crate) struct TtlvDeserializer<'de: 'c, 'c> {
     src: &'c mut Cursor<&'de [u8]>,
 
@@ -254,12 +1692,17 @@ pub(crate) struct TtlvDeserializer<'de: 'c, 'c> {
     // for container/group types (map, seq)
     #[allow(dead_code)]
     group_start: u64,
+    group_depth: usize, // stack exhaustion protection: how many parsers enclose this one?
     group_tag: Option<TtlvTag>,
     group_type: Option<TtlvType>,
     group_end: Option<u64>,
     group_fields: &'static [&'static str], // optional field handling: expected fields to compare to actual fields
     group_item_count: usize,               // optional field handling: index into the group_fields array
     group_homogenous: bool,                // sequence/map field handling: are all items in the group of the same type?
+    group_any: bool,                       // schema-less handling: announce item tags as map keys?
+    group_tuple: bool,                     // tuple struct handling: are the items visited as a sequence of fields?
+    group_matched_tags: Vec<TtlvTag>,      // duplicate handling: the tags of the fields that have been deserialized
+    group_superseding_items: Vec<u64>,     // duplicate handling: the start of items used in place of an earlier one
 
     // for the current field being parsed
     item_start: u64, // optional field handling: point to return to if field is missing
@@ -267,13 +1710,134 @@ pub(crate) struct TtlvDeserializer<'de: 'c, 'c> {
     item_type: Option<TtlvType>,
     item_unexpected: bool, // optional field handling: is this tag wrong for the expected field (and thus is missing?)
     item_identifier: Option<String>,
+    item_matched: bool, // duplicate handling: is this item the first with the tag of the field being deserialized?
+    item_resume: Option<u64>, // duplicate handling: where to resume if a later item is deserialized in place of this
 
     // lookup maps
     tag_value_store: Rc<RefCell<HashMap<TtlvTag, String>>>,
-    matcher_rule_handlers: [(&'static str, MatcherRuleHandlerFn<'de, 'c>); 3],
+    seen_tags: Rc<RefCell<HashSet<TtlvTag>>>,
+    tag_path_value_store: Rc<RefCell<TagPathValueStore>>,
+    matcher_rule_handlers: [(&'static str, MatcherRuleHandlerFn<'de, 'c>); 6],
 
     // diagnostic support
     tag_path: Rc<RefCell<Vec<TtlvTag>>>,
+    report: Rc<RefCell<DeserializationReport>>,
+
+    // limits, strictness and the configured functions
+    settings: Rc<Settings>,
+}
+
+/// How the members of a TTLV Structure are presented to a Serde visitor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StructureAccess {
+    /// As a map keyed by the expected Rust struct field names.
+    Fields,
+
+    /// As a map keyed by the tag of each member in hex form.
+    Tags,
+
+    /// As a sequence, in the order that the members occur.
+    Tuple,
+}
+
+// str::split_once() wasn't stablized until Rust 1.52.0 but as we want to be usable by Krill, and Krill supported Rust
+// >= 1.49.0 at the time of writing, we use our own split_once() implementation.
+fn split_once<'a>(value: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    value
+        .find(delimiter)
+        .map(|idx| (&value[..idx], &value[idx + delimiter.len()..]))
+}
+
+/// Check whether the value matches the pattern, in which `*` matches any sequence of characters (including none) and
+/// `?` matches exactly one character.
+fn is_wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    // Where to resume if the characters matched since the last `*` turn out not to fit: the pattern index just after
+    // the `*` and the value index that the `*` should then extend to.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || (pattern[p] != '*' && pattern[p] == value[v])) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, v + 1));
+        } else if let Some((next_p, next_v)) = backtrack {
+            p = next_p;
+            v = next_v;
+            backtrack = Some((next_p, next_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Parse a value in a variant matcher, e.g. `0x00000005`. Unlike a tag a value may use all 32 bits.
+fn parse_matcher_value(s: &str) -> std::result::Result<u32, types::Error> {
+    u32::from_str_radix(s.trim_start_matches("0x"), 16).map_err(|_| types::Error::InvalidTtlvTag(s.to_string()))
+}
+
+/// The name of the enum variant to select when no other variant matches, see `deserialize_enum()`.
+const FALLBACK_VARIANT_NAME: &str = "else";
+
+/// Values remembered while deserializing, oldest first, with the path of structure tags leading to each of them.
+///
+/// The paths form a tree of `(parent index, tag)` nodes shared by all values with the same path, so that remembering
+/// a value does not require copying the path that leads to it.
+#[derive(Debug, Default)]
+struct TagPathValueStore {
+    nodes: Vec<(Option<usize>, TtlvTag)>,
+    node_indices: HashMap<(Option<usize>, TtlvTag), usize>,
+    values: Vec<(usize, String)>,
+}
+
+impl TagPathValueStore {
+    /// Remember the value of the item with the given tag inside structures with the given tags, outermost first.
+    fn push(&mut self, parent_tags: &[TtlvTag], tag: TtlvTag, value: String) {
+        let mut parent = None;
+        for &tag in parent_tags.iter().chain(std::iter::once(&tag)) {
+            let nodes = &mut self.nodes;
+            let index = *self.node_indices.entry((parent, tag)).or_insert_with(|| {
+                nodes.push((parent, tag));
+                nodes.len() - 1
+            });
+            parent = Some(index);
+        }
+        if let Some(index) = parent {
+            self.values.push((index, value));
+        }
+    }
+
+    /// Find the last remembered value whose path ends with, or if absolute is equal to, the given path.
+    fn find(&self, wanted_path: &[TtlvTag], is_absolute: bool) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(index, _)| self.path_matches(*index, wanted_path, is_absolute))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn path_matches(&self, index: usize, wanted_path: &[TtlvTag], is_absolute: bool) -> bool {
+        let mut node = Some(index);
+        for wanted_tag in wanted_path.iter().rev() {
+            match node.map(|index| self.nodes[index]) {
+                Some((parent, tag)) if tag == *wanted_tag => node = parent,
+                _ => return false,
+            }
+        }
+        !is_absolute || node.is_none()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.node_indices.clear();
+        self.values.clear();
+    }
 }
 
 type MatcherRuleHandlerFn<'de, 'c> =
@@ -284,10 +1848,16 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
     // by the Self::fn_name references which is in turn required because the handler functions can use arbitrary data
     // from the current instance of the deserializer. One could argue that the set of matcher fns is fixed and thus we
     // can concretely specify everything in advance, but I'm not convinced that's really more readable.
-    fn init_matcher_rule_handlers() -> [(&'static str, MatcherRuleHandlerFn<'de, 'c>); 3] {
+    //
+    // Rules are tried in the order given and the first operator found in the rule is used, so "!=", "startswith" and
+    // "like" must precede "in" which could otherwise be found inside a textual value such as "Contact Information".
+    fn init_matcher_rule_handlers() -> [(&'static str, MatcherRuleHandlerFn<'de, 'c>); 6] {
         [
             ("==", Self::handle_matcher_rule_eq),
+            ("!=", Self::handle_matcher_rule_ne),
             (">=", Self::handle_matcher_rule_ge),
+            ("startswith", Self::handle_matcher_rule_starts_with),
+            ("like", Self::handle_matcher_rule_like),
             ("in", Self::handle_matcher_rule_in),
         ]
     }
@@ -297,59 +1867,91 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
             src: cursor,
             state: Rc::new(RefCell::new(TtlvStateMachine::new(TtlvStateMachineMode::Deserializing))),
             group_start: 0,
+            group_depth: 0,
             group_tag: None,
             group_type: None,
             group_end: None,
             group_fields: &[],
             group_item_count: 0,
             group_homogenous: false,
+            group_any: false,
+            group_tuple: false,
+            group_matched_tags: Vec::new(),
+            group_superseding_items: Vec::new(),
             item_start: 0,
             item_tag: None,
             item_type: None,
             item_unexpected: false,
             item_identifier: None,
+            item_matched: false,
+            item_resume: None,
             tag_value_store: Rc::new(RefCell::new(HashMap::new())),
+            seen_tags: Rc::new(RefCell::new(HashSet::new())),
+            tag_path_value_store: Rc::new(RefCell::new(TagPathValueStore::default())),
             matcher_rule_handlers: Self::init_matcher_rule_handlers(),
             tag_path: Rc::new(RefCell::new(Vec::new())),
+            report: Rc::new(RefCell::new(DeserializationReport::default())),
+            settings: Rc::new(Settings::default()),
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn from_cursor(
-        src: &'c mut Cursor<&'de [u8]>,
-        state: Rc<RefCell<TtlvStateMachine>>,
+    /// Create a parser for the items of the group, e.g. a TTLV Structure, whose header has just been read.
+    ///
+    /// The new parser reads from the given cursor, shares the lookup maps, diagnostic support and settings of this
+    /// parser and stops at the given end of the group.
+    ///
+    /// Each nested parser is another level of recursion, whether or not the group is a TTLV Structure, e.g. a sequence
+    /// of items or the items of a tuple enum variant. Fails with [ErrorKind::NestingDepthExceedsLimit] if parsers are
+    /// nested more deeply than the call stack can bear, however the Rust types being deserialized into are defined.
+    fn nested<'n>(
+        &self,
+        src: &'n mut Cursor<&'de [u8]>,
         group_tag: TtlvTag,
         group_type: TtlvType,
         group_end: u64,
         group_fields: &'static [&'static str],
         group_homogenous: bool, // are all items in the group the same tag and type?
-        unit_enum_store: Rc<RefCell<HashMap<TtlvTag, String>>>,
-        tag_path: Rc<RefCell<Vec<TtlvTag>>>,
-    ) -> Self {
+    ) -> Result<TtlvDeserializer<'de, 'n>>
+    where
+        'de: 'n,
+    {
+        let group_depth = self.group_depth + 1;
+        if group_depth > MAX_NESTING_DEPTH {
+            return Err(pinpoint!(ErrorKind::NestingDepthExceedsLimit(group_depth), self));
+        }
+
         let group_start = src.position();
-        let group_tag = Some(group_tag);
-        let group_type = Some(group_type);
-        let group_end = Some(group_end);
 
-        Self {
+        Ok(TtlvDeserializer {
             src,
-            state,
+            state: self.state.clone(),
             group_start,
-            group_tag,
-            group_type,
-            group_end,
+            group_depth,
+            group_tag: Some(group_tag),
+            group_type: Some(group_type),
+            group_end: Some(group_end),
             group_fields,
             group_item_count: 0,
             group_homogenous,
+            group_any: false,
+            group_tuple: false,
+            group_matched_tags: Vec::new(),
+            group_superseding_items: Vec::new(),
             item_start: group_start,
             item_tag: None,
             item_type: None,
             item_unexpected: false,
             item_identifier: None,
-            tag_value_store: unit_enum_store,
-            matcher_rule_handlers: Self::init_matcher_rule_handlers(),
-            tag_path,
-        }
+            item_matched: false,
+            item_resume: None,
+            tag_value_store: self.tag_value_store.clone(),
+            seen_tags: self.seen_tags.clone(),
+            tag_path_value_store: self.tag_path_value_store.clone(),
+            matcher_rule_handlers: TtlvDeserializer::init_matcher_rule_handlers(),
+            tag_path: self.tag_path.clone(),
+            report: self.report.clone(),
+            settings: self.settings.clone(),
+        })
     }
 
     /// Read a 3-byte TTLV tag into an [TtlvTag].
@@ -433,6 +2035,23 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
     /// structure) has been reached or an I/O error or `MalformedTtlvError` (e.g. if the tag or type are invalid or if
     /// the read cursor is past the last byte of the group).
     fn read_item_key(&mut self, use_group_fields: bool) -> Result<bool> {
+        loop {
+            if !self.read_item_header(use_group_fields)? {
+                return Ok(false);
+            }
+            if use_group_fields || !self.skip_duplicate_item()? {
+                break;
+            }
+        }
+
+        self.match_item_to_field();
+        Ok(true)
+    }
+
+    /// Read the tag and type of the next item, if any, in the current group.
+    ///
+    /// Returns Ok(false) if the end of the group has been reached.
+    fn read_item_header(&mut self, use_group_fields: bool) -> Result<bool> {
         if let Some(group_end) = self.group_end {
             match self.pos().cmp(&group_end) {
                 Ordering::Less => {
@@ -458,23 +2077,94 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
             self.item_tag = self.group_tag;
             self.item_type = self.group_type;
         } else {
-            self.item_start = self.pos() as u64;
+            self.item_start = self.pos();
             self.item_tag = None;
             self.item_type = None;
 
             let loc = self.location(); // See the note above about working around greedy closure capturing
-            self.item_tag = Some(
-                Self::read_tag(&mut self.src, Some(&mut self.state.borrow_mut()))
-                    .map_err(|err| Error::pinpoint(err, loc))?,
-            );
+            let item_tag = Self::read_tag(&mut self.src, Some(&mut self.state.borrow_mut()))
+                .map_err(|err| Error::pinpoint(err, loc))?;
+            self.item_tag = Some(item_tag);
+            self.seen_tags.borrow_mut().insert(item_tag);
 
             let loc = self.location(); // See the note above about working around greedy closure capturing
-            self.item_type = Some(
-                Self::read_type(&mut self.src, Some(&mut self.state.borrow_mut()))
-                    .map_err(|err| Error::pinpoint(err, loc))?,
-            );
+            let item_type = Self::read_type(&mut self.src, Some(&mut self.state.borrow_mut()))
+                .map_err(|err| Error::pinpoint(err, loc))?;
+            self.item_type = Some(item_type);
+
+            self.observe_item(self.item_start, item_tag, item_type)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Handle the item whose header was just read if it has the tag of a struct field that was already deserialized.
+    ///
+    /// Depending on the [DuplicateTagPolicy] either fails or skips over the item, returning Ok(true) if skipped.
+    fn skip_duplicate_item(&mut self) -> Result<bool> {
+        let item_tag = self.item_tag.unwrap();
+        if !self.group_matched_tags.contains(&item_tag) {
+            return Ok(false);
+        }
+
+        if self.settings.duplicate_tag_policy == DuplicateTagPolicy::Error {
+            return Err(pinpoint!(SerdeError::DuplicateTag(item_tag), self));
+        }
+
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
+        if let Err(err) = self.read_borrowed_value() {
+            return Err(pinpoint!(err, self));
+        }
+
+        // Keep a record of what was skipped, unless the value of this item was used in place of the first, see
+        // next_value_seed().
+        if !self.group_superseding_items.contains(&self.item_start) {
+            self.report.borrow_mut().ignored_items.push(IgnoredItem {
+                tag: item_tag,
+                r#type: self.item_type.unwrap(),
+                byte_range: (self.item_start as usize)..(self.pos() as usize),
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Find the last item in the current group with the same tag and type as the item whose header was just read.
+    ///
+    /// Returns the start of the item, if any, following the current one. Only the headers of the items are read, the
+    /// cursor is not moved.
+    fn find_last_duplicate_item(&self) -> Result<Option<u64>> {
+        let bytes: &'de [u8] = self.src.get_ref();
+        let mut src = Cursor::new(bytes);
+        src.set_position(self.src.position());
+        let group_end = self.group_end.unwrap();
+        let mut last = None;
+        let mut item_start = self.item_start;
+        let mut item_tag = self.item_tag.unwrap();
+
+        loop {
+            let item_len = TtlvLength::read(&mut src).map_err(|err| pinpoint!(err, item_start, item_tag))?;
+            let item_len = *item_len + TtlvByteString::calc_pad_bytes(*item_len);
+            src.set_position(src.position() + (item_len as u64));
+
+            item_start = src.position();
+            if item_start >= group_end {
+                return Ok(last);
+            }
+            item_tag = TtlvTag::read(&mut src).map_err(|err| pinpoint!(err, item_start))?;
+            let item_type = TtlvType::read(&mut src).map_err(|err| pinpoint!(err, item_start, item_tag))?;
+            if Some(item_tag) == self.item_tag && Some(item_type) == self.item_type {
+                last = Some(item_start);
+            }
         }
+    }
 
+    /// Determine the field to announce for the item whose header was just read, see `read_item_key()`.
+    fn match_item_to_field(&mut self) {
         // As we are invoked for every field that Serde derive found on the target Rust struct we need to handle the
         // not just the case where the expected tag is present in the byte stream in the expected position in the
         // sequence, but also:
@@ -517,24 +2207,175 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
         // byte stream.
 
         self.group_item_count += 1;
+        self.item_matched = false;
 
         self.item_unexpected = if self.group_fields.is_empty() {
             // We have no idea which field is expected so this field cannot be unexpected, but we also cannot set the
             // item identifier to announce for this field (though we might establish an identifier subsequently, e.g.
             // in the case of selecting the appropriate Rust enum variant).
             false
-        } else {
+        } else if self.settings.opaque_extension_types && matches!(self.item_type, Some(TtlvType::Extension(_))) {
+            // Items of a type that is not defined by the KMIP specification are never matched to fields by tag, only
+            // the catch-all field, if any, can take them. Otherwise the item is announced under a name that matches no
+            // field so that Serde derive skips it, and the next item is matched against the same field as this one.
             let field_index = self.group_item_count - 1;
-            let actual_tag_str = &self.item_tag.unwrap().to_string();
+            let catch_all_offset = self
+                .group_fields
+                .iter()
+                .skip(field_index)
+                .position(|field| *field == CATCH_ALL_FIELD_NAME);
+            if let Some(offset) = catch_all_offset {
+                self.group_item_count = field_index + offset + 1;
+                self.item_identifier = Some(CATCH_ALL_FIELD_NAME.to_string());
+            } else {
+                self.group_item_count = field_index;
+                self.item_identifier = Some(format!("{} {}", self.item_tag.unwrap(), self.item_type.unwrap()));
+            }
+            false
+        } else {
+            let mut field_index = self.group_item_count - 1;
+            let actual_tag = self.item_tag.unwrap();
+            let actual_tag_str = &actual_tag.to_string();
+            let tag_provider = self.settings.tag_provider;
+            let names_actual_tag = |field: &str| {
+                field == actual_tag_str
+                    || matches!(keyed_field_tags(field), Some((structure_tag, _)) if structure_tag == actual_tag_str)
+                    || matches!(tag_provider.and_then(|provider| provider(field)), Some(tag) if tag == actual_tag)
+            };
+
+            // If the item is not the expected one but is the one expected by a later field then the fields in between
+            // are absent from the byte stream. Skip past them so that Serde derive sees them as missing, which it
+            // permits for `Option` fields and for fields annotated with `#[serde(default)]`.
+            let is_expected = |field: &&str| names_actual_tag(field) || *field == CATCH_ALL_FIELD_NAME;
+            if matches!(self.group_fields.get(field_index), Some(field) if !is_expected(field)) {
+                if let Some(offset) = self.group_fields[field_index + 1..]
+                    .iter()
+                    .position(|field| names_actual_tag(field))
+                {
+                    field_index += offset + 1;
+                    self.group_item_count = field_index + 1;
+                }
+            }
+
             let expected_tag_str = self
                 .group_fields
                 .get(field_index)
                 .map_or_else(|| actual_tag_str.clone(), |v| v.to_string());
             self.item_identifier = Some(expected_tag_str.clone());
-            actual_tag_str != &expected_tag_str
+
+            // Remember which fields have been given a value so that any later items with the same tag can be
+            // recognized as duplicates. The items of a sequence field are consumed all at once and so are not seen
+            // here again, unless separated from the others by an item with a different tag.
+            if names_actual_tag(&expected_tag_str) && self.group_fields.iter().any(|field| names_actual_tag(field)) {
+                self.item_matched = true;
+                self.group_matched_tags.push(actual_tag);
+            }
+
+            expected_tag_str != CATCH_ALL_FIELD_NAME && !names_actual_tag(&expected_tag_str)
         };
+    }
 
-        Ok(true)
+    /// Read the tag and type of the next item in a sequence.
+    ///
+    /// Returns Ok(false) if the end of the containing group was reached or, for a homogenous sequence, if the next item
+    /// has a different tag or type than the first and so is not part of the sequence.
+    fn read_next_seq_item_key(&mut self) -> Result<bool> {
+        // The header of the first item in a sequence has already been read, unless the sequence is the members of a
+        // TTLV Structure being visited as a tuple struct.
+        let first_item_of_seq = self.group_item_count == 0 && !self.group_tuple;
+        if !self.read_item_key(first_item_of_seq)? {
+            // The end of the containing group was reached
+            Ok(false)
+        } else if self.group_homogenous && (self.item_tag != self.group_tag || self.item_type != self.group_type) {
+            // The next tag is not part of the sequence.
+            // Walk the cursor back before the tag because we didn't consume it.
+            self.src.set_position(self.item_start);
+            // And reset the state machine to expect a tag again
+            self.state.borrow_mut().restart_item();
+            Ok(false)
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Visit the TTLV Structures starting with the current item as a map keyed by the value of their `key_tag` child.
+    ///
+    /// If the current item does not have the tag of the structures the map is empty and the item is left to be read
+    /// again for the next field.
+    fn visit_keyed_structures<V>(&mut self, key_tag: TtlvTag, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.item_unexpected {
+            // Rewind as for a missing `Option` field, see `deserialize_option()`
+            self.src.set_position(self.item_start);
+            self.state.borrow_mut().restart_item();
+            let no_structures = serde::de::value::MapDeserializer::new(std::iter::empty::<((), ())>());
+            return visitor.visit_map(no_structures);
+        }
+
+        let seq_tag = self.item_tag.unwrap();
+        let seq_type = self.item_type.unwrap();
+        let seq_end = self.group_end.unwrap();
+
+        let mut seq_cursor = self.src.clone();
+
+        let items = self.nested(
+            &mut seq_cursor,
+            seq_tag,
+            seq_type,
+            seq_end,
+            &[],
+            true, // the structures must all have the same tag and type
+        )?;
+
+        let r = visitor.visit_map(KeyedMapAccess { items, key_tag }); // jumps to impl MapAccess for KeyedMapAccess
+
+        // The descendant parser cursor advanced but ours did not. Skip the tag that we just read.
+        self.src.set_position(seq_cursor.position());
+
+        r
+    }
+
+    /// Find the child item with the given tag of the TTLV Structure whose tag and type have just been read.
+    ///
+    /// Only the headers of the children are read in order to find the key item, the cursor is not moved.
+    fn find_key_item(&self, key_tag: TtlvTag) -> Result<KeyItemDeserializer<'de>> {
+        if self.item_type != Some(TtlvType::Structure) {
+            let error = SerdeError::UnexpectedType {
+                expected: TtlvType::Structure,
+                actual: self.item_type.unwrap(),
+            };
+            return Err(pinpoint!(error, self));
+        }
+
+        let bytes: &'de [u8] = self.src.get_ref();
+        let mut src = Cursor::new(bytes);
+        src.set_position(self.src.position());
+        let group_len = TtlvLength::read(&mut src).map_err(|err| pinpoint!(err, self))?;
+        let group_end = src.position() + (*group_len as u64);
+
+        while src.position() < group_end {
+            let item_start = src.position();
+            let item_tag = TtlvTag::read(&mut src).map_err(|err| pinpoint!(err, item_start))?;
+            let item_type = TtlvType::read(&mut src).map_err(|err| pinpoint!(err, item_start, item_tag))?;
+            let location = ErrorLocation::at(ByteOffset(item_start))
+                .with_tag(item_tag)
+                .with_type(item_type);
+            if item_tag == key_tag {
+                return Ok(KeyItemDeserializer {
+                    src,
+                    location,
+                    r#type: item_type,
+                });
+            }
+
+            let item_len = TtlvLength::read(&mut src).map_err(|err| Error::pinpoint(err, location))?;
+            let item_len = *item_len + TtlvByteString::calc_pad_bytes(*item_len);
+            src.set_position(src.position() + (item_len as u64));
+        }
+
+        Err(pinpoint!(SerdeError::MissingMapKey(key_tag), self))
     }
 
     fn get_start_tag_type(&mut self) -> Result<(u64, TtlvTag, TtlvType)> {
@@ -547,12 +2388,15 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
             let group_tag =
                 Self::read_tag(&mut self.src, Some(&mut self.state.borrow_mut())).map_err(|err| pinpoint!(err, loc))?;
             self.item_tag = Some(group_tag);
+            self.seen_tags.borrow_mut().insert(group_tag);
 
             let loc = self.location(); // See the note above about working around greedy closure capturing
             let group_type = Self::read_type(&mut self.src, Some(&mut self.state.borrow_mut()))
                 .map_err(|err| pinpoint!(err, loc))?;
             self.item_type = Some(group_type);
 
+            self.observe_item(group_start, group_tag, group_type)?;
+
             (group_start, group_tag, group_type)
         } else {
             // When invoked while visiting a map the opening tag and type of the struct header will have already been
@@ -562,9 +2406,80 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
         Ok((group_start, group_tag, group_type))
     }
 
+    fn read_integer(&mut self) -> Result<i32> {
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
+        match self.item_type {
+            Some(TtlvType::Integer) | None => {
+                let v = match self.read_ttlv::<TtlvInteger>() {
+                    Ok(v) => v,
+                    Err(err) => return Err(pinpoint!(err, self)),
+                };
+
+                // Remember the value so that it can be tested by variant matchers, e.g. against a bit mask
+                if let Some(item_tag) = self.item_tag {
+                    let int_hex = format!("0x{}", hex::encode_upper(v.to_be_bytes()));
+                    self.remember_tag_value(item_tag, int_hex);
+                }
+
+                Ok(*v)
+            }
+            Some(other_type) => {
+                let error = SerdeError::UnexpectedType {
+                    expected: TtlvType::Integer,
+                    actual: other_type,
+                };
+                Err(pinpoint!(error, self))
+            }
+        }
+    }
+
+    /// Read a TTLV Integer and narrow it to a smaller Rust integer type, e.g. `i8`, named by `target`.
+    fn read_narrowed_integer<T: TryFrom<i32>>(&mut self, target: &'static str) -> Result<T> {
+        let value = self.read_integer()?;
+        T::try_from(value).map_err(|_| pinpoint!(SerdeError::IntegerOutOfRange { value, target }, self))
+    }
+
+    /// Read a TTLV Big Integer and convert it to a Rust integer type, e.g. `i128`, named by `target`.
+    fn read_narrowed_big_integer<T: TryFrom<TtlvBigInteger>>(&mut self, target: &'static str) -> Result<T> {
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
+        match self.item_type {
+            Some(TtlvType::BigInteger) | None => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                let v = self.read_big_integer().map_err(|err| pinpoint!(err, loc))?;
+                T::try_from(v).map_err(|_| pinpoint!(SerdeError::BigIntegerOutOfRange { target }, self))
+            }
+            Some(other_type) => {
+                let error = SerdeError::UnexpectedType {
+                    expected: TtlvType::BigInteger,
+                    actual: other_type,
+                };
+                Err(pinpoint!(error, self))
+            }
+        }
+    }
+
+    /// Determine the tag named by the given Rust type or field name, either in hexadecimal form or, failing that, by
+    /// the configured tag provider if any.
+    fn resolve_tag(&self, name: &str) -> std::result::Result<TtlvTag, types::Error> {
+        TtlvTag::from_str(name).or_else(|err| {
+            self.settings
+                .tag_provider
+                .and_then(|provider| provider(name))
+                .ok_or(err)
+        })
+    }
+
     fn prepare_to_descend(&mut self, name: &'static str) -> Result<(u64, TtlvTag, TtlvType, u64)> {
         let loc = self.location(); // See the note above about working around greedy closure capturing
-        let wanted_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, loc))?;
+        let wanted_tag = self.resolve_tag(name).map_err(|err| pinpoint!(err, loc))?;
 
         let (group_start, group_tag, group_type) = self.get_start_tag_type()?;
 
@@ -595,32 +2510,140 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
         Ok((group_start, group_tag, group_type, group_end))
     }
 
-    fn is_variant_applicable(&self, variant: &'static str) -> Result<bool> {
-        // str::split_once() wasn't stablized until Rust 1.52.0 but as we want to be usable by Krill, and Krill
-        // supported Rust >= 1.49.0 at the time of writing, we use our own split_once() implementation.
-        pub fn split_once<'a>(value: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
-            value
-                .find(delimiter)
-                .map(|idx| (&value[..idx], &value[idx + delimiter.len()..]))
-        }
-
-        if let Some(rule) = variant.strip_prefix("if ") {
-            for (op, handler_fn) in &self.matcher_rule_handlers {
-                if let Some((wanted_tag, wanted_val)) = split_once(rule, op) {
-                    return handler_fn(self, wanted_tag.trim(), wanted_val.trim()).map_err(|err| pinpoint!(err, self));
-                }
+    /// Signal that the value of a TTLV Structure is about to be deserialized.
+    ///
+    /// Fails with [ErrorKind::NestingDepthExceedsLimit] if TTLV Structures are then nested more deeply than the
+    /// configured limit, or than the call stack can bear if no limit or a higher limit is configured.
+    fn enter_structure(&mut self) -> Result<()> {
+        let depth = {
+            let mut state = self.state.borrow_mut();
+            state.enter_structure();
+            state.current_depth()
+        };
+        if depth
+            > self
+                .settings
+                .max_nesting_depth
+                .map_or(MAX_NESTING_DEPTH, |max| max.min(MAX_NESTING_DEPTH))
+        {
+            let error = ErrorKind::NestingDepthExceedsLimit(depth);
+            return Err(pinpoint!(error, self));
+        }
+        Ok(())
+    }
+
+    /// Visit the members of the TTLV Structure whose header has just been read.
+    ///
+    /// The `access` argument determines whether the members are visited as a map or as a sequence, see
+    /// [StructureAccess].
+    fn visit_structure<V>(
+        &mut self,
+        group_tag: TtlvTag,
+        group_type: TtlvType,
+        group_end: u64,
+        fields: &'static [&'static str],
+        access: StructureAccess,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut struct_cursor = self.src.clone();
+
+        self.enter_structure()?;
+
+        self.tag_path.borrow_mut().push(group_tag);
+
+        let mut descendent_parser = self.nested(
+            &mut struct_cursor,
+            group_tag,
+            group_type,
+            group_end,
+            fields,
+            false, // struct member fields can have different tags and types
+        )?;
+        descendent_parser.group_any = access == StructureAccess::Tags;
+        descendent_parser.group_tuple = access == StructureAccess::Tuple;
+
+        let r = if access == StructureAccess::Tuple {
+            let r = visitor.visit_seq(descendent_parser); // jumps to impl SeqAccess below
+
+            // Serde derive stops visiting once it has seen as many items as the tuple struct has fields, skip any
+            // remaining items in the structure.
+            struct_cursor.set_position(group_end);
+            r
+        } else {
+            visitor.visit_map(descendent_parser) // jumps to impl MapAccess below
+        };
+
+        // The descendant parser cursor advanced but ours did not. Skip the tag that we just read.
+        self.src.set_position(struct_cursor.position());
+
+        match r {
+            Ok(_) => {
+                self.tag_path.borrow_mut().pop();
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                self.state
+                    .borrow_mut()
+                    .leave_structure()
+                    .map_err(|err| pinpoint!(err, loc))?;
+                r
+            }
+            Err(err) => {
+                // Errors can be raised directly by Serde Derive, e.g. SerdeError::Other("missing field"), which
+                // necessarily have ErrorLocation::is_unknown() as Serde Derive is not aware of our ErrorLocation type.
+                // When that happens, this is the first opportunity after calling `visitor.visit_map()` that we have to
+                // add the missing location data. However, if the error _was_ raised by our code and not by Serde
+                // Derive it probably already has location details. Therefore we "merge" the current location into the
+                // error so that only missing details are added if needed as the existing location details may more
+                // point more accurately to the source of the problem than we are able to indicate here (we don't know
+                // where in the `visit_map()` process the issue occured, on which field and at which byte, we just use
+                // the current cursor position and hope that is good enough).
+                let (kind, loc) = err.into_inner();
+                let new_loc = loc.merge(self.location());
+                Err(Error::new(kind, new_loc))
             }
+        }
+    }
 
-            return Err(pinpoint!(SerdeError::InvalidVariantMatcherSyntax(variant.into()), self));
+    fn is_variant_applicable(&self, variant: &'static str) -> Result<bool> {
+        if let Some(rules) = variant.strip_prefix("if ") {
+            // The variant is applicable only if all of the "&&" separated conditions match. Every condition is checked,
+            // even once one has failed to match, so that syntax errors are reported consistently.
+            let mut applicable = true;
+            for rule in rules.split("&&") {
+                applicable &= self.is_matcher_rule_satisfied(variant, rule)?;
+            }
+            return Ok(applicable);
         }
 
         Ok(false)
     }
 
+    fn is_matcher_rule_satisfied(&self, variant: &'static str, rule: &str) -> Result<bool> {
+        let rule = rule.trim();
+        if let Some(wanted_tag) = rule.strip_prefix("present ") {
+            return self.is_tag_seen(wanted_tag).map_err(|err| pinpoint!(err, self));
+        } else if let Some(wanted_tag) = rule.strip_prefix("absent ") {
+            return self
+                .is_tag_seen(wanted_tag)
+                .map(|seen| !seen)
+                .map_err(|err| pinpoint!(err, self));
+        }
+
+        for (op, handler_fn) in &self.matcher_rule_handlers {
+            if let Some((wanted_tag, wanted_val)) = split_once(rule, op) {
+                return handler_fn(self, wanted_tag.trim(), wanted_val.trim()).map_err(|err| pinpoint!(err, self));
+            }
+        }
+
+        Err(pinpoint!(SerdeError::InvalidVariantMatcherSyntax(variant.into()), self))
+    }
+
     fn handle_matcher_rule_eq(&self, wanted_tag: &str, wanted_val: &str) -> std::result::Result<bool, types::Error> {
         if wanted_tag == "type" {
             // See if wanted_val is a literal string that matches the TTLV type we are currently deserializing
-            // TODO: Add BigInteger and Interval when supported
+            // TODO: Add BigInteger when supported
             if matches!(
                 (wanted_val, self.item_type.unwrap()),
                 ("Structure", TtlvType::Structure)
@@ -631,23 +2654,70 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
                     | ("TextString", TtlvType::TextString)
                     | ("ByteString", TtlvType::ByteString)
                     | ("DateTime", TtlvType::DateTime)
+                    | ("Interval", TtlvType::Interval)
             ) {
                 return Ok(true);
             }
-        } else if let Ok(wanted_tag) = TtlvTag::from_str(wanted_tag) {
-            if let Some(seen_enum_val) = self.lookup_tag_value(wanted_tag) {
-                if seen_enum_val == wanted_val {
-                    return Ok(true);
-                }
+        } else if let Some((wanted_tag, mask)) = split_once(wanted_tag, "&") {
+            if let Some(masked_val) = self.lookup_masked_value(wanted_tag, mask)? {
+                return Ok(masked_val == parse_matcher_value(wanted_val)?);
+            }
+        } else if let Ok(Some(seen_enum_val)) = self.lookup_selector_value(wanted_tag) {
+            if seen_enum_val == wanted_val {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn handle_matcher_rule_ne(&self, wanted_tag: &str, wanted_val: &str) -> std::result::Result<bool, types::Error> {
+        if wanted_tag == "type" {
+            return self.handle_matcher_rule_eq(wanted_tag, wanted_val).map(|is_eq| !is_eq);
+        } else if let Some((wanted_tag, mask)) = split_once(wanted_tag, "&") {
+            if let Some(masked_val) = self.lookup_masked_value(wanted_tag, mask)? {
+                return Ok(masked_val != parse_matcher_value(wanted_val)?);
             }
+        } else if let Ok(Some(seen_val)) = self.lookup_selector_value(wanted_tag) {
+            // Only a value that was actually seen can differ from the wanted value
+            return Ok(seen_val != wanted_val);
+        }
+
+        Ok(false)
+    }
+
+    fn handle_matcher_rule_starts_with(
+        &self,
+        wanted_tag: &str,
+        wanted_prefix: &str,
+    ) -> std::result::Result<bool, types::Error> {
+        if let Some(seen_val) = self.lookup_selector_value(wanted_tag)? {
+            return Ok(seen_val.starts_with(wanted_prefix));
+        }
+
+        Ok(false)
+    }
+
+    fn handle_matcher_rule_like(
+        &self,
+        wanted_tag: &str,
+        wanted_pattern: &str,
+    ) -> std::result::Result<bool, types::Error> {
+        if let Some(seen_val) = self.lookup_selector_value(wanted_tag)? {
+            return Ok(is_wildcard_match(wanted_pattern, &seen_val));
         }
 
         Ok(false)
     }
 
+    fn is_tag_seen(&self, wanted_tag: &str) -> std::result::Result<bool, types::Error> {
+        let wanted_tag = TtlvTag::from_str(wanted_tag.trim())?;
+        Ok(self.seen_tags.borrow().contains(&wanted_tag))
+    }
+
     fn handle_matcher_rule_ge(&self, wanted_tag: &str, wanted_val: &str) -> std::result::Result<bool, types::Error> {
-        if let Some(seen_enum_val) = self.tag_value_store.borrow().get(&TtlvTag::from_str(wanted_tag)?) {
-            if TtlvTag::from_str(seen_enum_val)?.deref() >= TtlvTag::from_str(wanted_val)?.deref() {
+        if let Some(seen_enum_val) = self.lookup_selector_value(wanted_tag)? {
+            if parse_matcher_value(&seen_enum_val)? >= parse_matcher_value(wanted_val)? {
                 return Ok(true);
             }
         }
@@ -658,13 +2728,25 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
     fn handle_matcher_rule_in(&self, wanted_tag: &str, wanted_val: &str) -> std::result::Result<bool, types::Error> {
         let wanted_values = wanted_val.strip_prefix('[').and_then(|v| v.strip_suffix(']'));
         if let Some(wanted_values) = wanted_values {
-            if let Some(seen_enum_val) = self.tag_value_store.borrow().get(&TtlvTag::from_str(wanted_tag)?) {
+            if let Some(seen_enum_val) = self.lookup_selector_value(wanted_tag)? {
                 for wanted_value in wanted_values.split(',') {
-                    if *seen_enum_val == wanted_value.trim() {
+                    if seen_enum_val == wanted_value.trim() {
                         return Ok(true);
                     }
                 }
             }
+        } else if let Some((start, end)) = split_once(wanted_val, "..") {
+            // A Rust style range, either exclusive (start..end) or inclusive (start..=end) of the end value
+            let (end, inclusive) = match end.strip_prefix('=') {
+                Some(end) => (end, true),
+                None => (end, false),
+            };
+            let start = parse_matcher_value(start.trim())?;
+            let end = parse_matcher_value(end.trim())?;
+            if let Some(seen_enum_val) = self.lookup_selector_value(wanted_tag)? {
+                let seen = parse_matcher_value(&seen_enum_val)?;
+                return Ok(seen >= start && (seen < end || (inclusive && seen == end)));
+            }
         }
 
         Ok(false)
@@ -677,29 +2759,172 @@ impl<'de: 'c, 'c> TtlvDeserializer<'de, 'c> {
             loc = loc.with_tag(tag);
         }
 
-        if let Some(r#type) = self.item_type {
-            loc = loc.with_type(r#type);
+        if let Some(r#type) = self.item_type {
+            loc = loc.with_type(r#type);
+        }
+
+        if let Some(resolver) = self.settings.tag_name_resolver {
+            loc = loc.with_tag_name_resolver(resolver);
+        }
+
+        loc
+    }
+
+    /// Show the header of the TTLV item whose tag and type have just been read to the configured observer, if any.
+    ///
+    /// The length is peeked at rather than read as it is read later, if at all, as part of reading the value. If the
+    /// length bytes are missing the observer is not invoked, the read of the value will fail instead.
+    fn observe_item(&self, item_start: u64, tag: TtlvTag, r#type: TtlvType) -> Result<()> {
+        if let Some(observer) = &self.settings.item_observer {
+            let pos = self.src.position() as usize;
+            let length_bytes = self.src.get_ref().get(pos..pos + 4);
+            if let Some(length) = length_bytes.and_then(|bytes| <[u8; 4]>::try_from(bytes).ok()) {
+                let header = ItemHeader {
+                    offset: ByteOffset(item_start),
+                    tag,
+                    r#type,
+                    length: u32::from_be_bytes(length),
+                };
+                if let Err(reason) = observer(&header) {
+                    let location = self.location();
+                    return Err(Error::pinpoint(ErrorKind::RejectedByObserver(reason), location));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remember_tag_value<T>(&self, tag: TtlvTag, value: T)
+    where
+        String: From<T>,
+    {
+        let value = String::from(value);
+        self.tag_path_value_store
+            .borrow_mut()
+            .push(&self.tag_path.borrow(), tag, value.clone());
+        self.tag_value_store.borrow_mut().insert(tag, value);
+    }
+
+    fn lookup_tag_value(&self, tag: TtlvTag) -> Option<String> {
+        self.tag_value_store.borrow().get(&tag).cloned()
+    }
+
+    /// Lookup the last seen value of the item identified by a variant matcher selector.
+    ///
+    /// The selector is either a single tag, e.g. `0x42006A`, which matches an item with that tag anywhere, or a `/`
+    /// separated path of tags, e.g. `0x420077/0x420069/0x42006A`, which only matches an item with that tag if it is
+    /// nested in structures with the preceding tags. A path that starts with `/` must match the entire path from the
+    /// root structure, otherwise the path can start at any depth.
+    fn lookup_selector_value(&self, selector: &str) -> std::result::Result<Option<String>, types::Error> {
+        if !selector.contains('/') {
+            return Ok(self.lookup_tag_value(TtlvTag::from_str(selector)?));
+        }
+
+        let is_absolute = selector.starts_with('/');
+        let mut wanted_path = Vec::new();
+        for tag in selector.trim_start_matches('/').split('/') {
+            wanted_path.push(TtlvTag::from_str(tag.trim())?);
+        }
+
+        let store = self.tag_path_value_store.borrow();
+        Ok(store.find(&wanted_path, is_absolute).map(str::to_string))
+    }
+
+    /// Lookup the last seen value of the item identified by a variant matcher selector and apply a bit mask to it.
+    ///
+    /// Used for `0xNNNNNN & 0xMMMMMMMM` conditions which test individual bits of mask values such as the KMIP
+    /// Cryptographic Usage Mask.
+    fn lookup_masked_value(&self, selector: &str, mask: &str) -> std::result::Result<Option<u32>, types::Error> {
+        let mask = parse_matcher_value(mask.trim())?;
+        match self.lookup_selector_value(selector.trim())? {
+            Some(seen_val) => Ok(Some(parse_matcher_value(&seen_val)? & mask)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the length, value and padding of the current TTLV item, verifying the padding if so configured.
+    fn read_ttlv<V: SerializableTtlvType>(&mut self) -> std::result::Result<V, types::Error> {
+        if self.settings.verify_padding {
+            V::read_strict(&mut self.src)
+        } else {
+            V::read(&mut self.src)
         }
+    }
 
-        loc
+    /// Like [Self::read_ttlv()] for a TTLV Big Integer, enforcing the configured limit on the length of the value.
+    fn read_big_integer(&mut self) -> std::result::Result<TtlvBigInteger, types::Error> {
+        if self.settings.verify_padding {
+            TtlvBigInteger::read_strict_with_max_len(&mut self.src, self.settings.max_big_integer_len)
+        } else {
+            TtlvBigInteger::read_with_max_len(&mut self.src, self.settings.max_big_integer_len)
+        }
     }
 
-    fn remember_tag_value<T>(&self, tag: TtlvTag, value: T)
-    where
-        String: From<T>,
-    {
-        self.tag_value_store.borrow_mut().insert(tag, value.into());
+    /// Read the length and value of the current TTLV item without copying the value bytes.
+    ///
+    /// The returned slice borrows from the input buffer rather than from this deserializer and so can be handed to
+    /// Serde as borrowed data. The cursor is advanced past the value and any trailing padding bytes.
+    fn read_borrowed_value(&mut self) -> std::result::Result<&'de [u8], types::Error> {
+        let value_len = TtlvDeserializer::read_length(&mut self.src, None)?;
+        let num_pad_bytes = TtlvByteString::calc_pad_bytes(value_len);
+        let bytes: &'de [u8] = self.src.get_ref();
+        let start = self.src.position() as usize;
+        let end = start + value_len as usize;
+        let padded_end = end + num_pad_bytes as usize;
+        if padded_end > bytes.len() {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        if self.settings.verify_padding && bytes[end..padded_end].iter().any(|&b| b != 0) {
+            return Err(types::Error::InvalidTtlvPadding(
+                self.item_type.unwrap_or(TtlvType::ByteString),
+            ));
+        }
+        self.src.set_position(padded_end as u64);
+        Ok(&bytes[start..end])
     }
 
-    fn lookup_tag_value(&self, tag: TtlvTag) -> Option<String> {
-        self.tag_value_store.borrow().get(&tag).cloned()
+    /// Read the length and value of the current TTLV Text String without copying the value bytes if possible.
+    ///
+    /// If the value is not valid UTF-8 it is rejected, unless [Config::with_lossy_text_strings()] is in effect in which
+    /// case invalid sequences are replaced by U+FFFD and the item is recorded in the [DeserializationReport].
+    fn read_text_string(&mut self) -> Result<Cow<'de, str>> {
+        let item_start = self.pos() - 4; // the tag and type precede the length
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        let bytes = self.read_borrowed_value().map_err(|err| pinpoint!(err, loc.clone()))?;
+        match std::str::from_utf8(bytes) {
+            Ok(str) => Ok(Cow::Borrowed(str)),
+            Err(_) if self.settings.lossy_text_strings => {
+                self.report.borrow_mut().lossy_text_strings.push(LossyTextString {
+                    tag: self.item_tag.unwrap(),
+                    byte_range: (item_start as usize)..(self.pos() as usize),
+                });
+                Ok(String::from_utf8_lossy(bytes))
+            }
+            Err(_) => Err(pinpoint!(types::Error::InvalidTtlvValue(TtlvType::TextString), loc)),
+        }
     }
 
-    fn seek_forward(&mut self, num_bytes_to_skip: u32) -> Result<u64> {
-        use std::io::Seek;
-        self.src
-            .seek(std::io::SeekFrom::Current(num_bytes_to_skip as i64))
-            .map_err(|err| pinpoint!(err, self))
+    /// Read the current TTLV item, whatever its tag and type, without interpreting its value.
+    ///
+    /// Returns the 3 tag bytes and the 1 type byte followed by the value bytes. The value of a TTLV Structure is
+    /// returned as the raw bytes of its child items.
+    fn read_raw_item(&mut self) -> Result<Vec<u8>> {
+        let (_, item_tag, item_type) = self.get_start_tag_type()?;
+
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
+
+        // The length of a TTLV Structure is always a multiple of 8 so no padding will be skipped in that case.
+        let value = self.read_borrowed_value().map_err(|err| pinpoint!(err, self))?;
+
+        let mut raw_item = Vec::with_capacity(4 + value.len());
+        raw_item.extend_from_slice(&<[u8; 3]>::from(item_tag));
+        raw_item.push(u8::from(item_type));
+        raw_item.extend_from_slice(value);
+        Ok(raw_item)
     }
 }
 
@@ -768,56 +2993,41 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
         V: Visitor<'de>,
     {
         let (_, group_tag, group_type, group_end) = self.prepare_to_descend(name)?;
-
-        let mut struct_cursor = self.src.clone();
-
-        self.tag_path.borrow_mut().push(group_tag);
-
-        let descendent_parser = TtlvDeserializer::from_cursor(
-            &mut struct_cursor,
-            self.state.clone(),
+        self.visit_structure(
             group_tag,
             group_type,
             group_end,
             fields,
-            false, // struct member fields can have different tags and types
-            self.tag_value_store.clone(),
-            self.tag_path.clone(),
-        );
-
-        let r = visitor.visit_map(descendent_parser); // jumps to impl MapAccess below
-
-        // The descendant parser cursor advanced but ours did not. Skip the tag that we just read.
-        self.src.set_position(struct_cursor.position());
-
-        match r {
-            Ok(_) => {
-                self.tag_path.borrow_mut().pop();
-                r
-            }
-            Err(err) => {
-                // Errors can be raised directly by Serde Derive, e.g. SerdeError::Other("missing field"), which
-                // necessarily have ErrorLocation::is_unknown() as Serde Derive is not aware of our ErrorLocation type.
-                // When that happens, this is the first opportunity after calling `visitor.visit_map()` that we have to
-                // add the missing location data. However, if the error _was_ raised by our code and not by Serde
-                // Derive it probably already has location details. Therefore we "merge" the current location into the
-                // error so that only missing details are added if needed as the existing location details may more
-                // point more accurately to the source of the problem than we are able to indicate here (we don't know
-                // where in the `visit_map()` process the issue occured, on which field and at which byte, we just use
-                // the current cursor position and hope that is good enough).
-                let (kind, loc) = err.into_inner();
-                let new_loc = loc.merge(self.location());
-                Err(Error::new(kind, new_loc))
-            }
-        }
+            StructureAccess::Fields,
+            visitor,
+        )
     }
 
     /// Deserialize the bytes at the current cursor position to a Rust struct with a single field.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    ///
+    /// A [TtlvItem] is handed the raw bytes of the current TTLV item instead, whatever its tag and type.
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self) // jumps to to the appropriate deserializer fn such as deserialize_string()
+        if name == RAW_ITEM_NAME {
+            let raw_item = self.read_raw_item()?;
+            visitor.visit_bytes(&raw_item)
+        } else if name == TTLV_INTERVAL_NAME {
+            let loc = self.location(); // See the note above about working around greedy closure capturing
+            let (_, _, item_type) = self.get_start_tag_type()?;
+            if item_type != TtlvType::Interval {
+                let error = SerdeError::UnexpectedType {
+                    expected: TtlvType::Interval,
+                    actual: item_type,
+                };
+                return Err(pinpoint!(error, loc));
+            }
+            self.deserialize_u32(visitor)
+        } else {
+            // Jumps to the appropriate deserializer fn such as deserialize_string()
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     /// Deserialize the bytes at the current cursor position to a Rust vector.
@@ -851,23 +3061,28 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
     where
         V: Visitor<'de>,
     {
+        // Items after the first of the sequence are elements of it rather than duplicates, see next_value_seed().
+        if let Some(resume_pos) = self.item_resume.take() {
+            self.src.set_position(resume_pos);
+        }
+
         let seq_tag = self.item_tag.unwrap();
         let seq_type = self.item_type.unwrap();
         let seq_end = self.group_end.unwrap();
 
+        // A catch-all field collects every remaining item in the structure, whatever their tags and types.
+        let seq_homogenous = self.item_identifier.as_deref() != Some(CATCH_ALL_FIELD_NAME);
+
         let mut seq_cursor = self.src.clone();
 
-        let descendent_parser = TtlvDeserializer::from_cursor(
+        let descendent_parser = self.nested(
             &mut seq_cursor,
-            self.state.clone(),
             seq_tag,
             seq_type,
             seq_end,
             &[],
-            true, // sequence fields must all have the same tag and type
-            self.tag_value_store.clone(),
-            self.tag_path.clone(),
-        );
+            seq_homogenous, // sequence fields must normally all have the same tag and type
+        )?;
 
         let r = visitor.visit_seq(descendent_parser); // jumps to impl SeqAccess below
 
@@ -928,7 +3143,7 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
             // TTLV tag again.
             self.src.set_position(self.item_start);
             // Reset the state machine to expect a tag as it's currently expecting a value but should expect a tag.
-            self.state.borrow_mut().reset();
+            self.state.borrow_mut().restart_item();
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -1020,6 +3235,8 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
     ///
     /// The if syntax currently only supports matching against the value of earlier seen enum or string TTLV items that
     /// are looked up by their tag.
+    ///
+    /// A variant named `else` is selected if no other variant matches.
     fn deserialize_enum<V>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
@@ -1050,12 +3267,40 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
 
         self.item_identifier = None;
 
+        // Give the configured variant selector function, if any, the first chance to choose the variant
+        if let Some(selector) = &self.settings.variant_selector {
+            let context = MatcherContext {
+                enum_name: name,
+                variants,
+                item_tag: self.item_tag,
+                item_type: self.item_type,
+                tag_values: self.tag_value_store.borrow(),
+                seen_tags: self.seen_tags.borrow(),
+                tag_path_values: self.tag_path_value_store.borrow(),
+            };
+            self.item_identifier = selector(&context).map(str::to_string);
+        }
+
         // Check each enum variant name to see if it is of the form "if enum_tag==enum_val" and if so extract
         // enum_tag and enum_value:
-        for v in variants {
-            if self.is_variant_applicable(v)? {
-                self.item_identifier = Some(v.to_string());
-                break;
+        if self.item_identifier.is_none() {
+            for v in variants {
+                if self.is_variant_applicable(v)? {
+                    self.item_identifier = Some(v.to_string());
+                    break;
+                }
+            }
+        }
+
+        // If no "if" variant was applicable fall back to the "else" variant, if any. For enums whose variants are named
+        // after TTLV enumeration values, rather than selected by "if" matchers, the value has to be read first to know
+        // whether or not it matches one of the variants, see below.
+        let has_fallback_variant = variants.contains(&FALLBACK_VARIANT_NAME);
+        if self.item_identifier.is_none() && has_fallback_variant {
+            let is_matcher_enum = variants.iter().any(|v| v.starts_with("if "));
+            let is_enum_value = matches!(self.item_type, Some(TtlvType::Enumeration) | Some(TtlvType::Integer));
+            if is_matcher_enum || !is_enum_value {
+                self.item_identifier = Some(FALLBACK_VARIANT_NAME.to_string());
             }
         }
 
@@ -1074,13 +3319,17 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
                         .borrow_mut()
                         .advance(FieldType::LengthAndValue)
                         .map_err(|err| pinpoint!(err, loc.clone()))?;
-                    let enum_val = TtlvEnumeration::read(self.src).map_err(|err| pinpoint!(err, loc))?;
+                    let enum_val = self.read_ttlv::<TtlvEnumeration>().map_err(|err| pinpoint!(err, loc))?;
                     let enum_hex = format!("0x{}", hex::encode_upper(enum_val.to_be_bytes()));
 
                     // Insert or replace the last value seen for this enum in our enum value lookup table
                     self.remember_tag_value(self.item_tag.unwrap(), &enum_hex);
 
-                    self.item_identifier = Some(enum_hex);
+                    if has_fallback_variant && !variants.contains(&enum_hex.as_str()) {
+                        self.item_identifier = Some(FALLBACK_VARIANT_NAME.to_string());
+                    } else {
+                        self.item_identifier = Some(enum_hex);
+                    }
                 }
 
                 visitor.visit_enum(&mut *self) // jumps to impl EnumAccess (ending at unit_variant()) below
@@ -1137,7 +3386,33 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
         }
     }
 
+    /// Deserialize a TTLV Integer into a Rust `i8`, failing if the value does not fit.
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let v = self.read_narrowed_integer("i8")?;
+        visitor.visit_i8(v)
+    }
+
+    /// Deserialize a TTLV Integer into a Rust `i16`, failing if the value does not fit.
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let v = self.read_narrowed_integer("i16")?;
+        visitor.visit_i16(v)
+    }
+
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let v = self.read_integer()?;
+        visitor.visit_i32(v)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
@@ -1147,13 +3422,19 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
             .advance(FieldType::LengthAndValue)
             .map_err(|err| pinpoint!(err, loc))?;
         match self.item_type {
-            Some(TtlvType::Integer) | None => {
-                let v = TtlvInteger::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                visitor.visit_i32(*v)
+            Some(TtlvType::LongInteger) | None => {
+                let v = self
+                    .read_ttlv::<TtlvLongInteger>()
+                    .map_err(|err| pinpoint!(err, self))?;
+                visitor.visit_i64(*v)
+            }
+            Some(TtlvType::DateTime) => {
+                let v = self.read_ttlv::<TtlvDateTime>().map_err(|err| pinpoint!(err, self))?;
+                visitor.visit_i64(*v)
             }
             Some(other_type) => {
                 let error = SerdeError::UnexpectedType {
-                    expected: TtlvType::Integer,
+                    expected: TtlvType::LongInteger,
                     actual: other_type,
                 };
                 Err(pinpoint!(error, self))
@@ -1161,7 +3442,12 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
         }
     }
 
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    /// Deserialize a TTLV Enumeration to its raw value, or a TTLV Interval to its number of seconds.
+    ///
+    /// This permits passing through Enumeration values for which no Rust enum variant exists, e.g. vendor specific
+    /// values. As with Rust enums the value is remembered for use by later variant matchers such as
+    /// `if 0xNNNNNN==0xMMMMMMMM`.
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
@@ -1171,17 +3457,26 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
             .advance(FieldType::LengthAndValue)
             .map_err(|err| pinpoint!(err, loc))?;
         match self.item_type {
-            Some(TtlvType::LongInteger) | None => {
-                let v = TtlvLongInteger::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                visitor.visit_i64(*v)
+            Some(TtlvType::Enumeration) | None => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                let v = self.read_ttlv::<TtlvEnumeration>().map_err(|err| pinpoint!(err, loc))?;
+
+                // Insert or replace the last value seen for this enum in our enum value lookup table
+                if let Some(item_tag) = self.item_tag {
+                    let enum_hex = format!("0x{}", hex::encode_upper(v.to_be_bytes()));
+                    self.remember_tag_value(item_tag, enum_hex);
+                }
+
+                visitor.visit_u32(*v)
             }
-            Some(TtlvType::DateTime) => {
-                let v = TtlvDateTime::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                visitor.visit_i64(*v)
+            Some(TtlvType::Interval) => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                let v = self.read_ttlv::<TtlvInterval>().map_err(|err| pinpoint!(err, loc))?;
+                visitor.visit_u32(*v)
             }
             Some(other_type) => {
                 let error = SerdeError::UnexpectedType {
-                    expected: TtlvType::LongInteger,
+                    expected: TtlvType::Enumeration,
                     actual: other_type,
                 };
                 Err(pinpoint!(error, self))
@@ -1200,7 +3495,7 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
             .map_err(|err| pinpoint!(err, loc))?;
         match self.item_type {
             Some(TtlvType::Boolean) | None => {
-                let v = TtlvBoolean::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
+                let v = self.read_ttlv::<TtlvBoolean>().map_err(|err| pinpoint!(err, self))?;
                 visitor.visit_bool(*v)
             }
             Some(other_type) => {
@@ -1223,8 +3518,18 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
             .advance(FieldType::LengthAndValue)
             .map_err(|err| pinpoint!(err, loc))?;
         match self.item_type {
+            Some(TtlvType::TextString) | None if self.settings.lossy_text_strings => {
+                let str = self.read_text_string()?.into_owned();
+
+                // Insert or replace the last value seen for this tag in our value lookup table
+                self.remember_tag_value(self.item_tag.unwrap(), str.clone());
+
+                visitor.visit_string(str)
+            }
             Some(TtlvType::TextString) | None => {
-                let str = TtlvTextString::read(&mut self.src).map_err(|err| pinpoint!(err, self.location()))?;
+                let str = self
+                    .read_ttlv::<TtlvTextString>()
+                    .map_err(|err| pinpoint!(err, self.location()))?;
 
                 // Insert or replace the last value seen for this tag in our value lookup table
                 self.remember_tag_value(self.item_tag.unwrap(), str.0.clone());
@@ -1241,6 +3546,83 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
         }
     }
 
+    /// Deserialize a TTLV Text String into a `&str` borrowed from the input buffer, i.e. without allocating.
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
+        match self.item_type {
+            Some(TtlvType::TextString) | None => {
+                let str = self.read_text_string()?;
+
+                // Insert or replace the last value seen for this tag in our value lookup table
+                self.remember_tag_value(self.item_tag.unwrap(), &*str);
+
+                match str {
+                    Cow::Borrowed(str) => visitor.visit_borrowed_str(str),
+                    Cow::Owned(str) => visitor.visit_string(str),
+                }
+            }
+            Some(other_type) => {
+                let error = SerdeError::UnexpectedType {
+                    expected: TtlvType::TextString,
+                    actual: other_type,
+                };
+                Err(pinpoint!(error, self))
+            }
+        }
+    }
+
+    /// Deserialize a TTLV Byte String or Big Integer into a `&[u8]` borrowed from the input buffer, i.e. without
+    /// copying. Serde directs `&[u8]` and `serde_bytes::Bytes` to this deserializer function.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
+        match self.item_type {
+            Some(TtlvType::ByteString) | Some(TtlvType::BigInteger) | None => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                let bytes = self.read_borrowed_value().map_err(|err| pinpoint!(err, loc))?;
+                visitor.visit_borrowed_bytes(bytes)
+            }
+            Some(other_type) => {
+                let error = SerdeError::UnexpectedType {
+                    expected: TtlvType::ByteString,
+                    actual: other_type,
+                };
+                Err(pinpoint!(error, self))
+            }
+        }
+    }
+
+    /// Deserialize a TTLV Big Integer into a Rust `i128`, failing if the value does not fit.
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let v = self.read_narrowed_big_integer("i128")?;
+        visitor.visit_i128(v)
+    }
+
+    /// Deserialize a TTLV Big Integer into a Rust `u128`, failing if the value is negative or does not fit.
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let v = self.read_narrowed_big_integer("u128")?;
+        visitor.visit_u128(v)
+    }
+
     /// Use #[serde(with = "serde_bytes")] to direct Serde to this deserializer function for type Vec<u8>.
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -1253,7 +3635,7 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
             .map_err(|err| pinpoint!(err, loc))?;
         match self.item_type {
             Some(TtlvType::ByteString) | Some(TtlvType::BigInteger) | None => {
-                let v = TtlvByteString::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
+                let v = self.read_ttlv::<TtlvByteString>().map_err(|err| pinpoint!(err, self))?;
                 visitor.visit_byte_buf(v.0)
             }
             Some(other_type) => {
@@ -1274,67 +3656,31 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
     where
         V: Visitor<'de>,
     {
-        // Skip over the TTLV item. We can't just read the length and skip it because the meaning of the length is TTLV
-        // type dependent. For some types it is the entire byte size of the TTLV item, for others it is the length of
-        // the TTLV item value excluding padding. For TTLV Structures skip the whole structure content. For other types
-        // deserialize them but discard the deserialized value.
-
-        if matches!(self.item_type.unwrap(), TtlvType::Structure) {
-            // We're going to read the structure length and then skip it without reading the value
-            // Reading the length advances the state machine past the length but not past the value
-            // so we have to do that manually.
+        // Skip over the TTLV item without parsing its value. For TTLV Structures the length is the byte size of the
+        // entire structure content, for other types it is the length of the value excluding padding. As the length of
+        // a TTLV Structure is always a multiple of 8 skipping the value plus any padding is correct in both cases. This
+        // takes the same time no matter how large or deeply nested the skipped item is.
 
-            // Use the TTLV item length to skip the structure.
-            let num_bytes_to_skip = TtlvDeserializer::read_length(&mut self.src, Some(&mut self.state.borrow_mut()))
-                .map_err(|err| pinpoint!(err, self.location()))?;
+        // When ignoring the entire input, e.g. `from_slice::<IgnoredAny>()`, the tag and type have not been read yet.
+        let (item_start, item_tag, item_type) = self.get_start_tag_type()?;
 
-            // Skip the value bytes
-            self.seek_forward(num_bytes_to_skip)?;
-
-            // Tell the state machine that we're finished reading this TTLV item
-            self.state.borrow_mut().reset();
-        } else {
-            // We're going to read the value length, read the value and discard the value, all without involving
-            // the state machine, so tell it what we are about to do.
-            // TODO: pass the state machine to the ::read() functions instead and have them update it.
-            let loc = self.location(); // See the note above about working around greedy closure capturing
-            self.state
-                .borrow_mut()
-                .advance(FieldType::LengthAndValue)
-                .map_err(|err| pinpoint!(err, loc))?;
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .advance(FieldType::LengthAndValue)
+            .map_err(|err| pinpoint!(err, loc))?;
 
-            match self.item_type.unwrap() {
-                TtlvType::Structure => {
-                    // We handled this case above
-                    unreachable!()
-                }
-                TtlvType::Integer => {
-                    TtlvInteger::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::LongInteger => {
-                    TtlvLongInteger::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::BigInteger => {
-                    TtlvBigInteger::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::Enumeration => {
-                    TtlvEnumeration::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::Boolean => {
-                    TtlvBoolean::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::TextString => {
-                    TtlvTextString::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::ByteString => {
-                    TtlvByteString::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-                TtlvType::DateTime => {
-                    TtlvDateTime::read(&mut self.src).map_err(|err| pinpoint!(err, self))?;
-                }
-            }
+        if let Err(err) = self.read_borrowed_value() {
+            return Err(pinpoint!(err, self));
         }
 
+        // Keep a record of what was skipped, see from_slice_with_report()
+        self.report.borrow_mut().ignored_items.push(IgnoredItem {
+            tag: item_tag,
+            r#type: item_type,
+            byte_range: (item_start as usize)..(self.pos() as usize),
+        });
+
         // Any visitor fn can be invoked here, they all internally return Ok(IgnoredAny).
         visitor.visit_none()
     }
@@ -1344,30 +3690,84 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
 
     unsupported_type!(deserialize_u8, u8);
     unsupported_type!(deserialize_u16, u16);
-    unsupported_type!(deserialize_u32, u32);
     unsupported_type!(deserialize_u64, u64);
-    unsupported_type!(deserialize_i8, i8);
-    unsupported_type!(deserialize_i16, i16);
     unsupported_type!(deserialize_f32, f32);
     unsupported_type!(deserialize_f64, f64);
     unsupported_type!(deserialize_char, char);
-    unsupported_type!(deserialize_str, str);
-    unsupported_type!(deserialize_map, map);
-    unsupported_type!(deserialize_bytes, bytes);
     unsupported_type!(deserialize_unit, unit);
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    /// Deserialize the TTLV Structure at the current cursor position to a Rust map.
+    ///
+    /// The map keys are the tags of the structure members in hex form, e.g. "0x42006A". Serde derive also uses this
+    /// function to deserialize a Rust struct that has a `#[serde(flatten)]` field, in which case the struct fields are
+    /// matched by tag rather than by position and any members not claimed by the struct are offered to the flattened
+    /// field.
+    ///
+    /// If the map is a struct field renamed to `keyed:0xSSSSSS/0xNNNNNN` the map is instead populated from the sequence
+    /// of TTLV Structures with tag 0xSSSSSS at the current cursor position, each keyed by the value of its child item
+    /// with tag 0xNNNNNN.
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let key_tag = self
+            .item_identifier
+            .as_deref()
+            .and_then(keyed_field_tags)
+            .map(|(_, key_tag)| key_tag);
+        if let Some(key_tag) = key_tag {
+            let loc = self.location(); // See the note above about working around greedy closure capturing
+            let key_tag = TtlvTag::from_str(key_tag).map_err(|err| pinpoint!(err, loc))?;
+            return self.visit_keyed_structures(key_tag, visitor);
+        }
+
+        let (_, group_tag, group_type) = self.get_start_tag_type()?;
+
+        if group_type != TtlvType::Structure {
+            let error = SerdeError::UnexpectedType {
+                expected: TtlvType::Structure,
+                actual: group_type,
+            };
+            return Err(pinpoint!(error, self));
+        }
+
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        let group_len = TtlvDeserializer::read_length(&mut self.src, Some(&mut self.state.borrow_mut()))
+            .map_err(|err| pinpoint!(err, loc))?;
+        let group_end = self.pos() + (group_len as u64);
+        self.visit_structure(group_tag, group_type, group_end, &[], StructureAccess::Tags, visitor)
+    }
+
+    /// Deserialize the TTLV Structure at the current cursor position to a Rust unit struct.
+    ///
+    /// The structure is expected to be empty. As for structs with fields, any items that it contains are ignored.
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("unit struct"), self))
+        let (_, _, _, group_end) = self.prepare_to_descend(name)?;
+
+        // Skip any content and tell the state machine that we're finished reading this TTLV item
+        self.src.set_position(group_end);
+        self.state.borrow_mut().restart_item();
+
+        visitor.visit_unit()
     }
 
-    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, _visitor: V) -> Result<V::Value>
+    /// Deserialize the TTLV Structure at the current cursor position to a Rust tuple struct.
+    ///
+    /// Only supported if enabled with [Config::with_tuple_structs()], otherwise this crate prefers that structures are
+    /// deserialized into brace structs whose fields are named after the tags they expect.
+    fn deserialize_tuple_struct<V>(self, name: &'static str, _len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("tuple struct"), self))
+        if !self.settings.tuple_structs {
+            return Err(pinpoint!(SerdeError::UnsupportedRustType("tuple struct"), self));
+        }
+
+        let (_, group_tag, group_type, group_end) = self.prepare_to_descend(name)?;
+        self.visit_structure(group_tag, group_type, group_end, &[], StructureAccess::Tuple, visitor)
     }
 
     fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
@@ -1377,11 +3777,53 @@ impl<'de: 'c, 'c> Deserializer<'de> for &mut TtlvDeserializer<'de, 'c> {
         Err(pinpoint!(SerdeError::UnsupportedRustType("tuple"), self))
     }
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    /// Deserialize the current TTLV item without a Rust type to guide us.
+    ///
+    /// The visitor function invoked is chosen based on the TTLV item type. TTLV Structures are visited as maps whose
+    /// keys are the tags of the child items in hex form, e.g. "0x42000F", and whose values are in turn deserialized
+    /// via this function. Note that TTLV permits the same tag to occur more than once within a Structure, in which
+    /// case the visitor is given duplicate keys. The values of extension types are visited as opaque bytes.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("any"), self))
+        let (_, item_tag, item_type) = self.get_start_tag_type()?;
+
+        match item_type {
+            TtlvType::Structure => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                let group_len = TtlvDeserializer::read_length(&mut self.src, Some(&mut self.state.borrow_mut()))
+                    .map_err(|err| pinpoint!(err, loc))?;
+                let group_end = self.pos() + (group_len as u64);
+                self.visit_structure(item_tag, item_type, group_end, &[], StructureAccess::Tags, visitor)
+            }
+            TtlvType::Integer => self.deserialize_i32(visitor),
+            TtlvType::LongInteger | TtlvType::DateTime => self.deserialize_i64(visitor),
+            TtlvType::BigInteger | TtlvType::ByteString => self.deserialize_byte_buf(visitor),
+            TtlvType::Boolean => self.deserialize_bool(visitor),
+            TtlvType::TextString => self.deserialize_string(visitor),
+            TtlvType::Enumeration => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                self.state
+                    .borrow_mut()
+                    .advance(FieldType::LengthAndValue)
+                    .map_err(|err| pinpoint!(err, loc))?;
+                let v = self
+                    .read_ttlv::<TtlvEnumeration>()
+                    .map_err(|err| pinpoint!(err, self))?;
+                visitor.visit_u32(*v)
+            }
+            TtlvType::Interval => self.deserialize_u32(visitor),
+            TtlvType::Extension(_) => {
+                let loc = self.location(); // See the note above about working around greedy closure capturing
+                self.state
+                    .borrow_mut()
+                    .advance(FieldType::LengthAndValue)
+                    .map_err(|err| pinpoint!(err, loc))?;
+                let v = self.read_ttlv::<TtlvByteString>().map_err(|err| pinpoint!(err, self))?;
+                visitor.visit_byte_buf(v.0)
+            }
+        }
     }
 }
 
@@ -1394,7 +3836,11 @@ impl<'de: 'c, 'c> MapAccess<'de> for TtlvDeserializer<'de, 'c> {
         K: serde::de::DeserializeSeed<'de>,
     {
         if self.read_item_key(false)? {
-            seed.deserialize(self).map(Some) // jumps to deserialize_identifier() above
+            if self.group_any {
+                seed.deserialize(TagKeyDeserializer(self.item_tag.unwrap())).map(Some)
+            } else {
+                seed.deserialize(self).map(Some) // jumps to deserialize_identifier() above
+            }
         } else {
             // The end of the group was reached
             Ok(None)
@@ -1405,7 +3851,35 @@ impl<'de: 'c, 'c> MapAccess<'de> for TtlvDeserializer<'de, 'c> {
     where
         V: serde::de::DeserializeSeed<'de>,
     {
-        seed.deserialize(self) // jumps to deserialize_xxx() in impl Deserializer above
+        if !self.item_matched || self.settings.duplicate_tag_policy != DuplicateTagPolicy::LastWins {
+            return seed.deserialize(self); // jumps to deserialize_xxx() in impl Deserializer above
+        }
+
+        // Deserialize the value of the last item with the same tag and type instead, if there is one. The position of
+        // the value of this item is remembered so that deserialize_seq() can undo the jump, as for a sequence field
+        // the later item is not a duplicate but either a further element of the sequence or a duplicate of it.
+        let last_item_start = match self.find_last_duplicate_item()? {
+            Some(last_item_start) => last_item_start,
+            None => return seed.deserialize(self),
+        };
+        self.item_resume = Some(self.src.position());
+        self.src.set_position(last_item_start + 4); // skip the tag and type, they are the same as for this item
+
+        let v = seed.deserialize(&mut *self)?; // jumps to deserialize_xxx() in impl Deserializer above
+
+        if let Some(resume_pos) = self.item_resume.take() {
+            // Skip the value of this item, the later item will be skipped when reached, see skip_duplicate_item().
+            self.src.set_position(resume_pos);
+            self.read_borrowed_value().map_err(|err| pinpoint!(err, &*self))?;
+            self.report.borrow_mut().ignored_items.push(IgnoredItem {
+                tag: self.item_tag.unwrap(),
+                r#type: self.item_type.unwrap(),
+                byte_range: (self.item_start as usize)..(self.pos() as usize),
+            });
+            self.group_superseding_items.push(last_item_start);
+        }
+
+        Ok(v)
     }
 }
 
@@ -1417,19 +3891,11 @@ impl<'de: 'c, 'c> SeqAccess<'de> for TtlvDeserializer<'de, 'c> {
     where
         T: serde::de::DeserializeSeed<'de>,
     {
-        if !self.read_item_key(self.group_item_count == 0)? {
-            // The end of the containing group was reached
-            Ok(None)
-        } else if self.group_homogenous && (self.item_tag != self.group_tag || self.item_type != self.group_type) {
-            // The next tag is not part of the sequence.
-            // Walk the cursor back before the tag because we didn't consume it.
-            self.src.set_position(self.item_start);
-            // And reset the state machine to expect a tag again
-            self.state.borrow_mut().reset();
-            Ok(None)
-        } else {
+        if self.read_next_seq_item_key()? {
             // The tag and type match that of the first item in the sequence, process this element.
             seed.deserialize(self).map(Some) // jumps to deserialize_identifier() above
+        } else {
+            Ok(None)
         }
     }
 }
@@ -1468,11 +3934,14 @@ impl<'de: 'c, 'c> VariantAccess<'de> for &mut TtlvDeserializer<'de, 'c> {
         V: Visitor<'de>,
     {
         // The caller has provided a Rust enum variant in tuple form, i.e. SomeEnum(a, b, c), and expects us to
-        // deserialize the right number of items to match those fields.
+        // deserialize the right number of items to match those fields. The items are the members of the TTLV Structure
+        // whose tag and type have already been read.
+        self.enter_structure()?;
+
         let loc = self.location(); // See the note above about working around greedy closure capturing
         let seq_len = TtlvDeserializer::read_length(&mut self.src, Some(&mut self.state.borrow_mut()))
             .map_err(|err| pinpoint!(err, loc))?;
-        let seq_start = self.pos() as u64;
+        let seq_start = self.pos();
         let seq_end = seq_start + (seq_len as u64);
 
         let loc = self.location(); // See the note above about working around greedy closure capturing
@@ -1485,26 +3954,31 @@ impl<'de: 'c, 'c> VariantAccess<'de> for &mut TtlvDeserializer<'de, 'c> {
             .map_err(|err| pinpoint!(err, loc))?;
         self.item_type = Some(seq_type);
 
+        self.observe_item(seq_start, seq_tag, seq_type)?;
+
         let mut seq_cursor = self.src.clone();
 
-        let descendent_parser = TtlvDeserializer::from_cursor(
+        let descendent_parser = self.nested(
             &mut seq_cursor,
-            self.state.clone(),
             seq_tag,
             seq_type,
             seq_end,
             &[],
             false, // don't require all fields in the sequence to be of the same tag and type
-            self.tag_value_store.clone(),
-            self.tag_path.clone(),
-        );
+        )?;
 
-        let r = visitor.visit_seq(descendent_parser); // jumps to impl SeqAccess below
+        let r = visitor.visit_seq(descendent_parser)?; // jumps to impl SeqAccess below
 
         // The descendant parser cursor advanced but ours did not. Skip the tag that we just read.
         self.src.set_position(seq_cursor.position());
 
-        r
+        let loc = self.location(); // See the note above about working around greedy closure capturing
+        self.state
+            .borrow_mut()
+            .leave_structure()
+            .map_err(|err| pinpoint!(err, loc))?;
+
+        Ok(r)
     }
 
     fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
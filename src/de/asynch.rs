@@ -0,0 +1,104 @@
+//! Deserialization from async readers.
+//!
+//! The functions in this module are available when the corresponding async feature is enabled, irrespective of
+//! whether the `sync` feature or the other async features are also enabled:
+//!
+//! - `async-with-tokio`: `from_tokio_reader()` accepts any `tokio::io::AsyncRead`.
+//! - `async-with-async-std` or `async-with-futures`: `from_futures_reader()` accepts any `futures::io::AsyncRead`,
+//!   which includes the `async_std::io::Read` implementations. It doesn't depend on an async runtime so can also be
+//!   used with custom executors and test harnesses.
+//!
+//! Both behave exactly like the blocking `from_reader()` function. The bytes are read into the [Config] read buffer
+//! if one was configured with [Config::with_read_buf()], otherwise into a temporary buffer, and are read a chunk at a
+//! time if configured with [Config::with_read_chunk_size()].
+
+use serde::de::DeserializeOwned;
+
+use crate::error::{Error, ErrorLocation, Result};
+
+use super::{from_slice_with_config, next_read_range, verify_response_header, Config, TTL_HEADER_LEN};
+
+#[cfg(feature = "async-with-futures")]
+use futures_util::io::{AsyncRead as FuturesRead, AsyncReadExt as FuturesReadExt};
+
+#[cfg(all(feature = "async-with-async-std", not(feature = "async-with-futures")))]
+use async_std::io::{Read as FuturesRead, ReadExt as FuturesReadExt};
+
+/// Read and deserialize bytes from the given Tokio reader.
+///
+/// Note: Also accepts a mut reference.
+///
+/// Attempting to process a stream whose initial TTL header length value is larger the config max_bytes, if any, will
+/// result in`Error::ResponseSizeExceedsLimit`.
+#[cfg(feature = "async-with-tokio")]
+pub async fn from_tokio_reader<T, R>(mut reader: R, config: &Config) -> Result<T>
+where
+    T: DeserializeOwned,
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    // See sync::from_reader() for an explanation of this dance.
+    let mut buf_bytes;
+    let mut config_buf = config.read_buf();
+    let buf: &mut Vec<u8> = if let Some(ref mut buf) = config_buf {
+        buf
+    } else {
+        buf_bytes = Vec::new();
+        &mut buf_bytes
+    };
+
+    buf.resize(TTL_HEADER_LEN, 0);
+    reader.read_exact(buf).await.map_err(|err| pinpoint!(err, 0u64))?;
+    let (response_size, tag, r#type) = verify_response_header(buf, config)?;
+
+    while buf.len() < response_size {
+        let range = next_read_range(buf.len(), response_size, config);
+        buf.resize(range.end, 0);
+        reader
+            .read_exact(&mut buf[range])
+            .await
+            .map_err(|err| Error::pinpoint(err, ErrorLocation::from(buf.len()).with_tag(tag).with_type(r#type)))?;
+    }
+
+    from_slice_with_config(buf, config)
+}
+
+/// Read and deserialize bytes from the given `futures::io::AsyncRead` reader.
+///
+/// Note: Also accepts a mut reference.
+///
+/// Attempting to process a stream whose initial TTL header length value is larger the config max_bytes, if any, will
+/// result in`Error::ResponseSizeExceedsLimit`.
+#[cfg(any(feature = "async-with-async-std", feature = "async-with-futures"))]
+pub async fn from_futures_reader<T, R>(mut reader: R, config: &Config) -> Result<T>
+where
+    T: DeserializeOwned,
+    R: FuturesRead + Unpin,
+{
+    // See sync::from_reader() for an explanation of this dance.
+    let mut buf_bytes;
+    let mut config_buf = config.read_buf();
+    let buf: &mut Vec<u8> = if let Some(ref mut buf) = config_buf {
+        buf
+    } else {
+        buf_bytes = Vec::new();
+        &mut buf_bytes
+    };
+
+    buf.resize(TTL_HEADER_LEN, 0);
+    FuturesReadExt::read_exact(&mut reader, buf)
+        .await
+        .map_err(|err| pinpoint!(err, 0u64))?;
+    let (response_size, tag, r#type) = verify_response_header(buf, config)?;
+
+    while buf.len() < response_size {
+        let range = next_read_range(buf.len(), response_size, config);
+        buf.resize(range.end, 0);
+        FuturesReadExt::read_exact(&mut reader, &mut buf[range])
+            .await
+            .map_err(|err| Error::pinpoint(err, ErrorLocation::from(buf.len()).with_tag(tag).with_type(r#type)))?;
+    }
+
+    from_slice_with_config(buf, config)
+}
@@ -0,0 +1,219 @@
+//! Deserialization from blocking readers, i.e. implementations of `std::io::Read`.
+//!
+//! Available with the `sync` feature, which can be enabled at the same time as the async features.
+
+use std::{io::Read, marker::PhantomData};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::{Error, ErrorLocation, Result},
+    types::ByteOffset,
+};
+
+use super::{from_slice_with_config, next_read_range, rebase_error, verify_response_header, Config, TTL_HEADER_LEN};
+
+/// Read and deserialize bytes from the given reader.
+///
+/// Note: Also accepts a mut reference.
+///
+/// Attempting to process a stream whose initial TTL header length value is larger the config max_bytes, if any, will
+/// result in`Error::ResponseSizeExceedsLimit`.
+pub fn from_reader<T, R>(mut reader: R, config: &Config) -> Result<T>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    // Interior mutability access dance
+    // --------------------------------
+    // The Config object can optionally have its own buffer which we will write the read bytes into. This then allows
+    // the caller to log or save or pretty print those bytes or avoid allocating a new buffer on every read, or
+    // whatever the caller wants to do with them.
+    //
+    // We could take a buffer as an argument but that would complicate the default use case. We could also take a
+    // mutable reference to the Config object but then we'd appear to have the ability to alter the configuration
+    // settings which is not right. So instead we use the interior mutability pattern to get a mutable reference to
+    // just the buffer owned by the Config object, not the entire Config object. However, to write to the Config object
+    // buffer if available or otherwise to a local buffer requires a bit of a dance to get satisfy the Rust compiler
+    // borrow checker.
+    let mut buf_bytes;
+    let mut config_buf = config.read_buf();
+    let buf: &mut Vec<u8> = if let Some(ref mut buf) = config_buf {
+        // Use the buffer provided by the Config object.
+        buf
+    } else {
+        // Create and use our own temporary buffer.
+        buf_bytes = Vec::new();
+        &mut buf_bytes
+    };
+
+    // Read and verify the bytes of the first TTL (3 byte tag, 1 byte type, 4 byte len)
+    buf.resize(TTL_HEADER_LEN, 0);
+    reader.read_exact(buf).map_err(|err| pinpoint!(err, 0u64))?;
+    let (response_size, tag, r#type) = verify_response_header(buf, config)?;
+
+    // Now read the value bytes of the first TTLV item (i.e. the rest of the entire TTLV message), in one go or a chunk
+    // at a time if so configured.
+    // Warning: this will panic if it fails to allocate the requested amount of memory, at least until try_reserve() is
+    // stabilized!
+    while buf.len() < response_size {
+        let range = next_read_range(buf.len(), response_size, config);
+        buf.resize(range.end, 0);
+        reader
+            .read_exact(&mut buf[range])
+            .map_err(|err| Error::pinpoint(err, ErrorLocation::from(buf.len()).with_tag(tag).with_type(r#type)))?;
+    }
+
+    from_slice_with_config(buf, config)
+}
+
+/// Create a lazy iterator that reads and deserializes back-to-back TTLV Structures from the given reader.
+///
+/// See [TtlvReaderIter] for details.
+pub fn from_reader_iter<T, R>(reader: R, config: &Config) -> TtlvReaderIter<T, R>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    TtlvReaderIter::new(reader, config)
+}
+
+/// A lazy iterator over back-to-back TTLV Structures read from a reader, deserializing each in turn.
+///
+/// Each step reads and deserializes one TTLV item as if by [from_reader], including enforcement of
+/// [Config::max_bytes] per item. Iteration stops cleanly when the reader reaches end-of-file exactly at an item
+/// boundary. Reaching end-of-file part way through an item results in an [ErrorKind::IoError] of kind
+/// [std::io::ErrorKind::UnexpectedEof]. Error offsets are relative to the first byte read from the reader. No more
+/// items are returned after an error.
+///
+/// If [Config::with_read_chunk_size()] is set the reader is read a chunk at a time and bytes read beyond the end of
+/// one item are kept for the next. This reduces the number of reads needed when the items are small compared to the
+/// chunk size, at the cost of possibly reading from the reader before the next item is actually needed.
+///
+/// Only available with the `sync` feature as an async reader cannot be driven by a blocking iterator.
+///
+/// [ErrorKind::IoError]: crate::error::ErrorKind::IoError
+pub struct TtlvReaderIter<T, R> {
+    reader: R,
+    pos: u64,
+    config: Config,
+    failed: bool,
+    read_ahead: Vec<u8>,
+    phantom: PhantomData<T>,
+}
+
+impl<T, R> TtlvReaderIter<T, R> {
+    pub fn new(reader: R, config: &Config) -> Self {
+        Self {
+            reader,
+            pos: 0,
+            config: config.clone(),
+            failed: false,
+            read_ahead: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// The number of bytes read from the reader by the successfully deserialized TTLV items.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<T, R> Iterator for TtlvReaderIter<T, R>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let res = self.next_item();
+        self.failed = !matches!(res, Some(Ok(_)));
+        res
+    }
+}
+
+impl<T, R> TtlvReaderIter<T, R>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    fn next_item(&mut self) -> Option<Result<T>> {
+        if let Some(chunk_size) = self.config.read_chunk_size() {
+            return self.next_read_ahead_item(chunk_size);
+        }
+
+        // Read the TTL header ourselves so that we can distinguish end-of-file at an item boundary, which ends the
+        // iteration, from end-of-file part way through an item, which is an error.
+        let mut header = [0u8; TTL_HEADER_LEN];
+        let mut header_len = 0;
+        while header_len < header.len() {
+            match self.reader.read(&mut header[header_len..]) {
+                Ok(0) if header_len == 0 => return None,
+                Ok(0) => {
+                    let error = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+                    return Some(Err(pinpoint!(error, ByteOffset(self.pos + header_len as u64))));
+                }
+                Ok(n) => header_len += n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Some(Err(pinpoint!(err, ByteOffset(self.pos + header_len as u64)))),
+            }
+        }
+
+        let value_len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let start = self.pos;
+        let reader = (&header[..]).chain(&mut self.reader);
+        let res = from_reader(reader, &self.config).map_err(|err| rebase_error(err, start));
+        if res.is_ok() {
+            self.pos += TTL_HEADER_LEN as u64 + value_len as u64;
+        }
+        Some(res)
+    }
+
+    fn next_read_ahead_item(&mut self, chunk_size: usize) -> Option<Result<T>> {
+        let start = self.pos;
+
+        // Read until the read ahead buffer contains the entire next item
+        let mut item_len = None;
+        loop {
+            if item_len.is_none() && self.read_ahead.len() >= TTL_HEADER_LEN {
+                match verify_response_header(&self.read_ahead[..TTL_HEADER_LEN], &self.config) {
+                    Ok((response_size, _, _)) => item_len = Some(response_size),
+                    Err(err) => return Some(Err(rebase_error(err, start))),
+                }
+            }
+
+            if matches!(item_len, Some(item_len) if self.read_ahead.len() >= item_len) {
+                break;
+            }
+
+            let buf_len = self.read_ahead.len();
+            self.read_ahead.resize(buf_len + chunk_size, 0);
+            let read_len = match self.reader.read(&mut self.read_ahead[buf_len..]) {
+                Ok(0) if buf_len == 0 => return None,
+                Ok(0) => {
+                    let error = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+                    return Some(Err(pinpoint!(error, ByteOffset(start + buf_len as u64))));
+                }
+                Ok(n) => n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => 0,
+                Err(err) => return Some(Err(pinpoint!(err, ByteOffset(start + buf_len as u64)))),
+            };
+            self.read_ahead.truncate(buf_len + read_len);
+        }
+
+        let item_len = item_len.unwrap();
+        let res =
+            from_slice_with_config(&self.read_ahead[..item_len], &self.config).map_err(|err| rebase_error(err, start));
+        self.read_ahead.drain(..item_len);
+        if res.is_ok() {
+            self.pos += item_len as u64;
+        }
+        Some(res)
+    }
+}
@@ -41,6 +41,19 @@ impl Error {
 
 impl std::error::Error for Error {}
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(ErrorKind::IoError(err), ErrorLocation::unknown())
+    }
+}
+
+/// Converts an error reported by the low-level API, e.g. by [types::TtlvNode::parse()], whose location is unknown.
+impl From<types::Error> for Error {
+    fn from(err: types::Error) -> Self {
+        Self::new(ErrorKind::from(err), ErrorLocation::unknown())
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.kind {
@@ -53,12 +66,28 @@ impl Display for Error {
             ErrorKind::ResponseSizeExceedsLimit(size) => {
                 f.write_fmt(format_args!("Response size {} exceeds the configured limit", size))
             }
+            ErrorKind::NestingDepthExceedsLimit(depth) => f.write_fmt(format_args!(
+                "Nesting depth {} exceeds the configured limit (at {})",
+                depth, self.location
+            )),
+            ErrorKind::ValueLengthExceedsLimit(length) => f.write_fmt(format_args!(
+                "Value length {} exceeds the configured limit (at {})",
+                length, self.location
+            )),
             ErrorKind::MalformedTtlv(error) => {
                 f.write_fmt(format_args!("Malformed TTLV: {:?} (at {})", error, self.location))
             }
+            ErrorKind::RejectedByObserver(reason) => f.write_fmt(format_args!(
+                "Rejected by item observer: {} (at {})",
+                reason, self.location
+            )),
             ErrorKind::SerdeError(error) => {
                 f.write_fmt(format_args!("Serde error : {:?} (at {})", error, self.location))
             }
+            #[cfg(feature = "xml")]
+            ErrorKind::MalformedXml(error) => {
+                f.write_fmt(format_args!("Malformed XML: {} (at {})", error, self.location))
+            }
         }
     }
 }
@@ -107,16 +136,25 @@ impl Error {
 /// Details about the kind of error that occurred.
 ///
 /// Errors can be roughly split into the following categories:
-///   - Errors while reading/writing, i.e. [ErrorKind::IoError] and [ErrorKind::ResponseSizeExceedsLimit].
+///   - Errors while reading/writing, i.e. [ErrorKind::IoError], [ErrorKind::ResponseSizeExceedsLimit],
+///     [ErrorKind::NestingDepthExceedsLimit] and [ErrorKind::ValueLengthExceedsLimit].
 ///   - Errors while parsing/generating TTLV bytes, i.e. [ErrorKind::MalformedTtlv].
+///   - Input or output rejected by the function configured with `Config::with_item_observer()`, i.e.
+///     [ErrorKind::RejectedByObserver].
 ///   - Errors while (de)serializing from/to Rust data structures, i.e. [ErrorKind::SerdeError].
+///   - Errors while parsing KMIP XML, i.e. `ErrorKind::MalformedXml` (only available with the `xml` feature).
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ErrorKind {
     IoError(std::io::Error),
     ResponseSizeExceedsLimit(usize),
+    NestingDepthExceedsLimit(usize),
+    ValueLengthExceedsLimit(u64),
     MalformedTtlv(MalformedTtlvError),
+    RejectedByObserver(String),
     SerdeError(SerdeError),
+    #[cfg(feature = "xml")]
+    MalformedXml(String),
 }
 
 impl From<std::io::Error> for ErrorKind {
@@ -145,6 +183,18 @@ impl From<types::Error> for ErrorKind {
                 r#type,
             }),
             types::Error::InvalidTtlvValue(r#type) => Self::MalformedTtlv(MalformedTtlvError::InvalidValue { r#type }),
+            types::Error::InvalidTtlvPadding(r#type) => {
+                Self::MalformedTtlv(MalformedTtlvError::InvalidPadding { r#type })
+            }
+            types::Error::TtlvValueLengthExceedsLimit { length, .. } => Self::ValueLengthExceedsLimit(length),
+            types::Error::TrailingBytes(_) => Self::MalformedTtlv(MalformedTtlvError::TrailingBytes),
+            types::Error::NestingDepthExceedsLimit(depth) => Self::NestingDepthExceedsLimit(depth),
+            types::Error::NoItemAtOffset(offset) => {
+                Self::SerdeError(SerdeError::Other(format!("No TTLV item starts at offset {}", offset)))
+            }
+            types::Error::UnexpectedTtlvType { expected, actual } => {
+                Self::MalformedTtlv(MalformedTtlvError::UnexpectedType { expected, actual })
+            }
             types::Error::InvalidStateMachineOperation => Self::SerdeError(SerdeError::Other(
                 "Internal error: invalid state machine operaiton".into(),
             )),
@@ -172,6 +222,7 @@ pub struct ErrorLocation {
     offset: Option<ByteOffset>,
     parent_tags: Vec<TtlvTag>,
     tag: Option<TtlvTag>,
+    tag_name_resolver: Option<crate::de::TagNameResolver>,
     r#type: Option<TtlvType>,
 }
 
@@ -259,14 +310,17 @@ impl Display for ErrorLocation {
             f.write_fmt(format_args!("{}pos: {} bytes", sep(), *offset))?;
         }
         if !self.parent_tags.is_empty() {
-            let mut iter = self.parent_tags.iter();
-            f.write_fmt(format_args!("{}parent tags: {}", sep(), iter.next().unwrap()))?;
-            for tag in iter {
-                f.write_fmt(format_args!(" > {}", tag))?
+            f.write_fmt(format_args!("{}parent tags: ", sep()))?;
+            for (i, tag) in self.parent_tags.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" > ")?;
+                }
+                self.write_tag(f, *tag)?;
             }
         }
         if let Some(tag) = self.tag {
-            f.write_fmt(format_args!("{}tag: {}", sep(), tag))?;
+            f.write_fmt(format_args!("{}tag: ", sep()))?;
+            self.write_tag(f, tag)?;
         }
         if let Some(r#type) = self.r#type {
             f.write_fmt(format_args!("{}type: {}", sep(), r#type))?;
@@ -277,6 +331,14 @@ impl Display for ErrorLocation {
 }
 
 impl ErrorLocation {
+    /// Write a tag in hex form, preceded by its human readable name if known, e.g. `ProtocolVersionMajor (0x42006A)`.
+    fn write_tag(&self, f: &mut std::fmt::Formatter<'_>, tag: TtlvTag) -> std::fmt::Result {
+        match self.name_of(tag) {
+            Some(tag_name) => f.write_fmt(format_args!("{} ({})", tag_name, tag)),
+            None => f.write_fmt(format_args!("{}", tag)),
+        }
+    }
+
     pub(crate) fn at(offset: ByteOffset) -> Self {
         Self {
             offset: Some(offset),
@@ -306,6 +368,11 @@ impl ErrorLocation {
         self
     }
 
+    pub(crate) fn with_tag_name_resolver(mut self, resolver: crate::de::TagNameResolver) -> Self {
+        let _ = self.tag_name_resolver.get_or_insert(resolver);
+        self
+    }
+
     pub(crate) fn with_type(mut self, r#type: TtlvType) -> Self {
         let _ = self.r#type.get_or_insert(r#type);
         self
@@ -319,6 +386,9 @@ impl ErrorLocation {
         if let Some(tag) = loc.tag {
             self = self.with_tag(tag);
         }
+        if let Some(resolver) = loc.tag_name_resolver {
+            self = self.with_tag_name_resolver(resolver);
+        }
         if let Some(r#type) = loc.r#type {
             self = self.with_type(r#type);
         }
@@ -344,6 +414,17 @@ impl ErrorLocation {
         self.tag
     }
 
+    /// The human readable name of the tag, if a [crate::de::TagNameResolver] was configured and it knows the tag.
+    pub fn tag_name(&self) -> Option<&'static str> {
+        self.tag.and_then(|tag| self.name_of(tag))
+    }
+
+    /// The human readable name of the given tag, e.g. one of the [Self::parent_tags()], if a
+    /// [crate::de::TagNameResolver] was configured and it knows the tag.
+    pub fn name_of(&self, tag: TtlvTag) -> Option<&'static str> {
+        self.tag_name_resolver.and_then(|resolver| resolver(tag))
+    }
+
     pub fn r#type(&self) -> Option<TtlvType> {
         self.r#type
     }
@@ -388,6 +469,12 @@ pub enum MalformedTtlvError {
     /// bytes of a TTLV structure once its length was known and this was detected during serialization or later during
     /// deserialization.
     UnknownStructureLength,
+
+    /// Bytes remain after the end of the root TTLV item.
+    TrailingBytes,
+
+    /// The padding bytes following a TTLV value are not all zero.
+    InvalidPadding { r#type: TtlvType },
 }
 
 impl MalformedTtlvError {
@@ -407,6 +494,25 @@ impl MalformedTtlvError {
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum SerdeError {
+    /// The TTLV Integer value being deserialized does not fit in the narrower Rust integer type, e.g. `i8`, being
+    /// deserialized into.
+    IntegerOutOfRange { value: i32, target: &'static str },
+
+    /// The TTLV Big Integer value being deserialized does not fit in the Rust integer type, e.g. `u128`, being
+    /// deserialized into.
+    BigIntegerOutOfRange { target: &'static str },
+
+    /// The tag of an item being serialized is outside the ranges permitted by
+    /// [crate::ser::Config::with_allowed_tag_range()].
+    ///
+    /// The field is the name of the Rust struct field that the item was serialized from, if known, e.g. not for an
+    /// item serialized from a tuple struct field.
+    DisallowedTag { tag: TtlvTag, field: Option<&'static str> },
+
+    /// A TTLV Structure contains more than one item with the tag of a Rust struct field being deserialized into. See
+    /// [crate::de::DuplicateTagPolicy].
+    DuplicateTag(TtlvTag),
+
     /// An enum variant name is neither a hexadecimal string value nor valid matcher syntax.
     ///
     /// Valid enum variant names must be set using `#[serde(rename = "...")]` and must either be a hexadecimal string
@@ -421,6 +527,10 @@ pub enum SerdeError {
     /// value such as `0x12ABEF`.
     InvalidTag(String),
 
+    /// A TTLV Structure being deserialized into a `keyed:0xSSSSSS/0xNNNNNN` map has no child item with the key tag
+    /// 0xNNNNNN.
+    MissingMapKey(TtlvTag),
+
     /// None of the `#[serde(rename = "...")]` named fields in the Rust struct being deserialized into matches the TTLV
     /// tag value being deserialized.
     MissingIdentifier,
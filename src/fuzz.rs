@@ -0,0 +1,59 @@
+//! Stable entry points for fuzz testing this crate, e.g. with `cargo fuzz`.
+//!
+//! The functions in this module are intended to be called from fuzz targets so that the targets do not need to know
+//! anything about the internals of this crate and do not need to change as the crate evolves. They also act as an
+//! explicit contract: no byte sequence should cause this crate to panic.
+//!
+//! For example a `cargo fuzz` target could look like this:
+//!
+//! ```ignore
+//! #![no_main]
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|data: &[u8]| {
+//!     let _ = kmip_ttlv::fuzz::try_parse_raw(data);
+//! });
+//! ```
+//!
+//! Only available when the `fuzz` feature is enabled.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{de::validate, from_slice, to_vec, Config};
+
+/// The maximum number of bytes that [try_parse_raw()] will attempt to parse.
+pub const MAX_BYTES: u32 = 1 << 20;
+
+/// The maximum depth to which [try_parse_raw()] permits TTLV Structures to be nested.
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+/// Attempt to parse the given bytes as a complete tree of TTLV items.
+///
+/// Every item in the tree is parsed, using the same checks as [validate()], with [MAX_BYTES] and [MAX_NESTING_DEPTH]
+/// as limits. Returns `true` if the bytes were parsed successfully, `false` otherwise.
+pub fn try_parse_raw(data: &[u8]) -> bool {
+    let config = Config::new()
+        .with_max_bytes(MAX_BYTES)
+        .with_max_nesting_depth(MAX_NESTING_DEPTH);
+    validate(data, &config).is_ok()
+}
+
+/// Serialize `T::default()` to TTLV, deserialize the bytes back to a `T` and serialize that again.
+///
+/// Returns `true` if every step succeeded and both serializations produced the same bytes, `false` otherwise.
+pub fn try_serde_roundtrip<T>() -> bool
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let bytes = match to_vec(&T::default()) {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+
+    let value: T = match from_slice(&bytes) {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+
+    matches!(to_vec(&value), Ok(new_bytes) if new_bytes == bytes)
+}
@@ -0,0 +1,384 @@
+//! A registry of the names of the tags defined by the KMIP specifications.
+//!
+//! Rather than keeping a private copy of the KMIP tag table for debugging or for defining the tags of Rust types, the
+//! functions in this module can be plugged into the existing extension points of this crate:
+//!
+//! - [tag_name] can be passed to [crate::de::Config::with_tag_name_resolver()] to include tag names in the location
+//!   of deserialization errors, e.g. `tag: RequestMessage (0x420078)`.
+//! - [tag_for_name] can be passed to [crate::de::Config::with_tag_provider()] and
+//!   [crate::ser::Config::with_tag_provider()] so that Rust types and fields named after KMIP tags, e.g.
+//!   `UniqueIdentifier`, no longer need a `#[serde(rename = "0x420094")]` attribute.
+//! - [tag_map] can be passed to [crate::util::PrettyPrinter::with_tag_map()] to show tag names in pretty printed
+//!   output.
+//!
+//! The names are those used by the KMIP XML encoding, i.e. the tag names of the KMIP specification with spaces,
+//! hyphens and other punctuation removed. The registry covers the tags defined by KMIP v1.0 to v2.0 inclusive. Tags
+//! outside the registry, e.g. extension tags in the 0x54XXXX range, resolve to `None`.
+
+#[cfg(feature = "high-level")]
+use std::collections::HashMap;
+
+use crate::types::TtlvTag;
+
+/// The standard KMIP tags and their names, ordered by tag.
+pub const TAGS: &[(TtlvTag, &str)] = &[
+    (TtlvTag::new(0x420001), "ActivationDate"),
+    (TtlvTag::new(0x420002), "ApplicationData"),
+    (TtlvTag::new(0x420003), "ApplicationNamespace"),
+    (TtlvTag::new(0x420004), "ApplicationSpecificInformation"),
+    (TtlvTag::new(0x420005), "ArchiveDate"),
+    (TtlvTag::new(0x420006), "AsynchronousCorrelationValue"),
+    (TtlvTag::new(0x420007), "AsynchronousIndicator"),
+    (TtlvTag::new(0x420008), "Attribute"),
+    (TtlvTag::new(0x420009), "AttributeIndex"),
+    (TtlvTag::new(0x42000A), "AttributeName"),
+    (TtlvTag::new(0x42000B), "AttributeValue"),
+    (TtlvTag::new(0x42000C), "Authentication"),
+    (TtlvTag::new(0x42000D), "BatchCount"),
+    (TtlvTag::new(0x42000E), "BatchErrorContinuationOption"),
+    (TtlvTag::new(0x42000F), "BatchItem"),
+    (TtlvTag::new(0x420010), "BatchOrderOption"),
+    (TtlvTag::new(0x420011), "BlockCipherMode"),
+    (TtlvTag::new(0x420012), "CancellationResult"),
+    (TtlvTag::new(0x420013), "Certificate"),
+    (TtlvTag::new(0x420014), "CertificateIdentifier"),
+    (TtlvTag::new(0x420015), "CertificateIssuer"),
+    (TtlvTag::new(0x420016), "CertificateIssuerAlternativeName"),
+    (TtlvTag::new(0x420017), "CertificateIssuerDistinguishedName"),
+    (TtlvTag::new(0x420018), "CertificateRequest"),
+    (TtlvTag::new(0x420019), "CertificateRequestType"),
+    (TtlvTag::new(0x42001A), "CertificateSubject"),
+    (TtlvTag::new(0x42001B), "CertificateSubjectAlternativeName"),
+    (TtlvTag::new(0x42001C), "CertificateSubjectDistinguishedName"),
+    (TtlvTag::new(0x42001D), "CertificateType"),
+    (TtlvTag::new(0x42001E), "CertificateValue"),
+    (TtlvTag::new(0x42001F), "CommonTemplateAttribute"),
+    (TtlvTag::new(0x420020), "CompromiseDate"),
+    (TtlvTag::new(0x420021), "CompromiseOccurrenceDate"),
+    (TtlvTag::new(0x420022), "ContactInformation"),
+    (TtlvTag::new(0x420023), "Credential"),
+    (TtlvTag::new(0x420024), "CredentialType"),
+    (TtlvTag::new(0x420025), "CredentialValue"),
+    (TtlvTag::new(0x420026), "CriticalityIndicator"),
+    (TtlvTag::new(0x420027), "CRTCoefficient"),
+    (TtlvTag::new(0x420028), "CryptographicAlgorithm"),
+    (TtlvTag::new(0x420029), "CryptographicDomainParameters"),
+    (TtlvTag::new(0x42002A), "CryptographicLength"),
+    (TtlvTag::new(0x42002B), "CryptographicParameters"),
+    (TtlvTag::new(0x42002C), "CryptographicUsageMask"),
+    (TtlvTag::new(0x42002D), "CustomAttribute"),
+    (TtlvTag::new(0x42002E), "D"),
+    (TtlvTag::new(0x42002F), "DeactivationDate"),
+    (TtlvTag::new(0x420030), "DerivationData"),
+    (TtlvTag::new(0x420031), "DerivationMethod"),
+    (TtlvTag::new(0x420032), "DerivationParameters"),
+    (TtlvTag::new(0x420033), "DestroyDate"),
+    (TtlvTag::new(0x420034), "Digest"),
+    (TtlvTag::new(0x420035), "DigestValue"),
+    (TtlvTag::new(0x420036), "EncryptionKeyInformation"),
+    (TtlvTag::new(0x420037), "G"),
+    (TtlvTag::new(0x420038), "HashingAlgorithm"),
+    (TtlvTag::new(0x420039), "InitialDate"),
+    (TtlvTag::new(0x42003A), "InitializationVector"),
+    (TtlvTag::new(0x42003B), "Issuer"),
+    (TtlvTag::new(0x42003C), "IterationCount"),
+    (TtlvTag::new(0x42003D), "IVCounterNonce"),
+    (TtlvTag::new(0x42003E), "J"),
+    (TtlvTag::new(0x42003F), "Key"),
+    (TtlvTag::new(0x420040), "KeyBlock"),
+    (TtlvTag::new(0x420041), "KeyCompressionType"),
+    (TtlvTag::new(0x420042), "KeyFormatType"),
+    (TtlvTag::new(0x420043), "KeyMaterial"),
+    (TtlvTag::new(0x420044), "KeyPartIdentifier"),
+    (TtlvTag::new(0x420045), "KeyValue"),
+    (TtlvTag::new(0x420046), "KeyWrappingData"),
+    (TtlvTag::new(0x420047), "KeyWrappingSpecification"),
+    (TtlvTag::new(0x420048), "LastChangeDate"),
+    (TtlvTag::new(0x420049), "LeaseTime"),
+    (TtlvTag::new(0x42004A), "Link"),
+    (TtlvTag::new(0x42004B), "LinkType"),
+    (TtlvTag::new(0x42004C), "LinkedObjectIdentifier"),
+    (TtlvTag::new(0x42004D), "MACSignature"),
+    (TtlvTag::new(0x42004E), "MACSignatureKeyInformation"),
+    (TtlvTag::new(0x42004F), "MaximumItems"),
+    (TtlvTag::new(0x420050), "MaximumResponseSize"),
+    (TtlvTag::new(0x420051), "MessageExtension"),
+    (TtlvTag::new(0x420052), "Modulus"),
+    (TtlvTag::new(0x420053), "Name"),
+    (TtlvTag::new(0x420054), "NameType"),
+    (TtlvTag::new(0x420055), "NameValue"),
+    (TtlvTag::new(0x420056), "ObjectGroup"),
+    (TtlvTag::new(0x420057), "ObjectType"),
+    (TtlvTag::new(0x420058), "Offset"),
+    (TtlvTag::new(0x420059), "OpaqueDataType"),
+    (TtlvTag::new(0x42005A), "OpaqueDataValue"),
+    (TtlvTag::new(0x42005B), "OpaqueObject"),
+    (TtlvTag::new(0x42005C), "Operation"),
+    (TtlvTag::new(0x42005D), "OperationPolicyName"),
+    (TtlvTag::new(0x42005E), "P"),
+    (TtlvTag::new(0x42005F), "PaddingMethod"),
+    (TtlvTag::new(0x420060), "PrimeExponentP"),
+    (TtlvTag::new(0x420061), "PrimeExponentQ"),
+    (TtlvTag::new(0x420062), "PrimeFieldSize"),
+    (TtlvTag::new(0x420063), "PrivateExponent"),
+    (TtlvTag::new(0x420064), "PrivateKey"),
+    (TtlvTag::new(0x420065), "PrivateKeyTemplateAttribute"),
+    (TtlvTag::new(0x420066), "PrivateKeyUniqueIdentifier"),
+    (TtlvTag::new(0x420067), "ProcessStartDate"),
+    (TtlvTag::new(0x420068), "ProtectStopDate"),
+    (TtlvTag::new(0x420069), "ProtocolVersion"),
+    (TtlvTag::new(0x42006A), "ProtocolVersionMajor"),
+    (TtlvTag::new(0x42006B), "ProtocolVersionMinor"),
+    (TtlvTag::new(0x42006C), "PublicExponent"),
+    (TtlvTag::new(0x42006D), "PublicKey"),
+    (TtlvTag::new(0x42006E), "PublicKeyTemplateAttribute"),
+    (TtlvTag::new(0x42006F), "PublicKeyUniqueIdentifier"),
+    (TtlvTag::new(0x420070), "PutFunction"),
+    (TtlvTag::new(0x420071), "Q"),
+    (TtlvTag::new(0x420072), "QString"),
+    (TtlvTag::new(0x420073), "Qlength"),
+    (TtlvTag::new(0x420074), "QueryFunction"),
+    (TtlvTag::new(0x420075), "RecommendedCurve"),
+    (TtlvTag::new(0x420076), "ReplacedUniqueIdentifier"),
+    (TtlvTag::new(0x420077), "RequestHeader"),
+    (TtlvTag::new(0x420078), "RequestMessage"),
+    (TtlvTag::new(0x420079), "RequestPayload"),
+    (TtlvTag::new(0x42007A), "ResponseHeader"),
+    (TtlvTag::new(0x42007B), "ResponseMessage"),
+    (TtlvTag::new(0x42007C), "ResponsePayload"),
+    (TtlvTag::new(0x42007D), "ResultMessage"),
+    (TtlvTag::new(0x42007E), "ResultReason"),
+    (TtlvTag::new(0x42007F), "ResultStatus"),
+    (TtlvTag::new(0x420080), "RevocationMessage"),
+    (TtlvTag::new(0x420081), "RevocationReason"),
+    (TtlvTag::new(0x420082), "RevocationReasonCode"),
+    (TtlvTag::new(0x420083), "KeyRoleType"),
+    (TtlvTag::new(0x420084), "Salt"),
+    (TtlvTag::new(0x420085), "SecretData"),
+    (TtlvTag::new(0x420086), "SecretDataType"),
+    (TtlvTag::new(0x420087), "SerialNumber"),
+    (TtlvTag::new(0x420088), "ServerInformation"),
+    (TtlvTag::new(0x420089), "SplitKey"),
+    (TtlvTag::new(0x42008A), "SplitKeyMethod"),
+    (TtlvTag::new(0x42008B), "SplitKeyParts"),
+    (TtlvTag::new(0x42008C), "SplitKeyThreshold"),
+    (TtlvTag::new(0x42008D), "State"),
+    (TtlvTag::new(0x42008E), "StorageStatusMask"),
+    (TtlvTag::new(0x42008F), "SymmetricKey"),
+    (TtlvTag::new(0x420090), "Template"),
+    (TtlvTag::new(0x420091), "TemplateAttribute"),
+    (TtlvTag::new(0x420092), "TimeStamp"),
+    (TtlvTag::new(0x420093), "UniqueBatchItemID"),
+    (TtlvTag::new(0x420094), "UniqueIdentifier"),
+    (TtlvTag::new(0x420095), "UsageLimits"),
+    (TtlvTag::new(0x420096), "UsageLimitsCount"),
+    (TtlvTag::new(0x420097), "UsageLimitsTotal"),
+    (TtlvTag::new(0x420098), "UsageLimitsUnit"),
+    (TtlvTag::new(0x420099), "Username"),
+    (TtlvTag::new(0x42009A), "ValidityDate"),
+    (TtlvTag::new(0x42009B), "ValidityIndicator"),
+    (TtlvTag::new(0x42009C), "VendorExtension"),
+    (TtlvTag::new(0x42009D), "VendorIdentification"),
+    (TtlvTag::new(0x42009E), "WrappingMethod"),
+    (TtlvTag::new(0x42009F), "X"),
+    (TtlvTag::new(0x4200A0), "Y"),
+    (TtlvTag::new(0x4200A1), "Password"),
+    (TtlvTag::new(0x4200A2), "DeviceIdentifier"),
+    (TtlvTag::new(0x4200A3), "EncodingOption"),
+    (TtlvTag::new(0x4200A4), "ExtensionInformation"),
+    (TtlvTag::new(0x4200A5), "ExtensionName"),
+    (TtlvTag::new(0x4200A6), "ExtensionTag"),
+    (TtlvTag::new(0x4200A7), "ExtensionType"),
+    (TtlvTag::new(0x4200A8), "Fresh"),
+    (TtlvTag::new(0x4200A9), "MachineIdentifier"),
+    (TtlvTag::new(0x4200AA), "MediaIdentifier"),
+    (TtlvTag::new(0x4200AB), "NetworkIdentifier"),
+    (TtlvTag::new(0x4200AC), "ObjectGroupMember"),
+    (TtlvTag::new(0x4200AD), "CertificateLength"),
+    (TtlvTag::new(0x4200AE), "DigitalSignatureAlgorithm"),
+    (TtlvTag::new(0x4200AF), "CertificateSerialNumber"),
+    (TtlvTag::new(0x4200B0), "DeviceSerialNumber"),
+    (TtlvTag::new(0x4200B1), "IssuerAlternativeName"),
+    (TtlvTag::new(0x4200B2), "IssuerDistinguishedName"),
+    (TtlvTag::new(0x4200B3), "SubjectAlternativeName"),
+    (TtlvTag::new(0x4200B4), "SubjectDistinguishedName"),
+    (TtlvTag::new(0x4200B5), "X509CertificateIdentifier"),
+    (TtlvTag::new(0x4200B6), "X509CertificateIssuer"),
+    (TtlvTag::new(0x4200B7), "X509CertificateSubject"),
+    (TtlvTag::new(0x4200B8), "KeyValueLocation"),
+    (TtlvTag::new(0x4200B9), "KeyValueLocationValue"),
+    (TtlvTag::new(0x4200BA), "KeyValueLocationType"),
+    (TtlvTag::new(0x4200BB), "KeyValuePresent"),
+    (TtlvTag::new(0x4200BC), "OriginalCreationDate"),
+    (TtlvTag::new(0x4200BD), "PGPKey"),
+    (TtlvTag::new(0x4200BE), "PGPKeyVersion"),
+    (TtlvTag::new(0x4200BF), "AlternativeName"),
+    (TtlvTag::new(0x4200C0), "AlternativeNameValue"),
+    (TtlvTag::new(0x4200C1), "AlternativeNameType"),
+    (TtlvTag::new(0x4200C2), "Data"),
+    (TtlvTag::new(0x4200C3), "SignatureData"),
+    (TtlvTag::new(0x4200C4), "DataLength"),
+    (TtlvTag::new(0x4200C5), "RandomIV"),
+    (TtlvTag::new(0x4200C6), "MACData"),
+    (TtlvTag::new(0x4200C7), "AttestationType"),
+    (TtlvTag::new(0x4200C8), "Nonce"),
+    (TtlvTag::new(0x4200C9), "NonceID"),
+    (TtlvTag::new(0x4200CA), "NonceValue"),
+    (TtlvTag::new(0x4200CB), "AttestationMeasurement"),
+    (TtlvTag::new(0x4200CC), "AttestationAssertion"),
+    (TtlvTag::new(0x4200CD), "IVLength"),
+    (TtlvTag::new(0x4200CE), "TagLength"),
+    (TtlvTag::new(0x4200CF), "FixedFieldLength"),
+    (TtlvTag::new(0x4200D0), "CounterLength"),
+    (TtlvTag::new(0x4200D1), "InitialCounterValue"),
+    (TtlvTag::new(0x4200D2), "InvocationFieldLength"),
+    (TtlvTag::new(0x4200D3), "AttestationCapableIndicator"),
+    (TtlvTag::new(0x4200D4), "OffsetItems"),
+    (TtlvTag::new(0x4200D5), "LocatedItems"),
+    (TtlvTag::new(0x4200D6), "CorrelationValue"),
+    (TtlvTag::new(0x4200D7), "InitIndicator"),
+    (TtlvTag::new(0x4200D8), "FinalIndicator"),
+    (TtlvTag::new(0x4200D9), "RNGParameters"),
+    (TtlvTag::new(0x4200DA), "RNGAlgorithm"),
+    (TtlvTag::new(0x4200DB), "DRBGAlgorithm"),
+    (TtlvTag::new(0x4200DC), "FIPS186Variation"),
+    (TtlvTag::new(0x4200DD), "PredictionResistance"),
+    (TtlvTag::new(0x4200DE), "RandomNumberGenerator"),
+    (TtlvTag::new(0x4200DF), "ValidationInformation"),
+    (TtlvTag::new(0x4200E0), "ValidationAuthorityType"),
+    (TtlvTag::new(0x4200E1), "ValidationAuthorityCountry"),
+    (TtlvTag::new(0x4200E2), "ValidationAuthorityURI"),
+    (TtlvTag::new(0x4200E3), "ValidationVersionMajor"),
+    (TtlvTag::new(0x4200E4), "ValidationVersionMinor"),
+    (TtlvTag::new(0x4200E5), "ValidationType"),
+    (TtlvTag::new(0x4200E6), "ValidationLevel"),
+    (TtlvTag::new(0x4200E7), "ValidationCertificateIdentifier"),
+    (TtlvTag::new(0x4200E8), "ValidationCertificateURI"),
+    (TtlvTag::new(0x4200E9), "ValidationVendorURI"),
+    (TtlvTag::new(0x4200EA), "ValidationProfile"),
+    (TtlvTag::new(0x4200EB), "ProfileInformation"),
+    (TtlvTag::new(0x4200EC), "ProfileName"),
+    (TtlvTag::new(0x4200ED), "ServerURI"),
+    (TtlvTag::new(0x4200EE), "ServerPort"),
+    (TtlvTag::new(0x4200EF), "StreamingCapability"),
+    (TtlvTag::new(0x4200F0), "AsynchronousCapability"),
+    (TtlvTag::new(0x4200F1), "AttestationCapability"),
+    (TtlvTag::new(0x4200F2), "UnwrapMode"),
+    (TtlvTag::new(0x4200F3), "DestroyAction"),
+    (TtlvTag::new(0x4200F4), "ShreddingAlgorithm"),
+    (TtlvTag::new(0x4200F5), "RNGMode"),
+    (TtlvTag::new(0x4200F6), "ClientRegistrationMethod"),
+    (TtlvTag::new(0x4200F7), "CapabilityInformation"),
+    (TtlvTag::new(0x4200F8), "KeyWrapType"),
+    (TtlvTag::new(0x4200F9), "BatchUndoCapability"),
+    (TtlvTag::new(0x4200FA), "BatchContinueCapability"),
+    (TtlvTag::new(0x4200FB), "PKCS12FriendlyName"),
+    (TtlvTag::new(0x4200FC), "Description"),
+    (TtlvTag::new(0x4200FD), "Comment"),
+    (TtlvTag::new(0x4200FE), "AuthenticatedEncryptionAdditionalData"),
+    (TtlvTag::new(0x4200FF), "AuthenticatedEncryptionTag"),
+    (TtlvTag::new(0x420100), "SaltLength"),
+    (TtlvTag::new(0x420101), "MaskGenerator"),
+    (TtlvTag::new(0x420102), "MaskGeneratorHashingAlgorithm"),
+    (TtlvTag::new(0x420103), "PSource"),
+    (TtlvTag::new(0x420104), "TrailerField"),
+    (TtlvTag::new(0x420105), "ClientCorrelationValue"),
+    (TtlvTag::new(0x420106), "ServerCorrelationValue"),
+    (TtlvTag::new(0x420107), "DigestedData"),
+    (TtlvTag::new(0x420108), "CertificateSubjectCN"),
+    (TtlvTag::new(0x420109), "CertificateSubjectO"),
+    (TtlvTag::new(0x42010A), "CertificateSubjectOU"),
+    (TtlvTag::new(0x42010B), "CertificateSubjectEmail"),
+    (TtlvTag::new(0x42010C), "CertificateSubjectC"),
+    (TtlvTag::new(0x42010D), "CertificateSubjectST"),
+    (TtlvTag::new(0x42010E), "CertificateSubjectL"),
+    (TtlvTag::new(0x42010F), "CertificateSubjectUID"),
+    (TtlvTag::new(0x420110), "CertificateSubjectSerialNumber"),
+    (TtlvTag::new(0x420111), "CertificateSubjectTitle"),
+    (TtlvTag::new(0x420112), "CertificateSubjectDC"),
+    (TtlvTag::new(0x420113), "CertificateSubjectDNQualifier"),
+    (TtlvTag::new(0x420114), "CertificateIssuerCN"),
+    (TtlvTag::new(0x420115), "CertificateIssuerO"),
+    (TtlvTag::new(0x420116), "CertificateIssuerOU"),
+    (TtlvTag::new(0x420117), "CertificateIssuerEmail"),
+    (TtlvTag::new(0x420118), "CertificateIssuerC"),
+    (TtlvTag::new(0x420119), "CertificateIssuerST"),
+    (TtlvTag::new(0x42011A), "CertificateIssuerL"),
+    (TtlvTag::new(0x42011B), "CertificateIssuerUID"),
+    (TtlvTag::new(0x42011C), "CertificateIssuerSerialNumber"),
+    (TtlvTag::new(0x42011D), "CertificateIssuerTitle"),
+    (TtlvTag::new(0x42011E), "CertificateIssuerDC"),
+    (TtlvTag::new(0x42011F), "CertificateIssuerDNQualifier"),
+    (TtlvTag::new(0x420120), "Sensitive"),
+    (TtlvTag::new(0x420121), "AlwaysSensitive"),
+    (TtlvTag::new(0x420122), "Extractable"),
+    (TtlvTag::new(0x420123), "NeverExtractable"),
+    (TtlvTag::new(0x420124), "ReplaceExisting"),
+    (TtlvTag::new(0x420125), "Attributes"),
+    (TtlvTag::new(0x420126), "CommonAttributes"),
+    (TtlvTag::new(0x420127), "PrivateKeyAttributes"),
+    (TtlvTag::new(0x420128), "PublicKeyAttributes"),
+    (TtlvTag::new(0x420129), "ExtensionEnumeration"),
+    (TtlvTag::new(0x42012A), "ExtensionAttribute"),
+    (TtlvTag::new(0x42012B), "ExtensionParentStructureTag"),
+    (TtlvTag::new(0x42012C), "ExtensionDescription"),
+    (TtlvTag::new(0x42012D), "ServerName"),
+    (TtlvTag::new(0x42012E), "ServerSerialNumber"),
+    (TtlvTag::new(0x42012F), "ServerVersion"),
+    (TtlvTag::new(0x420130), "ServerLoad"),
+    (TtlvTag::new(0x420131), "ProductName"),
+    (TtlvTag::new(0x420132), "BuildLevel"),
+    (TtlvTag::new(0x420133), "BuildDate"),
+    (TtlvTag::new(0x420134), "ClusterInfo"),
+    (TtlvTag::new(0x420135), "AlternateFailoverEndpoints"),
+    (TtlvTag::new(0x420136), "ShortUniqueIdentifier"),
+    (TtlvTag::new(0x420138), "Tag"),
+    (TtlvTag::new(0x420139), "CertificateRequestUniqueIdentifier"),
+    (TtlvTag::new(0x42013A), "NISTKeyType"),
+    (TtlvTag::new(0x42013B), "AttributeReference"),
+    (TtlvTag::new(0x42013C), "CurrentAttribute"),
+    (TtlvTag::new(0x42013D), "NewAttribute"),
+    (TtlvTag::new(0x420140), "CertificateRequestValue"),
+    (TtlvTag::new(0x420141), "LogMessage"),
+    (TtlvTag::new(0x420142), "ProfileVersion"),
+    (TtlvTag::new(0x420143), "ProfileVersionMajor"),
+    (TtlvTag::new(0x420144), "ProfileVersionMinor"),
+    (TtlvTag::new(0x420145), "ProtectionLevel"),
+    (TtlvTag::new(0x420146), "ProtectionPeriod"),
+    (TtlvTag::new(0x420147), "QuantumSafe"),
+    (TtlvTag::new(0x420148), "QuantumSafeCapability"),
+    (TtlvTag::new(0x420149), "Ticket"),
+    (TtlvTag::new(0x42014A), "TicketType"),
+    (TtlvTag::new(0x42014B), "TicketValue"),
+    (TtlvTag::new(0x42014C), "RequestCount"),
+    (TtlvTag::new(0x42014D), "Rights"),
+    (TtlvTag::new(0x42014E), "Objects"),
+    (TtlvTag::new(0x42014F), "Operations"),
+    (TtlvTag::new(0x420150), "Right"),
+    (TtlvTag::new(0x420151), "EndpointRole"),
+    (TtlvTag::new(0x420152), "DefaultsInformation"),
+    (TtlvTag::new(0x420153), "ObjectDefaults"),
+    (TtlvTag::new(0x420154), "Ephemeral"),
+    (TtlvTag::new(0x420155), "ServerHashedPassword"),
+    (TtlvTag::new(0x420156), "OneTimePassword"),
+    (TtlvTag::new(0x420157), "HashedPassword"),
+];
+
+/// Lookup the name of a standard KMIP tag, e.g. "RequestMessage" for tag 0x420078.
+pub fn tag_name(tag: TtlvTag) -> Option<&'static str> {
+    TAGS.binary_search_by_key(&tag, |(tag, _)| *tag)
+        .ok()
+        .map(|idx| TAGS[idx].1)
+}
+
+/// Lookup the standard KMIP tag with the given name, e.g. tag 0x420078 for "RequestMessage".
+pub fn tag_for_name(name: &str) -> Option<TtlvTag> {
+    TAGS.iter().find(|(_, tag_name)| *tag_name == name).map(|(tag, _)| *tag)
+}
+
+/// Create a map of the standard KMIP tags to their names in the form expected by
+/// [crate::util::PrettyPrinter::with_tag_map()].
+#[cfg(feature = "high-level")]
+pub fn tag_map() -> HashMap<TtlvTag, &'static str> {
+    TAGS.iter().copied().collect()
+}
@@ -35,25 +35,29 @@
 //! let my_other_struct: MyStruct = from_slice(&mut bytes)?;
 //! ```
 //!
+//! To deserialize a buffer that may contain several back-to-back responses use [from_slice_multi] or, to process
+//! them one at a time, [TtlvFrameIter].
+//!
 //! ## Low level API
 //!
 //! There is also a low-level API which is much more labourious to use. The high-level API should be
 //! sufficient unless you wish to avoid depending on the Serde crates. You can disable the dependence on Serde by
-//! setting `default-features = false` in `Cargo.toml`, e.g.:
+//! setting `default-features = false` in `Cargo.toml` and enabling only the `std` feature, e.g.:
 //!
 //! ```toml
 //! [dependencies]
-//! kmip-ttlv = { version = "0.3.1", default-features = false }
+//! kmip-ttlv = { version = "0.3.1", default-features = false, features = ["std"] }
 //! ```
 //!
-//! To learn more about the low-level API see the [types] module.
+//! To learn more about the low-level API see the [types] module. To inspect or rewrite TTLV without defining Rust types
+//! for it, parse it into a tree of [types::TtlvNode] values.
 //!
 //! ## Async API
 //!
-//! This crate also supports _deserialization_ from an async reader via the feature flags `async-with-async-std` and
-//! `async-with-tokio`. Only one of these flags can be specified at once and neither can be mixed with the default
-//! 'sync' feature flag. The example below also enables the high level API which is disabled otherwise when you
-//! use `default-features = false`.
+//! This crate also supports _deserialization_ from an async reader via the feature flags `async-with-async-std`,
+//! `async-with-tokio` and `async-with-futures`. These can be enabled alongside each other and alongside the default
+//! `sync` feature, e.g. by an application that contains both blocking tools and an async server. The example below
+//! also enables the high level API which is disabled otherwise when you use `default-features = false`.
 //!
 //! ```toml
 //! [dependencies.kmip-ttlv]
@@ -62,11 +66,55 @@
 //! features = ["async-with-async-std", "high-level"]
 //! ```
 //!
-//! Without an async feature enabled you can only pass something that implements the `Read` trait to [de::from_reader].
+//! The blocking and async reader functions live in separate modules so that they don't clash:
+//!
+//! - `de::sync::from_reader()` accepts anything that implements `std::io::Read`.
+//! - `de::asynch::from_tokio_reader()` accepts anything that implements `tokio::io::AsyncRead`.
+//! - `de::asynch::from_futures_reader()` accepts anything that implements `futures::io::AsyncRead`, including
+//!   `async_std::io::Read` implementations. It doesn't pull in an async runtime so can be used with custom executors
+//!   and test harnesses. Enabled by either `async-with-async-std` or `async-with-futures`.
+//!
+//! Call the async functions with `.await` from an `async` function or block.
+//!
+//! [de::from_reader] is always the blocking function and only exists when the `sync` feature is enabled, whichever
+//! async features are enabled. Async code should call the functions in `de::asynch` by name.
+//!
+//! ## Optional features
+//!
+//! The following optional feature flags enable integration with other crates:
+//!
+//! - `bytes`: adds `to_bytes_mut()` and `from_bytes()` functions for (de)serializing directly to/from the
+//!   `bytes::BytesMut` and `bytes::Bytes` buffer types used by `tokio-util` codecs, and `from_buf()` for
+//!   deserializing from any `bytes::Buf`, including chained non-contiguous segments.
+//! - `codec`: adds the `codec` module with `TtlvCodec` and `TypedTtlvCodec`, `tokio_util::codec::Decoder`
+//!   implementations that split a byte stream into complete TTLV messages and optionally deserialize them. Implies
+//!   `high-level` and `bytes`.
+//! - `kmip-tags`: adds the `kmip_tags` module with a registry of the names of the tags defined by the KMIP
+//!   specifications, for use with [de::Config::with_tag_name_resolver()], the `with_tag_provider()` settings and
+//!   `PrettyPrinter::with_tag_map()`.
+//! - `num-bigint`: adds `From`/`TryFrom` conversions between [types::TtlvBigInteger] and the `num_bigint::BigInt`
+//!   and `num_bigint::BigUint` types, and the `bigint` module of Serde `with` helpers for (de)serializing such fields
+//!   as TTLV Big Integers.
+//! - `serde`: implements Serde `Serialize` and `Deserialize` for [types::TtlvTag], [types::TtlvType] and
+//!   [types::TtlvLength] so that they can be used in config files, JSON diagnostics and test fixtures. Implied by
+//!   `high-level`.
+//! - `xml`: adds the `xml` module for (de)serializing to/from the KMIP XML encoding of TTLV. Implies `high-level` and
+//!   `kmip-tags`.
+//! - `fuzz`: adds the `fuzz` module with stable entry points for use by fuzz targets. Implies `high-level`.
+//!
+//! The `std` feature, which is enabled by default and by every feature that needs the Rust standard library, e.g.
+//! `high-level`, `sync` and the async features, makes the low-level API read and write via `std::io::Read` and
+//! `std::io::Write`. Without it the [types] module is usable in a `#![no_std]` environment that provides the `alloc`
+//! crate, reading from `&[u8]` and writing to `Vec<u8>` or `&mut [u8]` via the [types::TtlvRead] and
+//! [types::TtlvWrite] traits instead. The `no-std` feature flag is kept for compatibility and has no effect of its
+//! own, so enabling it alongside features that need the standard library, e.g. with `--all-features`, is harmless.
 //!
-//! With an async feature enabled you can pass something that implements `async_std::io::ReadExt` or
-//! `tokio::io::AsyncReadExt`. You'll also need to then suffix the call to [de::from_reader] with `.await` and call
-//! it from an `async` function or block.
+//! ```toml
+//! [dependencies.kmip-ttlv]
+//! version = "0.3.1"
+//! default-features = false
+//! features = ["no-std"]
+//! ```
 //!
 //! # TTLV format
 //!
@@ -112,7 +160,9 @@
 //! a 4 byte "length". There is no "value" part in this case because the struct doesn't have any fields so the value
 //! length is zero.
 //!
-//! > **NOTE:** If we omit the `#[serde(rename = "...")]` attribute this code will print an error.
+//! > **NOTE:** If we omit the `#[serde(rename = "...")]` attribute this code will print an error, unless a function
+//! > that looks up the tag by name has been configured with [ser::Config::with_tag_provider()] and
+//! > [de::Config::with_tag_provider()].
 //!
 //! # Choosing tag values
 //!
@@ -128,50 +178,105 @@
 //! | TTLV data type      | Serializes from     | Deserializes to     |
 //! |---------------------|---------------------|---------------------|
 //! | Structure (0x01)    | `SomeStruct { .. }`, `SomeStruct( .. )`, tuple variant | `SomeStruct { .. }` |
-//! | Integer (0x02)      | `i8`, `i16`, `i32`  | `i32`               |
-//! | Long Integer (0x03) | `i64`               | `i64`               |
-//! | Big Integer (0x04)  | **UNSUPPORTED**     | `Vec<u8>`           |
-//! | Enumeration (0x05)  | `u32`               | See above           |
+//! | Integer (0x02)      | `i8`-`i32`, `u8`, `u16`, `u32` via `unsigned` | `i8`, `i16`, `i32`, `u32` via `unsigned` |
+//! | Long Integer (0x03) | `i64`, `u64` via `unsigned` | `i64`, `u64` via `unsigned` |
+//! | Big Integer (0x04)  | `TtlvBigInteger`, `i128`, `u128` | `Vec<u8>`, `i128`, `u128` |
+//! | Enumeration (0x05)  | `u32`               | `u32`, see above    |
 //! | Boolean (0x06)      | `bool`              | `bool`              |
-//! | Text String (0x07)  | `str``              | `String`            |
-//! | Byte String (0x08)  | `&[u8]`             | `Vec<u8>`           |
+//! | Text String (0x07)  | `str``              | `String`, `&str`    |
+//! | Byte String (0x08)  | `&[u8]`             | `Vec<u8>`, `&[u8]`  |
 //! | Date Time (0x09)    | `u64`               | `i64`               |
-//! | Interval (0x0A)     | **UNSUPPORTED**     | **UNSUPPORTED**     |
+//! | Interval (0x0A)     | `TtlvInterval`      | `u32`, `TtlvInterval` |
 //!
 //! # Unsupported data types
 //!
 //! Not all Rust and TTLV data types are supported by this crate, either because there is no obvious mapping from one to
 //! the other or because support for it wasn't needed yet:
 //!
-//! - The following Rust types **CANNOT** be _serialized_ to TTLV as TTLV has no concept of unsigned
-//!   integers, floating point, character or 'missing' values : `u8`, `u16`, `f32`, `f64`, `char`, `()`, `None` _(but
-//!   see below for a special note about `None`)_.
-//!
-//! - The following Rust types **CANNOT** be _deserialized_ from TTLV: `()`, `u8`, `u16`, `u32`, `u64`, `i8`, `i16`,
-//!  `f32`, `f64`, `char`, `str`, map, `&[u8]`, `()`. `char`,
+//! - The following Rust types **CANNOT** be _serialized_ to TTLV as TTLV has no concept of floating point, character
+//!   or 'missing' values : `f32`, `f64`, `char`, `()`, `None` _(but see below for a special note about `None`)_. TTLV
+//!   also has no concept of unsigned integers, but `u8` and `u16` values are widened to a TTLV Integer.
 //!
-//! - The following TTLV types **CANNOT** _yet_ be serialized to TTLV: Big Integer (0x04), Interval (0x0A).
-//!
-//! - The following TTLV types **CANNOT** _yet_ be deserialized from TTLV: Interval (0x0A).
+//! - The following Rust types **CANNOT** be _deserialized_ from TTLV: `()`, `u8`, `u16`, `u64`,
+//!   `f32`, `f64`, `char`, `()`. `char`,
 //!
 //! - The following Rust types **CANNOT** be deserialized as this crate is opinionated and prefers to
-//!   deserialize only into named fields, not nameless groups of values: unit struct, tuple struct, tuple.
+//!   deserialize only into named fields, not nameless groups of values: tuple struct, tuple. Tuple struct support can
+//!   however be opted into, see [de::Config::with_tuple_structs()].
 //!
 //! # Data types treated specially
 //!
 //! - The Rust `struct` type by default serializes to a TTLV Structure However sometimes it is useful to be able to use a
 //!   newtype struct as a wrapper around a primitive type so that you can associate a TTLV tag value with it. This can be
 //!   done by using the `Transparent:` prefix when renaming the type, e.g. `#[serde(rename = "Transparent:0xNNNNNN")]`.
+//!   To serialize an inner integer of any width as a TTLV Enumeration, Interval or Date-Time also name the TTLV type,
+//!   e.g. `#[serde(rename = "Transparent(Enumeration):0xNNNNNN")]`.
 //!
 //! - The Rust `Some` type is handled as if it were only the value inside the Option, the `Some` wrapper is ignored.
 //!
-//! - The Rust `None` type cannot be serialized to TTLV. Instead use `#[serde(skip_serializing_if = "Option::is_none")]`
-//!   on the `Option` field to be serialized so that Serde skips it if it has value `None` when serializing. When
+//! - The Rust `None` type cannot be serialized to TTLV. Instead an `Option` field that has value `None` is omitted when
+//!   serializing, as if it were annotated with `#[serde(skip_serializing_if = "Option::is_none")]`. This requires the
+//!   `Option` to be outside of any `Transparent:0xNNNNNN` newtype struct that tags the value, as the tag cannot be
+//!   omitted once written. To fail rather than omit `None` values use [ser::Config::with_reject_none()]. When
 //!   deserializing into an `Option` if no value with the specified tag is present in the TTLV bytes the Option will be
-//!   set to `None`.
+//!   set to `None`. Likewise a field annotated with `#[serde(default)]` will be set to its default value if no value
+//!   with the specified tag is present.
+//!
+//! - A Rust `&str` field borrows a deserialized TTLV Text String directly from the input slice rather than allocating
+//!   a `String`. Likewise a `&[u8]`, [BytesRef] or `serde_bytes::Bytes` field borrows a TTLV Byte String rather than
+//!   copying it.
+//!   This requires deserializing with [de::from_slice] and the input to outlive the deserialized value.
 //!
 //! - The Rust `Vec` type can be used to (de)serialize sequences of TTLV items. To serialize a `Vec` of bytes to a TTLV
-//!   Byte String however you should annotate the field with the Serde derive attribute `#[serde(with = "serde_bytes")]`.
+//!   Byte String however you should either use the [Bytes] type instead or annotate the field with the Serde derive
+//!   attribute `#[serde(with = "serde_bytes")]`.
+//!   When deserializing, a TTLV Structure that contains more than one item for a field that is not a `Vec` is rejected
+//!   unless configured otherwise with [Config::with_duplicate_tag_policy()].
+//!
+//! - A Rust tuple, or a tuple struct renamed to `Transparent`, serializes its elements in order into the enclosing TTLV
+//!   Structure, each element supplying its own tag e.g. via a `Transparent:0xNNNNNN` newtype struct. This avoids having
+//!   to define a named struct to model a short fixed sequence of items. Inside a `Transparent:0xNNNNNN` newtype struct
+//!   a tuple instead serializes to a TTLV Structure with that tag.
+//!
+//! - To serialize a large TTLV Byte String without holding its content in memory use a [ByteStream], which reads the
+//!   content from a [std::io::Read] implementation while it is being written by [to_writer()].
+//!
+//! - A [types::TtlvItem] captures any TTLV item as its tag, type and raw value bytes when deserializing. A last struct
+//!   field of type `Vec<TtlvItem>` annotated with `#[serde(default, rename = "*")]` collects all remaining TTLV items
+//!   in the structure that were not matched by earlier fields, e.g. vendor extensions that you do not model. To pass
+//!   these items on use [types::TtlvItem::write()]. To also pass on items whose type is not defined by the KMIP
+//!   specification, even where their tag is modelled, use [Config::with_opaque_extension_types()].
+//!
+//! - A Rust map keyed by TTLV tag, e.g. `BTreeMap<`[types::TtlvTag]`, V>`, `HashMap<u32, V>` or `HashMap<String, V>`
+//!   with keys in hex form such as `"0x42006A"`, can be deserialized from a TTLV Structure. When serializing, each map
+//!   entry becomes a TTLV item tagged by its key. Like a `Vec` a map has no tag of its own so wrap it in a newtype
+//!   struct, e.g. `#[serde(rename = "0xNNNNNN")] struct Attributes(BTreeMap<TtlvTag, V>)`, to serialize it as a TTLV
+//!   Structure. The entries of a `HashMap` are serialized in an unspecified order, use
+//!   [ser::Config::with_canonical_order()] to serialize them in tag order instead.
+//!
+//! - A sequence of TTLV Structures with the same tag can be deserialized directly into a Rust map keyed by the value of
+//!   one of their child items by renaming the struct field to `keyed:0xSSSSSS/0xNNNNNN`, where 0xSSSSSS is the tag of
+//!   the structures and 0xNNNNNN is the tag of the child item, e.g.
+//!   `#[serde(rename = "keyed:0x420008/0x42000A")] attributes: HashMap<String, Attribute>` to key KMIP Attribute
+//!   structures by their Attribute Name. If the next item does not have the structure tag the map is absent, as for
+//!   any other field, so use `Option` or `#[serde(default)]` for a map that may be empty. Text String, Integer, Long
+//!   Integer, Enumeration, Interval, Boolean, Date Time and Byte String child items can be used as keys. This is only
+//!   supported when deserializing.
+//!
+//! - Fields annotated with `#[serde(flatten)]` are supported. This can be used to factor out fields that are common to
+//!   several structures into a shared struct. As Serde derive deserializes such a struct via a map, its fields are
+//!   matched by tag rather than by position and the struct's own `rename` tag is not checked. The flattened fields are
+//!   first buffered by Serde and so cannot use the special handling described here for `enum` types and borrowed data.
+//!   When serializing, the items of the flattened struct become items of the enclosing TTLV Structure. Serde also
+//!   serializes such a struct via a map and does not pass on its `rename` tag, so wrap it in a newtype struct, e.g.
+//!   `#[serde(rename = "Transparent:0xNNNNNN")] struct Request(Message)`, to serialize it as a TTLV Structure. Fields
+//!   not renamed to a tag must supply their own tag, e.g. by being a `Transparent:0xNNNNNN` newtype struct.
+//!
+//! - TTLV is self-describing, so generic Serde consumers that do not know the data types in advance, e.g.
+//!   `serde_json::Value` or `serde::de::IgnoredAny`, can be deserialized into. TTLV Structures are presented as maps
+//!   keyed by tag in hex form such as `"0x42006A"` and items of other types as the corresponding Rust primitive type.
+//!   TTLV Byte Strings and Big Integers are presented as bytes which not every consumer supports, e.g. `serde_json`
+//!   doesn't.
 //!
 //! - The Rust `enum` type is serialized differently depending on the type of the variant being serialized. For unit
 //!   variants a `#[serde(rename = "0xNNNNNNNN")]` attribute should be used to cause this crate to serialize the value
@@ -187,19 +292,78 @@
 //!     0xMMMMMMMM.
 //!   - `#[serde(rename = "if 0xNNNNNN in [0xAAAAAAAA, 0xBBBBBBBB, ..]")]` is like the previous syntax but can match
 //!     against more than one possible value.
+//!   - `#[serde(rename = "if 0xNNNNNN in 0xAAAAAAAA..0xBBBBBBBB")]` selects the variant if a previously seen value for
+//!     the specified tag lies within the given range, e.g. a block of vendor extension values. As in Rust the end of
+//!     the range is excluded, use `..=` to include it.
+//!   - `#[serde(rename = "if 0xNNNNNN != 0xMMMMMMMM")]` is the negation of the `==` syntax. It selects this `enum`
+//!     variant if the previously encountered value for tag 0xNNNNNN was anything other than 0xMMMMMMMM, e.g. to
+//!     provide a fallback variant without listing every other possible value. It does not match if no value for the
+//!     tag was encountered. `!=` can also be used with textual content and with `type`.
 //!   - `#[serde(rename = "if 0xNNNNNN >= 0xMMMMMMMM")]` can be used to select the variant if a previously seen value
 //!     for the specified tag was at least the given value.
 //!   - `#[serde(rename = "if 0xNNNNNN==Textual Content")]` syntax will cause this crate to look for a previously
 //!     encountered TTLV Text String with tag value 0xNNNNNN and to select this `enum` variant if that Text String had
 //!     value `Textual Content`.
+//!   - `#[serde(rename = "if 0xNNNNNN startswith Textual")]` selects the variant if a previously seen value for the
+//!     specified tag starts with the given text, e.g. `urn:` to tell apart identifiers by their shape. For more
+//!     elaborate shapes `#[serde(rename = "if 0xNNNNNN like urn:*:key-?")]` matches the value against a pattern in
+//!     which `*` matches any sequence of characters and `?` matches any single character.
+//!   - `#[serde(rename = "if 0xNNNNNN & 0xMMMMMMMM != 0")]` selects the variant if any of the bits in the mask are set
+//!     in a previously seen TTLV Integer or Enumeration value for the specified tag, e.g. to key off individual bits of
+//!     the KMIP Cryptographic Usage Mask. Use `== 0` to test that none of the bits are set, or compare against the mask
+//!     itself to test that all of them are set.
 //!   - `#[serde(rename = "if type==XXX")]` syntax (where `XXX` is a camel case TTLV type name without spaces such as
 //!     `LongInteger`) will cause this crate to select the enum variant if the TTLV type encountered while deserializing
 //!     has the specified type.
+//!   - `#[serde(rename = "if present 0xNNNNNN")]` and `#[serde(rename = "if absent 0xNNNNNN")]` select the variant
+//!     if a TTLV item with tag 0xNNNNNN, of any type, was or was not encountered earlier in the TTLV byte stream, e.g.
+//!     to tell apart responses that differ only in whether an optional item is present.
+//!   - Where the same tag occurs in several structures with different meanings the `0xNNNNNN` tag in the `==`, `!=`,
+//!     `in` and `>=` syntaxes can be replaced by a `/` separated path of tags, e.g. `#[serde(rename = "if
+//!     0x420077/0x420069/0x42006A >= 0x00000002")]`, to only consider items with that tag nested within structures with
+//!     the preceding tags. Start the path with `/` to require it to match from the root structure.
+//!   - Any of the above conditions can be combined with `&&`, e.g. `#[serde(rename = "if 0xNNNNNN==0xAAAAAAAA &&
+//!     0xMMMMMM==0xBBBBBBBB")]`, to select the `enum` variant only if all of the conditions are met. As a consequence
+//!     textual content to match against cannot contain `&&`.
+//!   - `#[serde(rename = "else")]` marks the variant to select when none of the other variants match, e.g. to cope
+//!     with responses from servers that use unknown extensions. For an `enum` whose variants are selected by the `if`
+//!     syntaxes above the `else` variant can have data like any other. For an `enum` of unit variants named after TTLV
+//!     Enumeration values the `else` variant is selected if the value read matches none of the other variants, and as
+//!     the value has then already been read it must be a unit variant too.
+//!
+//!   For selection rules that these syntaxes cannot express use [Config::with_variant_selector()] to supply a function
+//!   that chooses the variant, by name, given a [de::MatcherContext] describing the enum and the TTLV items seen so
+//!   far. Like a matched `if` variant, a variant chosen this way is deserialized from the TTLV item without first
+//!   reading it.
 //!
 //! - TTLV Big Integer values can be deserialized to a `Vec<u8>` in their raw byte format. Using a crate like
 //!   `num_bigint` you can work with these byte sequences as if they were normal Rust integers. For example, To convert
 //!   from a `Vec<u8>` obtained from a TTLV Big Integer to a `num_bigint::BigInt` use the
-//!   `num_bigint::BigInt::from_signed_bytes_be` function.
+//!   `num_bigint::BigInt::from_signed_bytes_be` function, or enable the `num-bigint` feature of this crate and use the
+//!   `From`/`TryFrom` conversions provided for [types::TtlvBigInteger].
+//!   Values that fit in 128 bits can also be deserialized directly to an `i128` or `u128`, with an out of range value
+//!   causing deserialization to fail. Likewise `i128` and `u128` values serialize to a TTLV Big Integer, sign-extended
+//!   to the shortest multiple of eight bytes. To serialize larger values use a [types::TtlvBigInteger] field, or with
+//!   the `num-bigint` feature annotate a `num_bigint::BigInt` or `num_bigint::BigUint` field with
+//!   `#[serde(with = "kmip_ttlv::bigint")]`.
+//!
+//! - TTLV Date-Time values can be (de)serialized from/to `chrono::DateTime<Utc>` fields by enabling the `chrono`
+//!   feature of this crate and annotating the field with `#[serde(with = "kmip_ttlv::datetime")]`, see the `datetime`
+//!   module. Likewise with the `time` feature `time::OffsetDateTime` fields can be annotated with
+//!   `#[serde(with = "kmip_ttlv::offset_datetime")]`. Any fractional second is discarded when serializing, use the
+//!   `strict` submodule of either module to fail serialization instead.
+//!
+//! - TTLV Interval values can be deserialized to a `u32` number of seconds. To serialize an Interval, or to reject
+//!   other TTLV types when deserializing, use a [types::TtlvInterval] inside a `Transparent:0xNNNNNN` newtype struct.
+//!
+//! - A `u32` serializes to a TTLV Enumeration and a `u64` to a TTLV Date-Time. To instead (de)serialize a `u32` from/to
+//!   a TTLV Integer or a `u64` from/to a TTLV Long Integer annotate the field with
+//!   `#[serde(with = "kmip_ttlv::unsigned")]`, see the [unsigned] module. Values that do not fit in the signed TTLV
+//!   type cause serialization to fail, and negative values cause deserialization to fail.
+//!
+//! - To (de)serialize an integer field of any width from/to a TTLV Date-Time, Enumeration or Interval regardless of its
+//!   Rust type annotate the field with e.g. `#[serde(with = "kmip_ttlv::as_type::date_time")]`, see the [as_type]
+//!   module.
 //!
 //! # Examples
 //!
@@ -223,28 +387,43 @@
 //! # Error handling
 //!
 //! By default Serde ignores any items present in the TTLV byte stream that do not correspond to a tagged field in the
-//! Rust struct being deserialized into. You can disable this behaviour and make the presence of unexpected TTLV items
-//! into a deserialization error by using the `#[serde(deny_unknown_fields)]` container level Serde derive attribute.
-//! You can also explicitly ignore an unsupported item by using the `#[serde(skip_deserializing)]` field level
-//! attribute.
-//!
-//! This crate does not try to be clone free or to support `no_std` scenarios. Memory is allocated to serialize and
-//! deserialize into. In particular when deserializing bytes received from an untrusted source with `from_reader()` this
-//! could cause allocation of a large amount of memory at which point Rust will panic if the allocation fails. When
+//! Rust struct being deserialized into. Such items are skipped using their length without parsing their content, so
+//! large unmodelled structures are cheap to skip but are also not checked for validity. You can disable this behaviour
+//! and make the presence of unexpected TTLV items into a deserialization error by using the
+//! `#[serde(deny_unknown_fields)]` container level Serde derive attribute. You can also explicitly ignore an
+//! unsupported item by using the `#[serde(skip_deserializing)]` field level attribute. To find out which items were
+//! skipped, e.g. which vendor extensions a server sends that the client does not model, use
+//! [de::from_slice_with_report()].
+//!
+//! The high-level API does not try to be clone free or to support `no_std` scenarios. Memory is allocated to serialize
+//! and deserialize into. In particular when deserializing bytes received from an untrusted source with `from_reader()`
+//! this could cause allocation of a large amount of memory at which point Rust will panic if the allocation fails. When
 //! deserializing with `from_reader()` you are strongly advised to use a `Config` object that specifies a maximum byte
-//! length to deserialize to prevent such abuse.
+//! length to deserialize to prevent such abuse. The length of TTLV Big Integer values, for which space is allocated
+//! before the value is read, is limited to a few KiB by default, see [Config::with_max_big_integer_len()].
+//!
+//! Likewise when serializing data that is derived from an untrusted source use [to_vec_with_config()] or
+//! [to_writer_with_config()] with a [ser::Config] that limits the size and nesting depth of the output and, if
+//! desired, the tags that may be written.
+//!
+//! To check that bytes received from an untrusted source are structurally valid TTLV before attempting to deserialize
+//! them use [de::validate()]. This walks the TTLV items without involving any Rust types and reports the byte offset of
+//! the first problem found, if any, or otherwise returns a [de::ValidationReport] summarizing what was found.
 //!
 //! If serialization or deserialization fails this crate tries to return sufficient contextual information to aid
-//! diagnosing where the problem in the data is and why.
+//! diagnosing where the problem in the data is and why. To see human readable tag names alongside the hexadecimal tag
+//! values in deserialization errors use [Config::with_tag_name_resolver()] with [de::from_slice_with_config()] or
+//! [de::from_reader()].
 //!
 //! For logging or storing of requests and responses for later diagnostic purposes use the
 //! [PrettyPrinter::to_diag_string()] function to render TTLV bytes in a compact textual representation with most
-//! values redacted (only enumeration values are included in the generated string).
-#[cfg(all(
-    feature = "sync",
-    any(feature = "async-with-async-std", feature = "async-with-tokio")
-))]
-compile_error!("feature \"sync\" cannot be enabled at the same time as either of the \"async-with-async-std\" or \"async-with-tokio\" features");
+//! values redacted (only enumeration values are included in the generated string). Alternatively, to collect metrics
+//! about, log or enforce policies on the items received as they are deserialized, use
+//! [Config::with_item_observer()]. To find out where in the output each item was written, e.g. in order to sign or
+//! redact parts of it afterwards, use [ser::Config::with_item_observer()] when serializing.
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+extern crate alloc;
 
 #[cfg(all(feature = "async-std", not(feature = "async-with-async-std")))]
 compile_error!("do not enable the \"async-std\" feature directly, instead enable the \"async-with-async-std\" feature");
@@ -252,33 +431,88 @@ compile_error!("do not enable the \"async-std\" feature directly, instead enable
 #[cfg(all(feature = "tokio", not(feature = "async-with-tokio")))]
 compile_error!("do not enable the \"tokio\" feature directly, instead enable the \"async-with-tokio\" feature");
 
+#[cfg(all(feature = "futures-util", not(feature = "async-with-futures")))]
+compile_error!(
+    "do not enable the \"futures-util\" feature directly, instead enable the \"async-with-futures\" feature"
+);
+
 #[cfg(feature = "high-level")]
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "high-level")]
+pub mod as_type;
+#[cfg(all(feature = "high-level", feature = "num-bigint"))]
+pub mod bigint;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(all(feature = "high-level", feature = "chrono"))]
+pub mod datetime;
 #[cfg(feature = "high-level")]
 pub mod de;
 #[cfg(feature = "high-level")]
 pub mod error;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+#[cfg(feature = "kmip-tags")]
+pub mod kmip_tags;
+#[cfg(all(feature = "high-level", feature = "time"))]
+pub mod offset_datetime;
 #[cfg(feature = "high-level")]
 pub mod ser;
 #[cfg(feature = "high-level")]
 pub mod traits;
 pub mod types;
 #[cfg(feature = "high-level")]
+pub mod unsigned;
+#[cfg(feature = "high-level")]
 pub mod util;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 #[cfg(feature = "high-level")]
 #[doc(inline)]
-pub use de::{from_reader, from_slice, Config};
+pub use de::{
+    from_slice, from_slice_iter, from_slice_multi, from_slice_partial, from_slice_with_config, from_slice_with_partial,
+    from_slice_with_report, validate, Config, DeserializationReport, DuplicateTagPolicy, IgnoredItem, LossyTextString,
+    PartialError, ReusableDeserializer, TtlvFrameIter, ValidationReport,
+};
+
+#[cfg(all(feature = "high-level", feature = "sync"))]
+#[doc(inline)]
+pub use de::{from_reader, from_reader_iter, TtlvReaderIter};
 
 #[cfg(feature = "high-level")]
 #[doc(inline)]
-pub use ser::{to_vec, to_writer};
+pub use ser::{
+    serialized_size, serialized_size_with_config, to_base64_string, to_hex_string, to_seekable_writer,
+    to_seekable_writer_with_config, to_vec, to_vec_with_config, to_writer, to_writer_with_config, ByteStream,
+    HexWriter, MessageWriter, WriteStats,
+};
+
+#[cfg(feature = "high-level")]
+#[doc(inline)]
+pub use types::{Bytes, BytesRef};
+
+#[cfg(all(feature = "high-level", feature = "bytes"))]
+#[doc(inline)]
+pub use ser::{to_bytes_mut, to_bytes_mut_with_config};
+
+#[cfg(all(feature = "high-level", feature = "bytes"))]
+#[doc(inline)]
+pub use de::{from_buf, from_bytes};
+
+#[cfg(feature = "codec")]
+#[doc(inline)]
+pub use codec::{TtlvCodec, TypedTtlvCodec};
 
 #[cfg(feature = "high-level")]
 #[doc(inline)]
 pub use util::PrettyPrinter;
 
+#[cfg(feature = "xml")]
+#[doc(inline)]
+pub use xml::{from_xml, to_xml};
+
 #[cfg(test)]
 mod tests;
@@ -1,870 +1,3894 @@
 //! High-level Serde based serialization of Rust data types to TTLV bytes.
 
-use std::{io::Write, str::FromStr};
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    io::{IoSlice, Read, Seek, SeekFrom, Write},
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::Arc,
+};
 
 use serde::{
-    ser::{self, Impossible, SerializeTupleStruct},
+    ser::{self, Impossible, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use types::{TtlvBoolean, TtlvEnumeration, TtlvInteger, TtlvLength, TtlvLongInteger, TtlvTextString};
+use types::{
+    TtlvBigInteger, TtlvBoolean, TtlvEnumeration, TtlvInteger, TtlvInterval, TtlvLength, TtlvLongInteger,
+    TtlvTextString, TTLV_BIG_INTEGER_NAME, TTLV_DATE_TIME_NAME, TTLV_ENUMERATION_NAME, TTLV_INTERVAL_NAME,
+};
+
+#[cfg(feature = "async-with-futures")]
+use futures_util::io::{AsyncWrite as FuturesWrite, AsyncWriteExt as FuturesWriteExt};
+
+#[cfg(all(feature = "async-with-async-std", not(feature = "async-with-futures")))]
+use async_std::io::{Write as FuturesWrite, WriteExt as FuturesWriteExt};
 
 use crate::{
-    error::{Error, ErrorLocation, MalformedTtlvError, Result, SerdeError},
+    de::{ItemHeader, TagProvider},
+    error::{Error, ErrorKind, ErrorLocation, MalformedTtlvError, Result, SerdeError},
     types::{
-        self, ByteOffset, FieldType, SerializableTtlvType, TtlvByteString, TtlvDateTime, TtlvStateMachine,
-        TtlvStateMachineMode, TtlvTag, TtlvType,
+        self, ByteOffset, Bytes, BytesRef, FieldType, SerializableTtlvType, TtlvByteString, TtlvDateTime,
+        TtlvStateMachine, TtlvStateMachineMode, TtlvTag, TtlvType,
     },
 };
 
 // --- Public interface ------------------------------------------------------------------------------------------------
 
-/// Serialize and write bytes into a new Vector.
-pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
-    let mut ser = TtlvSerializer::new();
-    value.serialize(&mut ser)?;
-    ser.into_vec()
+/// A function that is shown the header of every TTLV item as it is serialized.
+///
+/// Returning an error fails serialization. See [Config::with_item_observer()].
+pub type ItemObserver = Arc<dyn Fn(&ItemHeader) -> std::result::Result<(), String> + Send + Sync>;
+
+/// Configuration settings used by the serializer.
+///
+/// Use this when serializing data derived from an untrusted source, e.g. a response echoing values from a request, to
+/// limit the size and shape of the TTLV bytes produced.
+#[derive(Clone, Default)]
+pub struct Config {
+    allowed_tag_ranges: Vec<RangeInclusive<TtlvTag>>,
+    canonical_order: bool,
+    item_observer: Option<ItemObserver>,
+    max_bytes: Option<u32>,
+    max_nesting_depth: Option<usize>,
+    reject_none: bool,
+    tag_provider: Option<TagProvider>,
 }
 
-/// Serialize and write bytes to a Writer.
-pub fn to_writer<T, W>(value: &T, mut writer: W) -> Result<()>
-where
-    T: Serialize,
-    W: Write,
-{
-    let vec = to_vec(value)?;
-    writer
-        .write_all(&vec)
-        .map_err(|err| pinpoint!(err, ErrorLocation::unknown()))?;
-    Ok(())
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The configured observer cannot be shown, only whether or not it is set
+        f.debug_struct("Config")
+            .field("allowed_tag_ranges", &self.allowed_tag_ranges)
+            .field("canonical_order", &self.canonical_order)
+            .field("item_observer", &self.item_observer.is_some())
+            .field("max_bytes", &self.max_bytes)
+            .field("max_nesting_depth", &self.max_nesting_depth)
+            .field("reject_none", &self.reject_none)
+            .field("tag_provider", &self.tag_provider)
+            .finish()
+    }
 }
 
-impl serde::ser::Error for Error {
-    fn custom<T: std::fmt::Display>(msg: T) -> Self {
-        pinpoint!(SerdeError::Other(msg.to_string()), ErrorLocation::unknown())
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
     }
 }
 
-// --- Private implementation details ----------------------------------------------------------------------------------
+impl Config {
+    /// Which tags may be serialized? All tags are permitted if no ranges are configured.
+    pub fn allowed_tag_ranges(&self) -> &[RangeInclusive<TtlvTag>] {
+        &self.allowed_tag_ranges
+    }
 
-impl From<&mut TtlvSerializer> for ErrorLocation {
-    fn from(ser: &mut TtlvSerializer) -> Self {
-        use std::convert::TryFrom;
-        match u64::try_from(ser.dst.len()) {
-            Ok(offset) => ErrorLocation::from(ByteOffset::from(offset)),
-            Err(_) => ErrorLocation::unknown(),
-        }
+    /// Should the items of each TTLV Structure be sorted by tag?
+    pub fn canonical_order(&self) -> bool {
+        self.canonical_order
     }
-}
 
-pub struct TtlvSerializer {
-    /// The destination buffer to serialize TTLV bytes into. If we want to write to something else in future we will need
-    /// a way to be able to write to an earlier position in the output so that we can rewrite an items length value once
-    /// we know how long it is (with padding rules per TTLV type taken into account). Currently this is done simply by
-    /// indexing directly into the output buffer. An alternate approach could be to require the Seek trait to be
-    /// implemented.
-    dst: Vec<u8>,
+    /// What, if any, is the configured function for observing the TTLV items being serialized?
+    pub fn item_observer(&self) -> Option<ItemObserver> {
+        self.item_observer.clone()
+    }
 
-    /// A push/pop stack of indexes into the `dst` buffer to the points at which TTLV value byte lengths must be returned
-    /// to and overwritten once the length of the value being written, and any padding to ignore, is known.
-    bookmarks: Vec<usize>,
+    /// What, if any, is the configured maximum permitted number of bytes to produce?
+    pub fn max_bytes(&self) -> Option<u32> {
+        self.max_bytes
+    }
 
-    state: TtlvStateMachine,
+    /// What, if any, is the configured maximum permitted depth of nested TTLV Structures?
+    pub fn max_nesting_depth(&self) -> Option<usize> {
+        self.max_nesting_depth
+    }
+
+    /// Should serializing a `None` value fail rather than omit the value?
+    pub fn reject_none(&self) -> bool {
+        self.reject_none
+    }
+
+    /// What, if any, is the configured function for looking up the tags of Rust type names?
+    pub fn tag_provider(&self) -> Option<TagProvider> {
+        self.tag_provider
+    }
 }
 
-impl Default for TtlvSerializer {
-    fn default() -> Self {
+// Builder style interface
+impl Config {
+    /// Permit serialization of items with tags in the given range.
+    ///
+    /// May be used more than once to permit several ranges, e.g. the `0x420000..=0x42FFFF` range reserved for KMIP
+    /// tags and the `0x540000..=0x54FFFF` range reserved for extensions. Once a range is specified serializing an item
+    /// with a tag outside all of the specified ranges fails with [SerdeError::DisallowedTag], which names the Rust
+    /// struct field being serialized so that a mistyped `#[serde(rename = "...")]` attribute is easy to find.
+    pub fn with_allowed_tag_range(mut self, range: RangeInclusive<TtlvTag>) -> Self {
+        self.allowed_tag_ranges.push(range);
+        self
+    }
+
+    /// Sort the items of each TTLV Structure by tag.
+    ///
+    /// By default the items of a TTLV Structure are written in the order of the Rust fields that they were serialized
+    /// from. With this setting they are instead written in ascending order of tag, with items that have the same tag
+    /// kept in the order that they were serialized in, so that the same data always produces the same bytes regardless
+    /// of how the Rust types are laid out, e.g. for comparison against golden test data or for caching based on the
+    /// serialized bytes. Items that are not inside a TTLV Structure, e.g. the items of a top-level sequence, are not
+    /// reordered.
+    ///
+    /// Sorting requires the serialized bytes to be held in memory in their entirety, [to_writer_with_config] therefore
+    /// serializes into a buffer first when this setting is used.
+    pub fn with_canonical_order(self) -> Self {
         Self {
-            dst: Default::default(),
-            bookmarks: Default::default(),
-            state: TtlvStateMachine::new(TtlvStateMachineMode::Serializing),
+            canonical_order: true,
+            ..self
         }
     }
-}
 
-impl TtlvSerializer {
-    pub fn new() -> Self {
-        Self::default()
+    /// Specify a function to be shown the header of every TTLV item that is serialized.
+    ///
+    /// The given function is invoked with the offset in the output, tag, type and length of each TTLV item once the
+    /// item has been serialized, e.g. to build an index of where each item landed in the output in order to sign or
+    /// redact parts of it afterwards, or to log the items sent. As the length of a TTLV Structure is only known once
+    /// its content has been serialized a TTLV Structure is shown to the function after the items that it contains.
+    ///
+    /// The function can also enforce policies, e.g. that certain tags must not be sent, by returning an error which
+    /// causes serialization to fail with [ErrorKind::RejectedByObserver] located at the rejected item. When serializing
+    /// with [to_writer_with_config] the function is invoked while determining the TTLV Structure lengths, i.e. before
+    /// anything is written.
+    ///
+    /// [ErrorKind::RejectedByObserver]: crate::error::ErrorKind::RejectedByObserver
+    ///
+    /// The function may be a closure, e.g. one that builds up the index, and is shared by clones of this config.
+    pub fn with_item_observer<F>(self, observer: F) -> Self
+    where
+        F: Fn(&ItemHeader) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            item_observer: Some(Arc::new(observer)),
+            ..self
+        }
     }
 
-    pub fn into_vec(mut self) -> Result<Vec<u8>> {
-        self.finalize()?;
-        Ok(self.dst)
+    /// Specify a maximum number of bytes to produce.
+    ///
+    /// Serialization fails with [ErrorKind::ResponseSizeExceedsLimit] once more bytes than this have been produced.
+    ///
+    /// [ErrorKind::ResponseSizeExceedsLimit]: crate::error::ErrorKind::ResponseSizeExceedsLimit
+    pub fn with_max_bytes(self, max_bytes: u32) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+            ..self
+        }
     }
 
-    /// Write the item tag (a "three-byte binary unsigned integer, transmitted big-endian"). The caller is
-    /// responsible for ensuring that the given tag value is big-endian encoded, i.e.
-    /// assert_eq!(0x42007B_u32.to_be_bytes(), [00, 0x42, 0x00, 0x7B]); This will advance the buffer write position
-    /// by 3 bytes.
-    fn write_tag(&mut self, item_tag: TtlvTag, set_ignore_next_tag: bool) -> Result<()> {
-        if self.advance_state_machine(FieldType::Tag)? {
-            if set_ignore_next_tag {
-                let loc = self.location();
-                self.state.ignore_next_tag().map_err(|err| pinpoint!(err, loc))?;
-            }
-            item_tag.write(&mut self.dst).map_err(|err| pinpoint!(err, self))?;
+    /// Specify a maximum depth to which TTLV Structures may be nested.
+    ///
+    /// A root TTLV Structure containing only primitive items has a nesting depth of 1. Serialization fails with
+    /// [ErrorKind::NestingDepthExceedsLimit] if a TTLV Structure would be nested more deeply. A limit of 128 applies
+    /// if no limit is configured, as deeper nesting could otherwise overflow the stack. Values that are nested without
+    /// producing TTLV Structures, e.g. sequences of sequences, are limited to a depth of 1024 regardless.
+    ///
+    /// [ErrorKind::NestingDepthExceedsLimit]: crate::error::ErrorKind::NestingDepthExceedsLimit
+    pub fn with_max_nesting_depth(self, max_nesting_depth: usize) -> Self {
+        Self {
+            max_nesting_depth: Some(max_nesting_depth),
+            ..self
         }
-        Ok(())
     }
 
-    /// Write the TTLV item type ("a byte containing a coded value"). This will advance the buffer write position by
-    /// 1 byte.
-    fn write_type(&mut self, item_type: TtlvType) -> Result<()> {
-        if self.advance_state_machine(FieldType::Type)? {
-            item_type.write(&mut self.dst).map_err(|err| pinpoint!(err, self))?;
+    /// Fail with [SerdeError::UnsupportedRustType] when serializing a `None` value.
+    ///
+    /// By default a `None` value is omitted from the output. With this setting serializing a `None` value fails
+    /// instead, e.g. to catch `Option` fields that were unintentionally left unset. `Option` fields annotated with
+    /// `#[serde(skip_serializing_if = "Option::is_none")]` are still omitted.
+    pub fn with_reject_none(self) -> Self {
+        Self {
+            reject_none: true,
+            ..self
         }
-        Ok(())
     }
 
-    /// Push a dummy 0x000000 4-byte TTLV item length. After writing the value bytes we'll come back later and replace
-    /// the dummy bytes with the correct item length. Adds a bookmark at the current buffer write location so that
-    /// fn rewite_len() knows where to come back to.
-    fn write_zero_len(&mut self) -> Result<()> {
-        if self.advance_state_machine(FieldType::Length)? {
-            TtlvLength::new(0)
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self.location()))?;
-            self.bookmarks.push(self.dst.len());
+    /// Specify a function for looking up the tag of a Rust type by name.
+    ///
+    /// By default the tag to serialize a Rust type with must be given in hexadecimal form by its name, typically by
+    /// annotating it with `#[serde(rename = "0xNNNNNN")]` or `#[serde(rename = "Transparent:0xNNNNNN")]`. With this
+    /// setting a name that is not a tag in hexadecimal form is passed to the given function instead, e.g. the name of a
+    /// struct without a rename attribute or `KeyFormatType` for a struct renamed to `Transparent:KeyFormatType`, so
+    /// that the tags can be defined in one place rather than by attributes spread over many types. Serialization fails
+    /// if the function returns `None`. The deserializer can be configured to use the same function, see
+    /// [crate::de::Config::with_tag_provider()].
+    pub fn with_tag_provider(self, provider: TagProvider) -> Self {
+        Self {
+            tag_provider: Some(provider),
+            ..self
         }
-        Ok(())
     }
+}
 
-    /// Replace the most recent dummy 0x00000000 4-byte TTLV item length written by the last call to fn write_zero_len()
-    /// with the actual TTLV item length value. Assumes that the most recently bookmarked location in the write buffer
-    /// is the start of the 4 bytes to overwrite.
-    fn rewrite_len(&mut self) -> Result<()> {
-        if let Some(v_start_pos) = self.bookmarks.pop() {
-            // the bookmark is the position just after the L in TTLV, i.e. the start of the value V. Calculate the length of
-            // V by comparing the bookmarked position to our current position in the write buffer, then write that length
-            // into the bookmarked L position.
-            let len_to_write: u32 = (self.dst.len() - v_start_pos) as u32;
-            let bytes_to_overwrite = &mut self.dst.as_mut_slice()[v_start_pos - 4..v_start_pos];
-            bytes_to_overwrite.copy_from_slice(&len_to_write.to_be_bytes());
+/// Serialize and write bytes into a new Vector.
+pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    to_vec_with_config(value, &Config::default())
+}
+
+/// Serialize and write bytes into a new Vector using the given configuration.
+pub fn to_vec_with_config<T: Serialize>(value: &T, config: &Config) -> Result<Vec<u8>> {
+    to_vec_with_stats(value, config).map(|(bytes, _)| bytes)
+}
+
+/// Serialize and write bytes into a new Vector using the given configuration, also returning statistics about them.
+fn to_vec_with_stats<T: Serialize>(value: &T, config: &Config) -> Result<(Vec<u8>, WriteStats)> {
+    let mut ser = TtlvSerializer::with_config(config);
+    if config.canonical_order {
+        // Items can only be observed once they have been moved to their final position
+        ser.item_observer = None;
+        value.serialize(&mut ser)?;
+        let stats = ser.stats();
+        let mut bytes = ser.into_vec()?;
+        sort_structure_items(&mut bytes, false);
+        if let Some(observer) = &config.item_observer {
+            observe_items(&bytes, 0, observer)?;
         }
-        Ok(())
+        Ok((bytes, stats))
+    } else {
+        value.serialize(&mut ser)?;
+        let stats = ser.stats();
+        Ok((ser.into_vec()?, stats))
     }
+}
 
-    /// To be called at the end of serializing the stream of TTLV bytes. Makes sure that we didn't forget to rewrite the
-    /// last dummy TTLV length value and verifies afterwards that there are no bookmarks left.
-    fn finalize(&mut self) -> Result<()> {
-        if !self.bookmarks.is_empty() {
-            // This shouldn't happen.
-            Err(pinpoint!(MalformedTtlvError::UnknownStructureLength, self))
-        } else {
-            Ok(())
-        }
+/// Statistics about the TTLV bytes written by [to_writer()].
+///
+/// Use these to log the size of outgoing messages or to enforce policies about them without serializing twice.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteStats {
+    num_bytes: usize,
+    num_items: usize,
+    max_depth: usize,
+}
+
+impl WriteStats {
+    /// The number of bytes written.
+    pub fn num_bytes(&self) -> usize {
+        self.num_bytes
     }
 
-    fn location(&self) -> ErrorLocation {
-        ErrorLocation::from(self.dst.len())
+    /// The number of TTLV items written, including TTLV Structures and the items within them.
+    pub fn num_items(&self) -> usize {
+        self.num_items
     }
 
-    fn advance_state_machine(&mut self, next_state: FieldType) -> Result<bool> {
-        self.state.advance(next_state).map_err(|err| pinpoint!(err, self))
+    /// The deepest level of TTLV Structure nesting written, i.e. 1 if no TTLV Structure contains another, or 0 if no
+    /// TTLV Structures were written at all.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
     }
 }
 
-impl serde::ser::Serializer for &mut TtlvSerializer {
-    type Ok = ();
-    type Error = Error;
+/// Serialize and write bytes to a Writer.
+pub fn to_writer<T, W>(value: &T, writer: W) -> Result<WriteStats>
+where
+    T: Serialize,
+    W: Write,
+{
+    to_writer_with_config(value, writer, &Config::default())
+}
 
-    // =======================================================
-    // RUST TYPES FOR WHICH SERIALIZATION TO TTLV IS SUPPORTED
-    // =======================================================
-    type SerializeSeq = Self;
-    type SerializeStruct = Self;
-    type SerializeTupleStruct = Self;
-    type SerializeTupleVariant = Self;
+/// Serialize and write bytes to a Writer using the given configuration.
+///
+/// The value is serialized twice: first to determine the length of every TTLV Structure without producing any output,
+/// then to write the TTLV bytes to the writer in a single forward pass. The serialized bytes are therefore never held
+/// in memory in their entirety, and nothing is written if serialization fails, e.g. because a configured limit was
+/// exceeded. If writing fails part of the TTLV bytes may already have been written.
+///
+/// The value must serialize identically both times, which is the case for any type that does not serialize differently
+/// based on external state, e.g. the current time.
+///
+/// Returns statistics about the bytes written, see [WriteStats].
+pub fn to_writer_with_config<T, W>(value: &T, mut writer: W, config: &Config) -> Result<WriteStats>
+where
+    T: Serialize,
+    W: Write,
+{
+    if config.canonical_order {
+        let (bytes, stats) = to_vec_with_stats(value, config)?;
+        return writer
+            .write_all(&bytes)
+            .and_then(|_| writer.flush())
+            .map(|_| stats)
+            .map_err(|err| pinpoint!(err, ErrorLocation::unknown()));
+    }
 
-    /// This fn is called at the start of serializing a Rust tuple struct, e.g. struct SomeStruct(type, type, type). The
-    /// struct contents will be written out as a tree of TTLV structures (TTLV type 0x01) with each field in the Rust
-    /// structure being represented as a TTLV tag, type, len and value byte sequence. Inner structs or other supported
-    /// complex Rust types that can be serialized by this Serializer will be rendered as inner TTLV structure sequences
-    /// in the created TTLV byte sequence. The TTLV tag value to write is taken from the name argument passed to this fn.
-    /// When using #[derive(Serialize)] you should use #[serde(rename = "0xAABBCC")] to cause the name argument value
-    /// received here to be the TTLV tag value to use when serializing the structure to the write buffer.
-    fn serialize_tuple_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
-        let item_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, self.location()))?;
-        self.write_tag(item_tag, false)?;
-        self.write_type(TtlvType::Structure)?;
-        self.write_zero_len()?;
-        // SerializeTupleStruct will write out the tuple fields then call rewrite_len()
-        Ok(self)
+    // Sizing pass
+    let mut ser = TtlvSerializer::with_output(std::io::sink(), config, StructureLengths::Measure(Vec::new()));
+    ser.measure_only = true;
+    value.serialize(&mut ser)?;
+    ser.finalize()?;
+    let lengths = ser.lengths.into_replay();
+
+    // Writing pass, the items were already shown to the observer, if any, by the sizing pass
+    let writer = VectoredWriter::new(writer);
+    let mut ser = TtlvSerializer::with_output(writer, config, lengths);
+    ser.item_observer = None;
+    value.serialize(&mut ser)?;
+    ser.finalize()?;
+    let stats = ser.stats();
+    ser.dst
+        .inner
+        .flush()
+        .map(|_| stats)
+        .map_err(|err| pinpoint!(err, ErrorLocation::unknown()))
+}
+
+/// Serialize and write bytes to a seekable Writer, e.g. a file.
+pub fn to_seekable_writer<T, W>(value: &T, writer: W) -> Result<WriteStats>
+where
+    T: Serialize,
+    W: Write + Seek,
+{
+    to_seekable_writer_with_config(value, writer, &Config::default())
+}
+
+/// Serialize and write bytes to a seekable Writer, e.g. a file, using the given configuration.
+///
+/// Unlike [to_writer_with_config()] the value is serialized only once. A placeholder is written for the length of each
+/// TTLV Structure and, once the value has been written, the writer seeks back to each placeholder to replace it with
+/// the actual length before seeking to the end of the written bytes again. This makes it practical to write very large
+/// values, e.g. containing [ByteStream]s whose content can only be read once, directly to a file. For efficiency wrap
+/// the writer in a [std::io::BufWriter], which also implements [Seek].
+///
+/// The bytes are written starting at the current position of the writer. If serialization fails, e.g. because a
+/// configured limit was exceeded, part of the TTLV bytes may already have been written.
+///
+/// Returns statistics about the bytes written, see [WriteStats].
+pub fn to_seekable_writer_with_config<T, W>(value: &T, mut writer: W, config: &Config) -> Result<WriteStats>
+where
+    T: Serialize,
+    W: Write + Seek,
+{
+    if config.canonical_order {
+        return to_writer_with_config(value, writer, config);
     }
 
-    /// Serialize a Rust bool value into the TTLV write buffer as TTLV type 0x06 (Boolean).
-    fn serialize_bool(self, v: bool) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvBoolean(v)
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
+    let to_error = |err| pinpoint!(err, ErrorLocation::unknown());
+    #[allow(clippy::seek_from_current)] // Seek::stream_position() requires Rust 1.51
+    let start = writer.seek(SeekFrom::Current(0)).map_err(to_error)?;
+
+    let writer = VectoredWriter::new(writer);
+    let mut ser = TtlvSerializer::with_output(writer, config, StructureLengths::Measure(Vec::new()));
+    value.serialize(&mut ser)?;
+    ser.finalize()?;
+    let stats = ser.stats();
+    ser.dst.inner.flush().map_err(to_error)?;
+
+    let mut writer = ser.dst.inner.inner;
+    if let StructureLengths::Measure(lengths) = ser.lengths {
+        for (len_pos, len) in lengths {
+            writer
+                .seek(SeekFrom::Start(start + len_pos as u64))
+                .and_then(|_| writer.write_all(&len.to_be_bytes()))
+                .map_err(|err| pinpoint!(err, len_pos))?;
         }
-        Ok(())
     }
+    writer
+        .seek(SeekFrom::Start(start + stats.num_bytes as u64))
+        .and_then(|_| writer.flush())
+        .map(|_| stats)
+        .map_err(to_error)
+}
 
-    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
-    fn serialize_i8(self, v: i8) -> Result<()> {
-        self.serialize_i32(v as i32)
-    }
+/// Determine the number of bytes that the given value serializes to, without producing the bytes.
+///
+/// Use this to preallocate a buffer or to check the size of a message against a limit, e.g. the KMIP Maximum Response
+/// Size, before serializing it. The content of a [ByteStream] is not read.
+pub fn serialized_size<T: Serialize>(value: &T) -> Result<usize> {
+    serialized_size_with_config(value, &Config::default())
+}
 
-    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
-    fn serialize_i16(self, v: i16) -> Result<()> {
-        self.serialize_i32(v as i32)
+/// Determine the number of bytes that the given value serializes to using the given configuration, without producing
+/// the bytes. See [serialized_size()].
+///
+/// Configured limits are enforced as when serializing, but no items are shown to the [Config::item_observer()], if
+/// any.
+pub fn serialized_size_with_config<T: Serialize>(value: &T, config: &Config) -> Result<usize> {
+    let mut ser = TtlvSerializer::with_output(std::io::sink(), config, StructureLengths::Measure(Vec::new()));
+    ser.measure_only = true;
+    ser.item_observer = None;
+    value.serialize(&mut ser)?;
+    ser.finalize()?;
+    Ok(ser.dst.count)
+}
+
+/// Serialize into a sequence of segments that can be written with a single vectored write.
+///
+/// Consecutive small fields, e.g. TTLV tag, type and length fields and short values, are gathered into shared segments
+/// while large values each occupy a segment of their own. Use [TtlvSegments::io_slices()] to pass the segments to
+/// [Write::write_vectored] or an asynchronous equivalent.
+pub fn to_segments<T: Serialize>(value: &T) -> Result<TtlvSegments> {
+    to_segments_with_config(value, &Config::default())
+}
+
+/// Serialize into a sequence of segments using the given configuration. See [to_segments].
+pub fn to_segments_with_config<T: Serialize>(value: &T, config: &Config) -> Result<TtlvSegments> {
+    if config.canonical_order {
+        let bytes = to_vec_with_config(value, config)?;
+        return Ok(TtlvSegments {
+            len: bytes.len(),
+            segments: vec![bytes],
+        });
     }
 
-    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
-    fn serialize_i32(self, v: i32) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvInteger(v)
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
+    // Sizing pass
+    let mut ser = TtlvSerializer::with_output(std::io::sink(), config, StructureLengths::Measure(Vec::new()));
+    ser.measure_only = true;
+    value.serialize(&mut ser)?;
+    ser.finalize()?;
+    let lengths = ser.lengths.into_replay();
+
+    // Writing pass, the items were already shown to the observer, if any, by the sizing pass
+    let mut ser = TtlvSerializer::with_output(TtlvSegments::default(), config, lengths);
+    ser.item_observer = None;
+    value.serialize(&mut ser)?;
+    ser.finalize()?;
+    Ok(ser.dst.inner)
+}
+
+/// Serialize into a string of uppercase hexadecimal digits, e.g. "42007801...".
+///
+/// This is the form in which TTLV test vectors, such as those in the KMIP test case specifications, are usually
+/// written and can be turned back into bytes with `hex::decode()` or printed by the `hex_to_txt` example. See
+/// [HexWriter] to write the hexadecimal digits to a Writer instead.
+pub fn to_hex_string<T: Serialize>(value: &T) -> Result<String> {
+    to_vec(value).map(hex::encode_upper)
+}
+
+/// Serialize into a string of standard, padded, base64 characters.
+pub fn to_base64_string<T: Serialize>(value: &T) -> Result<String> {
+    to_vec(value).map(|bytes| encode_base64(&bytes))
+}
+
+/// Encode the given bytes using the standard base64 alphabet with padding, as defined by RFC 4648.
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len() * 4 / 3 + 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, b)| acc | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
         }
-        Ok(())
     }
+    encoded
+}
 
-    /// Serialize a Rust unsigned 32-bit integer value into the TTLV write buffer as TTLV type 0x05 (Enumeration).
-    fn serialize_u32(self, v: u32) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvEnumeration(v)
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
-        }
-        Ok(())
+/// An adaptor that writes the bytes written to it to the inner writer as uppercase hexadecimal digits.
+///
+/// ```ignore
+/// to_writer(&request, HexWriter::new(std::io::stderr()))?;
+/// ```
+pub struct HexWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> HexWriter<W> {
+    /// Write hexadecimal digits to the given writer.
+    pub fn new(inner: W) -> Self {
+        Self { inner }
     }
 
-    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x03 (Long Integer).
-    fn serialize_i64(self, v: i64) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvLongInteger(v)
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
-        }
-        Ok(())
+    /// Get back the writer that the hexadecimal digits are written to.
+    pub fn into_inner(self) -> W {
+        self.inner
     }
+}
 
-    /// Serialize a Rust unsigned 64-bit integer value into the TTLV write buffer as TTLV type 0x09 (DateTime).
-    ///
-    /// TTLV DateTime values are serialized as a signed 64-bit value but as we need to ensure that we serialize the
-    /// correct TTLV type we can't handle these in serialize_i64 as that is already used for TTLV type 0x03
-    /// (Long Integer).
-    fn serialize_u64(self, v: u64) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvDateTime(v as i64)
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
+impl<W: Write> Write for HexWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write_all(hex::encode_upper(buf).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes a batch of TTLV messages one after another to a single Writer, e.g. several KMIP operations at once.
+///
+/// The offset at which each message starts is recorded so that, for example, a failed message can be identified in
+/// the batch. Each message is serialized using the [Config] given to [MessageWriter::with_config()], if any. If a
+/// maximum batch size is set with [MessageWriter::with_max_batch_size()] a message that would take the batch beyond
+/// that size is rejected with [ErrorKind::ResponseSizeExceedsLimit] without writing any of it, so that it can be
+/// submitted in the next batch instead.
+///
+/// ```ignore
+/// let mut batch = MessageWriter::new(socket).with_max_batch_size(64 * 1024);
+/// for request in requests {
+///     batch.write(&request)?;
+/// }
+/// ```
+///
+/// Messages are written with [to_writer_with_config()] by [MessageWriter::write()], or with `write_tokio()` or
+/// `write_futures()` to an async writer when the corresponding async feature is enabled.
+pub struct MessageWriter<W> {
+    inner: W,
+    config: Config,
+    max_batch_size: Option<usize>,
+    offsets: Vec<usize>,
+    len: usize,
+}
+
+impl<W> MessageWriter<W> {
+    /// Write messages to the given writer using the default configuration.
+    pub fn new(inner: W) -> Self {
+        Self::with_config(inner, &Config::default())
+    }
+
+    /// Write messages to the given writer using the given configuration.
+    pub fn with_config(inner: W, config: &Config) -> Self {
+        Self {
+            inner,
+            config: config.clone(),
+            max_batch_size: None,
+            offsets: Vec::new(),
+            len: 0,
         }
-        Ok(())
     }
 
-    /// Serialize a Rust str value into the TTLV write buffer as TTLV type 0x07 (Text String).
-    fn serialize_str(self, v: &str) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvTextString(v.to_string())
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
+    /// Reject messages that would make the total number of bytes written larger than the given maximum.
+    pub fn with_max_batch_size(self, max_batch_size: usize) -> Self {
+        Self {
+            max_batch_size: Some(max_batch_size),
+            ..self
         }
-        Ok(())
     }
 
-    /// Use #[serde(with = "serde_bytes")] to direct Serde to this serializer function for type Vec<u8>.
-    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
-        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
-            TtlvByteString(v.to_vec())
-                .write(&mut self.dst)
-                .map_err(|err| pinpoint!(err, self))?;
+    /// The maximum total number of bytes to write, if any.
+    pub fn max_batch_size(&self) -> Option<usize> {
+        self.max_batch_size
+    }
+
+    /// The offset at which each message written so far starts, in the order that they were written.
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// The total number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Has nothing been written yet?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get back the writer that the messages were written to.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// The configuration to serialize the next message with, limited to the space left in the batch.
+    fn message_config(&self) -> Config {
+        let mut config = self.config.clone();
+        if let Some(max_batch_size) = self.max_batch_size {
+            let remaining = u32::try_from(max_batch_size.saturating_sub(self.len)).unwrap_or(u32::MAX);
+            config.max_bytes = Some(config.max_bytes.map_or(remaining, |max_bytes| max_bytes.min(remaining)));
         }
-        Ok(())
+        config
     }
 
-    /// Serialize a unit enum variant.
+    /// Record that a message of the given length was written, returning the offset at which it starts.
+    fn add_message(&mut self, len: usize) -> usize {
+        let offset = self.len;
+        self.offsets.push(offset);
+        self.len += len;
+        offset
+    }
+}
+
+impl<W: Write> MessageWriter<W> {
+    /// Serialize and write the given message, returning the offset at which it starts.
     ///
-    /// We can't serialize based on the discriminant as Serde doesn't make that available to us. We also can't serialize
-    /// based on the variant index as most KMIP enumerations start at one rather than zero, and we can't work based on
-    /// that assumption either as some start at other numbers entirely (e.g. the KMIP spec 1.0 section 9.1.3.2.19 Link
-    /// Type Enumeration defines an enumeration that starts at 0x00000101). And we can't serialize based on the variant
-    /// name if that name is a string, e.g. "Query", as TTLV requires an enumeration to be serialized as a 32-bit
-    /// unsigned integer and we only have a string which might not be (correctly) convertable to an integer. And we also
-    /// can't serialize using serde_repr which would give us access to the discriminant, but would invoke our
-    /// `fn serialize_u32()` function with ONLY the discriminant, we wouldn't be able to write out the TTLV tag as we
-    /// wouldn't know what it was.
+    /// Nothing is written if serialization fails. If writing fails part of the message may already have been written,
+    /// in which case the batch should be abandoned.
+    pub fn write<T: Serialize>(&mut self, value: &T) -> Result<usize> {
+        let config = self.message_config();
+        let stats = to_writer_with_config(value, &mut self.inner, &config)?;
+        Ok(self.add_message(stats.num_bytes()))
+    }
+}
+
+#[cfg(feature = "async-with-tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> MessageWriter<W> {
+    /// Serialize and write the given message to a Tokio writer, returning the offset at which it starts.
     ///
-    /// Therefore we require the tag AND the discriminant to be communicated to us. The tag should be passed via the
-    /// enum name and the discriminant via the variant name. When using serde-derive both should be overridden using the
-    /// `#[serde(rename = "0xAABBCC")]` syntax, e.g.
+    /// See [MessageWriter::write()].
+    pub async fn write_tokio<T: Serialize>(&mut self, value: &T) -> Result<usize> {
+        use tokio::io::AsyncWriteExt;
+
+        let bytes = to_vec_with_config(value, &self.message_config())?;
+        self.inner
+            .write_all(&bytes)
+            .await
+            .map_err(|err| pinpoint!(err, self.len))?;
+        Ok(self.add_message(bytes.len()))
+    }
+}
+
+#[cfg(any(feature = "async-with-async-std", feature = "async-with-futures"))]
+impl<W: FuturesWrite + Unpin> MessageWriter<W> {
+    /// Serialize and write the given message to a `futures::io::AsyncWrite` writer, returning the offset at which it
+    /// starts.
     ///
-    /// ```ignore
-    /// #[derive(Serialize)]
-    /// #[serde(rename = "0x42005C")]
-    /// enum MyEnum {
-    ///     #[serde(rename = "0x000000001")] // The discriminant has to be defined here.
-    ///     SomeVariant // = 1,                 Any discriminant value assigned here will be ignored
-    /// }
-    /// ```
-    fn serialize_unit_variant(self, name: &'static str, _variant_index: u32, variant: &'static str) -> Result<()> {
-        // Don't write the tag if we just wrote a tag. This can happen in situations like this:
-        //
-        //   Tag: Template-Attribute (0x420091), Type: Structure (0x01), Data:
-        //     Tag: Attribute (0x420008), Type: Structure (0x01), Data:
-        //       Tag: Attribute Name (0x42000A), Type: Text String (0x07), Data: Cryptographic Algorithm
-        //       Tag: Attribute Value (0x42000B), Type: Enumeration (0x05), Data: 0x00000003 (AES)
-        //
-        // Here we've just written out the tag 0x42000B and we're about to write the enum value 0x00000003. However, the
-        // input to Serde that we are processing looked like this:
-        //
-        //   #[derive(Clone, Copy, Debug, Deserialize, Serialize, Display, PartialEq, Eq)]
-        //   #[serde(rename = "0x420028")]
-        //   #[non_exhaustive]
-        //   #[allow(non_camel_case_types)]
-        //   pub enum CryptographicAlgorithm {
-        //       #[serde(rename = "0x00000001")]
-        //       ...
-        //
-        // This type has its own tag, 0x4200028, and we would normally write this out as a full TTLV. In the case of a
-        // KMIP Attribute Value however the tag is always the same, 0x420000B, and the type of the data is inferred by
-        // the deserializer by looking at the Data of the preceeding Attribute Name.
-        //
-        // So in this case we should skip writing out the tag and only write the type, length and value.
+    /// See [MessageWriter::write()].
+    pub async fn write_futures<T: Serialize>(&mut self, value: &T) -> Result<usize> {
+        let bytes = to_vec_with_config(value, &self.message_config())?;
+        self.inner
+            .write_all(&bytes)
+            .await
+            .map_err(|err| pinpoint!(err, self.len))?;
+        Ok(self.add_message(bytes.len()))
+    }
+}
 
-        let item_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, self.location()))?;
-        self.write_tag(item_tag, false)?;
+/// Serialized TTLV bytes split into segments, see [to_segments].
+#[derive(Clone, Debug, Default)]
+pub struct TtlvSegments {
+    segments: Vec<Vec<u8>>,
+    len: usize,
+}
 
-        let variant = u32::from_str_radix(variant.trim_start_matches("0x"), 16)
-            .map_err(|_| pinpoint!(SerdeError::InvalidVariant(variant), self.location()))?;
-        variant.serialize(self)
+impl TtlvSegments {
+    /// The segments, which when concatenated form the serialized TTLV bytes.
+    pub fn segments(&self) -> &[Vec<u8>] {
+        &self.segments
     }
 
-    /// Serialize a struct SomeEnumVariant(a, b, c) to the TTLV write buffer as a TTLV Structure with fields a, b and c.
-    fn serialize_tuple_variant(
-        self,
-        name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
-    ) -> Result<Self::SerializeTupleVariant> {
-        // The Override name prefix has no meaning in the case of a tuple variant, it only applies to a single inner
-        // tagged value whose tag should be overriden. See serialize_newtype_variant().
-        let name = name.strip_prefix("Override:").unwrap_or(name);
-        let item_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, self.location()))?;
-        self.write_tag(item_tag, false)?;
-        self.write_type(TtlvType::Structure)?;
-        self.write_zero_len()?;
-        // SerializeTupleVariant will write out the tuple fields then call rewrite_len()
-        Ok(self)
+    /// The segments as slices to pass to [Write::write_vectored].
+    pub fn io_slices(&self) -> Vec<IoSlice<'_>> {
+        self.segments.iter().map(|segment| IoSlice::new(segment)).collect()
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
-        self,
-        name: &'static str,
-        variant_index: u32,
-        variant: &'static str,
-        value: &T,
-    ) -> Result<()>
-    where
-        T: Serialize,
-    {
-        // If the Override name prefix is present use the tag of this enum when writing the next item instead of that
-        // items own tag.
-        let (name, set_ignore_next_tag) = if let Some(name) = name.strip_prefix("Override:") {
-            (name, true)
-        } else {
-            (name, false)
-        };
-
-        // If the variant name is "Transparent" serialize the inner value directly, don't wrap it in a TTLV Structure.
-        if variant == "Transparent" {
-            let item_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, self.location()))?;
-            self.write_tag(item_tag, set_ignore_next_tag)?;
-            value.serialize(self)
-        } else {
-            let mut ser = self.serialize_tuple_variant(name, variant_index, variant, 1)?;
-            ser.serialize_field(value)?;
-            ser.end()
-        }
+    /// The total number of bytes in all segments.
+    pub fn len(&self) -> usize {
+        self.len
     }
 
-    /// Serialize a struct SomeStruct(type) to the TTLV write buffer as if it were the naked type without the enclosing
-    /// "newtype" SomeStruct wrapper.
-    ///
-    /// We don't use `#[serde(transparent)]` on the structs because then the serialization process would go straight to
-    /// functions such as `serialize_i32()` which serialize the V in TTLV but we also need to serialize the TTL part as
-    /// well.
-    fn serialize_newtype_struct<T: ?Sized>(self, name: &'static str, value: &T) -> Result<()>
-    where
-        T: Serialize,
-    {
-        if let Some(name) = name.strip_prefix("Transparent:") {
-            let item_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, self.location()))?;
-            self.write_tag(item_tag, false)?;
-            value.serialize(self)
-        } else {
-            let mut ser = self.serialize_tuple_struct(name, 1)?;
-            ser.serialize_field(value)?;
-            ser.end()
-        }
+    /// Are there no bytes in any of the segments?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    /// Serializing Rust brace structs to TTLV.
-    ///
-    /// Use of newtype and tuple structs is preferred as it leads to less verbose (yet still well named) Rust
-    /// hierarchical data structures because the field names do not need to be expressed. Usually this would be less
-    /// readable but because wrapper types must be used around primitive types (in order to give them a Serde "name"
-    /// which will be used as the TTLV "tag") then the unnamed primitive value is still wrapped in a named wrapper type.
-    ///
-    /// One use case for brace structs however is to avoid having to define separately a tuple struct for a type sent in
-    /// a request and a brace struct for the same type when received in a response. For structs with many fields this
-    /// can lead to a lot of duplication. If instead a single brace struct is defined but helper functions on the struct
-    /// are used to streamline the request construction this can be a way to achieve the best of both worlds: simple
-    /// requests based on anonymous fields that are self-evident from their type names, and responses with helpfully
-    /// named member fields for cases where there is no need to explicitly name the field type in order to use it.
-    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        let item_tag = TtlvTag::from_str(name).map_err(|err| pinpoint!(err, self.location()))?;
-        self.write_tag(item_tag, false)?;
-        self.write_type(TtlvType::Structure)?;
-        self.write_zero_len()?;
-        // SerializeStruct will write out the tuple fields then call rewrite_len()
-        Ok(self)
+    /// Concatenate the segments.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.segments.concat()
     }
+}
 
-    /// Dispatch serialization of a Rust sequence type such as Vec to the implementation of SerializeSeq that we
-    /// provide.
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Ok(self)
+impl Write for TtlvSegments {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.segments.last_mut() {
+            Some(last) if buf.len() < VECTORED_WRITE_THRESHOLD && last.len() < VECTORED_WRITE_THRESHOLD => {
+                last.extend_from_slice(buf)
+            }
+            _ => self.segments.push(buf.to_vec()),
+        }
+        self.len += buf.len();
+        Ok(buf.len())
     }
 
-    /// Serialize a `Some(value)` as if it were plain `value`.
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<()>
-    where
-        T: Serialize,
-    {
-        value.serialize(self)
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
+}
 
-    // ==============================================================
-    // RUST TYPES FOR WHICH SERIALIZATION TO TTLV IS _NOT_ SUPPORTED!
-    // ==============================================================
-
-    type SerializeMap = Impossible<(), Self::Error>;
-    type SerializeStructVariant = Impossible<(), Self::Error>;
-    type SerializeTuple = Impossible<(), Self::Error>;
+/// Serialize and append bytes to the given [bytes::BytesMut] buffer.
+///
+/// The buffer grows as needed. Bytes already present in the buffer are left untouched.
+#[cfg(feature = "bytes")]
+pub fn to_bytes_mut<T: Serialize>(value: &T, buf: &mut bytes::BytesMut) -> Result<()> {
+    to_bytes_mut_with_config(value, buf, &Config::default())
+}
 
-    fn serialize_u8(self, _v: u8) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("u8"), self))
-    }
+/// Serialize and append bytes to the given [bytes::BytesMut] buffer using the given configuration.
+///
+/// The buffer grows as needed. Bytes already present in the buffer are left untouched.
+#[cfg(feature = "bytes")]
+pub fn to_bytes_mut_with_config<T: Serialize>(value: &T, buf: &mut bytes::BytesMut, config: &Config) -> Result<()> {
+    to_writer_with_config(value, BufMutWriter(buf), config).map(|_| ())
+}
 
-    fn serialize_u16(self, _v: u16) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("u16"), self))
+/// Sort the items of each TTLV Structure in the given sequence of TTLV items by tag, see
+/// [Config::with_canonical_order()]. The items themselves are only sorted if `sort` is true.
+///
+/// The given bytes must be valid TTLV as produced by the serializer. Recursion is bounded by the nesting depth limit
+/// enforced while serializing.
+fn sort_structure_items(items: &mut [u8], sort: bool) {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= items.len() {
+        let end = pos + 8 + padded_len(&items[pos..pos + 8]);
+        if items[pos + 3] == u8::from(TtlvType::Structure) {
+            sort_structure_items(&mut items[pos + 8..end], true);
+        }
+        spans.push(([items[pos], items[pos + 1], items[pos + 2]], pos, end));
+        pos = end;
     }
 
-    fn serialize_f32(self, _v: f32) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("f32"), self))
+    if sort && spans.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+        // Tags are big-endian so comparing their bytes orders them numerically, and sorting is stable so items with
+        // the same tag stay in the order they were serialized in.
+        spans.sort_by_key(|span| span.0);
+        let mut sorted = Vec::with_capacity(items.len());
+        for (_, start, end) in spans {
+            sorted.extend_from_slice(&items[start..end]);
+        }
+        items.copy_from_slice(&sorted);
     }
+}
 
-    fn serialize_f64(self, _v: f64) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("f64"), self))
+/// Show the header of each item in the given sequence of TTLV items to the given observer, in the same order as the
+/// serializer does, i.e. the items of a TTLV Structure before the Structure itself.
+fn observe_items(items: &[u8], offset: usize, observer: &ItemObserver) -> Result<()> {
+    let mut pos = 0;
+    while pos + 8 <= items.len() {
+        let end = pos + 8 + padded_len(&items[pos..pos + 8]);
+        let tag = TtlvTag::from([items[pos], items[pos + 1], items[pos + 2]]);
+        let r#type = TtlvType::try_from(items[pos + 3]).map_err(|err| pinpoint!(err, offset + pos))?;
+        if r#type == TtlvType::Structure {
+            observe_items(&items[pos + 8..end], offset + pos + 8, observer)?;
+        }
+        let len = u32::from_be_bytes([items[pos + 4], items[pos + 5], items[pos + 6], items[pos + 7]]);
+        let header = ItemHeader::new(ByteOffset((offset + pos) as u64), tag, r#type, len);
+        if let Err(reason) = observer(&header) {
+            return Err(pinpoint!(
+                ErrorKind::RejectedByObserver(reason),
+                offset + pos,
+                tag,
+                r#type
+            ));
+        }
+        pos = end;
     }
+    Ok(())
+}
 
-    fn serialize_char(self, _v: char) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("char"), self))
-    }
+/// The length of the value of the TTLV item with the given tag, type and length fields, including padding.
+fn padded_len(header: &[u8]) -> usize {
+    let len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    (len + TtlvByteString::calc_pad_bytes(len)) as usize
+}
 
-    /// Serializing `None` values, e.g. Option::<TypeName>::None, is not supported.
-    ///
-    /// TTLV doesn't support the notion of a serialized value that indicates the absence of a value.
-    ///
-    /// ### Using Serde to "skip" a missing value
-    ///
-    /// The correct way to omit None values is to not attempt to serialize them at all, e.g. using the
-    /// `#[serde(skip_serializing_if = "Option::is_none")]` Serde derive field attribute. Note that at the time of
-    /// writing it seems that Serde derive only handles this attribute correctly when used on Rust brace struct field
-    /// members (which we do not support), or on tuple struct fields (i.e. there must be more than one field). Also,
-    /// note that not serializing a None struct field value will still result in the struct itself being serialized as
-    /// a TTLV "Structure" unless you also mark the struct as "transparent" (using the rename attribute like so:
-    /// `[#serde(rename = "Transparent:0xAABBCC"))]`. Using the attribute on newtype structs still causes Serde derive
-    /// to invoke `serialize_none()` which will result in an unsupported error.
-    ///
-    /// ### Rationale
-    ///
-    /// As we have already serialized the item tag to the output by the time we process the `Option` value, serializing
-    /// nothing here would still result in something having been serialized. We could in theory remove the already
-    /// serialized bytes from the stream but is not necessarily safe, e.g. if the already serialized bytes were a TTLV
-    /// Structure "header" (i.e. 0xAABBCC 0x00000001 0x00000000) removing the header might be incorrect if there are
-    /// other structure items that will be serialized to the stream after this "none". Removing the Structure "header"
-    /// bytes would also break the current logic which at the end of a structure goes back to the start and replaces the
-    /// zero length value in the TTLV Structure "header" with the actual length as the bytes to replace would no longer
-    /// exist.
-    fn serialize_none(self) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("None"), self))
+impl serde::ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        pinpoint!(SerdeError::Other(msg.to_string()), ErrorLocation::unknown())
     }
+}
 
-    fn serialize_unit(self) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("unit"), self))
-    }
+// --- Private implementation details ----------------------------------------------------------------------------------
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("unit struct"), self))
+impl<W: Write> From<&mut TtlvSerializer<W>> for ErrorLocation {
+    fn from(ser: &mut TtlvSerializer<W>) -> Self {
+        ser.location()
     }
+}
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("tuple"), self))
-    }
+/// An adaptor to write to a [bytes::BytesMut] via the [Write] trait.
+#[cfg(feature = "bytes")]
+struct BufMutWriter<'a>(&'a mut bytes::BytesMut);
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("map"), self))
+#[cfg(feature = "bytes")]
+impl<'a> Write for BufMutWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
     }
 
-    fn serialize_struct_variant(
-        self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
-    ) -> Result<Self::SerializeStructVariant> {
-        Err(pinpoint!(SerdeError::UnsupportedRustType("struct variant"), self))
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
-// =======================================
-// SERIALIZATION OF RUST SEQUENCES TO TTLV
-// =======================================
-impl ser::SerializeSeq for &mut TtlvSerializer {
-    type Ok = ();
-    type Error = Error;
+/// The maximum depth to which TTLV Structures may be nested when serializing, if not configured otherwise.
+///
+/// Serde serializes nested data by recursing, so without a limit a deeply nested value could exhaust the call stack.
+const DEFAULT_MAX_NESTING_DEPTH: usize = 128;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
-    where
-        T: Serialize,
-    {
-        value.serialize(&mut **self)
+/// The maximum depth to which values may be nested when serializing, whether or not they produce TTLV Structures.
+///
+/// Nesting a value inside a TTLV Structure typically involves a few levels of Serde recursion, e.g. for the field, an
+/// `Option` and a `Transparent:0xNNNNNN` newtype.
+const MAX_RECURSION_DEPTH: usize = 1024;
+
+/// Writes of at least this many bytes are not copied into a buffer but handed to the writer directly.
+const VECTORED_WRITE_THRESHOLD: usize = 4096;
+
+/// The maximum number of bytes buffered by a [VectoredWriter].
+const VECTORED_WRITE_BUF_SIZE: usize = 8192;
+
+/// An adaptor that buffers small writes, like [std::io::BufWriter], but hands large writes to the inner writer without
+/// copying them, in a single vectored write together with any buffered bytes that precede them.
+struct VectoredWriter<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> VectoredWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(VECTORED_WRITE_BUF_SIZE),
+        }
     }
 
-    fn end(self) -> Result<()> {
+    /// Write all of the buffered bytes followed by all of the given bytes.
+    fn write_all_after_buf(&mut self, mut data: &[u8]) -> std::io::Result<()> {
+        let mut buf = &self.buf[..];
+        while !buf.is_empty() || !data.is_empty() {
+            let n = match self.inner.write_vectored(&[IoSlice::new(buf), IoSlice::new(data)]) {
+                Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                Ok(n) => n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let from_buf = n.min(buf.len());
+            buf = &buf[from_buf..];
+            data = &data[n - from_buf..];
+        }
+        self.buf.clear();
         Ok(())
     }
 }
 
-// =====================================
-// SERIALIZATION OF RUST STRUCTS TO TTLV
-// =====================================
-impl ser::SerializeStruct for &mut TtlvSerializer {
-    type Ok = ();
-    type Error = Error;
-
-    fn serialize_field<T: ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<()>
-    where
-        T: Serialize,
-    {
-        value.serialize(&mut **self)
+impl<W: Write> Write for VectoredWriter<W> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if data.len() >= VECTORED_WRITE_THRESHOLD {
+            self.write_all_after_buf(data)?;
+        } else {
+            if self.buf.len() + data.len() > VECTORED_WRITE_BUF_SIZE {
+                self.write_all_after_buf(&[])?;
+            }
+            self.buf.extend_from_slice(data);
+        }
+        Ok(data.len())
     }
 
-    fn end(self) -> Result<()> {
-        // This fn is called at the end of serializing a Struct.
-        self.rewrite_len()
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_all_after_buf(&[])?;
+        self.inner.flush()
     }
 }
 
-// ===========================================
-// SERIALIZATION OF RUST TUPLE STRUCTS TO TTLV
-// ===========================================
-impl ser::SerializeTupleStruct for &mut TtlvSerializer {
-    type Ok = ();
-    type Error = Error;
+/// An adaptor that counts the bytes written to the inner writer.
+struct CountingWriter<W> {
+    inner: W,
+    count: usize,
+}
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
-    where
-        T: Serialize,
-    {
-        value.serialize(&mut **self)
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n;
+        Ok(n)
     }
 
-    fn end(self) -> Result<()> {
-        // This fn is called at the end of serializing a Struct.
-        self.rewrite_len()
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
     }
 }
 
-// ============================================
-// SERIALIZATION OF RUST TUPLE VARIANTS TO TTLV
-// ============================================
-impl ser::SerializeTupleVariant for &mut TtlvSerializer {
-    type Ok = ();
-    type Error = Error;
+/// How the serializer determines the value of the length field of each TTLV Structure.
+///
+/// The length of a TTLV Structure is not known until its last child item has been serialized, by which time the length
+/// field preceding the children has already been written.
+enum StructureLengths {
+    /// Write a zero length and record the offset of the length field and the actual length, in the order that the
+    /// Structures start. When writing to a buffer the recorded lengths can then be patched into the buffer, or the
+    /// output can be discarded and the recorded lengths replayed.
+    Measure(Vec<(usize, u32)>),
+
+    /// Write the lengths recorded by an earlier [StructureLengths::Measure] pass over the same value.
+    Replay(std::vec::IntoIter<u32>),
+}
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
-    where
-        T: Serialize,
-    {
-        value.serialize(&mut **self)
+impl StructureLengths {
+    /// Replay the lengths recorded by a [StructureLengths::Measure] pass.
+    fn into_replay(self) -> Self {
+        match self {
+            StructureLengths::Measure(lengths) => {
+                let lengths: Vec<u32> = lengths.into_iter().map(|(_, len)| len).collect();
+                StructureLengths::Replay(lengths.into_iter())
+            }
+            replay => replay,
+        }
     }
+}
 
-    fn end(self) -> Result<Self::Ok> {
-        // This fn is called at the end of serializing a tuple variant.
-        // TODO: go back to the length byte pos in the vec and write in our distance from that point
-        // Either we need to receive back from ... from where? we get no values passed to us, so instead we need to
-        // store the position to go back to in the vec, but we'll need to do that for each level of struct nesting, push
-        // them on and pop them off.
-        self.rewrite_len()
-    }
+/// The progress of the serializer through a [ByteStream], which is serialized as a newtype struct with a special name
+/// wrapping a sequence of a `u32` length followed by `&[u8]` chunks of the content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ByteStreamState {
+    Inactive,
+    /// The newtype struct has been seen, the length is expected next.
+    Pending,
+    /// The TTLV type and length have been written, content chunks are expected next.
+    Streaming {
+        len: u32,
+        remaining: u32,
+    },
+}
+
+/// A TTLV Structure whose length will be known once its content has been serialized.
+struct Bookmark {
+    /// The offset at which the value of the TTLV Structure starts.
+    value_start: usize,
+
+    /// The index of the TTLV Structure in order of starting, i.e. its index in [StructureLengths::Measure].
+    index: usize,
+
+    tag: TtlvTag,
+
+    /// The length that was written for the TTLV Structure.
+    written_len: u32,
 }
 
-#[cfg(test)]
-mod test {
-    #[allow(unused_imports)]
-    use pretty_assertions::{assert_eq, assert_ne};
+pub struct TtlvSerializer<W = Vec<u8>> {
+    /// The destination to serialize TTLV bytes into, along with the number of bytes serialized so far.
+    dst: CountingWriter<W>,
+
+    /// A push/pop stack of the TTLV Structures currently being serialized.
+    bookmarks: Vec<Bookmark>,
+
+    /// The tag most recently written, i.e. the tag of the TTLV item currently being serialized.
+    item_tag: TtlvTag,
+
+    /// The name of the Rust struct field currently being serialized, if any.
+    field_name: Option<&'static str>,
+
+    /// The number of TTLV Structures started so far.
+    num_structures: usize,
+
+    /// The number of TTLV items started so far.
+    num_items: usize,
+
+    /// The deepest level of TTLV Structure nesting reached so far.
+    max_depth: usize,
+
+    lengths: StructureLengths,
+
+    state: TtlvStateMachine,
+
+    /// Set while serializing a [TtlvInterval], a field annotated with one of the [crate::as_type] helpers or a
+    /// `Transparent(Type):0xNNNNNN` newtype struct so that the inner integer is written as the given TTLV type rather
+    /// than as the type it would otherwise be written as.
+    forced_type: Option<TtlvType>,
+
+    /// Set while serializing a [TtlvBigInteger] so that the inner bytes are written as TTLV type 0x04 (Big Integer)
+    /// rather than as TTLV type 0x08 (Byte String).
+    big_integer: bool,
+
+    /// The number of nested calls to fn serialize_nested() currently in progress.
+    recursion_depth: usize,
+
+    /// Progress through serializing a [ByteStream].
+    byte_stream: ByteStreamState,
+
+    /// Set when the output is discarded, e.g. in the sizing pass of [to_writer], so that the content of a
+    /// [ByteStream] is counted rather than read.
+    measure_only: bool,
+
+    /// The tag of the map entry whose value is being serialized. It is written in place of the tag of the value, if
+    /// any, once the value starts to be written so that nothing is written for a value that is omitted, e.g. `None`.
+    pending_tag: Option<TtlvTag>,
+
+    allowed_tag_ranges: Vec<RangeInclusive<TtlvTag>>,
+
+    item_observer: Option<ItemObserver>,
+
+    max_bytes: Option<u32>,
+
+    max_nesting_depth: Option<usize>,
+
+    reject_none: bool,
+
+    tag_provider: Option<TagProvider>,
+}
+
+impl Default for TtlvSerializer {
+    fn default() -> Self {
+        Self::with_config(&Config::default())
+    }
+}
+
+impl TtlvSerializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: &Config) -> Self {
+        Self::with_output(Vec::new(), config, StructureLengths::Measure(Vec::new()))
+    }
+
+    pub fn into_vec(mut self) -> Result<Vec<u8>> {
+        self.finalize()?;
+        let mut dst = self.dst.inner;
+        if let StructureLengths::Measure(lengths) = self.lengths {
+            for (len_pos, len) in lengths {
+                dst[len_pos..len_pos + 4].copy_from_slice(&len.to_be_bytes());
+            }
+        }
+        Ok(dst)
+    }
+}
+
+impl<W: Write> TtlvSerializer<W> {
+    fn with_output(dst: W, config: &Config, lengths: StructureLengths) -> Self {
+        Self {
+            dst: CountingWriter { inner: dst, count: 0 },
+            bookmarks: Vec::new(),
+            item_tag: TtlvTag::from([0, 0, 0]),
+            field_name: None,
+            num_structures: 0,
+            num_items: 0,
+            max_depth: 0,
+            lengths,
+            state: TtlvStateMachine::new(TtlvStateMachineMode::Serializing),
+            forced_type: None,
+            big_integer: false,
+            recursion_depth: 0,
+            byte_stream: ByteStreamState::Inactive,
+            measure_only: false,
+            pending_tag: None,
+            allowed_tag_ranges: config.allowed_tag_ranges.clone(),
+            item_observer: config.item_observer.clone(),
+            max_bytes: config.max_bytes,
+            max_nesting_depth: config.max_nesting_depth,
+            reject_none: config.reject_none,
+            tag_provider: config.tag_provider,
+        }
+    }
+
+    /// Write the item tag (a "three-byte binary unsigned integer, transmitted big-endian"). The caller is
+    /// responsible for ensuring that the given tag value is big-endian encoded, i.e.
+    /// assert_eq!(0x42007B_u32.to_be_bytes(), [00, 0x42, 0x00, 0x7B]); This will advance the buffer write position
+    /// by 3 bytes.
+    fn write_tag(&mut self, item_tag: TtlvTag, set_ignore_next_tag: bool) -> Result<()> {
+        let item_tag = self.pending_tag.take().unwrap_or(item_tag);
+        if self.advance_state_machine(FieldType::Tag)? {
+            if !self.allowed_tag_ranges.is_empty() && !self.allowed_tag_ranges.iter().any(|r| r.contains(&item_tag)) {
+                let error = SerdeError::DisallowedTag {
+                    tag: item_tag,
+                    field: self.field_name,
+                };
+                return Err(pinpoint!(error, self, item_tag));
+            }
+            if set_ignore_next_tag {
+                let loc = self.location();
+                self.state.ignore_next_tag().map_err(|err| pinpoint!(err, loc))?;
+            }
+            item_tag
+                .write(&mut self.dst)
+                .map_err(|err| pinpoint!(err, self.location()))?;
+            self.item_tag = item_tag;
+            self.num_items += 1;
+        }
+        Ok(())
+    }
+
+    /// Write the tag of the map entry whose value is about to be written, if the value did not write a tag itself.
+    fn write_pending_tag(&mut self) -> Result<()> {
+        match self.pending_tag.take() {
+            Some(item_tag) => self.write_tag(item_tag, false),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the TTLV item type ("a byte containing a coded value"). This will advance the buffer write position by
+    /// 1 byte.
+    fn write_type(&mut self, item_type: TtlvType) -> Result<()> {
+        self.write_pending_tag()?;
+        self.check_forced_type(item_type)?;
+        if self.advance_state_machine(FieldType::Type)? {
+            item_type.write(&mut self.dst).map_err(|err| pinpoint!(err, self))?;
+        }
+        Ok(())
+    }
+
+    /// Write the 4-byte TTLV Structure length. When measuring this is a dummy zero length whose actual value is
+    /// recorded by fn rewrite_len() once known, otherwise it is the next length recorded by the measuring pass. Adds a
+    /// bookmark at the current write position so that fn rewrite_len() can determine the length.
+    fn write_zero_len(&mut self) -> Result<()> {
+        if self.advance_state_machine(FieldType::Length)? {
+            let depth = self.bookmarks.len() + 1;
+            if depth > self.max_nesting_depth.unwrap_or(DEFAULT_MAX_NESTING_DEPTH) {
+                return Err(pinpoint!(ErrorKind::NestingDepthExceedsLimit(depth), self));
+            }
+            self.max_depth = self.max_depth.max(depth);
+            let len = match &mut self.lengths {
+                StructureLengths::Measure(lengths) => {
+                    lengths.push((self.dst.count, 0));
+                    0
+                }
+                StructureLengths::Replay(lengths) => match lengths.next() {
+                    Some(len) => len,
+                    None => return Err(pinpoint!(MalformedTtlvError::UnknownStructureLength, self)),
+                },
+            };
+            TtlvLength::new(len)
+                .write(&mut self.dst)
+                .map_err(|err| pinpoint!(err, self.location()))?;
+            self.bookmarks.push(Bookmark {
+                value_start: self.dst.count,
+                index: self.num_structures,
+                tag: self.item_tag,
+                written_len: len,
+            });
+            self.num_structures += 1;
+            self.state.enter_structure();
+        }
+        Ok(())
+    }
+
+    /// Determine the length of the TTLV Structure whose length was written by the last call to fn write_zero_len()
+    /// from the current write position. When measuring the length is recorded, otherwise it is verified to match the
+    /// length that was written.
+    fn rewrite_len(&mut self) -> Result<()> {
+        if let Some(bookmark) = self.bookmarks.pop() {
+            // the bookmark is the position just after the L in TTLV, i.e. the start of the value V. Calculate the length of
+            // V by comparing the bookmarked position to our current position.
+            let len = (self.dst.count - bookmark.value_start) as u32;
+            match &mut self.lengths {
+                StructureLengths::Measure(lengths) => lengths[bookmark.index].1 = len,
+                StructureLengths::Replay(_) if len != bookmark.written_len => {
+                    // The value serialized differently during the measuring pass, e.g. it contains a collection that
+                    // was modified in the meantime, and so the bytes written are not valid TTLV.
+                    return Err(pinpoint!(
+                        MalformedTtlvError::UnknownStructureLength,
+                        bookmark.value_start
+                    ));
+                }
+                StructureLengths::Replay(_) => {}
+            }
+            let loc = self.location(); // See the note in de.rs about working around greedy closure capturing
+            self.state.leave_structure().map_err(|err| pinpoint!(err, loc))?;
+            // The tag, type and length fields precede the value
+            self.observe_item(bookmark.value_start - 8, bookmark.tag, TtlvType::Structure, len)?;
+        }
+        Ok(())
+    }
+
+    /// Write the TTLV type and length of a [ByteStream] whose content is about to be serialized.
+    fn start_byte_stream(&mut self, len: u32) -> Result<()> {
+        self.write_pending_tag()?;
+        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
+            TtlvType::ByteString
+                .write(&mut self.dst)
+                .and_then(|_| TtlvLength::new(len).write(&mut self.dst))
+                .map_err(|err| pinpoint!(err, self.location()))?;
+            self.byte_stream = ByteStreamState::Streaming { len, remaining: len };
+            if self.measure_only {
+                // Count the content instead of reading it, it will be read when the output is not discarded.
+                self.dst.count += len as usize;
+                self.byte_stream = ByteStreamState::Streaming { len, remaining: 0 };
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a chunk of the content of a [ByteStream].
+    fn write_byte_stream_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        if let ByteStreamState::Streaming { len, remaining } = self.byte_stream {
+            if chunk.len() > remaining as usize {
+                return Err(pinpoint!(
+                    SerdeError::Other(format!(
+                        "Byte stream is longer than its declared length of {} bytes",
+                        len
+                    )),
+                    self
+                ));
+            }
+            self.dst
+                .write_all(chunk)
+                .map_err(|err| pinpoint!(err, self.location()))?;
+            self.byte_stream = ByteStreamState::Streaming {
+                len,
+                remaining: remaining - chunk.len() as u32,
+            };
+            self.check_size()?;
+        }
+        Ok(())
+    }
+
+    /// Write the padding following the content of a [ByteStream].
+    fn end_byte_stream(&mut self) -> Result<()> {
+        if let ByteStreamState::Streaming { len, remaining } = self.byte_stream {
+            self.byte_stream = ByteStreamState::Inactive;
+            if remaining > 0 {
+                return Err(pinpoint!(
+                    SerdeError::Other(format!(
+                        "Byte stream is shorter than its declared length of {} bytes",
+                        len
+                    )),
+                    self
+                ));
+            }
+            let num_pad_bytes = TtlvByteString::calc_pad_bytes(len) as usize;
+            self.dst
+                .write_all(&[0u8; 8][..num_pad_bytes])
+                .map_err(|err| pinpoint!(err, self.location()))?;
+            let item_start = self.dst.count - num_pad_bytes - len as usize - 8;
+            self.observe_item(item_start, self.item_tag, TtlvType::ByteString, len)?;
+        }
+        Ok(())
+    }
+
+    /// Determine the tag named by the given Rust type name, either in hexadecimal form or, failing that, by the
+    /// configured tag provider if any.
+    fn resolve_tag(&self, name: &str) -> Result<TtlvTag> {
+        TtlvTag::from_str(name)
+            .or_else(|err| self.tag_provider.and_then(|provider| provider(name)).ok_or(err))
+            .map_err(|err| pinpoint!(err, self.location()))
+    }
+
+    /// Write an integer as the given TTLV type, failing if it is out of range for that type.
+    fn write_integer_as(&mut self, r#type: TtlvType, v: i128) -> Result<()> {
+        let out_of_range = || SerdeError::Other(format!("Value {} is out of range for TTLV type {}", v, r#type));
+        match r#type {
+            TtlvType::Enumeration | TtlvType::Interval => {
+                let v = u32::try_from(v).map_err(|_| pinpoint!(out_of_range(), self.location()))?;
+                if r#type == TtlvType::Interval {
+                    self.write_value(TtlvInterval(v))
+                } else {
+                    self.write_value(TtlvEnumeration(v))
+                }
+            }
+            _ => {
+                let v = i64::try_from(v).map_err(|_| pinpoint!(out_of_range(), self.location()))?;
+                self.write_value(TtlvDateTime(v))
+            }
+        }
+    }
+
+    /// Fail if an item of the given TTLV type is about to be written in place of the one of the TTLV type required by
+    /// a `Transparent(Type):0xNNNNNN` newtype struct.
+    fn check_forced_type(&mut self, actual: TtlvType) -> Result<()> {
+        match self.forced_type.take() {
+            Some(expected) if expected != actual => Err(pinpoint!(
+                SerdeError::UnexpectedType { expected, actual },
+                self.location()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the TTLV type, length and value of a primitive TTLV item whose tag has just been written.
+    fn write_value<T: SerializableTtlvType>(&mut self, value: T) -> Result<()> {
+        self.write_pending_tag()?;
+        self.check_forced_type(T::TTLV_TYPE)?;
+        if self.advance_state_machine(FieldType::TypeAndLengthAndValue)? {
+            // The tag field precedes the type
+            let item_start = self.dst.count - 3;
+            let len = value
+                .write_returning_len(&mut self.dst)
+                .map_err(|err| pinpoint!(err, self.location()))?;
+            self.observe_item(item_start, self.item_tag, T::TTLV_TYPE, len)?;
+        }
+        Ok(())
+    }
+
+    /// Show the header of the TTLV item that has just been serialized to the configured observer, if any.
+    fn observe_item(&self, item_start: usize, tag: TtlvTag, r#type: TtlvType, len: u32) -> Result<()> {
+        if let Some(observer) = &self.item_observer {
+            let header = ItemHeader::new(ByteOffset(item_start as u64), tag, r#type, len);
+            if let Err(reason) = observer(&header) {
+                return Err(pinpoint!(
+                    ErrorKind::RejectedByObserver(reason),
+                    item_start,
+                    tag,
+                    r#type
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize a value nested inside the value currently being serialized.
+    ///
+    /// Serde serializes nested values by recursing, so a deeply nested value, e.g. a long linked list built from
+    /// untrusted input, could exhaust the call stack. TTLV Structures are limited by the maximum nesting depth, this
+    /// also limits nesting that does not produce TTLV Structures, e.g. of sequences, options or newtypes.
+    fn serialize_nested<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        if self.recursion_depth >= MAX_RECURSION_DEPTH {
+            return Err(pinpoint!(
+                ErrorKind::NestingDepthExceedsLimit(self.recursion_depth + 1),
+                self
+            ));
+        }
+        self.recursion_depth += 1;
+        let res = value.serialize(&mut *self);
+        self.recursion_depth -= 1;
+        res
+    }
+
+    /// To be called at the end of serializing the stream of TTLV bytes. Makes sure that we didn't forget to determine
+    /// the last TTLV Structure length and verifies afterwards that there are no bookmarks left.
+    fn finalize(&mut self) -> Result<()> {
+        if !self.bookmarks.is_empty() {
+            // This shouldn't happen.
+            Err(pinpoint!(MalformedTtlvError::UnknownStructureLength, self))
+        } else {
+            self.check_size()
+        }
+    }
+
+    /// Fail if more bytes have been produced than the configured maximum.
+    fn check_size(&self) -> Result<()> {
+        match self.max_bytes {
+            Some(max_bytes) if self.dst.count > max_bytes as usize => Err(pinpoint!(
+                ErrorKind::ResponseSizeExceedsLimit(self.dst.count),
+                self.location()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn location(&self) -> ErrorLocation {
+        ErrorLocation::from(self.dst.count)
+    }
+
+    /// Serialize the value of a struct field, remembering the name of the field, if any, for use in error messages.
+    fn serialize_named_field<T>(&mut self, name: Option<&'static str>, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let outer_name = std::mem::replace(&mut self.field_name, name);
+        let res = self.serialize_nested(value);
+        self.field_name = outer_name;
+        res
+    }
+
+    /// Statistics about the bytes serialized so far.
+    fn stats(&self) -> WriteStats {
+        WriteStats {
+            num_bytes: self.dst.count,
+            num_items: self.num_items,
+            max_depth: self.max_depth,
+        }
+    }
+
+    fn advance_state_machine(&mut self, next_state: FieldType) -> Result<bool> {
+        // Checking before each field is written bounds the overshoot to the size of a single value.
+        self.check_size()?;
+        self.state.advance(next_state).map_err(|err| pinpoint!(err, self))
+    }
+}
+
+impl<'a, W: Write> serde::ser::Serializer for &'a mut TtlvSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    // =======================================================
+    // RUST TYPES FOR WHICH SERIALIZATION TO TTLV IS SUPPORTED
+    // =======================================================
+    type SerializeMap = MapSerializer<'a, W>;
+    type SerializeSeq = Self;
+    type SerializeStruct = Self;
+    type SerializeTuple = TupleSerializer<'a, W>;
+    type SerializeTupleStruct = TupleSerializer<'a, W>;
+    type SerializeTupleVariant = Self;
+
+    /// This fn is called at the start of serializing a Rust tuple struct, e.g. struct SomeStruct(type, type, type). The
+    /// struct contents will be written out as a tree of TTLV structures (TTLV type 0x01) with each field in the Rust
+    /// structure being represented as a TTLV tag, type, len and value byte sequence. Inner structs or other supported
+    /// complex Rust types that can be serialized by this Serializer will be rendered as inner TTLV structure sequences
+    /// in the created TTLV byte sequence. The TTLV tag value to write is taken from the name argument passed to this fn.
+    /// When using #[derive(Serialize)] you should use #[serde(rename = "0xAABBCC")] to cause the name argument value
+    /// received here to be the TTLV tag value to use when serializing the structure to the write buffer.
+    ///
+    /// A tuple struct renamed to `Transparent` is instead serialized like a tuple, see `serialize_tuple()`.
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct> {
+        if name == "Transparent" {
+            return self.serialize_tuple(len);
+        }
+        let item_tag = self.resolve_tag(name)?;
+        self.write_tag(item_tag, false)?;
+        self.write_type(TtlvType::Structure)?;
+        self.write_zero_len()?;
+        // SerializeTupleStruct will write out the tuple fields then call rewrite_len()
+        Ok(TupleSerializer {
+            ser: self,
+            structure: true,
+        })
+    }
+
+    /// Serialize a Rust tuple by serializing its elements in order, each of which must supply its own tag, e.g. by
+    /// being a `Transparent:0xNNNNNN` newtype struct.
+    ///
+    /// Like a `Vec` a tuple does not have a tag of its own and so its elements become items of the enclosing TTLV
+    /// Structure. A tuple inside a `Transparent:0xNNNNNN` newtype struct or that is the value of a map entry is instead
+    /// serialized as a TTLV Structure with that tag.
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        // If a tag has just been written for this tuple complete it as a TTLV Structure.
+        let structure = self.pending_tag.is_some() || self.state.expected_next_field_type() == FieldType::Type;
+        if structure {
+            self.write_type(TtlvType::Structure)?;
+            self.write_zero_len()?;
+        }
+        Ok(TupleSerializer { ser: self, structure })
+    }
+
+    /// Serialize a Rust bool value into the TTLV write buffer as TTLV type 0x06 (Boolean).
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.write_value(TtlvBoolean(v))
+    }
+
+    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.serialize_i32(v as i32)
+    }
+
+    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.serialize_i32(v as i32)
+    }
+
+    /// Serialize a Rust unsigned integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
+    ///
+    /// TTLV has no unsigned integer types but every `u8` value fits in a signed 32-bit TTLV Integer.
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.serialize_i32(v as i32)
+    }
+
+    /// Serialize a Rust unsigned integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
+    ///
+    /// TTLV has no unsigned integer types but every `u16` value fits in a signed 32-bit TTLV Integer.
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.serialize_i32(v as i32)
+    }
+
+    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x02 (Integer).
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        match self.forced_type.take() {
+            Some(r#type) => self.write_integer_as(r#type, v.into()),
+            None => self.write_value(TtlvInteger(v)),
+        }
+    }
+
+    /// Serialize a Rust unsigned 32-bit integer value into the TTLV write buffer as TTLV type 0x05 (Enumeration), or
+    /// as TTLV type 0x0A (Interval) if wrapped in a [TtlvInterval].
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        if self.byte_stream == ByteStreamState::Pending {
+            return self.start_byte_stream(v);
+        }
+        match self.forced_type.take() {
+            Some(r#type) => self.write_integer_as(r#type, v.into()),
+            None => self.write_value(TtlvEnumeration(v)),
+        }
+    }
+
+    /// Serialize a Rust integer value into the TTLV write buffer as TTLV type 0x03 (Long Integer).
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        match self.forced_type.take() {
+            Some(r#type) => self.write_integer_as(r#type, v.into()),
+            None => self.write_value(TtlvLongInteger(v)),
+        }
+    }
+
+    /// Serialize a Rust unsigned 64-bit integer value into the TTLV write buffer as TTLV type 0x09 (DateTime).
+    ///
+    /// TTLV DateTime values are serialized as a signed 64-bit value but as we need to ensure that we serialize the
+    /// correct TTLV type we can't handle these in serialize_i64 as that is already used for TTLV type 0x03
+    /// (Long Integer).
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        match self.forced_type.take() {
+            Some(r#type) => self.write_integer_as(r#type, v.into()),
+            None => self.write_value(TtlvDateTime(v as i64)),
+        }
+    }
+
+    /// Serialize a Rust 128-bit integer value into the TTLV write buffer as TTLV type 0x04 (Big Integer).
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.write_value(TtlvBigInteger::from(v))
+    }
+
+    /// Serialize a Rust unsigned 128-bit integer value into the TTLV write buffer as TTLV type 0x04 (Big Integer).
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.write_value(TtlvBigInteger::from(v))
+    }
+
+    /// Serialize a Rust str value into the TTLV write buffer as TTLV type 0x07 (Text String).
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.write_value(TtlvTextString(v.to_string()))
+    }
+
+    /// Use #[serde(with = "serde_bytes")] to direct Serde to this serializer function for type Vec<u8>.
+    ///
+    /// The bytes are written as TTLV type 0x08 (Byte String), or as TTLV type 0x04 (Big Integer) if wrapped in a
+    /// [TtlvBigInteger].
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        if let ByteStreamState::Streaming { .. } = self.byte_stream {
+            return self.write_byte_stream_chunk(v);
+        }
+        if std::mem::replace(&mut self.big_integer, false) {
+            self.write_value(TtlvBigInteger(v.to_vec()))
+        } else {
+            self.write_value(TtlvByteString(v.to_vec()))
+        }
+    }
+
+    /// Serialize a unit enum variant.
+    ///
+    /// We can't serialize based on the discriminant as Serde doesn't make that available to us. We also can't serialize
+    /// based on the variant index as most KMIP enumerations start at one rather than zero, and we can't work based on
+    /// that assumption either as some start at other numbers entirely (e.g. the KMIP spec 1.0 section 9.1.3.2.19 Link
+    /// Type Enumeration defines an enumeration that starts at 0x00000101). And we can't serialize based on the variant
+    /// name if that name is a string, e.g. "Query", as TTLV requires an enumeration to be serialized as a 32-bit
+    /// unsigned integer and we only have a string which might not be (correctly) convertable to an integer. And we also
+    /// can't serialize using serde_repr which would give us access to the discriminant, but would invoke our
+    /// `fn serialize_u32()` function with ONLY the discriminant, we wouldn't be able to write out the TTLV tag as we
+    /// wouldn't know what it was.
+    ///
+    /// Therefore we require the tag AND the discriminant to be communicated to us. The tag should be passed via the
+    /// enum name and the discriminant via the variant name. When using serde-derive both should be overridden using the
+    /// `#[serde(rename = "0xAABBCC")]` syntax, e.g.
+    ///
+    /// ```ignore
+    /// #[derive(Serialize)]
+    /// #[serde(rename = "0x42005C")]
+    /// enum MyEnum {
+    ///     #[serde(rename = "0x000000001")] // The discriminant has to be defined here.
+    ///     SomeVariant // = 1,                 Any discriminant value assigned here will be ignored
+    /// }
+    /// ```
+    fn serialize_unit_variant(self, name: &'static str, _variant_index: u32, variant: &'static str) -> Result<()> {
+        // Don't write the tag if we just wrote a tag. This can happen in situations like this:
+        //
+        //   Tag: Template-Attribute (0x420091), Type: Structure (0x01), Data:
+        //     Tag: Attribute (0x420008), Type: Structure (0x01), Data:
+        //       Tag: Attribute Name (0x42000A), Type: Text String (0x07), Data: Cryptographic Algorithm
+        //       Tag: Attribute Value (0x42000B), Type: Enumeration (0x05), Data: 0x00000003 (AES)
+        //
+        // Here we've just written out the tag 0x42000B and we're about to write the enum value 0x00000003. However, the
+        // input to Serde that we are processing looked like this:
+        //
+        //   #[derive(Clone, Copy, Debug, Deserialize, Serialize, Display, PartialEq, Eq)]
+        //   #[serde(rename = "0x420028")]
+        //   #[non_exhaustive]
+        //   #[allow(non_camel_case_types)]
+        //   pub enum CryptographicAlgorithm {
+        //       #[serde(rename = "0x00000001")]
+        //       ...
+        //
+        // This type has its own tag, 0x4200028, and we would normally write this out as a full TTLV. In the case of a
+        // KMIP Attribute Value however the tag is always the same, 0x420000B, and the type of the data is inferred by
+        // the deserializer by looking at the Data of the preceeding Attribute Name.
+        //
+        // So in this case we should skip writing out the tag and only write the type, length and value.
+
+        let item_tag = self.resolve_tag(name)?;
+        self.write_tag(item_tag, false)?;
+
+        let variant = u32::from_str_radix(variant.trim_start_matches("0x"), 16)
+            .map_err(|_| pinpoint!(SerdeError::InvalidVariant(variant), self.location()))?;
+        variant.serialize(self)
+    }
+
+    /// Serialize a struct SomeEnumVariant(a, b, c) to the TTLV write buffer as a TTLV Structure with fields a, b and c.
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        // The Override name prefix has no meaning in the case of a tuple variant, it only applies to a single inner
+        // tagged value whose tag should be overriden. See serialize_newtype_variant().
+        let name = name.strip_prefix("Override:").unwrap_or(name);
+        let item_tag = self.resolve_tag(name)?;
+        self.write_tag(item_tag, false)?;
+        self.write_type(TtlvType::Structure)?;
+        self.write_zero_len()?;
+        // SerializeTupleVariant will write out the tuple fields then call rewrite_len()
+        Ok(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        // If the Override name prefix is present use the tag of this enum when writing the next item instead of that
+        // items own tag.
+        let (name, set_ignore_next_tag) = if let Some(name) = name.strip_prefix("Override:") {
+            (name, true)
+        } else {
+            (name, false)
+        };
+
+        // If the variant name is "Transparent" serialize the inner value directly, don't wrap it in a TTLV Structure.
+        if variant == "Transparent" {
+            let item_tag = self.resolve_tag(name)?;
+            self.write_tag(item_tag, set_ignore_next_tag)?;
+            self.serialize_nested(value)
+        } else {
+            let mut ser = self.serialize_tuple_variant(name, variant_index, variant, 1)?;
+            ser.serialize_field(value)?;
+            ser.end()
+        }
+    }
+
+    /// Serialize a struct SomeStruct(type) to the TTLV write buffer as if it were the naked type without the enclosing
+    /// "newtype" SomeStruct wrapper.
+    ///
+    /// We don't use `#[serde(transparent)]` on the structs because then the serialization process would go straight to
+    /// functions such as `serialize_i32()` which serialize the V in TTLV but we also need to serialize the TTL part as
+    /// well.
+    ///
+    /// By default the TTLV type is determined by the Rust type of the inner value, e.g. an `i64` is written as TTLV
+    /// type 0x03 (Long Integer). Renaming the struct to `Transparent(Enumeration):0xNNNNNN`,
+    /// `Transparent(Interval):0xNNNNNN` or `Transparent(DateTime):0xNNNNNN` instead writes an inner integer of any
+    /// width as that TTLV type, failing if the value is out of range for it.
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if let Some(name) = name.strip_prefix("Transparent:") {
+            let item_tag = self.resolve_tag(name)?;
+            self.write_tag(item_tag, false)?;
+            self.serialize_nested(value)
+        } else if let Some(name) = name.strip_prefix("Transparent(") {
+            // E.g. "Transparent(Enumeration):0xNNNNNN"
+            let (type_name, name) = match name.find("):") {
+                Some(pos) => (&name[..pos], &name[pos + 2..]),
+                None => return Err(pinpoint!(SerdeError::InvalidTag(name.to_string()), self)),
+            };
+            let r#type = match type_name {
+                "Enumeration" => TtlvType::Enumeration,
+                "Interval" => TtlvType::Interval,
+                "DateTime" => TtlvType::DateTime,
+                _ => {
+                    let error =
+                        SerdeError::Other(format!("Unsupported TTLV type '{}' for a transparent value", type_name));
+                    return Err(pinpoint!(error, self));
+                }
+            };
+            let item_tag = self.resolve_tag(name)?;
+            self.write_tag(item_tag, false)?;
+            self.forced_type = Some(r#type);
+            self.serialize_nested(value)
+        } else if name == TTLV_INTERVAL_NAME {
+            self.forced_type = Some(TtlvType::Interval);
+            self.serialize_nested(value)
+        } else if name == TTLV_ENUMERATION_NAME {
+            self.forced_type = Some(TtlvType::Enumeration);
+            self.serialize_nested(value)
+        } else if name == TTLV_DATE_TIME_NAME {
+            self.forced_type = Some(TtlvType::DateTime);
+            self.serialize_nested(value)
+        } else if name == TTLV_BIG_INTEGER_NAME {
+            self.big_integer = true;
+            self.serialize_nested(value)
+        } else if name == TTLV_BYTE_STREAM_NAME {
+            self.byte_stream = ByteStreamState::Pending;
+            self.serialize_nested(value)
+        } else {
+            let mut ser = self.serialize_tuple_struct(name, 1)?;
+            ser.serialize_field(value)?;
+            ser.end()
+        }
+    }
+
+    /// Serializing Rust brace structs to TTLV.
+    ///
+    /// Use of newtype and tuple structs is preferred as it leads to less verbose (yet still well named) Rust
+    /// hierarchical data structures because the field names do not need to be expressed. Usually this would be less
+    /// readable but because wrapper types must be used around primitive types (in order to give them a Serde "name"
+    /// which will be used as the TTLV "tag") then the unnamed primitive value is still wrapped in a named wrapper type.
+    ///
+    /// One use case for brace structs however is to avoid having to define separately a tuple struct for a type sent in
+    /// a request and a brace struct for the same type when received in a response. For structs with many fields this
+    /// can lead to a lot of duplication. If instead a single brace struct is defined but helper functions on the struct
+    /// are used to streamline the request construction this can be a way to achieve the best of both worlds: simple
+    /// requests based on anonymous fields that are self-evident from their type names, and responses with helpfully
+    /// named member fields for cases where there is no need to explicitly name the field type in order to use it.
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        let item_tag = self.resolve_tag(name)?;
+        self.write_tag(item_tag, false)?;
+        self.write_type(TtlvType::Structure)?;
+        self.write_zero_len()?;
+        // SerializeStruct will write out the tuple fields then call rewrite_len()
+        Ok(self)
+    }
+
+    /// Dispatch serialization of a Rust sequence type such as Vec to the implementation of SerializeSeq that we
+    /// provide.
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(self)
+    }
+
+    /// Serialize a Rust map such as `BTreeMap<TtlvTag, V>` to TTLV items, one per map entry, tagged by the map keys.
+    ///
+    /// Map keys must be a [TtlvTag], a tag in hex form such as `"0x42000A"` or an unsigned integer. Like a `Vec` a map
+    /// does not have a tag of its own and so is serialized as a sequence of TTLV items. To serialize it as a TTLV
+    /// Structure instead wrap it in a newtype struct, e.g. `#[serde(rename = "0xAABBCC")] struct Attributes(...)`. A
+    /// map that is itself the value of a map entry is serialized as a TTLV Structure with the tag of that entry.
+    ///
+    /// Entries are serialized in map iteration order, which for a `HashMap` is unspecified. For output that does not
+    /// vary from one run to the next use a `BTreeMap` or [Config::with_canonical_order()].
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        // If a tag has just been written for this map, e.g. as the key of an outer map, complete it as a Structure.
+        let structure = self.pending_tag.is_some() || self.state.expected_next_field_type() == FieldType::Type;
+        if structure {
+            self.write_type(TtlvType::Structure)?;
+            self.write_zero_len()?;
+        }
+        Ok(MapSerializer {
+            ser: self,
+            structure,
+            key: None,
+        })
+    }
+
+    /// Serialize a `Some(value)` as if it were plain `value`.
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_nested(value)
+    }
+
+    /// Serialize a `None` value, e.g. `Option::<TypeName>::None`, by omitting it from the output.
+    ///
+    /// TTLV doesn't support the notion of a serialized value that indicates the absence of a value, instead the item is
+    /// simply absent. An `Option` field that is `None` is therefore skipped as if it were annotated with
+    /// `#[serde(skip_serializing_if = "Option::is_none")]`. Use [Config::with_reject_none()] to fail instead.
+    ///
+    /// A `None` cannot be omitted if its tag has already been written, e.g. because the `Option` is inside a
+    /// `Transparent:0xAABBCC` newtype struct, as removing already written bytes from the output is not possible in
+    /// general. Serializing such a `None` fails with [SerdeError::UnsupportedRustType]. Put the `Option` around the
+    /// newtype struct instead, i.e. use `Option<Wrapper>` rather than `Wrapper(Option<T>)`. A `None` map value is
+    /// omitted together with its key.
+    fn serialize_none(self) -> Result<()> {
+        if self.reject_none || self.state.expected_next_field_type() == FieldType::Type {
+            Err(pinpoint!(SerdeError::UnsupportedRustType("None"), self))
+        } else {
+            self.pending_tag = None;
+            Ok(())
+        }
+    }
+
+    // ==============================================================
+    // RUST TYPES FOR WHICH SERIALIZATION TO TTLV IS _NOT_ SUPPORTED!
+    // ==============================================================
+
+    type SerializeStructVariant = Impossible<(), Self::Error>;
+
+    fn serialize_f32(self, _v: f32) -> Result<()> {
+        Err(pinpoint!(SerdeError::UnsupportedRustType("f32"), self))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<()> {
+        Err(pinpoint!(SerdeError::UnsupportedRustType("f64"), self))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<()> {
+        Err(pinpoint!(SerdeError::UnsupportedRustType("char"), self))
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(pinpoint!(SerdeError::UnsupportedRustType("unit"), self))
+    }
+
+    /// Serialize a Rust unit struct to an empty TTLV Structure.
+    ///
+    /// As with other structs the TTLV tag is taken from the struct name, e.g. `#[serde(rename = "0xAABBCC")]`.
+    fn serialize_unit_struct(self, name: &'static str) -> Result<()> {
+        ser::SerializeStruct::end(self.serialize_struct(name, 0)?)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(pinpoint!(SerdeError::UnsupportedRustType("struct variant"), self))
+    }
+}
+
+// =======================================
+// SERIALIZATION OF RUST SEQUENCES TO TTLV
+// =======================================
+impl<W: Write> ser::SerializeSeq for &mut TtlvSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.measure_only && matches!(self.byte_stream, ByteStreamState::Streaming { .. }) {
+            // Don't read the content of a ByteStream when it would be discarded anyway.
+            return Ok(());
+        }
+        self.serialize_nested(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.end_byte_stream()
+    }
+}
+
+// =====================================
+// SERIALIZATION OF RUST STRUCTS TO TTLV
+// =====================================
+impl<W: Write> ser::SerializeStruct for &mut TtlvSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_named_field(Some(key), value)
+    }
+
+    fn end(self) -> Result<()> {
+        // This fn is called at the end of serializing a Struct.
+        self.rewrite_len()
+    }
+}
+
+// ====================================================
+// SERIALIZATION OF RUST TUPLES AND TUPLE STRUCTS TO TTLV
+// ====================================================
+
+/// Serializes the elements of a Rust tuple or tuple struct, see `TtlvSerializer::serialize_tuple()`.
+pub struct TupleSerializer<'a, W = Vec<u8>> {
+    ser: &'a mut TtlvSerializer<W>,
+    structure: bool, // was a TTLV Structure header written for the tuple which must be completed at the end?
+}
+
+impl<'a, W: Write> ser::SerializeTuple for TupleSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.ser.serialize_named_field(None, value)
+    }
+
+    fn end(self) -> Result<()> {
+        if self.structure {
+            self.ser.rewrite_len()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'a, W: Write> ser::SerializeTupleStruct for TupleSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeTuple::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<()> {
+        ser::SerializeTuple::end(self)
+    }
+}
+
+// ============================================
+// SERIALIZATION OF RUST TUPLE VARIANTS TO TTLV
+// ============================================
+impl<W: Write> ser::SerializeTupleVariant for &mut TtlvSerializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_named_field(None, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        // This fn is called at the end of serializing a tuple variant.
+        // TODO: go back to the length byte pos in the vec and write in our distance from that point
+        // Either we need to receive back from ... from where? we get no values passed to us, so instead we need to
+        // store the position to go back to in the vec, but we'll need to do that for each level of struct nesting, push
+        // them on and pop them off.
+        self.rewrite_len()
+    }
+}
+
+// ==================================
+// SERIALIZATION OF RUST MAPS TO TTLV
+// ==================================
+
+/// Serializes the entries of a Rust map, see `TtlvSerializer::serialize_map()`.
+pub struct MapSerializer<'a, W = Vec<u8>> {
+    ser: &'a mut TtlvSerializer<W>,
+    structure: bool, // was a TTLV Structure header written for the map which must be completed at the end?
+    key: Option<TtlvTag>, // the tag of the current entry, or None if the value supplies its own tag
+}
+
+impl<'a, W: Write> ser::SerializeMap for MapSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key_ser = MapKeySerializer {
+            tag_provider: self.ser.tag_provider,
+        };
+        self.key = key.serialize(key_ser).map_err(|err| {
+            let (kind, loc) = err.into_inner();
+            Error::new(kind, loc.merge(self.ser.location()))
+        })?;
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        // The tag of the key is written in place of the tag of the value, if it has one, when the value is written.
+        self.ser.pending_tag = self.key.take();
+        let res = self.ser.serialize_nested(value);
+        self.ser.pending_tag = None;
+        res
+    }
+
+    fn end(self) -> Result<()> {
+        if self.structure {
+            self.ser.rewrite_len()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Converts a Rust map key to the TTLV tag to serialize the map value with.
+///
+/// A string key that is neither a tag in hex form nor known to the tag provider, if any, is taken to be the name of a
+/// struct field, e.g. one contributed by a `#[serde(flatten)]` field, whose value must supply its own tag.
+struct MapKeySerializer {
+    tag_provider: Option<TagProvider>,
+}
+
+impl MapKeySerializer {
+    fn unsupported(&self) -> Error {
+        pinpoint!(
+            SerdeError::UnsupportedRustType("map key other than tag, string or unsigned integer"),
+            ErrorLocation::unknown()
+        )
+    }
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = Option<TtlvTag>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Option<TtlvTag>, Error>;
+    type SerializeTuple = Impossible<Option<TtlvTag>, Error>;
+    type SerializeTupleStruct = Impossible<Option<TtlvTag>, Error>;
+    type SerializeTupleVariant = Impossible<Option<TtlvTag>, Error>;
+    type SerializeMap = Impossible<Option<TtlvTag>, Error>;
+    type SerializeStruct = Impossible<Option<TtlvTag>, Error>;
+    type SerializeStructVariant = Impossible<Option<TtlvTag>, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<Option<TtlvTag>> {
+        let tag_provider = self.tag_provider;
+        Ok(TtlvTag::from_str(v)
+            .ok()
+            .or_else(|| tag_provider.and_then(|provider| provider(v))))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Option<TtlvTag>> {
+        use std::convert::TryFrom;
+        TtlvTag::try_from(v)
+            .map(Some)
+            .map_err(|err| pinpoint!(err, ErrorLocation::unknown()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Option<TtlvTag>> {
+        use std::convert::TryFrom;
+        match u32::try_from(v) {
+            Ok(v) => self.serialize_u32(v),
+            Err(_) => Err(pinpoint!(
+                types::Error::InvalidTtlvTag(format!("0x{:X}", v)),
+                ErrorLocation::unknown()
+            )),
+        }
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Option<TtlvTag>>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_none(self) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Option<TtlvTag>>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(self.unsupported())
+    }
+
+    fn serialize_unit(self) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Option<TtlvTag>> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Option<TtlvTag>>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(self.unsupported())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(self.unsupported())
+    }
+}
+
+/// Serialize as a TTLV Byte String. The tag must be supplied by an enclosing `Transparent:0xNNNNNN` newtype.
+impl Serialize for Bytes {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+/// Serialize as a TTLV Byte String. The tag must be supplied by an enclosing `Transparent:0xNNNNNN` newtype.
+impl<'a> Serialize for BytesRef<'a> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Serialize as a TTLV Interval. The tag must be supplied by an enclosing `Transparent:0xNNNNNN` newtype.
+impl Serialize for TtlvInterval {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TTLV_INTERVAL_NAME, &self.0)
+    }
+}
+
+/// Serialize as a TTLV Big Integer. The tag must be supplied by an enclosing `Transparent:0xNNNNNN` newtype.
+///
+/// The value is sign-extended to a multiple of eight bytes as required by the KMIP specification.
+impl Serialize for TtlvBigInteger {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TTLV_BIG_INTEGER_NAME, &BigIntegerBytes(&self.0))
+    }
+}
+
+/// Serializes the value bytes of a [TtlvBigInteger] as bytes rather than as a sequence of `u8`.
+struct BigIntegerBytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for BigIntegerBytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+const TTLV_BYTE_STREAM_NAME: &str = "ByteString:ByteStream";
+
+/// The number of bytes of a [ByteStream] to read at once.
+const BYTE_STREAM_CHUNK_SIZE: u32 = 8192;
+
+/// A TTLV Byte String whose content is read from a [Read] implementation as it is serialized.
+///
+/// Use this for large values, e.g. multi-megabyte key material, that should not be held in memory in their entirety.
+/// The tag must be supplied by an enclosing `Transparent:0xNNNNNN` newtype. The length of the content must be known in
+/// advance as it is written before the content. Serialization fails if the reader yields fewer bytes than the given
+/// length, any further bytes are left unread.
+///
+/// [to_writer] reads the content once, in chunks, while writing it and [to_vec] reads it into the output vector. The
+/// content can only be read once, serializing the same [ByteStream] again fails unless the reader can yield the content
+/// again.
+///
+/// ```ignore
+/// #[derive(Serialize)]
+/// #[serde(rename = "Transparent:0x420043")]
+/// struct KeyMaterial(ByteStream<File>);
+///
+/// let len = file.metadata()?.len() as u32;
+/// to_writer(&KeyMaterial(ByteStream::new(file, len)), socket)?;
+/// ```
+pub struct ByteStream<R> {
+    reader: RefCell<R>,
+    len: u32,
+}
+
+impl<R: Read> ByteStream<R> {
+    /// Create a TTLV Byte String with `len` bytes of content read from `reader`.
+    pub fn new(reader: R, len: u32) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+            len,
+        }
+    }
+
+    /// The length of the content in bytes.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Is the content empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get back the reader that the content is read from.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+/// Serialize as a TTLV Byte String. The tag must be supplied by an enclosing `Transparent:0xNNNNNN` newtype.
+impl<R: Read> Serialize for ByteStream<R> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TTLV_BYTE_STREAM_NAME, &ByteStreamChunks(self))
+    }
+}
+
+/// Serializes the length of a [ByteStream] followed by its content in chunks.
+struct ByteStreamChunks<'a, R>(&'a ByteStream<R>);
+
+impl<'a, R: Read> Serialize for ByteStreamChunks<'a, R> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(None)?;
+        seq.serialize_element(&self.0.len)?;
+        let mut remaining = self.0.len;
+        while remaining > 0 {
+            let len = remaining.min(BYTE_STREAM_CHUNK_SIZE);
+            seq.serialize_element(&ByteStreamChunk {
+                reader: &self.0.reader,
+                len,
+            })?;
+            remaining -= len;
+        }
+        seq.end()
+    }
+}
+
+/// Reads and serializes a single chunk of the content of a [ByteStream]. Reading is deferred until serialization so
+/// that the serializer can skip reading if the output would be discarded.
+struct ByteStreamChunk<'a, R> {
+    reader: &'a RefCell<R>,
+    len: u32,
+}
+
+impl<'a, R: Read> Serialize for ByteStreamChunk<'a, R> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut buf = vec![0u8; self.len as usize];
+        self.reader
+            .borrow_mut()
+            .read_exact(&mut buf)
+            .map_err(|err| ser::Error::custom(format!("Failed to read byte stream: {}", err)))?;
+        serializer.serialize_bytes(&buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use serde_derive::Serialize;
+
+    use crate::ser::to_vec;
+
+    #[test]
+    fn test_kmip_10_create_destroy_use_case_create_request_serialization() {
+        // Define the types used by the test below. Note that these are structured so as to be easy to compose with minimal
+        // boilerplate overhead. For example tuple structs are heavily used rather than structs with named fields. If this
+        // were for deserialization instead of serialization these types should instead be verbose with named fields to make
+        // it easy to interact with the response objects.
+        #[derive(Serialize)]
+        #[serde(rename = "0x420078")]
+        struct RequestMessage(RequestHeader, Vec<BatchItem>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420077")]
+        struct RequestHeader(ProtocolVersion, BatchCount);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x42006B")]
+        struct ProtocolVersionMinor(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x42006A")]
+        struct ProtocolVersionMajor(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420069")]
+        struct ProtocolVersion(ProtocolVersionMajor, ProtocolVersionMinor);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x42000D")]
+        struct BatchCount(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x42000F")]
+        struct BatchItem(Operation, RequestPayload);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x42005C")]
+        enum Operation {
+            #[serde(rename = "0x00000001")]
+            Create,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420079")]
+        struct RequestPayload(ObjectType, TemplateAttribute);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420057")]
+        enum ObjectType {
+            #[serde(rename = "0x00000002")]
+            SymmetricKey,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420091")]
+        struct TemplateAttribute(Vec<Attribute>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420008")]
+        struct Attribute(AttributeName, AttributeValue);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x42000A")]
+        struct AttributeName(&'static str);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Override:0x42000B")]
+        enum AttributeValue {
+            #[serde(rename = "Transparent")]
+            CryptographicAlgorithm(CryptographicAlgorithm),
+
+            #[serde(rename = "Transparent")]
+            Integer(i32),
+        }
+
+        impl Attribute {
+            #[allow(non_snake_case)]
+            fn CryptographicAlgorithm(value: CryptographicAlgorithm) -> Self {
+                Attribute(
+                    AttributeName("Cryptographic Algorithm"),
+                    AttributeValue::CryptographicAlgorithm(value),
+                )
+            }
+
+            #[allow(non_snake_case)]
+            fn CryptographicLength(value: i32) -> Self {
+                Attribute(AttributeName("Cryptographic Length"), AttributeValue::Integer(value))
+            }
+
+            #[allow(non_snake_case)]
+            fn CryptographicUsageMask(value: i32) -> Self {
+                Attribute(
+                    AttributeName("Cryptographic Usage Mask"),
+                    AttributeValue::Integer(value),
+                )
+            }
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "420028")]
+        #[allow(clippy::upper_case_acronyms)]
+        enum CryptographicAlgorithm {
+            #[serde(rename = "0x00000003")]
+            AES,
+        }
+
+        // Attempt to generate correct binary TTLV for KMIP specification v1.0 use case 3.1.1 Create / Destroy as the\
+        // use case definition includes the input structure and the corresponding expected binary output.
+        // See: http://docs.oasis-open.org/kmip/usecases/v1.0/cs01/kmip-usecases-1.0-cs-01.html
+
+        let use_case_input = RequestMessage(
+            RequestHeader(
+                ProtocolVersion(ProtocolVersionMajor(1), ProtocolVersionMinor(0)),
+                BatchCount(1),
+            ),
+            vec![BatchItem(
+                Operation::Create,
+                RequestPayload(
+                    ObjectType::SymmetricKey,
+                    TemplateAttribute(vec![
+                        Attribute::CryptographicAlgorithm(CryptographicAlgorithm::AES),
+                        Attribute::CryptographicLength(128),
+                        Attribute::CryptographicUsageMask(0x0000_000C),
+                    ]),
+                ),
+            )],
+        );
+
+        let use_case_output = concat!(
+            "42007801000001204200770100000038420069010000002042006A0200000004000000010000000042006B0200000",
+            "004000000000000000042000D0200000004000000010000000042000F01000000D842005C05000000040000000100",
+            "00000042007901000000C04200570500000004000000020000000042009101000000A8420008010000003042000A0",
+            "70000001743727970746F6772617068696320416C676F726974686D0042000B050000000400000003000000004200",
+            "08010000003042000A070000001443727970746F67726170686963204C656E6774680000000042000B02000000040",
+            "000008000000000420008010000003042000A070000001843727970746F67726170686963205573616765204D6173",
+            "6B42000B02000000040000000C00000000"
+        );
+
+        assert_eq!(
+            use_case_output,
+            hex::encode_upper(to_vec(&use_case_input).unwrap()),
+            "expected hex (left) differs to the generated hex (right)"
+        );
+    }
+
+    // The rule for how Rust structs are by default mapped to TTLV is: a struct will be serialized as a Structure,
+    // UNLESS it has been marked as "transparent". To use a Rust struct as a container to hang a Serde attribute off
+    // without actually serializing it as a TTLV Structure one must mark the struct as "transparent". Option types
+    // are also transparent in the sense that either the entire value SHOULD NOT be serialized if it is None, or if
+    // Some then only its inner value will be serialized.
+
+    #[test]
+    fn test_structure_members_must_be_tagged() {
+        // The following cannot be serialized as valid TTLV because a Rust struct is serialized as a TTLV Structure and
+        // a TTLV Structure must contain complete TTLV items (i.e. a full Tag+Type+Length+Value). This doesn't work for
+        // primitive types as they are passed by Serde Derive to serializer functions that only take a value as an
+        // argument, e.g. `serialize_i32(self, value)`, and so the serializer has no name from which to create the tag
+        // (for the initial T in TTLV) for the item.
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct SomeStruct(i32);
+        let to_encode = SomeStruct(3);
+        assert!(to_vec(&to_encode).is_err()); // Error: attempt to serialize malformed TTLTLV.
+    }
+
+    #[test]
+    fn test_a_transparent_struct_can_be_used_to_tag_a_primitive_value() {
+        // If we instead mark the struct as transparent we can then serialize the inner value using the Serde "name" of
+        // the struct as the TTLV tag, instead of creating a containing TTLV Structure with that tag as happens
+        // otherwise.
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct SomeStruct(i32);
+        let to_encode = SomeStruct(3);
+        assert_eq!(
+            "AABBCC02000000040000000300000000",
+            hex::encode_upper(to_vec(&to_encode).unwrap()),
+            "expected hex (left) differs to the generated hex (right)"
+        );
+    }
+
+    #[test]
+    fn test_ttlv_has_no_concept_of_values_that_denote_absence() {
+        // A None is omitted from the output, unless configured to reject it.
+        use crate::ser::{to_vec_with_config, Config};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct SomeStruct(Option<i32>);
+        let to_encode = SomeStruct(None);
+        assert_eq!(
+            "AABBCC0100000000",
+            hex::encode_upper(to_vec(&to_encode).unwrap()),
+            "expected hex (left) differs to the generated hex (right)"
+        );
+
+        let config = Config::new().with_reject_none();
+        assert!(to_vec_with_config(&to_encode, &config).is_err()); // Error: serializing None is not supported.
+    }
+
+    #[test]
+    fn test_optional_values_that_are_absent_are_omitted() {
+        use crate::error::{ErrorKind, SerdeError};
+        use crate::ser::{to_vec_with_config, Config};
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x123456")]
+        struct SomeTaggedValue(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct SomeStruct(Option<SomeTaggedValue>, SomeTaggedValue, Option<SomeTaggedValue>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct SkippingStruct(
+            #[serde(skip_serializing_if = "Option::is_none")] Option<SomeTaggedValue>,
+            SomeTaggedValue,
+            #[serde(skip_serializing_if = "Option::is_none")] Option<SomeTaggedValue>,
+        );
+
+        let expected = "AABBCC010000001012345602000000040000000300000000";
+        let to_encode = SomeStruct(None, SomeTaggedValue(3), None);
+        assert_eq!(expected, hex::encode_upper(to_vec(&to_encode).unwrap()));
+
+        let config = Config::new().with_reject_none();
+        let err = to_vec_with_config(&to_encode, &config).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::SerdeError(SerdeError::UnsupportedRustType("None"))
+        ));
+
+        // Explicitly skipped values are omitted regardless
+        let to_encode = SkippingStruct(None, SomeTaggedValue(3), None);
+        assert_eq!(
+            expected,
+            hex::encode_upper(to_vec_with_config(&to_encode, &config).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_optional_values_that_are_present_are_serialized_as_the_value_directly() {
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct SomeStruct(Option<i32>);
+        let to_encode = SomeStruct(Some(3));
+        assert_eq!(
+            "AABBCC02000000040000000300000000",
+            hex::encode_upper(to_vec(&to_encode).unwrap()),
+            "expected hex (left) differs to the generated hex (right)"
+        );
+    }
+
+    #[test]
+    fn test_serde_derive_doesnt_skip_an_inner_none_inside_a_newtype() {
+        // One would expect the following to work, but Serde Derive ignores the skip directive in this case and still
+        // attempts to serialize the None. What would it mean for a Structure expected to have a single field for that
+        // field to be missing anyway?
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct TransparentItemWithConditionallySerializedOptionalField(
+            #[serde(skip_serializing_if = "Option::is_none")] Option<i32>,
+        );
+        let transparent_conditional_with_none = TransparentItemWithConditionallySerializedOptionalField(None);
+        assert!(to_vec(&transparent_conditional_with_none).is_err()); // Error: serializing None is not supported.
+    }
+
+    #[test]
+    fn test_transparent_is_only_for_newtypes_not_for_tuples() {
+        // Serde Derive will correctly ignore the None field if it is not the only field, but then in this case
+        // "Transparent:0xNNNNNNN" isn't supported because it is intended only for the case of a single inner field.
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct TransparentTupleWithConditionallySerializedOptionalField(
+            i32,
+            #[serde(skip_serializing_if = "Option::is_none")] Option<i32>,
+        );
+        let transparent_tuple_conditional_with_none = TransparentTupleWithConditionallySerializedOptionalField(1, None);
+        assert!(to_vec(&transparent_tuple_conditional_with_none).is_err()); // Error: "Transparent" is not supported here.
+    }
+
+    #[test]
+    fn test_serde_derive_can_skip_optional_none_values_in_a_tuple() {
+        // We can use Serde Derive to skip serialization of a None value if it is not the only inner value in the type
+        // being serialized:
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x123456")]
+        struct SomeTaggedValue(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct TupleWithConditionallySerializedOptionalField(
+            SomeTaggedValue,
+            #[serde(skip_serializing_if = "Option::is_none")] Option<SomeTaggedValue>,
+        );
+        let tuple_conditional_with_none = TupleWithConditionallySerializedOptionalField(SomeTaggedValue(3), None);
+        assert_eq!(
+            "AABBCC010000001012345602000000040000000300000000",
+            hex::encode_upper(to_vec(&tuple_conditional_with_none).unwrap()),
+            "expected hex (left) differs to the generated hex (right)"
+        );
+    }
+
+    #[test]
+    fn test_unit_struct_serializes_to_empty_structure() {
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct Marker;
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x112233")]
+        struct Wrapper(Marker, Marker);
+
+        assert_eq!("AABBCC0100000000", hex::encode_upper(to_vec(&Marker).unwrap()));
+        assert_eq!(
+            "1122330100000010AABBCC0100000000AABBCC0100000000",
+            hex::encode_upper(to_vec(&Wrapper(Marker, Marker)).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_big_integer_serialization() {
+        use crate::types::TtlvBigInteger;
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct Modulus(TtlvBigInteger);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x112233")]
+        struct Key {
+            modulus: Modulus,
+            bytes: ByteString,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xDDEEFF")]
+        struct ByteString(#[serde(with = "serde_bytes")] Vec<u8>);
+
+        assert_eq!(
+            "AABBCC04000000080000000000000101",
+            hex::encode_upper(
+                to_vec(&Modulus(TtlvBigInteger(vec![
+                    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01
+                ])))
+                .unwrap()
+            )
+        );
+
+        // The Big Integer marker applies only to the value it wraps, not to Byte Strings that follow it
+        let key = Key {
+            modulus: Modulus(TtlvBigInteger(vec![0xFF; 8])),
+            bytes: ByteString(vec![0x01]),
+        };
+        assert_eq!(
+            "1122330100000020AABBCC0400000008FFFFFFFFFFFFFFFFDDEEFF08000000010100000000000000",
+            hex::encode_upper(to_vec(&key).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_to_writer_writes_the_same_bytes_as_to_vec() {
+        use crate::ser::to_writer;
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Outer(Inner, Vec<Inner>, Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xBBBBBB")]
+        struct Inner(Value, Vec<Value>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct Value(String);
+
+        let value = Outer(
+            Inner(Value("a".into()), vec![]),
+            vec![
+                Inner(Value("bb".into()), vec![Value("ccccccccc".into())]),
+                Inner(Value("dddd".into()), vec![Value("e".into()), Value("f".into())]),
+            ],
+            Value("g".into()),
+        );
+
+        let mut written = Vec::new();
+        to_writer(&value, &mut written).unwrap();
+        assert_eq!(written, to_vec(&value).unwrap());
+    }
+
+    #[test]
+    fn test_to_writer_returns_write_stats() {
+        use crate::ser::{to_writer, to_writer_with_config, Config};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Outer(Value, Vec<Inner>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xBBBBBB")]
+        struct Inner(Value, Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct Value(i32);
+
+        let value = Outer(Value(1), vec![Inner(Value(2), Value(3)), Inner(Value(4), Value(5))]);
+
+        let mut written = Vec::new();
+        let stats = to_writer(&value, &mut written).unwrap();
+        assert_eq!(stats.num_bytes(), written.len());
+        assert_eq!(stats.num_items(), 8);
+        assert_eq!(stats.max_depth(), 2);
+
+        // Sorting the items does not change the statistics
+        let config = Config::new().with_canonical_order();
+        assert_eq!(to_writer_with_config(&value, std::io::sink(), &config).unwrap(), stats);
+
+        // A lone primitive item is not nested in any TTLV Structure
+        let stats = to_writer(&Value(1), std::io::sink()).unwrap();
+        assert_eq!((stats.num_bytes(), stats.num_items(), stats.max_depth()), (16, 1, 0));
+    }
+
+    #[test]
+    fn test_to_writer_rejects_values_that_serialize_differently_each_time() {
+        use crate::error::{ErrorKind, MalformedTtlvError};
+        use crate::ser::to_writer;
+        use std::cell::Cell;
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Outer(Growing);
+
+        // Serializes one more item each time it is serialized
+        struct Growing(Cell<usize>);
+
+        impl serde::Serialize for Growing {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #[derive(Serialize)]
+                #[serde(rename = "Transparent:0xBBBBBB")]
+                struct Item(i32);
+
+                self.0.set(self.0.get() + 1);
+                serializer.collect_seq((0..self.0.get()).map(|_| Item(1)))
+            }
+        }
+
+        let mut written = Vec::new();
+        let err = to_writer(&Outer(Growing(Cell::new(0))), &mut written).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::MalformedTtlv(MalformedTtlvError::UnknownStructureLength)
+        ));
+    }
+
+    #[test]
+    fn test_text_encodings() {
+        use crate::ser::{encode_base64, to_base64_string, to_hex_string, to_writer, HexWriter};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Root(Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xBBBBBB")]
+        struct Value(i32);
+
+        let value = Root(Value(0x7F));
+        let hex = "AAAAAA0100000010BBBBBB02000000040000007F00000000";
+        assert_eq!(to_hex_string(&value).unwrap(), hex);
+
+        let mut writer = HexWriter::new(Vec::new());
+        to_writer(&value, &mut writer).unwrap();
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), hex);
+
+        assert_eq!(to_base64_string(&value).unwrap(), "qqqqAQAAABC7u7sCAAAABAAAAH8AAAAA");
+
+        // Test vectors from RFC 4648 section 10
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (input, output) in vectors.iter() {
+            assert_eq!(encode_base64(input.as_bytes()), *output);
+        }
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Message(MessageValue);
+
+    #[derive(Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct MessageValue(String);
+
+    #[test]
+    fn test_message_writer() {
+        use crate::error::ErrorKind;
+        use crate::ser::MessageWriter;
+
+        let small = Message(MessageValue("a".into()));
+        let large = Message(MessageValue("0123456789".into()));
+        let small_bytes = to_vec(&small).unwrap();
+        let large_bytes = to_vec(&large).unwrap();
+        assert_eq!((small_bytes.len(), large_bytes.len()), (24, 32));
+
+        let mut writer = MessageWriter::new(Vec::new()).with_max_batch_size(80);
+        assert_eq!(writer.write(&small).unwrap(), 0);
+        assert_eq!(writer.write(&large).unwrap(), 24);
+
+        // A message that does not fit in the remaining space is rejected without writing any of it
+        let err = writer.write(&large).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(_)));
+        assert_eq!(writer.write(&small).unwrap(), 56);
+
+        assert_eq!(writer.offsets(), &[0, 24, 56]);
+        assert_eq!(writer.len(), 80);
+        assert_eq!(
+            writer.into_inner(),
+            [&small_bytes[..], &large_bytes, &small_bytes].concat()
+        );
+    }
+
+    #[cfg(feature = "async-with-tokio")]
+    #[test]
+    fn test_message_writer_with_tokio_writer() {
+        use crate::ser::MessageWriter;
+
+        let message = Message(MessageValue("a".into()));
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on(async {
+            let mut writer = MessageWriter::new(Vec::new()).with_max_batch_size(24);
+            assert_eq!(writer.write_tokio(&message).await.unwrap(), 0);
+            assert!(writer.write_tokio(&message).await.is_err());
+            assert_eq!(writer.into_inner(), to_vec(&message).unwrap());
+        });
+    }
+
+    #[cfg(feature = "async-with-futures")]
+    #[test]
+    fn test_message_writer_with_futures_writer() {
+        use crate::ser::MessageWriter;
+        use futures_util::FutureExt;
+
+        // Writing to a Vec never has to wait so no executor is needed to drive the futures to completion
+        let message = Message(MessageValue("a".into()));
+        let mut writer = MessageWriter::new(Vec::new());
+        assert_eq!(writer.write_futures(&message).now_or_never().unwrap().unwrap(), 0);
+        assert_eq!(writer.write_futures(&message).now_or_never().unwrap().unwrap(), 24);
+        assert_eq!(writer.offsets(), &[0, 24]);
+    }
+
+    #[test]
+    fn test_serialized_size() {
+        use crate::error::ErrorKind;
+        use crate::ser::{serialized_size, serialized_size_with_config, ByteStream, Config};
+        use std::io::Cursor;
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Outer(Inner, Vec<Inner>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xBBBBBB")]
+        struct Inner(Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct Value(String);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xDDDDDD")]
+        struct Content(ByteStream<Cursor<Vec<u8>>>);
+
+        let value = Outer(Inner(Value("a".into())), vec![Inner(Value("0123456789".into()))]);
+        let size = serialized_size(&value).unwrap();
+        assert_eq!(size, to_vec(&value).unwrap().len());
+        assert_eq!(size, 8 + (8 + 16) + (8 + 24));
+
+        let config = Config::new().with_max_bytes(size as u32 - 1);
+        let err = serialized_size_with_config(&value, &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(_)));
+
+        // The content of a ByteStream is counted rather than read
+        let content = Content(ByteStream::new(Cursor::new(vec![1, 2, 3]), 3));
+        assert_eq!(serialized_size(&content).unwrap(), 16);
+        assert_eq!(content.0.into_inner().position(), 0);
+    }
+
+    #[test]
+    fn test_to_seekable_writer_patches_structure_lengths_in_place() {
+        use crate::ser::{to_seekable_writer, to_writer, ByteStream};
+        use std::io::{Cursor, Seek, SeekFrom};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Outer(Inner, Vec<Inner>, Content);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xBBBBBB")]
+        struct Inner(Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct Value(String);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xDDDDDD")]
+        struct Content(ByteStream<Cursor<Vec<u8>>>);
+
+        let value = || {
+            Outer(
+                Inner(Value("a".into())),
+                vec![Inner(Value("bb".into())), Inner(Value("ccc".into()))],
+                Content(ByteStream::new(Cursor::new(vec![1, 2, 3]), 3)),
+            )
+        };
+        let mut expected = Vec::new();
+        to_writer(&value(), &mut expected).unwrap();
+
+        // Bytes are written from the current position onwards and the position is left at the end of them
+        let mut written = Cursor::new(vec![0xFF, 0xFF]);
+        written.seek(SeekFrom::End(0)).unwrap();
+        let stats = to_seekable_writer(&value(), &mut written).unwrap();
+        assert_eq!(stats.num_bytes(), expected.len());
+        assert_eq!(written.position() as usize, 2 + expected.len());
+        assert_eq!(&written.get_ref()[..2], &[0xFF, 0xFF]);
+        assert_eq!(&written.get_ref()[2..], expected.as_slice());
+
+        // The content of a ByteStream is only read once
+        let value = value();
+        to_seekable_writer(&value, Cursor::new(Vec::new())).unwrap();
+        assert_eq!((value.2).0.into_inner().position(), 3);
+    }
+
+    #[test]
+    fn test_byte_stream() {
+        use crate::ser::{to_writer, ByteStream};
+        use std::io::Read;
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Streamed(StreamedValue, Other);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xBBBBBB")]
+        struct StreamedValue(ByteStream<CountingReader>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Buffered(BufferedValue, Other);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xBBBBBB")]
+        struct BufferedValue(#[serde(with = "serde_bytes")] Vec<u8>);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct Other(i32);
+
+        // Counts the number of bytes read
+        struct CountingReader(std::io::Cursor<Vec<u8>>, usize);
+
+        impl Read for CountingReader {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.0.read(buf)?;
+                self.1 += n;
+                Ok(n)
+            }
+        }
+
+        // Longer than a single chunk and not a multiple of eight bytes long so that padding is needed
+        let content: Vec<u8> = (0..20_005).map(|i| i as u8).collect();
+        let expected = to_vec(&Buffered(BufferedValue(content.clone()), Other(1))).unwrap();
+        let stream =
+            |content: Vec<u8>, len: u32| ByteStream::new(CountingReader(std::io::Cursor::new(content), 0), len);
+
+        // The content is read only once by to_writer, despite it serializing the value twice
+        let value = Streamed(StreamedValue(stream(content.clone(), 20_005)), Other(1));
+        let mut written = Vec::new();
+        to_writer(&value, &mut written).unwrap();
+        assert_eq!(written, expected);
+        assert_eq!((value.0).0.into_inner().1, 20_005);
+
+        let value = Streamed(StreamedValue(stream(content.clone(), 20_005)), Other(1));
+        assert_eq!(to_vec(&value).unwrap(), expected);
+
+        // The reader must yield at least as many bytes as the declared length, further bytes are not read
+        let value = Streamed(StreamedValue(stream(content.clone(), 20_006)), Other(1));
+        assert!(to_writer(&value, &mut Vec::new()).is_err());
+        let value = Streamed(StreamedValue(stream(content, 16)), Other(1));
+        let mut written = Vec::new();
+        to_writer(&value, &mut written).unwrap();
+        assert_eq!(
+            hex::encode_upper(&written[8..32]),
+            "BBBBBB0800000010000102030405060708090A0B0C0D0E0F"
+        );
+        assert_eq!((value.0).0.into_inner().1, 16);
+    }
+
+    #[test]
+    fn test_vectored_writes() {
+        use crate::ser::{to_segments, to_writer};
+        use std::io::{IoSlice, Write};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0xAAAAAA")]
+        struct Outer(Small, Large, Small);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xBBBBBB")]
+        struct Small(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct Large(#[serde(with = "serde_bytes")] Vec<u8>);
+
+        // Records the bytes written and the number of calls made to write them
+        #[derive(Default)]
+        struct RecordingWriter {
+            written: Vec<u8>,
+            calls: usize,
+        }
+
+        impl Write for RecordingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.write_vectored(&[IoSlice::new(buf)])
+            }
+
+            fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+                self.calls += 1;
+                // Accept at most 10000 bytes per call to exercise the handling of partial writes
+                let mut n = 0;
+                for buf in bufs {
+                    let len = buf.len().min(10_000 - n);
+                    self.written.extend_from_slice(&buf[..len]);
+                    n += len;
+                }
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value = Outer(Small(1), Large(vec![0xFF; 20_001]), Small(2));
+        let expected = to_vec(&value).unwrap();
+
+        // The large value is written along with the preceding buffered headers without being copied into the buffer
+        let mut writer = RecordingWriter::default();
+        to_writer(&value, &mut writer).unwrap();
+        assert_eq!(writer.written, expected);
+        assert_eq!(writer.calls, 4);
+
+        let segments = to_segments(&value).unwrap();
+        assert_eq!(segments.len(), expected.len());
+        assert_eq!(segments.segments().len(), 3);
+        assert_eq!(segments.segments()[1].len(), 20_001);
+        let slices = segments.io_slices();
+        assert_eq!(
+            slices.iter().flat_map(|s| s.iter().copied()).collect::<Vec<u8>>(),
+            expected
+        );
+        assert_eq!(segments.into_vec(), expected);
+    }
+
+    #[test]
+    fn test_deeply_nested_values_do_not_overflow_the_stack() {
+        use crate::error::ErrorKind;
+        use crate::ser::{to_vec_with_config, to_writer, Config};
+        use serde::ser::{SerializeSeq, SerializeStruct};
+
+        // Nests TTLV Structures to the given depth
+        struct NestedStructures(usize);
+
+        impl serde::Serialize for NestedStructures {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut s = serializer.serialize_struct("0xAAAAAA", 1)?;
+                if self.0 > 1 {
+                    s.serialize_field("inner", &NestedStructures(self.0 - 1))?;
+                }
+                s.end()
+            }
+        }
+
+        // Nests sequences, which do not produce TTLV Structures, to the given depth
+        struct NestedSequences(usize);
+
+        impl serde::Serialize for NestedSequences {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut s = serializer.serialize_seq(None)?;
+                if self.0 > 1 {
+                    s.serialize_element(&NestedSequences(self.0 - 1))?;
+                }
+                s.end()
+            }
+        }
+
+        // Without a configured limit nesting is still limited
+        assert_eq!(to_vec(&NestedStructures(128)).unwrap().len(), 128 * 8);
+        let err = to_vec(&NestedStructures(100_000)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(129)));
+        let err = to_writer(&NestedStructures(100_000), std::io::sink()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(129)));
+
+        let config = Config::new().with_max_nesting_depth(10);
+        let err = to_vec_with_config(&NestedStructures(100_000), &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(11)));
+
+        // A higher limit can be configured
+        let config = Config::new().with_max_nesting_depth(300);
+        assert_eq!(
+            to_vec_with_config(&NestedStructures(300), &config).unwrap().len(),
+            300 * 8
+        );
+
+        // Nesting that does not produce TTLV Structures is limited too
+        assert!(to_vec(&NestedSequences(1024)).unwrap().is_empty());
+        let err = to_vec(&NestedSequences(100_000)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(1025)));
+    }
+
+    #[test]
+    fn test_serializer_config_limits() {
+        use crate::error::{ErrorKind, SerdeError};
+        use crate::ser::{to_vec_with_config, Config};
+        use crate::types::TtlvTag;
+        use std::str::FromStr;
+
+        let tag = |tag: &str| TtlvTag::from_str(tag).unwrap();
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420001")]
+        struct Outer(Inner, Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420002")]
+        struct Inner(Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x540003")]
+        struct Value(i32);
+
+        let value = Outer(Inner(Value(1)), Value(2));
+        let expected = to_vec(&value).unwrap();
+        assert_eq!(expected.len(), 48);
+
+        // Limits that are not exceeded have no effect
+        let config = Config::new()
+            .with_max_bytes(48)
+            .with_max_nesting_depth(2)
+            .with_allowed_tag_range(tag("0x420000")..=tag("0x42FFFF"))
+            .with_allowed_tag_range(tag("0x540000")..=tag("0x54FFFF"));
+        assert_eq!(to_vec_with_config(&value, &config).unwrap(), expected);
+
+        let config = Config::new().with_max_bytes(47);
+        let err = to_vec_with_config(&value, &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(48)));
+
+        let config = Config::new().with_max_nesting_depth(1);
+        let err = to_vec_with_config(&value, &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(2)));
+
+        let config = Config::new().with_allowed_tag_range(tag("0x420000")..=tag("0x42FFFF"));
+        let err = to_vec_with_config(&value, &config).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::SerdeError(SerdeError::DisallowedTag { tag: t, field: None }) if *t == tag("0x540003")
+        ));
+        assert_eq!(err.location().tag(), Some(tag("0x540003")));
+    }
+
+    #[test]
+    fn test_disallowed_tag_names_the_rust_field() {
+        use crate::error::{ErrorKind, SerdeError};
+        use crate::ser::{to_vec_with_config, Config};
+        use crate::types::TtlvTag;
+        use std::str::FromStr;
+
+        let tag = |tag: &str| TtlvTag::from_str(tag).unwrap();
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420001")]
+        struct Request {
+            operation: Operation,
+            payload: Payload,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420002")]
+        struct Payload {
+            unique_identifier: UniqueIdentifier,
+            attributes: Vec<Attribute>,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420003")]
+        struct Operation(u32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420004")]
+        struct UniqueIdentifier(String);
+
+        // A typo, 0x4200XX was intended
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x240005")]
+        struct Attribute(i32);
+
+        let config = Config::new()
+            .with_allowed_tag_range(tag("0x420000")..=tag("0x42FFFF"))
+            .with_allowed_tag_range(tag("0x540000")..=tag("0x54FFFF"));
+
+        let value = Request {
+            operation: Operation(1),
+            payload: Payload {
+                unique_identifier: UniqueIdentifier("a".to_string()),
+                attributes: vec![],
+            },
+        };
+        assert!(to_vec_with_config(&value, &config).is_ok());
+
+        let value = Request {
+            payload: Payload {
+                unique_identifier: UniqueIdentifier("a".to_string()),
+                attributes: vec![Attribute(1)],
+            },
+            ..value
+        };
+        let err = to_vec_with_config(&value, &config).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::SerdeError(SerdeError::DisallowedTag {
+                tag: t,
+                field: Some("attributes")
+            }) if *t == tag("0x240005")
+        ));
+        assert!(err.to_string().contains("attributes"));
+    }
+
+    #[test]
+    fn test_item_observer() {
+        use crate::de::ItemHeader;
+        use crate::error::ErrorKind;
+        use crate::ser::{to_vec_with_config, to_writer_with_config, Config};
+        use crate::types::{ByteOffset, TtlvTag, TtlvType};
+        use std::str::FromStr;
+        use std::sync::{Arc, Mutex};
+
+        fn reject_tag_420003(header: &ItemHeader) -> std::result::Result<(), String> {
+            if header.tag() == TtlvTag::from_str("0x420003").unwrap() {
+                Err("forbidden tag".to_string())
+            } else {
+                Ok(())
+            }
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420001")]
+        struct Outer(Inner, Name);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420002")]
+        struct Inner(Value);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x540003")]
+        struct Value(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420003")]
+        struct Name(String);
+
+        let value = Outer(Inner(Value(1)), Name("abc".to_string()));
+        let tag = |tag: &str| TtlvTag::from_str(tag).unwrap();
+
+        // TTLV Structures are observed after their content
+        let expected = vec![
+            (16, tag("0x540003"), TtlvType::Integer, 4),
+            (8, tag("0x420002"), TtlvType::Structure, 16),
+            (32, tag("0x420003"), TtlvType::TextString, 3),
+            (0, tag("0x420001"), TtlvType::Structure, 40),
+        ];
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let config = Config::new().with_item_observer(move |header: &ItemHeader| {
+            let item = (*header.offset(), header.tag(), header.r#type(), header.length());
+            recorder.lock().unwrap().push(item);
+            Ok(())
+        });
+        let take_seen = || std::mem::take(&mut *seen.lock().unwrap());
+        let bytes = to_vec_with_config(&value, &config).unwrap();
+        assert_eq!(bytes, to_vec(&value).unwrap());
+        assert_eq!(take_seen(), expected);
+
+        // Items are observed only once even though to_writer() serializes twice
+        let mut written = Vec::new();
+        to_writer_with_config(&value, &mut written, &config).unwrap();
+        assert_eq!(written, bytes);
+        assert_eq!(take_seen(), expected);
+
+        // Rejected output is not written
+        let config = Config::new().with_item_observer(reject_tag_420003);
+        let err = to_vec_with_config(&value, &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::RejectedByObserver(reason) if reason == "forbidden tag"));
+        assert_eq!(err.location().offset(), Some(ByteOffset(32)));
+        assert_eq!(err.location().tag(), Some(tag("0x420003")));
+
+        let mut written = Vec::new();
+        assert!(to_writer_with_config(&value, &mut written, &config).is_err());
+        assert!(written.is_empty());
+    }
+
+    #[test]
+    fn test_canonical_order() {
+        use crate::ser::{to_segments_with_config, to_vec_with_config, to_writer_with_config, Config};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420001")]
+        struct Root(C, Vec<B>, Inner);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420000")]
+        struct Inner(C, B);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420001")]
+        struct SortedRoot(SortedInner, Vec<B>, C);
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420000")]
+        struct SortedInner(B, C);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420002")]
+        struct B(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420003")]
+        struct C(i32);
+
+        let value = Root(C(1), vec![B(2), B(3)], Inner(C(4), B(5)));
+        let expected = to_vec(&SortedRoot(SortedInner(B(5), C(4)), vec![B(2), B(3)], C(1))).unwrap();
+        assert_ne!(to_vec(&value).unwrap(), expected);
+
+        let config = Config::new().with_canonical_order();
+        assert_eq!(to_vec_with_config(&value, &config).unwrap(), expected);
+
+        let mut written = Vec::new();
+        to_writer_with_config(&value, &mut written, &config).unwrap();
+        assert_eq!(written, expected);
+
+        assert_eq!(to_segments_with_config(&value, &config).unwrap().into_vec(), expected);
+    }
+
+    #[test]
+    fn test_item_observer_with_canonical_order() {
+        use crate::de::ItemHeader;
+        use crate::ser::{to_vec_with_config, Config};
+        use crate::types::{TtlvTag, TtlvType};
+        use std::str::FromStr;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Serialize)]
+        #[serde(rename = "0x420001")]
+        struct Root(C, B);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420002")]
+        struct B(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x420003")]
+        struct C(i32);
+
+        // The offsets observed are those of the items after sorting
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let config = Config::new()
+            .with_canonical_order()
+            .with_item_observer(move |header: &ItemHeader| {
+                let item = (*header.offset(), header.tag(), header.r#type(), header.length());
+                recorder.lock().unwrap().push(item);
+                Ok(())
+            });
+        to_vec_with_config(&Root(C(1), B(2)), &config).unwrap();
+        let seen = seen.lock().unwrap().clone();
+        let tag = |tag: &str| TtlvTag::from_str(tag).unwrap();
+        assert_eq!(
+            seen,
+            vec![
+                (8, tag("0x420002"), TtlvType::Integer, 4),
+                (24, tag("0x420003"), TtlvType::Integer, 4),
+                (0, tag("0x420001"), TtlvType::Structure, 32),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transparent_with_type() {
+        use crate::error::{ErrorKind, SerdeError};
+        use crate::types::TtlvType;
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent(Enumeration):0xAABBCC")]
+        struct SomeEnumeration(i32);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent(Interval):0xAABBCC")]
+        struct SomeInterval(u64);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent(DateTime):0xAABBCC")]
+        struct SomeDateTime(i64);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent(Enumeration):0xAABBCC")]
+        struct NotAnInteger(String);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent(Boolean):0xAABBCC")]
+        struct UnsupportedType(bool);
+
+        assert_eq!(
+            "AABBCC05000000040000000300000000",
+            hex::encode_upper(to_vec(&SomeEnumeration(3)).unwrap())
+        );
+        assert_eq!(
+            "AABBCC0A000000040000000300000000",
+            hex::encode_upper(to_vec(&SomeInterval(3)).unwrap())
+        );
+        assert_eq!(
+            "AABBCC09000000080000000000000003",
+            hex::encode_upper(to_vec(&SomeDateTime(3)).unwrap())
+        );
+
+        // Values out of range for the TTLV type are rejected
+        assert!(to_vec(&SomeEnumeration(-1)).is_err());
+        assert!(to_vec(&SomeInterval(u64::MAX)).is_err());
+
+        let err = to_vec(&NotAnInteger("a".to_string())).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::SerdeError(SerdeError::UnexpectedType {
+                expected: TtlvType::Enumeration,
+                actual: TtlvType::TextString
+            })
+        ));
+
+        assert!(to_vec(&UnsupportedType(true)).is_err());
+    }
+
+    #[test]
+    fn test_small_unsigned_integers_are_widened_to_integer() {
+        #[derive(Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct Small {
+            a: Byte,
+            b: Short,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x111111")]
+        struct Byte(u8);
+
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0x222222")]
+        struct Short(u16);
+
+        let small = Small {
+            a: Byte(u8::MAX),
+            b: Short(u16::MAX),
+        };
+        assert_eq!(
+            "AABBCC01000000201111110200000004000000FF0000000022222202000000040000FFFF00000000",
+            hex::encode_upper(to_vec(&small).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_unsigned_integers_can_serialize_to_signed_types() {
+        use serde_derive::Deserialize;
+
+        use crate::de::from_slice;
 
-    use serde_derive::Serialize;
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct Sizes {
+            count: Count,
+            length: Length,
+        }
 
-    use crate::ser::to_vec;
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "Transparent:0x111111")]
+        struct Count(#[serde(with = "crate::unsigned")] u32);
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "Transparent:0x222222")]
+        struct Length(#[serde(with = "crate::unsigned")] u64);
+
+        let sizes = Sizes {
+            count: Count(i32::MAX as u32),
+            length: Length(i64::MAX as u64),
+        };
+        let bytes = to_vec(&sizes).unwrap();
+        assert_eq!(
+            "AABBCC010000002011111102000000047FFFFFFF0000000022222203000000087FFFFFFFFFFFFFFF",
+            hex::encode_upper(&bytes)
+        );
+        assert_eq!(from_slice::<Sizes>(&bytes).unwrap(), sizes);
+
+        // Values too large for the signed TTLV type cannot be serialized
+        let too_large_count = Sizes {
+            count: Count(i32::MAX as u32 + 1),
+            length: Length(0),
+        };
+        assert!(to_vec(&too_large_count).is_err());
+        let too_large_length = Sizes {
+            count: Count(0),
+            length: Length(i64::MAX as u64 + 1),
+        };
+        assert!(to_vec(&too_large_length).is_err());
+
+        // Negative values cannot be deserialized
+        let negative =
+            hex::decode("AABBCC0100000020111111020000000400000001000000002222220300000008FFFFFFFFFFFFFFFF").unwrap();
+        assert!(from_slice::<Sizes>(&negative).is_err());
+    }
 
     #[test]
-    fn test_kmip_10_create_destroy_use_case_create_request_serialization() {
-        // Define the types used by the test below. Note that these are structured so as to be easy to compose with minimal
-        // boilerplate overhead. For example tuple structs are heavily used rather than structs with named fields. If this
-        // were for deserialization instead of serialization these types should instead be verbose with named fields to make
-        // it easy to interact with the response objects.
-        #[derive(Serialize)]
-        #[serde(rename = "0x420078")]
-        struct RequestMessage(RequestHeader, Vec<BatchItem>);
+    fn test_as_type_fields() {
+        use serde_derive::Deserialize;
 
-        #[derive(Serialize)]
-        #[serde(rename = "0x420077")]
-        struct RequestHeader(ProtocolVersion, BatchCount);
+        use crate::de::from_slice;
 
-        #[derive(Serialize)]
-        #[serde(rename = "Transparent:0x42006B")]
-        struct ProtocolVersionMinor(i32);
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "0xAABBCC")]
+        struct Times {
+            state: State,
+            activation_date: ActivationDate,
+            lease_time: LeaseTime,
+        }
 
-        #[derive(Serialize)]
-        #[serde(rename = "Transparent:0x42006A")]
-        struct ProtocolVersionMajor(i32);
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "Transparent:0x111111")]
+        struct State(#[serde(with = "crate::as_type::enumeration")] u8);
 
-        #[derive(Serialize)]
-        #[serde(rename = "0x420069")]
-        struct ProtocolVersion(ProtocolVersionMajor, ProtocolVersionMinor);
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "Transparent:0x222222")]
+        struct ActivationDate(#[serde(with = "crate::as_type::date_time")] i64);
 
-        #[derive(Serialize)]
-        #[serde(rename = "Transparent:0x42000D")]
-        struct BatchCount(i32);
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "Transparent:0x333333")]
+        struct LeaseTime(#[serde(with = "crate::as_type::interval")] i32);
 
-        #[derive(Serialize)]
-        #[serde(rename = "0x42000F")]
-        struct BatchItem(Operation, RequestPayload);
+        let times = Times {
+            state: State(1),
+            activation_date: ActivationDate(3),
+            lease_time: LeaseTime(60),
+        };
+        let bytes = to_vec(&times).unwrap();
+        assert_eq!(
+            "AABBCC01000000301111110500000004000000010000000022222209000000080000000000000003\
+             3333330A000000040000003C00000000",
+            hex::encode_upper(&bytes)
+        );
+        assert_eq!(from_slice::<Times>(&bytes).unwrap(), times);
+
+        // Values out of range for the TTLV type cannot be serialized
+        let negative_interval = Times {
+            lease_time: LeaseTime(-1),
+            ..times
+        };
+        assert!(to_vec(&negative_interval).is_err());
+
+        // Values out of range for the Rust type cannot be deserialized
+        let too_large_state = hex::decode(
+            "AABBCC01000000301111110500000004000001000000000022222209000000080000000000000003\
+                         3333330A000000040000003C00000000"
+                .replace(" ", ""),
+        )
+        .unwrap();
+        assert!(from_slice::<Times>(&too_large_state).is_err());
+
+        // Other TTLV types are rejected
+        let integer_lease_time = hex::decode(
+            "AABBCC01000000301111110500000004000000010000000022222209000000080000000000000003\
+                         33333302000000040000003C00000000"
+                .replace(" ", ""),
+        )
+        .unwrap();
+        assert!(from_slice::<Times>(&integer_lease_time).is_err());
+    }
 
+    #[test]
+    fn test_tuples() {
         #[derive(Serialize)]
-        #[serde(rename = "0x42005C")]
-        enum Operation {
-            #[serde(rename = "0x00000001")]
-            Create,
+        #[serde(rename = "0xAAAAAA")]
+        struct Root {
+            range: (Low, High),
+            pair: Pair,
+            tagged: Tagged,
         }
 
         #[derive(Serialize)]
-        #[serde(rename = "0x420079")]
-        struct RequestPayload(ObjectType, TemplateAttribute);
+        #[serde(rename = "Transparent")]
+        struct Pair(Low, High);
 
         #[derive(Serialize)]
-        #[serde(rename = "0x420057")]
-        enum ObjectType {
-            #[serde(rename = "0x00000002")]
-            SymmetricKey,
-        }
+        #[serde(rename = "Transparent:0xDDDDDD")]
+        struct Tagged((Low, High));
 
         #[derive(Serialize)]
-        #[serde(rename = "0x420091")]
-        struct TemplateAttribute(Vec<Attribute>);
+        #[serde(rename = "Transparent:0xBBBBBB")]
+        struct Low(i32);
 
         #[derive(Serialize)]
-        #[serde(rename = "0x420008")]
-        struct Attribute(AttributeName, AttributeValue);
+        #[serde(rename = "Transparent:0xCCCCCC")]
+        struct High(i32);
 
-        #[derive(Serialize)]
-        #[serde(rename = "Transparent:0x42000A")]
-        struct AttributeName(&'static str);
+        let root = Root {
+            range: (Low(1), High(2)),
+            pair: Pair(Low(3), High(4)),
+            tagged: Tagged((Low(5), High(6))),
+        };
+        assert_eq!(
+            concat!(
+                "AAAAAA0100000068",
+                "BBBBBB02000000040000000100000000",
+                "CCCCCC02000000040000000200000000",
+                "BBBBBB02000000040000000300000000",
+                "CCCCCC02000000040000000400000000",
+                "DDDDDD0100000020",
+                "BBBBBB02000000040000000500000000",
+                "CCCCCC02000000040000000600000000",
+            ),
+            hex::encode_upper(to_vec(&root).unwrap())
+        );
 
+        // Tuple elements must supply their own tag
         #[derive(Serialize)]
-        #[serde(rename = "Override:0x42000B")]
-        enum AttributeValue {
-            #[serde(rename = "Transparent")]
-            CryptographicAlgorithm(CryptographicAlgorithm),
-
-            #[serde(rename = "Transparent")]
-            Integer(i32),
-        }
+        #[serde(rename = "0xAAAAAA")]
+        struct Untagged((i32, i32));
+        assert!(to_vec(&Untagged((1, 2))).is_err());
+    }
 
-        impl Attribute {
-            #[allow(non_snake_case)]
-            fn CryptographicAlgorithm(value: CryptographicAlgorithm) -> Self {
-                Attribute(
-                    AttributeName("Cryptographic Algorithm"),
-                    AttributeValue::CryptographicAlgorithm(value),
-                )
-            }
+    #[test]
+    fn test_flatten() {
+        use serde_derive::Deserialize;
 
-            #[allow(non_snake_case)]
-            fn CryptographicLength(value: i32) -> Self {
-                Attribute(AttributeName("Cryptographic Length"), AttributeValue::Integer(value))
-            }
+        use crate::de::from_slice;
 
-            #[allow(non_snake_case)]
-            fn CryptographicUsageMask(value: i32) -> Self {
-                Attribute(
-                    AttributeName("Cryptographic Usage Mask"),
-                    AttributeValue::Integer(value),
-                )
-            }
+        #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+        struct Header {
+            #[serde(rename = "0xBBBBBB")]
+            id: i32,
+            #[serde(default, rename = "0xCCCCCC")]
+            name: Option<String>,
         }
 
-        #[derive(Serialize)]
-        #[serde(rename = "420028")]
-        enum CryptographicAlgorithm {
-            #[serde(rename = "0x00000003")]
-            AES,
+        #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+        struct Message {
+            #[serde(flatten)]
+            header: Header,
+            #[serde(rename = "0xDDDDDD")]
+            flag: bool,
         }
 
-        // Attempt to generate correct binary TTLV for KMIP specification v1.0 use case 3.1.1 Create / Destroy as the\
-        // use case definition includes the input structure and the corresponding expected binary output.
-        // See: http://docs.oasis-open.org/kmip/usecases/v1.0/cs01/kmip-usecases-1.0-cs-01.html
-
-        let use_case_input = RequestMessage(
-            RequestHeader(
-                ProtocolVersion(ProtocolVersionMajor(1), ProtocolVersionMinor(0)),
-                BatchCount(1),
+        // Serde does not pass the tag of a struct with a flattened field on, so supply it from a wrapper
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xAAAAAA")]
+        struct Request(Message);
+
+        let mut msg = Message {
+            header: Header {
+                id: 1,
+                name: Some("hi".to_string()),
+            },
+            flag: true,
+        };
+        let ttlv_bytes = to_vec(&Request(msg.clone())).unwrap();
+        assert_eq!(
+            concat!(
+                "AAAAAA0100000030",
+                "BBBBBB02000000040000000100000000",
+                "CCCCCC07000000026869000000000000",
+                "DDDDDD06000000080000000000000001",
             ),
-            vec![BatchItem(
-                Operation::Create,
-                RequestPayload(
-                    ObjectType::SymmetricKey,
-                    TemplateAttribute(vec![
-                        Attribute::CryptographicAlgorithm(CryptographicAlgorithm::AES),
-                        Attribute::CryptographicLength(128),
-                        Attribute::CryptographicUsageMask(0x0000_000C),
-                    ]),
-                ),
-            )],
-        );
-
-        let use_case_output = concat!(
-            "42007801000001204200770100000038420069010000002042006A0200000004000000010000000042006B0200000",
-            "004000000000000000042000D0200000004000000010000000042000F01000000D842005C05000000040000000100",
-            "00000042007901000000C04200570500000004000000020000000042009101000000A8420008010000003042000A0",
-            "70000001743727970746F6772617068696320416C676F726974686D0042000B050000000400000003000000004200",
-            "08010000003042000A070000001443727970746F67726170686963204C656E6774680000000042000B02000000040",
-            "000008000000000420008010000003042000A070000001843727970746F67726170686963205573616765204D6173",
-            "6B42000B02000000040000000C00000000"
+            hex::encode_upper(&ttlv_bytes)
         );
+        assert_eq!(from_slice::<Message>(&ttlv_bytes).unwrap(), msg);
 
+        // A None field of the flattened struct is omitted
+        msg.header.name = None;
+        let ttlv_bytes = to_vec(&Request(msg.clone())).unwrap();
         assert_eq!(
-            use_case_output,
-            hex::encode_upper(to_vec(&use_case_input).unwrap()),
-            "expected hex (left) differs to the generated hex (right)"
+            concat!(
+                "AAAAAA0100000020",
+                "BBBBBB02000000040000000100000000",
+                "DDDDDD06000000080000000000000001",
+            ),
+            hex::encode_upper(&ttlv_bytes)
         );
-    }
+        assert_eq!(from_slice::<Message>(&ttlv_bytes).unwrap(), msg);
 
-    // The rule for how Rust structs are by default mapped to TTLV is: a struct will be serialized as a Structure,
-    // UNLESS it has been marked as "transparent". To use a Rust struct as a container to hang a Serde attribute off
-    // without actually serializing it as a TTLV Structure one must mark the struct as "transparent". Option types
-    // are also transparent in the sense that either the entire value SHOULD NOT be serialized if it is None, or if
-    // Some then only its inner value will be serialized.
+        // Fields whose name is not a tag take the tag of their value
+        #[derive(Serialize)]
+        #[serde(rename = "Transparent:0xEEEEEE")]
+        struct Flag(bool);
 
-    #[test]
-    fn test_structure_members_must_be_tagged() {
-        // The following cannot be serialized as valid TTLV because a Rust struct is serialized as a TTLV Structure and
-        // a TTLV Structure must contain complete TTLV items (i.e. a full Tag+Type+Length+Value). This doesn't work for
-        // primitive types as they are passed by Serde Derive to serializer functions that only take a value as an
-        // argument, e.g. `serialize_i32(self, value)`, and so the serializer has no name from which to create the tag
-        // (for the initial T in TTLV) for the item. We also cannot handle a None value inside a struct because a None
-        // value should not be serialized at all yet by the time serialize_none() is invoked, the outer struct TTL part
-        // has already been serialized to the byte stream and not serializing the V part doesn't remove the alraedy
-        // serialized TTL part.
         #[derive(Serialize)]
-        #[serde(rename = "0xAABBCC")]
-        struct SomeStruct(i32);
-        let to_encode = SomeStruct(3);
-        assert!(to_vec(&to_encode).is_err()); // Error: attempt to serialize malformed TTLTLV.
-    }
+        struct Named {
+            #[serde(flatten)]
+            header: Header,
+            flag: Flag,
+        }
 
-    #[test]
-    fn test_a_transparent_struct_can_be_used_to_tag_a_primitive_value() {
-        // If we instead mark the struct as transparent we can then serialize the inner value using the Serde "name" of
-        // the struct as the TTLV tag, instead of creating a containing TTLV Structure with that tag as happens
-        // otherwise.
         #[derive(Serialize)]
-        #[serde(rename = "Transparent:0xAABBCC")]
-        struct SomeStruct(i32);
-        let to_encode = SomeStruct(3);
+        #[serde(rename = "Transparent:0xAAAAAA")]
+        struct NamedRequest(Named);
+
+        let named = Named {
+            header: Header { id: 2, name: None },
+            flag: Flag(false),
+        };
         assert_eq!(
-            "AABBCC02000000040000000300000000",
-            hex::encode_upper(to_vec(&to_encode).unwrap()),
-            "expected hex (left) differs to the generated hex (right)"
+            concat!(
+                "AAAAAA0100000020",
+                "BBBBBB02000000040000000200000000",
+                "EEEEEE06000000080000000000000000",
+            ),
+            hex::encode_upper(to_vec(&NamedRequest(named)).unwrap())
         );
     }
 
     #[test]
-    fn test_ttlv_has_no_concept_of_values_that_denote_absence() {
+    fn test_128_bit_integers_serialize_to_big_integer() {
         #[derive(Serialize)]
-        #[serde(rename = "0xAABBCC")]
-        struct SomeStruct(Option<i32>);
-        let to_encode = SomeStruct(None);
-        assert!(to_vec(&to_encode).is_err()); // Error: serializing None is not supported.
-    }
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct Signed(i128);
 
-    #[test]
-    fn test_optional_values_that_are_present_are_serialized_as_the_value_directly() {
         #[derive(Serialize)]
         #[serde(rename = "Transparent:0xAABBCC")]
-        struct SomeStruct(Option<i32>);
-        let to_encode = SomeStruct(Some(3));
+        struct Unsigned(u128);
+
+        fn signed(v: i128) -> String {
+            hex::encode_upper(to_vec(&Signed(v)).unwrap())
+        }
+
+        fn unsigned(v: u128) -> String {
+            hex::encode_upper(to_vec(&Unsigned(v)).unwrap())
+        }
+
+        // Values that fit in eight bytes are not padded to sixteen
+        assert_eq!("AABBCC04000000080000000000000000", signed(0));
+        assert_eq!("AABBCC0400000008FFFFFFFFFFFFFFFF", signed(-1));
+        assert_eq!("AABBCC04000000087FFFFFFFFFFFFFFF", signed(i64::MAX as i128));
+        assert_eq!("AABBCC04000000088000000000000000", signed(i64::MIN as i128));
+
+        // Larger values are sign-extended to sixteen bytes
         assert_eq!(
-            "AABBCC02000000040000000300000000",
-            hex::encode_upper(to_vec(&to_encode).unwrap()),
-            "expected hex (left) differs to the generated hex (right)"
+            "AABBCC040000001000000000000000008000000000000000",
+            signed(i64::MAX as i128 + 1)
         );
-    }
+        assert_eq!(
+            "AABBCC0400000010FFFFFFFFFFFFFFFF7FFFFFFFFFFFFFFF",
+            signed(i64::MIN as i128 - 1)
+        );
+        assert_eq!("AABBCC040000001080000000000000000000000000000000", signed(i128::MIN));
 
-    #[test]
-    fn test_serde_derive_doesnt_skip_an_inner_none_inside_a_newtype() {
-        // One would expect the following to work, but Serde Derive ignores the skip directive in this case and still
-        // attempts to serialize the None. What would it mean for a Structure expected to have a single field for that
-        // field to be missing anyway?
-        #[derive(Serialize)]
-        #[serde(rename = "Transparent:0xAABBCC")]
-        struct TransparentItemWithConditionallySerializedOptionalField(
-            #[serde(skip_serializing_if = "Option::is_none")] Option<i32>,
+        // Unsigned values with the most significant bit set are extended with zero bytes to remain positive
+        assert_eq!("AABBCC04000000080000000000000001", unsigned(1));
+        assert_eq!(
+            "AABBCC04000000100000000000000000FFFFFFFFFFFFFFFF",
+            unsigned(u64::MAX as u128)
+        );
+        assert_eq!(
+            "AABBCC0400000018000000000000000080000000000000000000000000000000",
+            unsigned(i128::MAX as u128 + 1)
+        );
+        assert_eq!(
+            "AABBCC04000000180000000000000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            unsigned(u128::MAX)
         );
-        let transparent_conditional_with_none = TransparentItemWithConditionallySerializedOptionalField(None);
-        assert!(to_vec(&transparent_conditional_with_none).is_err()); // Error: serializing None is not supported.
     }
 
+    #[cfg(feature = "num-bigint")]
     #[test]
-    fn test_transparent_is_only_for_newtypes_not_for_tuples() {
-        // Serde Derive will correctly ignore the None field if it is not the only field, but then in this case
-        // "Transparent:0xNNNNNNN" isn't supported because it is intended only for the case of a single inner field.
-        #[derive(Serialize)]
+    fn test_big_integer_num_bigint_round_trip() {
+        use num_bigint::{BigInt, BigUint};
+        use serde_derive::Deserialize;
+
+        use crate::de::from_slice;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
         #[serde(rename = "Transparent:0xAABBCC")]
-        struct TransparentTupleWithConditionallySerializedOptionalField(
-            i32,
-            #[serde(skip_serializing_if = "Option::is_none")] Option<i32>,
-        );
-        let transparent_tuple_conditional_with_none = TransparentTupleWithConditionallySerializedOptionalField(1, None);
-        assert!(to_vec(&transparent_tuple_conditional_with_none).is_err()); // Error: "Transparent" is not supported here.
-    }
+        struct Signed(#[serde(with = "crate::bigint")] BigInt);
 
-    #[test]
-    fn test_serde_derive_can_skip_optional_none_values_in_a_tuple() {
-        // We can use Serde Derive to skip serialization of a None value if it is not the only inner value in the type
-        // being serialized:
-        #[derive(Serialize)]
-        #[serde(rename = "Transparent:0x123456")]
-        struct SomeTaggedValue(i32);
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "Transparent:0xAABBCC")]
+        struct Unsigned(#[serde(with = "crate::bigint")] BigUint);
 
-        #[derive(Serialize)]
-        #[serde(rename = "0xAABBCC")]
-        struct TupleWithConditionallySerializedOptionalField(
-            SomeTaggedValue,
-            #[serde(skip_serializing_if = "Option::is_none")] Option<SomeTaggedValue>,
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "0x112233")]
+        struct SignedKey {
+            value: Signed,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "0x112233")]
+        struct UnsignedKey {
+            value: Unsigned,
+        }
+
+        let minus_one = SignedKey {
+            value: Signed(BigInt::from(-1)),
+        };
+        let bytes = to_vec(&minus_one).unwrap();
+        assert_eq!(
+            "1122330100000010AABBCC0400000008FFFFFFFFFFFFFFFF",
+            hex::encode_upper(&bytes)
         );
-        let tuple_conditional_with_none = TupleWithConditionallySerializedOptionalField(SomeTaggedValue(3), None);
+        assert_eq!(from_slice::<SignedKey>(&bytes).unwrap(), minus_one);
+
+        // An unsigned value with the most significant bit set is sign-extended with zero bytes to remain positive
+        let max = UnsignedKey {
+            value: Unsigned(BigUint::from(u64::MAX)),
+        };
+        let bytes = to_vec(&max).unwrap();
         assert_eq!(
-            "AABBCC010000001012345602000000040000000300000000",
-            hex::encode_upper(to_vec(&tuple_conditional_with_none).unwrap()),
-            "expected hex (left) differs to the generated hex (right)"
+            "1122330100000018AABBCC04000000100000000000000000FFFFFFFFFFFFFFFF",
+            hex::encode_upper(&bytes)
         );
+        assert_eq!(from_slice::<UnsignedKey>(&bytes).unwrap(), max);
+
+        // A negative value cannot be deserialized into a BigUint
+        assert!(from_slice::<UnsignedKey>(&to_vec(&minus_one).unwrap()).is_err());
     }
 }
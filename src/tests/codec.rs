@@ -0,0 +1,162 @@
+use crate::error::{ErrorKind, MalformedTtlvError};
+use crate::tests::fixtures;
+use crate::{Config, TtlvCodec, TypedTtlvCodec};
+
+use assert_matches::assert_matches;
+use bytes::BytesMut;
+use serde_derive::{Deserialize, Serialize};
+use tokio_util::codec::{Decoder, Encoder};
+
+#[allow(unused_imports)]
+use pretty_assertions::{assert_eq, assert_ne};
+
+#[test]
+fn test_frames_arriving_piece_by_piece() {
+    let ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let mut codec = TtlvCodec::new(&Config::default());
+    let mut src = BytesMut::new();
+
+    // Nothing can be framed until the header and then all of the value bytes it announces have arrived
+    for (i, b) in ttlv_bytes.iter().enumerate() {
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+        assert_eq!(src.len(), i);
+        src.extend_from_slice(&[*b]);
+    }
+
+    // Once complete the frame is split off, leaving nothing behind
+    assert_eq!(codec.decode(&mut src).unwrap().as_deref(), Some(ttlv_bytes.as_slice()));
+    assert!(src.is_empty());
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+}
+
+#[test]
+fn test_frames_arriving_together() {
+    let ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let mut codec = TtlvCodec::default();
+
+    // Two and a bit messages received in one read
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&ttlv_bytes);
+    src.extend_from_slice(&ttlv_bytes);
+    src.extend_from_slice(&ttlv_bytes[..10]);
+
+    assert_eq!(codec.decode(&mut src).unwrap().as_deref(), Some(ttlv_bytes.as_slice()));
+    assert_eq!(codec.decode(&mut src).unwrap().as_deref(), Some(ttlv_bytes.as_slice()));
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+    assert_eq!(src.as_ref(), &ttlv_bytes[..10]);
+
+    // The caller is told that there are unframed bytes left when the stream ends
+    assert!(codec.decode_eof(&mut src).is_err());
+}
+
+#[test]
+fn test_typed_frames() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(rename = "0xCCCCCC")]
+        b: i32,
+    }
+
+    let ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let mut codec = TypedTtlvCodec::<Root>::new(&Config::default());
+
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&ttlv_bytes[..20]);
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+    src.extend_from_slice(&ttlv_bytes[20..]);
+    src.extend_from_slice(&ttlv_bytes);
+    assert_eq!(codec.decode(&mut src).unwrap(), Some(Root { a: 1, b: 2 }));
+    assert_eq!(codec.decode(&mut src).unwrap(), Some(Root { a: 1, b: 2 }));
+    assert!(src.is_empty());
+}
+
+#[test]
+fn test_encoded_frames_can_be_decoded() {
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        a: A,
+        b: B,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct A(i32);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xCCCCCC")]
+    struct B(i32);
+
+    let ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let mut codec = TypedTtlvCodec::<Root>::default();
+
+    // Messages are appended to whatever is already in the buffer
+    let mut dst = BytesMut::new();
+    codec.encode(Root { a: A(1), b: B(2) }, &mut dst).unwrap();
+    codec.encode(&Root { a: A(1), b: B(2) }, &mut dst).unwrap();
+    assert_eq!(&dst[..ttlv_bytes.len()], ttlv_bytes.as_slice());
+    assert_eq!(&dst[ttlv_bytes.len()..], ttlv_bytes.as_slice());
+
+    assert_eq!(codec.decode(&mut dst).unwrap(), Some(Root { a: A(1), b: B(2) }));
+    assert_eq!(codec.decode(&mut dst).unwrap(), Some(Root { a: A(1), b: B(2) }));
+    assert!(dst.is_empty());
+
+    // Nothing is appended if serialization fails
+    let serializer_config = crate::ser::Config::new().with_max_bytes(ttlv_bytes.len() as u32 - 1);
+    let mut codec = TtlvCodec::default().with_serializer_config(&serializer_config);
+    let mut dst = BytesMut::new();
+    dst.extend_from_slice(&[1, 2, 3]);
+    assert!(codec.encode(Root { a: A(1), b: B(2) }, &mut dst).is_err());
+    assert_eq!(dst.as_ref(), &[1, 2, 3]);
+}
+
+#[test]
+fn test_oversized_frame_is_rejected_on_receipt_of_the_header() {
+    let ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let config = Config::default().with_max_bytes(ttlv_bytes.len() as u32 - 1);
+    let mut codec = TtlvCodec::new(&config);
+
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&ttlv_bytes[..8]);
+    let err = codec.decode(&mut src).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(0x28));
+}
+
+#[test]
+fn test_frame_size_is_limited_by_default() {
+    let mut codec = TtlvCodec::default();
+
+    // Space is reserved for the announced bytes a bit at a time rather than all at once
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&hex::decode("AAAAAA01007FFFF8").unwrap());
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+    assert!(src.capacity() < 0x800000);
+
+    // Larger frames are rejected unless a limit is configured
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&hex::decode("AAAAAA01007FFFF9").unwrap());
+    let err = codec.decode(&mut src).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(0x800001));
+
+    let mut codec = TtlvCodec::new(&Config::default().with_max_bytes(0x800001));
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&hex::decode("AAAAAA01007FFFF9").unwrap());
+    assert_eq!(codec.decode(&mut src).unwrap(), None);
+}
+
+#[test]
+fn test_non_structure_frame_is_rejected_on_receipt_of_the_header() {
+    // An Integer rather than a Structure
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&hex::decode("BBBBBB0200000004").unwrap());
+
+    let err = TtlvCodec::default().decode(&mut src).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::UnexpectedType { .. })
+    );
+}
@@ -2,12 +2,19 @@
 
 use crate::error::{ErrorKind, MalformedTtlvError, SerdeError};
 use crate::tests::fixtures;
-use crate::tests::helpers::{make_limited_reader, make_reader, no_response_size_limit, reject_if_response_larger_than};
 use crate::types::{
     ByteOffset, SerializableTtlvType, TtlvBigInteger, TtlvBoolean, TtlvByteString, TtlvDateTime, TtlvEnumeration,
-    TtlvInteger, TtlvLongInteger, TtlvTag, TtlvTextString, TtlvType,
+    TtlvInteger, TtlvInterval, TtlvItem, TtlvLongInteger, TtlvNode, TtlvTag, TtlvTextString, TtlvType, TtlvValue,
+};
+use crate::{
+    from_slice, from_slice_iter, from_slice_multi, from_slice_partial, from_slice_with_config, from_slice_with_partial,
+    validate, Config, PrettyPrinter, ReusableDeserializer, TtlvFrameIter,
 };
-use crate::{from_reader, from_slice, Config};
+
+#[cfg(feature = "sync")]
+use crate::tests::helpers::{make_limited_reader, make_reader, no_response_size_limit, reject_if_response_larger_than};
+#[cfg(feature = "sync")]
+use crate::{from_reader, from_reader_iter};
 
 use assert_matches::assert_matches;
 
@@ -39,6 +46,55 @@ fn test_kmip_10_create_destroy_use_case_create_response_deserialization() {
     }
 }
 
+#[test]
+fn test_reusable_deserializer() {
+    use fixtures::kmip_10_create_destroy_use_case::*;
+    use serde_derive::Deserialize;
+
+    let mut deserializer = ReusableDeserializer::new(Config::new().with_reject_trailing_bytes());
+
+    let test_data = ttlv_bytes();
+    for _ in 0..3 {
+        let r: ResponseMessage = deserializer.from_slice(&test_data).unwrap();
+        assert_eq!(r.items[0].operation, Operation::Create);
+    }
+
+    // The configuration is applied
+    let mut trailing = test_data.clone();
+    trailing.push(0);
+    assert!(deserializer.from_slice::<ResponseMessage>(&trailing).is_err());
+    assert!(deserializer.config().reject_trailing_bytes());
+
+    // Values remembered for enum variant matching do not leak from one message to the next
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB", default)]
+        op: Option<u32>,
+        #[serde(rename = "0xCCCCCC")]
+        payload: Payload,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xCCCCCC")]
+    enum Payload {
+        #[serde(rename = "if 0xBBBBBB==0x00000001")]
+        One(i32),
+    }
+
+    let with_op = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB05000000040000000100000000",
+        "CCCCCC02000000040000000200000000"
+    ))
+    .unwrap();
+    let without_op = hex::decode("AAAAAA0100000010CCCCCC02000000040000000200000000").unwrap();
+
+    deserializer.from_slice::<Root>(&with_op).unwrap();
+    assert!(deserializer.from_slice::<Root>(&without_op).is_err());
+    deserializer.from_slice::<Root>(&with_op).unwrap();
+}
+
 #[test]
 fn test_is_variant_applicable_if_equal() {
     use fixtures::variant_selection::*;
@@ -74,6 +130,724 @@ fn test_is_variant_applicable_if_not_matched() {
 }
 
 #[test]
+fn test_is_variant_applicable_if_not_equal() {
+    use fixtures::variant_selection::*;
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x420043")]
+    enum KeyMaterial {
+        #[serde(rename = "if 0x420042 == 0x00000007")]
+        TransparentSymmetricKey(String),
+        #[serde(rename = "if 0x420042 != 0x00000007")]
+        Other(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct SomeKey {
+        #[serde(rename = "0x420042")]
+        _key_format_type: u32,
+        #[serde(rename = "0x420043")]
+        key_material: KeyMaterial,
+    }
+
+    let res = from_slice::<SomeKey>(&some_transparent_key::ttlv_bytes()).unwrap();
+    assert_eq!(res.key_material, KeyMaterial::TransparentSymmetricKey("Blah".into()));
+
+    // Any other value selects the fallback variant, including values not otherwise mentioned anywhere
+    let res = from_slice::<SomeKey>(&some_raw_key::ttlv_bytes()).unwrap();
+    assert_eq!(res.key_material, KeyMaterial::Other(0xFF));
+    let res = from_slice::<SomeKey>(&some_unknown_key_type::ttlv_bytes()).unwrap();
+    assert_eq!(res.key_material, KeyMaterial::Other(0xF0));
+
+    // Textual values can be negated too, and "in" within the textual value is not mistaken for the "in" syntax
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x42000B")]
+    enum AttributeValue {
+        #[serde(rename = "if 0x42000A != Contact Information")]
+        NotContactInformation(i32),
+        #[serde(rename = "if 0x42000A == Contact Information")]
+        ContactInformation(String),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x420008")]
+    struct Attribute {
+        #[serde(rename = "0x42000A")]
+        _name: String,
+        #[serde(rename = "0x42000B")]
+        value: AttributeValue,
+    }
+
+    let ttlv_bytes = hex::decode(
+        concat!(
+            "420008 01 00000020",
+            "  42000A 07 00000004 4E616D65 00000000", // "Name"
+            "  42000B 02 00000004 00000001 00000000"
+        )
+        .replace(" ", ""),
+    )
+    .unwrap();
+    let res = from_slice::<Attribute>(&ttlv_bytes).unwrap();
+    assert_eq!(res.value, AttributeValue::NotContactInformation(1));
+
+    let ttlv_bytes = hex::decode(
+        concat!(
+            "420008 01 00000030",
+            "  42000A 07 00000013 436F6E7461637420496E666F726D6174696F6E 0000000000", // "Contact Information"
+            "  42000B 07 00000001 78 00000000000000"                                  // "x"
+        )
+        .replace(" ", ""),
+    )
+    .unwrap();
+    let res = from_slice::<Attribute>(&ttlv_bytes).unwrap();
+    assert_eq!(res.value, AttributeValue::ContactInformation("x".into()));
+}
+
+#[test]
+fn test_is_variant_applicable_if_all_conditions_match() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x420079")]
+    enum Payload {
+        #[serde(rename = "if 0x42005C==0x00000001 && 0x420057==0x00000002")]
+        CreateSymmetricKey(i32),
+        #[serde(rename = "if 0x42005C==0x00000001 && 0x420057 in [0x00000003, 0x00000004]")]
+        CreateKeyPair(i32),
+        #[serde(rename = "if 0x42005C==0x00000001&&0x420057!=0x00000002&&0x420057!=0x00000003")]
+        CreateOther(i32),
+        #[serde(rename = "if 0x42005C!=0x00000001")]
+        NotCreate(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x42000F")]
+    struct BatchItem {
+        #[serde(rename = "0x42005C")]
+        _operation: u32,
+        #[serde(rename = "0x420057")]
+        _object_type: u32,
+        #[serde(rename = "0x420079")]
+        payload: Payload,
+    }
+
+    let ttlv_bytes = |operation: &str, object_type: &str| {
+        let test_data = format!(
+            "42000F 01 00000030 42005C 05 00000004 {} 00000000 420057 05 00000004 {} 00000000 420079 02 00000004 \
+             0000000A 00000000",
+            operation, object_type
+        );
+        hex::decode(test_data.replace(" ", "")).unwrap()
+    };
+
+    let select = |operation, object_type| {
+        from_slice::<BatchItem>(&ttlv_bytes(operation, object_type))
+            .unwrap()
+            .payload
+    };
+    assert_eq!(select("00000001", "00000002"), Payload::CreateSymmetricKey(10));
+    assert_eq!(select("00000001", "00000003"), Payload::CreateKeyPair(10));
+    assert_eq!(select("00000001", "00000004"), Payload::CreateKeyPair(10));
+    assert_eq!(select("00000001", "00000005"), Payload::CreateOther(10));
+    assert_eq!(select("00000002", "00000002"), Payload::NotCreate(10));
+
+    // A malformed condition is rejected even if it is not the first condition
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x420079")]
+    enum BadPayload {
+        #[serde(rename = "if 0x42005C==0x00000001 && malformed")]
+        Bad(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x42000F")]
+    struct BadBatchItem {
+        #[serde(rename = "0x42005C")]
+        _operation: u32,
+        #[serde(rename = "0x420057")]
+        _object_type: u32,
+        #[serde(rename = "0x420079")]
+        _payload: BadPayload,
+    }
+
+    let err = from_slice::<BadBatchItem>(&ttlv_bytes("00000002", "00000002")).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::InvalidVariantMatcherSyntax(_))
+    );
+}
+
+#[test]
+fn test_is_variant_applicable_if_in_range() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x420043")]
+    enum KeyMaterial {
+        #[serde(rename = "if 0x420042 in 0x00000001..0x00000003")]
+        Standard(i32),
+        #[serde(rename = "if 0x420042 in 0x80000000..=0x8000FFFF")]
+        VendorExtension(i32),
+        #[serde(rename = "if 0x420042 >= 0x00000003")]
+        Other(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct SomeKey {
+        #[serde(rename = "0x420042")]
+        _key_format_type: u32,
+        #[serde(rename = "0x420043")]
+        key_material: KeyMaterial,
+    }
+
+    let select = |key_format_type: &str| {
+        let test_data = format!(
+            "123456 01 00000020 420042 05 00000004 {} 00000000 420043 02 00000004 00000001 00000000",
+            key_format_type
+        );
+        from_slice::<SomeKey>(&hex::decode(test_data.replace(" ", "")).unwrap())
+            .unwrap()
+            .key_material
+    };
+
+    assert_eq!(select("00000001"), KeyMaterial::Standard(1));
+    assert_eq!(select("00000002"), KeyMaterial::Standard(1));
+    assert_eq!(select("00000003"), KeyMaterial::Other(1)); // the end of an exclusive range is not in the range
+    assert_eq!(select("80000000"), KeyMaterial::VendorExtension(1));
+    assert_eq!(select("8000FFFF"), KeyMaterial::VendorExtension(1)); // the end of an inclusive range is
+    assert_eq!(select("80010000"), KeyMaterial::Other(1));
+}
+
+#[test]
+fn test_is_variant_applicable_else() {
+    use fixtures::variant_selection::*;
+    use serde_derive::Deserialize;
+
+    // A fallback variant of an enum whose variants are selected by matchers can have data
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x420043")]
+    enum KeyMaterial {
+        #[serde(rename = "if 0x420042 == 0x00000007")]
+        TransparentSymmetricKey(String),
+        #[serde(rename = "else")]
+        Unknown(i32),
+    }
+
+    // A fallback variant of an enum whose variants are named after enumeration values must be a unit variant
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x420042")]
+    enum KeyFormatType {
+        #[serde(rename = "0x00000001")]
+        Raw,
+        #[serde(rename = "0x00000007")]
+        TransparentSymmetricKey,
+        #[serde(rename = "else")]
+        Unknown,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct SomeKey {
+        #[serde(rename = "0x420042")]
+        key_format_type: KeyFormatType,
+        #[serde(rename = "0x420043")]
+        key_material: KeyMaterial,
+    }
+
+    let res = from_slice::<SomeKey>(&some_transparent_key::ttlv_bytes()).unwrap();
+    assert_eq!(res.key_format_type, KeyFormatType::TransparentSymmetricKey);
+    assert_eq!(res.key_material, KeyMaterial::TransparentSymmetricKey("Blah".into()));
+
+    let res = from_slice::<SomeKey>(&some_raw_key::ttlv_bytes()).unwrap();
+    assert_eq!(res.key_format_type, KeyFormatType::Raw);
+    assert_eq!(res.key_material, KeyMaterial::Unknown(0xFF));
+
+    let res = from_slice::<SomeKey>(&some_unknown_key_type::ttlv_bytes()).unwrap();
+    assert_eq!(res.key_format_type, KeyFormatType::Unknown);
+    assert_eq!(res.key_material, KeyMaterial::Unknown(0xF0));
+}
+
+#[test]
+fn test_is_variant_applicable_if_present_or_absent() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xBBBBBB")]
+    enum Payload {
+        #[serde(rename = "if present 0xAAAAAA && 0xCCCCCC==0x00000001")]
+        WithOptionalItemAndOne(i32),
+        #[serde(rename = "if present 0xAAAAAA")]
+        WithOptionalItem(i32),
+        #[serde(rename = "if absent 0xAAAAAA")]
+        WithoutOptionalItem(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct Response {
+        #[serde(rename = "0xCCCCCC")]
+        _kind: u32,
+        #[serde(rename = "0xAAAAAA")]
+        _optional: Option<String>,
+        #[serde(rename = "0xBBBBBB")]
+        payload: Payload,
+    }
+
+    let with_optional_item = |kind: &str| {
+        let test_data = format!(
+            "123456 01 00000030 CCCCCC 05 00000004 {} 00000000 AAAAAA 07 00000001 78 00000000000000 \
+             BBBBBB 02 00000004 00000001 00000000",
+            kind
+        );
+        hex::decode(test_data.replace(" ", "")).unwrap()
+    };
+    let without_optional_item = hex::decode(
+        "123456 01 00000020 CCCCCC 05 00000004 00000001 00000000 BBBBBB 02 00000004 00000001 00000000".replace(" ", ""),
+    )
+    .unwrap();
+
+    let res = from_slice::<Response>(&with_optional_item("00000001")).unwrap();
+    assert_eq!(res.payload, Payload::WithOptionalItemAndOne(1));
+    let res = from_slice::<Response>(&with_optional_item("00000002")).unwrap();
+    assert_eq!(res.payload, Payload::WithOptionalItem(1));
+    let res = from_slice::<Response>(&without_optional_item).unwrap();
+    assert_eq!(res.payload, Payload::WithoutOptionalItem(1));
+
+    // Items seen while deserializing one message are forgotten before deserializing the next
+    let mut deserializer = ReusableDeserializer::new(Config::default());
+    let res = deserializer
+        .from_slice::<Response>(&with_optional_item("00000002"))
+        .unwrap();
+    assert_eq!(res.payload, Payload::WithOptionalItem(1));
+    let res = deserializer.from_slice::<Response>(&without_optional_item).unwrap();
+    assert_eq!(res.payload, Payload::WithoutOptionalItem(1));
+}
+
+#[test]
+fn test_is_variant_applicable_tag_path() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xBBBBBB")]
+    enum Payload {
+        #[serde(rename = "if /0xAAAAAA/0xCCCCCC==0x00000002")]
+        NotAnchoredAtTheRoot(i32),
+        #[serde(rename = "if /0x123456/0xAAAAAA/0xCCCCCC==0x00000002")]
+        FirstIsTwo(i32),
+        #[serde(rename = "if 0xDDDDDD/0xCCCCCC>=0x00000002")]
+        SecondIsAtLeastTwo(i32),
+        #[serde(rename = "else")]
+        Other(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct First {
+        #[serde(rename = "0xCCCCCC")]
+        _kind: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xDDDDDD")]
+    struct Second {
+        #[serde(rename = "0xCCCCCC")]
+        _kind: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct Response {
+        #[serde(rename = "0xAAAAAA")]
+        _first: First,
+        #[serde(rename = "0xDDDDDD")]
+        _second: Second,
+        #[serde(rename = "0xBBBBBB")]
+        payload: Payload,
+    }
+
+    // The same 0xCCCCCC tag occurs in two different structures, only the path tells them apart
+    let make_ttlv = |first_kind: &str, second_kind: &str| {
+        let test_data = format!(
+            "123456 01 00000040 AAAAAA 01 00000010 CCCCCC 05 00000004 {} 00000000 \
+             DDDDDD 01 00000010 CCCCCC 05 00000004 {} 00000000 BBBBBB 02 00000004 00000001 00000000",
+            first_kind, second_kind
+        );
+        hex::decode(test_data.replace(" ", "")).unwrap()
+    };
+
+    let res = from_slice::<Response>(&make_ttlv("00000002", "00000001")).unwrap();
+    assert_eq!(res.payload, Payload::FirstIsTwo(1));
+    let res = from_slice::<Response>(&make_ttlv("00000001", "00000003")).unwrap();
+    assert_eq!(res.payload, Payload::SecondIsAtLeastTwo(1));
+    let res = from_slice::<Response>(&make_ttlv("00000002", "00000003")).unwrap();
+    assert_eq!(res.payload, Payload::FirstIsTwo(1));
+    let res = from_slice::<Response>(&make_ttlv("00000001", "00000001")).unwrap();
+    assert_eq!(res.payload, Payload::Other(1));
+}
+
+#[test]
+fn test_is_variant_applicable_if_text_starts_with_or_is_like() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xBBBBBB")]
+    enum Payload {
+        #[serde(rename = "if 0xAAAAAA like urn:*:key-?")]
+        KeyUrn(i32),
+        #[serde(rename = "if 0xAAAAAA startswith urn:")]
+        OtherUrn(i32),
+        #[serde(rename = "if 0xAAAAAA like *information*")]
+        Information(i32),
+        #[serde(rename = "else")]
+        Other(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct Response {
+        #[serde(rename = "0xAAAAAA")]
+        _identifier: String,
+        #[serde(rename = "0xBBBBBB")]
+        payload: Payload,
+    }
+
+    let make_ttlv = |identifier: &str| {
+        let padded_len = identifier.len() + (8 - identifier.len() % 8) % 8;
+        let test_data = format!(
+            "123456 01 {:08X} AAAAAA 07 {:08X} {:0<width$} BBBBBB 02 00000004 00000001 00000000",
+            8 + padded_len + 16,
+            identifier.len(),
+            hex::encode(identifier),
+            width = padded_len * 2
+        );
+        hex::decode(test_data.replace(" ", "")).unwrap()
+    };
+
+    let res = from_slice::<Response>(&make_ttlv("urn:example:key-1")).unwrap();
+    assert_eq!(res.payload, Payload::KeyUrn(1));
+    let res = from_slice::<Response>(&make_ttlv("urn:example:key-12")).unwrap();
+    assert_eq!(res.payload, Payload::OtherUrn(1));
+    let res = from_slice::<Response>(&make_ttlv("urn:")).unwrap();
+    assert_eq!(res.payload, Payload::OtherUrn(1));
+    let res = from_slice::<Response>(&make_ttlv("Contact information")).unwrap();
+    assert_eq!(res.payload, Payload::Information(1));
+    let res = from_slice::<Response>(&make_ttlv("example:urn:key-1")).unwrap();
+    assert_eq!(res.payload, Payload::Other(1));
+}
+
+#[test]
+fn test_is_variant_applicable_if_bits_set() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xBBBBBB")]
+    enum Payload {
+        #[serde(rename = "if 0xAAAAAA & 0x0000000C == 0x0000000C")]
+        EncryptAndDecrypt(i32),
+        #[serde(rename = "if 0xAAAAAA & 0x00000003 != 0")]
+        SignOrVerify(i32),
+        #[serde(rename = "if 0xAAAAAA & 0xFFFFFFFF == 0")]
+        NoUsage(i32),
+        #[serde(rename = "else")]
+        Other(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct Response {
+        #[serde(rename = "0xAAAAAA")]
+        _usage_mask: i32,
+        #[serde(rename = "0xBBBBBB")]
+        payload: Payload,
+    }
+
+    let make_ttlv = |usage_mask: &str| {
+        let test_data = format!(
+            "123456 01 00000020 AAAAAA 02 00000004 {} 00000000 BBBBBB 02 00000004 00000001 00000000",
+            usage_mask
+        );
+        hex::decode(test_data.replace(" ", "")).unwrap()
+    };
+
+    let res = from_slice::<Response>(&make_ttlv("0000000D")).unwrap();
+    assert_eq!(res.payload, Payload::EncryptAndDecrypt(1));
+    let res = from_slice::<Response>(&make_ttlv("00000005")).unwrap();
+    assert_eq!(res.payload, Payload::SignOrVerify(1));
+    let res = from_slice::<Response>(&make_ttlv("00000000")).unwrap();
+    assert_eq!(res.payload, Payload::NoUsage(1));
+    let res = from_slice::<Response>(&make_ttlv("00000004")).unwrap();
+    assert_eq!(res.payload, Payload::Other(1));
+}
+
+#[test]
+fn test_variant_selector() {
+    use crate::de::MatcherContext;
+    use serde_derive::Deserialize;
+    use std::str::FromStr;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xBBBBBB")]
+    enum Payload {
+        Even(i32),
+        Odd(i32),
+        #[serde(rename = "if 0xCCCCCC==0x00000000")]
+        Zero(i32),
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct Response {
+        #[serde(rename = "0xCCCCCC")]
+        _count: u32,
+        #[serde(rename = "0xBBBBBB")]
+        payload: Payload,
+    }
+
+    // Select a variant based on a property of the earlier value that the matcher syntax cannot express, but leave
+    // zero to the "if" matcher
+    fn select_variant(ctx: &MatcherContext) -> Option<&'static str> {
+        if ctx.enum_name() != "0xBBBBBB" {
+            return None;
+        }
+        assert_eq!(ctx.variants(), &["Even", "Odd", "if 0xCCCCCC==0x00000000"]);
+        assert_eq!(ctx.item_tag(), Some(TtlvTag::from_str("0xBBBBBB").unwrap()));
+        assert_eq!(ctx.item_type(), Some(TtlvType::Integer));
+
+        let count_tag = TtlvTag::from_str("0xCCCCCC").unwrap();
+        assert!(ctx.is_tag_seen(count_tag));
+        let path = [TtlvTag::from_str("0x123456").unwrap(), count_tag];
+        assert_eq!(ctx.path_value(&path), ctx.tag_value(count_tag));
+
+        match ctx.tag_value(count_tag) {
+            Some("0x00000000") | None => None,
+            Some(count) if u32::from_str_radix(&count[2..], 16).unwrap() % 2 == 0 => Some("Even"),
+            Some(_) => Some("Odd"),
+        }
+    }
+
+    let make_ttlv = |count: &str| {
+        let test_data = format!(
+            "123456 01 00000020 CCCCCC 05 00000004 {} 00000000 BBBBBB 02 00000004 00000001 00000000",
+            count
+        );
+        hex::decode(test_data.replace(" ", "")).unwrap()
+    };
+
+    let config = Config::default().with_variant_selector(select_variant);
+    let res = from_slice_with_config::<Response>(&make_ttlv("00000002"), &config).unwrap();
+    assert_eq!(res.payload, Payload::Even(1));
+    let res = from_slice_with_config::<Response>(&make_ttlv("00000003"), &config).unwrap();
+    assert_eq!(res.payload, Payload::Odd(1));
+    let res = from_slice_with_config::<Response>(&make_ttlv("00000000"), &config).unwrap();
+    assert_eq!(res.payload, Payload::Zero(1));
+
+    // Without the selector only the matcher can select a variant
+    assert!(from_slice::<Response>(&make_ttlv("00000002")).is_err());
+
+    // The selector can be a closure that captures its rules
+    let forced = "Odd";
+    let config =
+        Config::default().with_variant_selector(move |ctx| Some(forced).filter(|_| ctx.enum_name() == "0xBBBBBB"));
+    let res = from_slice_with_config::<Response>(&make_ttlv("00000002"), &config.clone()).unwrap();
+    assert_eq!(res.payload, Payload::Odd(1));
+}
+
+#[test]
+fn test_item_observer() {
+    use crate::de::ItemHeader;
+    use fixtures::simple::*;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    fn reject_tag_cccccc(header: &ItemHeader) -> std::result::Result<(), String> {
+        if header.tag() == TtlvTag::from_str("0xCCCCCC").unwrap() {
+            Err("forbidden tag".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let config = Config::default().with_item_observer(move |header: &ItemHeader| {
+        let item = (*header.offset(), header.tag(), header.r#type(), header.length());
+        recorder.lock().unwrap().push(item);
+        Ok(())
+    });
+    from_slice_with_config::<RootType>(&ttlv_bytes(), &config).unwrap();
+
+    let seen = seen.lock().unwrap().clone();
+    let tag = |tag: &str| TtlvTag::from_str(tag).unwrap();
+    assert_eq!(
+        seen,
+        vec![
+            (0, tag("0xAAAAAA"), TtlvType::Structure, 32),
+            (8, tag("0xBBBBBB"), TtlvType::Integer, 4),
+            (24, tag("0xCCCCCC"), TtlvType::Integer, 4),
+        ]
+    );
+
+    let config = Config::default().with_item_observer(reject_tag_cccccc);
+    let err = from_slice_with_config::<RootType>(&ttlv_bytes(), &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::RejectedByObserver(reason) if reason == "forbidden tag");
+    assert_eq!(err.location().offset(), Some(ByteOffset(28)));
+    assert_eq!(err.location().tag(), Some(tag("0xCCCCCC")));
+}
+
+#[test]
+fn test_report_ignored_items() {
+    use crate::from_slice_with_report;
+    use serde_derive::Deserialize;
+    use std::str::FromStr;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Response {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+    }
+
+    // A response with an unknown Integer item and an unknown vendor extension Structure item
+    let ttlv_bytes = hex::decode(
+        "AAAAAA 01 00000038 \
+         BBBBBB 02 00000004 00000001 00000000 \
+         CCCCCC 02 00000004 00000002 00000000 \
+         540001 01 00000010 540002 07 00000003 414243 0000000000"
+            .replace(" ", ""),
+    )
+    .unwrap();
+
+    let (res, report) = from_slice_with_report::<Response>(&ttlv_bytes, &Config::default()).unwrap();
+    assert_eq!(res.a, 1);
+
+    let ignored: Vec<_> = report
+        .ignored_items()
+        .iter()
+        .map(|item| (item.tag(), item.r#type(), item.byte_range()))
+        .collect();
+    let tag = |tag: &str| TtlvTag::from_str(tag).unwrap();
+    assert_eq!(
+        ignored,
+        vec![
+            (tag("0xCCCCCC"), TtlvType::Integer, 24..40),
+            (tag("0x540001"), TtlvType::Structure, 40..64),
+        ]
+    );
+
+    // Nothing is reported when every item is deserialized
+    let (_, report) =
+        from_slice_with_report::<fixtures::simple::RootType>(&fixtures::simple::ttlv_bytes(), &Config::default())
+            .unwrap();
+    assert!(report.ignored_items().is_empty());
+}
+
+#[test]
+fn test_keyed_map_of_structures() {
+    use serde_derive::Deserialize;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x420008")]
+    struct Attribute {
+        #[serde(rename = "0x42000A")]
+        name: String,
+        #[serde(rename = "0x42000B")]
+        value: i32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct Response {
+        #[serde(rename = "0x111111")]
+        count: i32,
+        #[serde(rename = "keyed:0x420008/0x42000A")]
+        attributes: HashMap<String, Attribute>,
+        #[serde(rename = "0x222222")]
+        trailer: i32,
+    }
+
+    let attribute = |name: &str, value: &str| {
+        format!(
+            "420008 01 00000020 42000A 07 00000004 {} 00000000 42000B 02 00000004 {} 00000000",
+            hex::encode(name),
+            value
+        )
+    };
+    let ttlv_bytes = hex::decode(
+        format!(
+            "123456 01 00000070 111111 02 00000004 00000002 00000000 {} {} 222222 02 00000004 00000003 00000000",
+            attribute("Name", "00000001"),
+            attribute("Size", "00000020")
+        )
+        .replace(" ", ""),
+    )
+    .unwrap();
+
+    let res = from_slice::<Response>(&ttlv_bytes).unwrap();
+    assert_eq!(res.count, 2);
+    assert_eq!(res.trailer, 3);
+    assert_eq!(res.attributes.len(), 2);
+    assert_eq!(res.attributes["Name"].value, 1);
+    assert_eq!(res.attributes["Size"].value, 0x20);
+    assert_eq!(res.attributes["Size"].name, "Size");
+
+    // Every structure must have a key item
+    let ttlv_bytes = hex::decode(
+        format!(
+            "123456 01 00000060 111111 02 00000004 00000001 00000000 \
+             420008 01 00000010 42000B 02 00000004 00000001 00000000 {} \
+             222222 02 00000004 00000003 00000000",
+            attribute("Name", "00000001"),
+        )
+        .replace(" ", ""),
+    )
+    .unwrap();
+    let err = from_slice::<Response>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::MissingMapKey(tag)) if *tag == TtlvTag::from_str("0x42000A").unwrap()
+    );
+
+    // Items with another tag are not taken by the map, which is then absent
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct OptionalAttributes {
+        #[serde(rename = "keyed:0xCCCCCC/0x42000A", default)]
+        attributes: HashMap<String, Attribute>,
+        #[serde(rename = "0xDDDDDD")]
+        other: Option<i32>,
+    }
+
+    let ttlv_bytes = hex::decode("123456 01 00000010 DDDDDD 02 00000004 00000001 00000000".replace(" ", "")).unwrap();
+    let res = from_slice::<OptionalAttributes>(&ttlv_bytes).unwrap();
+    assert!(res.attributes.is_empty());
+    assert_eq!(res.other, Some(1));
+
+    let ttlv_bytes = hex::decode("123456 01 00000000".replace(" ", "")).unwrap();
+    let res = from_slice::<OptionalAttributes>(&ttlv_bytes).unwrap();
+    assert!(res.attributes.is_empty());
+    assert_eq!(res.other, None);
+
+    // If no later field takes the item the map is empty and the item is ignored like any other unknown item
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0x123456")]
+    struct OnlyAttributes {
+        #[serde(rename = "keyed:0xCCCCCC/0x42000A")]
+        attributes: HashMap<String, Attribute>,
+    }
+
+    let ttlv_bytes =
+        hex::decode(format!("123456 01 00000028 {}", attribute("Name", "00000001")).replace(" ", "")).unwrap();
+    let res = from_slice::<OnlyAttributes>(&ttlv_bytes).unwrap();
+    assert!(res.attributes.is_empty());
+}
+
+#[test]
+#[cfg(feature = "sync")]
 fn test_io_error_insufficient_read_buffer_size() {
     use fixtures::simple::*;
 
@@ -94,6 +868,7 @@ fn test_io_error_insufficient_read_buffer_size() {
 }
 
 #[test]
+#[cfg(feature = "sync")]
 fn test_io_error_unexpected_eof_with_reader() {
     use fixtures::simple::*;
 
@@ -401,3 +1176,2062 @@ fn test_mismatched_serde_configuration() {
     assert_eq!(err.location().tag(), Some(root_tag)); // TODO: Shouldn't really be root_tag here as then parent_tags is wrong
     assert_eq!(err.location().r#type(), Some(TtlvType::Structure));
 }
+
+#[test]
+fn test_validate_well_formed_ttlv() {
+    let config = Config::default();
+    validate(&fixtures::simple::ttlv_bytes(), &config).unwrap();
+    validate(&fixtures::kmip_10_create_destroy_use_case::ttlv_bytes(), &config).unwrap();
+    validate(&fixtures::malformed_ttlv::ttlv_bytes_with_valid_utf8(), &config).unwrap();
+}
+
+#[test]
+fn test_validate_malformed_ttlv() {
+    use fixtures::malformed_ttlv::*;
+
+    let config = Config::default();
+
+    let err = validate(&ttlv_bytes_with_invalid_root_type(), &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidType(ty)) if *ty == invalid_root_type());
+    assert_eq!(err.location().offset(), Some(ByteOffset(3)));
+    assert_eq!(err.location().tag(), Some(root_tag()));
+
+    let err = validate(&ttlv_bytes_with_length_overflow(), &config).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::Overflow {
+            field_end: ByteOffset(40)
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(4)));
+    assert_eq!(err.location().tag(), Some(root_tag()));
+    assert_eq!(err.location().r#type(), Some(TtlvType::Structure));
+
+    let err = validate(&ttlv_bytes_with_wrong_value_length(), &config).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidLength {
+            expected: 4,
+            actual: 5,
+            r#type: TtlvType::Integer
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(16)));
+    assert_eq!(err.location().parent_tags(), &[root_tag()]);
+    assert_eq!(err.location().tag(), Some(inner_tag()));
+
+    let err = validate(&ttlv_bytes_with_wrong_boolean_value(), &config).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidValue {
+            r#type: TtlvType::Boolean
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(16)));
+
+    let err = validate(&ttlv_bytes_with_invalid_utf8(), &config).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidValue {
+            r#type: TtlvType::TextString
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(16)));
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_validate_structure_length() {
+    let config = Config::default();
+
+    // A structure whose declared length leaves bytes unaccounted for
+    let ttlv_bytes = hex::decode("AAAAAA0100000014BBBBBB020000000400000001000000000000000000000000").unwrap();
+    let err = validate(&ttlv_bytes[..28], &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedTtlv(MalformedTtlvError::Overflow { field_end: ByteOffset(28) }));
+    assert_eq!(err.location().offset(), Some(ByteOffset(24)));
+    assert_eq!(err.location().parent_tags(), &[fixtures::malformed_ttlv::root_tag()]);
+
+    // A structure whose declared length is shorter than its content
+    let ttlv_bytes = hex::decode("AAAAAA0100000008BBBBBB02000000040000000100000000").unwrap();
+    let err = validate(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedTtlv(MalformedTtlvError::Overflow { field_end: ByteOffset(16) }));
+    assert_eq!(err.location().offset(), Some(ByteOffset(12)));
+
+    // Bytes following the root structure
+    let mut ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let root_len = ttlv_bytes.len() as u64;
+    ttlv_bytes.extend(&[0u8; 8]);
+    let err = validate(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedTtlv(MalformedTtlvError::TrailingBytes));
+    assert_eq!(err.location().offset(), Some(ByteOffset(root_len)));
+
+    // Truncated input
+    let ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let err = validate(&ttlv_bytes[..ttlv_bytes.len() - 1], &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedTtlv(MalformedTtlvError::Overflow { .. }));
+}
+
+#[test]
+fn test_validate_report() {
+    // The KMIP 1.0 create response has the structure: ResponseMessage > BatchItem > ResponsePayload
+    let ttlv_bytes = fixtures::kmip_10_create_destroy_use_case::ttlv_bytes();
+    let report = validate(&ttlv_bytes, &Config::default()).unwrap();
+    assert_eq!(report.max_nesting_depth(), 3);
+    assert!(report.non_zero_padding().is_empty());
+
+    // The simple fixture is a root structure containing two Integers
+    let mut ttlv_bytes = fixtures::simple::ttlv_bytes();
+    let report = validate(&ttlv_bytes, &Config::default()).unwrap();
+    assert_eq!(report.item_count(), 3);
+    assert_eq!(report.max_nesting_depth(), 1);
+
+    // Non-zero padding is reported unless it is verified, in which case it is an error
+    ttlv_bytes[23] = 0xFF;
+    let report = validate(&ttlv_bytes, &Config::default()).unwrap();
+    assert_eq!(report.non_zero_padding(), &[ByteOffset(20)]);
+    assert!(validate(&ttlv_bytes, &Config::new().with_verify_padding()).is_err());
+}
+
+#[test]
+fn test_validate_limits() {
+    let ttlv_bytes = fixtures::kmip_10_create_destroy_use_case::ttlv_bytes();
+
+    // The KMIP 1.0 create response has the structure: ResponseMessage > BatchItem > ResponsePayload
+    validate(&ttlv_bytes, &Config::new().with_max_nesting_depth(3)).unwrap();
+    let err = validate(&ttlv_bytes, &Config::new().with_max_nesting_depth(2)).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(3));
+    assert_eq!(err.location().parent_tags().len(), 2);
+    assert_eq!(err.location().r#type(), Some(TtlvType::Structure));
+
+    validate(&ttlv_bytes, &Config::new().with_max_bytes(ttlv_bytes.len() as u32)).unwrap();
+    let err = validate(&ttlv_bytes, &Config::new().with_max_bytes(ttlv_bytes.len() as u32 - 1)).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(len) if *len == ttlv_bytes.len());
+}
+
+#[test]
+fn test_extension_type_is_skipped() {
+    use fixtures::simple::*;
+
+    // Append an item with an extension type that is not defined by the KMIP specification after the expected items of
+    // the simple fixture. Without `#[serde(deny_unknown_fields)]` it should be skipped over.
+    let mut ttlv_bytes = ttlv_bytes();
+    ttlv_bytes.splice(40..40, hex::decode("DDDDDD0B000000030102030000000000").unwrap());
+    ttlv_bytes[7] += 16;
+    from_slice::<RootType>(&ttlv_bytes).unwrap();
+
+    // Extension types are however not considered valid KMIP TTLV
+    let err = validate(&ttlv_bytes, &Config::default()).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidType(0x0B))
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(43)));
+}
+
+#[test]
+fn test_from_slice_multi() {
+    use fixtures::simple::*;
+
+    let one = ttlv_bytes();
+    let mut two = one.clone();
+    two.extend(&one);
+
+    assert_eq!(from_slice_multi::<RootType>(&[], &Config::default()).unwrap().len(), 0);
+    assert_eq!(from_slice_multi::<RootType>(&one, &Config::default()).unwrap().len(), 1);
+    assert_eq!(from_slice_multi::<RootType>(&two, &Config::default()).unwrap().len(), 2);
+
+    // Fewer than 8 trailing bytes are not enough for a TTL header and are left unconsumed
+    let mut two_and_a_bit = two.clone();
+    two_and_a_bit.extend(&one[..7]);
+    let mut iter = TtlvFrameIter::<RootType>::new(&two_and_a_bit, &Config::default());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().is_none());
+    assert_eq!(iter.position(), two.len());
+
+    // A trailing incomplete response is however an error
+    let mut two_and_a_half = two.clone();
+    two_and_a_half.extend(&one[..20]);
+    let mut iter = TtlvFrameIter::<RootType>::new(&two_and_a_half, &Config::default());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    let err = iter.next().unwrap().unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(err.location().offset(), Some(ByteOffset(two.len() as u64)));
+    assert_eq!(err.location().tag(), Some(TtlvTag::from(*b"\xAA\xAA\xAA")));
+    assert!(iter.next().is_none());
+    assert_eq!(iter.position(), two.len());
+    assert!(from_slice_multi::<RootType>(&two_and_a_half, &Config::default()).is_err());
+
+    // Responses larger than the configured limit are rejected
+    let config = Config::new().with_max_bytes(one.len() as u32 - 1);
+    let err = from_slice_multi::<RootType>(&two, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(len) if *len == one.len());
+}
+
+#[test]
+fn test_from_slice_multi_error_offset() {
+    use fixtures::simple::*;
+
+    // Break the type of the first integer item in the response
+    let mut bad = ttlv_bytes();
+    bad[11] = 0x00;
+    let single_err = from_slice::<RootType>(&bad).unwrap_err();
+    let single_offset = *single_err.location().offset().unwrap();
+
+    // The reported error offset should be relative to the start of the entire buffer, not of the second response
+    let mut multi = ttlv_bytes();
+    multi.extend(&bad);
+    let multi_err = from_slice_multi::<RootType>(&multi, &Config::default()).unwrap_err();
+    assert_matches!(
+        multi_err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidType(0x00))
+    );
+    assert_eq!(
+        multi_err.location().offset(),
+        Some(ByteOffset(single_offset + bad.len() as u64))
+    );
+}
+
+#[test]
+fn test_from_slice_iter() {
+    use fixtures::simple::*;
+
+    let one = ttlv_bytes();
+    let mut two = one.clone();
+    two.extend(&one);
+
+    let mut iter = from_slice_iter::<RootType>(&two, &Config::default());
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(iter.position(), one.len());
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(iter.position(), two.len());
+    assert!(iter.next().is_none());
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn test_from_reader_iter() {
+    use fixtures::simple::*;
+
+    let one = ttlv_bytes();
+    let mut two = one.clone();
+    two.extend(&one);
+
+    assert_eq!(from_reader_iter::<RootType, _>(&[][..], &Config::default()).count(), 0);
+
+    let mut iter = from_reader_iter::<RootType, _>(two.as_slice(), &Config::default());
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(iter.position(), one.len() as u64);
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(iter.position(), two.len() as u64);
+    assert!(iter.next().is_none());
+
+    // End-of-file part way through a TTL header is an error
+    let mut two_and_a_bit = two.clone();
+    two_and_a_bit.extend(&one[..7]);
+    let mut iter = from_reader_iter::<RootType, _>(two_and_a_bit.as_slice(), &Config::default());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    let err = iter.next().unwrap().unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(err.location().offset(), Some(ByteOffset(two_and_a_bit.len() as u64)));
+    assert!(iter.next().is_none());
+
+    // As is end-of-file part way through a value, and error offsets are relative to the start of the stream
+    let mut two_and_a_half = two.clone();
+    two_and_a_half.extend(&one[..20]);
+    let mut iter = from_reader_iter::<RootType, _>(two_and_a_half.as_slice(), &Config::default());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    let err = iter.next().unwrap().unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(
+        err.location().offset(),
+        Some(ByteOffset((two.len() + one.len()) as u64))
+    );
+    assert_eq!(err.location().tag(), Some(TtlvTag::from(*b"\xAA\xAA\xAA")));
+    assert!(iter.next().is_none());
+    assert_eq!(iter.position(), two.len() as u64);
+
+    // Responses larger than the configured limit are rejected
+    let config = Config::new().with_max_bytes(one.len() as u32 - 1);
+    let err = from_reader_iter::<RootType, _>(two.as_slice(), &config)
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(len) if *len == one.len());
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn test_read_chunk_size() {
+    use fixtures::simple::*;
+    use std::io::Read;
+
+    struct CountingReader<R> {
+        inner: R,
+        reads: usize,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    let one = ttlv_bytes();
+    let mut three = one.clone();
+    three.extend(&one);
+    three.extend(&one);
+
+    // By default the header and then the remaining 32 bytes are each read at once
+    let mut reader = CountingReader {
+        inner: one.as_slice(),
+        reads: 0,
+    };
+    from_reader::<RootType, _>(&mut reader, &Config::default()).unwrap();
+    assert_eq!(reader.reads, 2);
+
+    // With a chunk size the remaining 32 bytes are read 5 bytes at a time
+    let config = Config::default().with_read_chunk_size(5).with_read_buf();
+    let mut reader = CountingReader {
+        inner: one.as_slice(),
+        reads: 0,
+    };
+    from_reader::<RootType, _>(&mut reader, &config).unwrap();
+    assert_eq!(reader.reads, 1 + 7);
+    assert_eq!(config.read_buf().unwrap().as_slice(), one.as_slice());
+
+    // By default the iterator reads the header and then the rest of each item, and detects end-of-file with one more
+    let mut reader = CountingReader {
+        inner: three.as_slice(),
+        reads: 0,
+    };
+    assert_eq!(
+        from_reader_iter::<RootType, _>(&mut reader, &Config::default()).count(),
+        3
+    );
+    assert_eq!(reader.reads, 3 * 2 + 1);
+
+    // With a chunk size the iterator reads ahead, here reading three 40 byte items with two 64 byte reads
+    let config = Config::default().with_read_chunk_size(64);
+    let mut reader = CountingReader {
+        inner: three.as_slice(),
+        reads: 0,
+    };
+    let mut iter = from_reader_iter::<RootType, _>(&mut reader, &config);
+    for i in 1..=3 {
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(iter.position(), (i * one.len()) as u64);
+    }
+    assert!(iter.next().is_none());
+    drop(iter);
+    assert_eq!(reader.reads, 2 + 1);
+
+    // Items that span several chunks are reassembled
+    let iter = from_reader_iter::<RootType, _>(three.as_slice(), &Config::default().with_read_chunk_size(1));
+    assert_eq!(iter.filter(|res| res.is_ok()).count(), 3);
+
+    // End-of-file part way through an item is still an error located relative to the start of the stream
+    let truncated = &three[..three.len() - 1];
+    let mut iter = from_reader_iter::<RootType, _>(truncated, &Config::default().with_read_chunk_size(16));
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    let err = iter.next().unwrap().unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(err.location().offset(), Some(ByteOffset(truncated.len() as u64)));
+    assert!(iter.next().is_none());
+
+    // As is an item larger than the configured limit
+    let config = Config::default()
+        .with_read_chunk_size(64)
+        .with_max_bytes(one.len() as u32 - 1);
+    let err = from_reader_iter::<RootType, _>(three.as_slice(), &config)
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ResponseSizeExceedsLimit(len) if *len == one.len());
+}
+
+#[cfg(feature = "async-with-tokio")]
+#[test]
+fn test_from_tokio_reader() {
+    use crate::de::asynch::from_tokio_reader;
+    use fixtures::simple::*;
+
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        let bytes = ttlv_bytes();
+        let config = Config::default().with_read_buf();
+        assert!(from_tokio_reader::<RootType, _>(bytes.as_slice(), &config).await.is_ok());
+        assert_eq!(config.read_buf().unwrap().as_slice(), bytes.as_slice());
+
+        let config = Config::default().with_max_bytes(bytes.len() as u32 - 1);
+        let res = from_tokio_reader::<RootType, _>(bytes.as_slice(), &config).await;
+        assert_matches!(res.unwrap_err().kind(), ErrorKind::ResponseSizeExceedsLimit(len) if *len == bytes.len());
+
+        let res = from_tokio_reader::<RootType, _>(&bytes[..bytes.len() - 1], &Config::default()).await;
+        assert_matches!(
+            res.unwrap_err().kind(),
+            ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof
+        );
+    });
+}
+
+#[cfg(feature = "async-with-futures")]
+#[test]
+fn test_from_futures_reader() {
+    use crate::de::asynch::from_futures_reader;
+    use fixtures::simple::*;
+    use futures_util::FutureExt;
+
+    // Reading from a slice never has to wait so no executor is needed to drive the futures to completion
+    let bytes = ttlv_bytes();
+    let config = Config::default().with_read_buf();
+    let res = from_futures_reader::<RootType, _>(bytes.as_slice(), &config)
+        .now_or_never()
+        .unwrap();
+    assert!(res.is_ok());
+    assert_eq!(config.read_buf().unwrap().as_slice(), bytes.as_slice());
+
+    let config = Config::default().with_max_bytes(bytes.len() as u32 - 1);
+    let res = from_futures_reader::<RootType, _>(bytes.as_slice(), &config)
+        .now_or_never()
+        .unwrap();
+    assert_matches!(res.unwrap_err().kind(), ErrorKind::ResponseSizeExceedsLimit(len) if *len == bytes.len());
+
+    let truncated = &bytes[..bytes.len() - 1];
+    let res = from_futures_reader::<RootType, _>(truncated, &Config::default())
+        .now_or_never()
+        .unwrap();
+    assert_matches!(res.unwrap_err().kind(), ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_reject_trailing_bytes() {
+    use fixtures::simple::*;
+
+    let mut ttlv_bytes = ttlv_bytes();
+    let root_len = ttlv_bytes.len() as u64;
+    ttlv_bytes.extend(&[0x00, 0x01]);
+
+    // Trailing bytes are ignored by default
+    from_slice::<RootType>(&ttlv_bytes).unwrap();
+
+    let config = Config::new().with_reject_trailing_bytes();
+    let err = from_slice_with_config::<RootType>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedTtlv(MalformedTtlvError::TrailingBytes));
+    assert_eq!(err.location().offset(), Some(ByteOffset(root_len)));
+
+    from_slice_with_config::<RootType>(&ttlv_bytes[..root_len as usize], &config).unwrap();
+}
+
+#[test]
+fn test_verify_padding() {
+    use fixtures::simple::*;
+
+    // Set a padding byte of the first integer item in the response
+    let mut ttlv_bytes = ttlv_bytes();
+    ttlv_bytes[20] = 0x01;
+
+    // Non-zero padding is ignored by default
+    from_slice::<RootType>(&ttlv_bytes).unwrap();
+    validate(&ttlv_bytes, &Config::default()).unwrap();
+
+    let config = Config::new().with_verify_padding();
+    let err = from_slice_with_config::<RootType>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidPadding {
+            r#type: TtlvType::Integer
+        })
+    );
+    assert_eq!(err.location().tag(), Some(TtlvTag::from(*b"\xBB\xBB\xBB")));
+
+    let err = validate(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidPadding {
+            r#type: TtlvType::Integer
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(20)));
+}
+
+#[test]
+fn test_from_slice_partial() {
+    use fixtures::simple::*;
+
+    let one = ttlv_bytes();
+    let mut buf = one.clone();
+    buf.extend(&[0xAA, 0xAA]);
+
+    // Trailing bytes are ignored even when configured to reject them as they may belong to the next message
+    let config = Config::new().with_reject_trailing_bytes();
+    let (_, consumed) = from_slice_partial::<RootType>(&buf, &config).unwrap();
+    assert_eq!(consumed, one.len());
+
+    let (_, consumed) = from_slice_partial::<RootType>(&one, &Config::default()).unwrap();
+    assert_eq!(consumed, one.len());
+}
+
+#[test]
+fn test_from_slice_with_partial() {
+    use fixtures::simple::*;
+
+    // Change the type of the second integer item in the response to a Long Integer
+    let mut ttlv_bytes = ttlv_bytes();
+    ttlv_bytes[27] = 0x03;
+
+    let err = from_slice_with_partial::<RootType>(&ttlv_bytes, &Config::default()).unwrap_err();
+    assert_matches!(
+        err.error().kind(),
+        ErrorKind::SerdeError(SerdeError::UnexpectedType { .. })
+    );
+    assert_eq!(err.error().location().tag(), Some(TtlvTag::from(*b"\xCC\xCC\xCC")));
+
+    // The complete items before the one that failed to deserialize are available for diagnostic purposes
+    assert_eq!(
+        err.decoded(),
+        &[TtlvNode::new(TtlvTag::from(*b"\xBB\xBB\xBB"), TtlvValue::Integer(1))]
+    );
+    assert_eq!(err.enclosing(), &[TtlvTag::from(*b"\xAA\xAA\xAA")]);
+}
+
+#[test]
+fn test_tag_name_resolver() {
+    use fixtures::simple::*;
+
+    const TAG_NAMES: [(u32, &str); 3] = [(0xAAAAAA, "Root"), (0xBBBBBB, "FieldA"), (0xCCCCCC, "FieldB")];
+
+    fn resolve_tag_name(tag: TtlvTag) -> Option<&'static str> {
+        TAG_NAMES.iter().find(|(v, _)| *v == *tag).map(|(_, name)| *name)
+    }
+
+    // Change the type of the first integer item in the response to a Long Integer
+    let mut ttlv_bytes = ttlv_bytes();
+    ttlv_bytes[11] = 0x03;
+
+    let err = from_slice::<RootType>(&ttlv_bytes).unwrap_err();
+    assert_eq!(err.location().tag_name(), None);
+    assert!(err.to_string().contains("tag: 0xBBBBBB, "));
+
+    let config = Config::new().with_tag_name_resolver(resolve_tag_name);
+    let err = from_slice_with_config::<RootType>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::UnexpectedType { .. }));
+    assert_eq!(err.location().tag(), Some(TtlvTag::from(*b"\xBB\xBB\xBB")));
+    assert_eq!(err.location().tag_name(), Some("FieldA"));
+    assert_eq!(err.location().name_of(err.location().parent_tags()[0]), Some("Root"));
+    assert!(err
+        .to_string()
+        .contains("parent tags: Root (0xAAAAAA), tag: FieldA (0xBBBBBB), "));
+}
+
+#[test]
+fn test_max_nesting_depth() {
+    use fixtures::kmip_10_create_destroy_use_case::*;
+
+    let ttlv_bytes = ttlv_bytes();
+
+    // The KMIP 1.0 create response has the structure: ResponseMessage > BatchItem > ResponsePayload
+    let config = Config::new().with_max_nesting_depth(3);
+    from_slice_with_config::<ResponseMessage>(&ttlv_bytes, &config).unwrap();
+
+    let config = Config::new().with_max_nesting_depth(2);
+    let err = from_slice_with_config::<ResponseMessage>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(3));
+    assert_eq!(err.location().parent_tags().len(), 2);
+    assert_eq!(err.location().r#type(), Some(TtlvType::Structure));
+}
+
+#[test]
+fn test_deeply_nested_input_does_not_overflow_the_stack() {
+    // Nest many empty TTLV Structures inside each other, innermost first
+    let depth = 100_000;
+    let mut ttlv_bytes = Vec::with_capacity(depth * 8);
+    for i in (0..depth).rev() {
+        ttlv_bytes.extend_from_slice(&[0xAA, 0xAA, 0xAA, 0x01]);
+        ttlv_bytes.extend_from_slice(&((i * 8) as u32).to_be_bytes());
+    }
+
+    // Without a configured limit deserializing into Rust types is still limited
+    let err = from_slice::<serde_json::Value>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(129));
+
+    let config = Config::new().with_max_nesting_depth(10);
+    let err = from_slice_with_config::<serde_json::Value>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(11));
+
+    // Nor can the limit be raised beyond what the stack can bear, even on a thread with a small stack
+    let ttlv_bytes_clone = ttlv_bytes.clone();
+    let err = std::thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(move || {
+            let config = Config::new().with_max_nesting_depth(1_000_000);
+            from_slice_with_config::<serde_json::Value>(&ttlv_bytes_clone, &config).unwrap_err()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(129));
+
+    // Skipping, validating and pretty printing do not recurse and so are not limited
+    from_slice::<serde::de::IgnoredAny>(&ttlv_bytes).unwrap();
+    validate(&ttlv_bytes, &Config::new()).unwrap();
+    assert_eq!(PrettyPrinter::new().to_diag_string(&ttlv_bytes).len(), depth * 8);
+}
+
+#[test]
+fn test_deeply_nested_enum_variants_do_not_overflow_the_stack() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xAAAAAA")]
+    enum Node {
+        #[serde(rename = "if type==Integer")]
+        Leaf(i32),
+        #[serde(rename = "else")]
+        Inner(Box<Node>, i32),
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0x123456")]
+    struct Root {
+        #[serde(rename = "0xAAAAAA")]
+        node: Node,
+    }
+
+    // Nest Inner variants inside each other around a Leaf, innermost first, inside a Root
+    let make_ttlv = |depth: usize| {
+        let mut ttlv_bytes = hex::decode("AAAAAA02000000040000000100000000").unwrap();
+        for _ in 0..depth {
+            let len = (ttlv_bytes.len() + 16) as u32;
+            let mut outer = hex::decode("AAAAAA01").unwrap();
+            outer.extend_from_slice(&len.to_be_bytes());
+            outer.extend_from_slice(&ttlv_bytes);
+            outer.extend_from_slice(&hex::decode("BBBBBB02000000040000000200000000").unwrap());
+            ttlv_bytes = outer;
+        }
+        let mut root = hex::decode("12345601").unwrap();
+        root.extend_from_slice(&(ttlv_bytes.len() as u32).to_be_bytes());
+        root.extend_from_slice(&ttlv_bytes);
+        root
+    };
+
+    let root = from_slice::<Root>(&make_ttlv(2)).unwrap();
+    assert_eq!(
+        root.node,
+        Node::Inner(Box::new(Node::Inner(Box::new(Node::Leaf(1)), 2)), 2)
+    );
+
+    // The items of a tuple variant are the members of a TTLV Structure and count towards the configured limit
+    let config = Config::new().with_max_nesting_depth(4);
+    from_slice_with_config::<Root>(&make_ttlv(3), &config).unwrap();
+    let err = from_slice_with_config::<Root>(&make_ttlv(4), &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(5));
+
+    // And are limited even when no limit is configured
+    let err = from_slice::<Root>(&make_ttlv(5000)).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::NestingDepthExceedsLimit(129));
+}
+
+#[test]
+fn test_ignored_structure() {
+    use fixtures::simple::*;
+
+    // Append a structure that has no corresponding field in the Rust struct, it should be skipped over
+    let mut ttlv_bytes = ttlv_bytes();
+    ttlv_bytes.extend(hex::decode("DDDDDD0100000010EEEEEE02000000040000000300000000").unwrap());
+    ttlv_bytes[7] += 24;
+    from_slice::<RootType>(&ttlv_bytes).unwrap();
+}
+
+#[test]
+fn test_missing_optional_field_in_nested_structure() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        inner: Inner,
+        #[serde(rename = "0xCCCCCC")]
+        b: i32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xBBBBBB")]
+    struct Inner {
+        #[serde(rename = "0xDDDDDD")]
+        missing: Option<i32>,
+        #[serde(rename = "0xEEEEEE")]
+        present: i32,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000028",
+        "BBBBBB0100000010",
+        "EEEEEE02000000040000000100000000",
+        "CCCCCC02000000040000000200000000"
+    ))
+    .unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.inner.missing, None);
+    assert_eq!(r.inner.present, 1);
+    assert_eq!(r.b, 2);
+}
+
+#[test]
+fn test_deserialize_any() {
+    use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Int(i64),
+        Bool(bool),
+        Text(String),
+        Bytes(Vec<u8>),
+        Enum(u32),
+        Struct(Vec<(String, Value)>),
+    }
+
+    struct ValueVisitor;
+
+    impl<'de> Visitor<'de> for ValueVisitor {
+        type Value = Value;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("any TTLV item")
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+            Ok(Value::Int(v))
+        }
+
+        fn visit_u32<E>(self, v: u32) -> Result<Value, E> {
+            Ok(Value::Enum(v))
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+            Ok(Value::Bool(v))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+            Ok(Value::Text(v.to_string()))
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Value, E> {
+            Ok(Value::Text(v))
+        }
+
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Value, E> {
+            Ok(Value::Bytes(v))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+            let mut items = Vec::new();
+            while let Some(item) = map.next_entry()? {
+                items.push(item);
+            }
+            Ok(Value::Struct(items))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Value {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(ValueVisitor)
+        }
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000058",
+        "BBBBBB02000000040000000100000000",
+        "DDDDDD0100000030",
+        "EEEEEE07000000026869000000000000",
+        "FFFFFF05000000040000000300000000",
+        "11111106000000080000000000000001",
+        "CCCCCC08000000020102000000000000",
+    ))
+    .unwrap();
+
+    let expected_inner = Value::Struct(vec![
+        ("0xEEEEEE".to_string(), Value::Text("hi".to_string())),
+        ("0xFFFFFF".to_string(), Value::Enum(3)),
+        ("0x111111".to_string(), Value::Bool(true)),
+    ]);
+
+    let r: Value = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(
+        r,
+        Value::Struct(vec![
+            ("0xBBBBBB".to_string(), Value::Int(1)),
+            ("0xDDDDDD".to_string(), expected_inner),
+            ("0xCCCCCC".to_string(), Value::Bytes(vec![0x01, 0x02])),
+        ])
+    );
+
+    // Schema-less deserialization can also be used for just part of a known structure
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(rename = "0xDDDDDD")]
+        inner: Value,
+        #[serde(rename = "0xCCCCCC")]
+        c: Value,
+    }
+
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, 1);
+    assert_matches!(r.inner, Value::Struct(items) if items.len() == 3);
+    assert_eq!(r.c, Value::Bytes(vec![0x01, 0x02]));
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes() {
+    use fixtures::simple::*;
+    use serde_derive::Serialize;
+
+    #[derive(Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root(A, B);
+
+    #[derive(Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct A(i32);
+
+    #[derive(Serialize)]
+    #[serde(rename = "Transparent:0xCCCCCC")]
+    struct B(i32);
+
+    let ttlv_bytes = ttlv_bytes();
+    crate::from_bytes::<RootType>(bytes::Bytes::from(ttlv_bytes.clone()), &Config::default()).unwrap();
+
+    // Serialized bytes are appended to any bytes already in the buffer
+    let mut buf = bytes::BytesMut::from(&b"prefix"[..]);
+    crate::to_bytes_mut(&Root(A(1), B(2)), &mut buf).unwrap();
+    assert_eq!(&buf[..6], b"prefix");
+    assert_eq!(&buf[6..], ttlv_bytes.as_slice());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_buf() {
+    use bytes::Buf;
+    use fixtures::simple::*;
+
+    let ttlv_bytes = ttlv_bytes();
+
+    // Contiguous
+    crate::from_buf::<RootType, _>(&ttlv_bytes[..], &Config::default()).unwrap();
+
+    // Non-contiguous, split part way through an item header
+    let (head, tail) = ttlv_bytes.split_at(13);
+    let chained = bytes::Bytes::copy_from_slice(head).chain(bytes::Bytes::copy_from_slice(tail));
+    crate::from_buf::<RootType, _>(chained, &Config::default()).unwrap();
+
+    // Non-contiguous segments are gathered into the read buffer of the Config if it has one
+    let config = Config::default().with_read_buf();
+    let chained = (&ttlv_bytes[..7]).chain(&ttlv_bytes[7..20]).chain(&ttlv_bytes[20..]);
+    crate::from_buf::<RootType, _>(chained, &config).unwrap();
+    assert_eq!(config.read_buf().unwrap().as_slice(), ttlv_bytes.as_slice());
+
+    // Trailing bytes are still detected
+    let config = Config::default().with_reject_trailing_bytes();
+    let chained = (&ttlv_bytes[..10]).chain(&ttlv_bytes[10..]).chain(&[0u8][..]);
+    assert!(crate::from_buf::<RootType, _>(chained, &config).is_err());
+}
+
+#[test]
+fn test_borrowed_str() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root<'a> {
+        #[serde(rename = "0xBBBBBB")]
+        a: &'a str,
+        #[serde(rename = "0xCCCCCC")]
+        b: i32,
+    }
+
+    let mut ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB07000000026869000000000000",
+        "CCCCCC02000000040000000200000000"
+    ))
+    .unwrap();
+
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, "hi");
+    assert_eq!(r.b, 2);
+
+    // The borrowed string points into the input buffer
+    let input_range = ttlv_bytes.as_ptr_range();
+    assert!(input_range.contains(&r.a.as_ptr()));
+
+    // Invalid UTF-8 is rejected
+    ttlv_bytes[16] = 0xFF;
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidValue {
+            r#type: TtlvType::TextString
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(12)));
+}
+
+#[test]
+fn test_borrowed_bytes() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root<'a> {
+        #[serde(rename = "0xBBBBBB")]
+        a: &'a [u8],
+        #[serde(rename = "0xCCCCCC", borrow)]
+        b: &'a serde_bytes::Bytes,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB08000000030102030000000000",
+        "CCCCCC08000000080102030405060708"
+    ))
+    .unwrap();
+
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, &[1, 2, 3]);
+    assert_eq!(r.b.as_ref(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    // The borrowed bytes point into the input buffer
+    let input_range = ttlv_bytes.as_ptr_range();
+    assert!(input_range.contains(&r.a.as_ptr()));
+    assert!(input_range.contains(&r.b.as_ptr()));
+
+    // A length that extends beyond the end of the input is rejected
+    let err = from_slice::<Root>(&ttlv_bytes[..ttlv_bytes.len() - 1]).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_catch_all_items() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(default, rename = "*")]
+        extra: Vec<TtlvItem>,
+    }
+
+    let unknown_items_hex = concat!(
+        "CCCCCC07000000026869000000000000",
+        "DDDDDD0100000010",
+        "EEEEEE06000000080000000000000001",
+        "FFFFFF02000000040000000300000000"
+    );
+    let ttlv_bytes = hex::decode(format!(
+        "AAAAAA0100000048BBBBBB02000000040000000100000000{}",
+        unknown_items_hex
+    ))
+    .unwrap();
+
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, 1);
+    assert_eq!(
+        r.extra,
+        vec![
+            TtlvItem::new(TtlvTag::from([0xCC, 0xCC, 0xCC]), TtlvType::TextString, b"hi".to_vec()),
+            TtlvItem::new(
+                TtlvTag::from([0xDD, 0xDD, 0xDD]),
+                TtlvType::Structure,
+                hex::decode("EEEEEE06000000080000000000000001").unwrap()
+            ),
+            TtlvItem::new(TtlvTag::from([0xFF, 0xFF, 0xFF]), TtlvType::Integer, vec![0, 0, 0, 3]),
+        ]
+    );
+
+    // The collected items can be written back out unchanged
+    let mut written = Vec::new();
+    for item in &r.extra {
+        item.write(&mut written).unwrap();
+    }
+    assert_eq!(hex::encode_upper(written), unknown_items_hex);
+
+    // Without unknown items the catch-all field is empty
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB02000000040000000100000000").unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, 1);
+    assert!(r.extra.is_empty());
+
+    // A single item can also be captured on its own
+    let item: TtlvItem = from_slice(&hex::decode("CCCCCC07000000026869000000000000").unwrap()).unwrap();
+    assert_eq!(item.r#type, TtlvType::TextString);
+    assert_eq!(item.value, b"hi");
+}
+
+#[test]
+fn test_opaque_extension_types() {
+    use crate::from_slice_with_report;
+    use serde_derive::Deserialize;
+    use std::convert::TryFrom;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(rename = "0xCCCCCC")]
+        b: Option<String>,
+        #[serde(default, rename = "*")]
+        extra: Vec<TtlvItem>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct RootWithoutCatchAll {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(rename = "0xCCCCCC")]
+        b: Option<String>,
+        #[serde(rename = "0xDDDDDD")]
+        c: i32,
+    }
+
+    // A known tag whose item has a type not defined by the KMIP specification, e.g. from a newer KMIP version
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000030",
+        "BBBBBB02000000040000000100000000",
+        "CCCCCC0B000000030102030000000000",
+        "DDDDDD02000000040000000300000000"
+    ))
+    .unwrap();
+
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::UnexpectedType {
+            expected: TtlvType::TextString,
+            actual: TtlvType::Extension(type_byte)
+        }) if **type_byte == 0x0B
+    );
+    assert!(validate(&ttlv_bytes, &Config::new()).is_err());
+
+    let config = Config::new().with_opaque_extension_types();
+    validate(&ttlv_bytes, &config).unwrap();
+
+    // The catch-all field takes the extension item and those that follow it
+    let r: Root = from_slice_with_config(&ttlv_bytes, &config).unwrap();
+    assert_eq!(r.a, 1);
+    assert_eq!(r.b, None);
+    assert_eq!(
+        r.extra,
+        vec![
+            TtlvItem::new(
+                TtlvTag::from([0xCC, 0xCC, 0xCC]),
+                TtlvType::try_from(0x0B).unwrap(),
+                vec![1, 2, 3]
+            ),
+            TtlvItem::new(TtlvTag::from([0xDD, 0xDD, 0xDD]), TtlvType::Integer, vec![0, 0, 0, 3]),
+        ]
+    );
+
+    // Without a catch-all field the extension item is skipped
+    let (r, report) = from_slice_with_report::<RootWithoutCatchAll>(&ttlv_bytes, &config).unwrap();
+    assert_eq!(r.a, 1);
+    assert_eq!(r.b, None);
+    assert_eq!(r.c, 3);
+    assert_eq!(report.ignored_items().len(), 1);
+    assert_eq!(report.ignored_items()[0].r#type(), TtlvType::try_from(0x0B).unwrap());
+    assert_eq!(report.ignored_items()[0].byte_range(), 24..40);
+}
+
+#[test]
+fn test_lossy_text_strings() {
+    use crate::from_slice_with_report;
+    use serde_derive::Deserialize;
+    use std::borrow::Cow;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root<'a> {
+        #[serde(rename = "0xBBBBBB")]
+        a: String,
+        #[serde(rename = "0xCCCCCC", borrow)]
+        b: Cow<'a, str>,
+    }
+
+    // Two Text Strings containing the Latin-1 encoding of "café", which is not valid UTF-8
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB0700000004636166E900000000",
+        "CCCCCC0700000004636166E900000000"
+    ))
+    .unwrap();
+
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidValue { r#type: TtlvType::TextString })
+    );
+    assert!(validate(&ttlv_bytes, &Config::new()).is_err());
+
+    let config = Config::new().with_lossy_text_strings();
+    validate(&ttlv_bytes, &config).unwrap();
+
+    let (r, report) = from_slice_with_report::<Root>(&ttlv_bytes, &config).unwrap();
+    assert_eq!(r.a, "caf\u{FFFD}");
+    assert_eq!(r.b, "caf\u{FFFD}");
+    assert_eq!(report.lossy_text_strings().len(), 2);
+    assert_eq!(report.lossy_text_strings()[0].tag(), TtlvTag::from([0xBB, 0xBB, 0xBB]));
+    assert_eq!(report.lossy_text_strings()[0].byte_range(), 8..24);
+    assert_eq!(report.lossy_text_strings()[1].byte_range(), 24..40);
+}
+
+#[test]
+fn test_duplicate_tag_policy() {
+    use crate::{from_slice_with_report, DuplicateTagPolicy};
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(rename = "0xCCCCCC")]
+        b: Option<i32>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct RootWithVec {
+        #[serde(rename = "0xBBBBBB")]
+        a: Vec<i32>,
+        #[serde(rename = "0xCCCCCC")]
+        b: Option<i32>,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000040",
+        "BBBBBB02000000040000000100000000",
+        "BBBBBB02000000040000000200000000",
+        "CCCCCC02000000040000000300000000",
+        "BBBBBB02000000040000000400000000"
+    ))
+    .unwrap();
+
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::DuplicateTag(tag)) if *tag == TtlvTag::from([0xBB, 0xBB, 0xBB])
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(28)));
+
+    let config = Config::new().with_duplicate_tag_policy(DuplicateTagPolicy::FirstWins);
+    let (r, report) = from_slice_with_report::<Root>(&ttlv_bytes, &config).unwrap();
+    assert_eq!(r.a, 1);
+    assert_eq!(r.b, Some(3));
+    let ignored: Vec<_> = report.ignored_items().iter().map(|item| item.byte_range()).collect();
+    assert_eq!(ignored, vec![24..40, 56..72]);
+
+    let config = Config::new().with_duplicate_tag_policy(DuplicateTagPolicy::LastWins);
+    let (r, report) = from_slice_with_report::<Root>(&ttlv_bytes, &config).unwrap();
+    assert_eq!(r.a, 4);
+    assert_eq!(r.b, Some(3));
+    let ignored: Vec<_> = report.ignored_items().iter().map(|item| item.byte_range()).collect();
+    assert_eq!(ignored, vec![8..24, 24..40]);
+
+    // Consecutive items are the elements of a sequence field, only a later separate item is a duplicate
+    let err = from_slice::<RootWithVec>(&ttlv_bytes).unwrap_err();
+    assert_eq!(err.location().offset(), Some(ByteOffset(60)));
+
+    for policy in [DuplicateTagPolicy::FirstWins, DuplicateTagPolicy::LastWins] {
+        let config = Config::new().with_duplicate_tag_policy(policy);
+        let (r, report) = from_slice_with_report::<RootWithVec>(&ttlv_bytes, &config).unwrap();
+        assert_eq!(r.a, vec![1, 2]);
+        assert_eq!(r.b, Some(3));
+        assert_eq!(report.ignored_items().len(), 1);
+        assert_eq!(report.ignored_items()[0].byte_range(), 56..72);
+    }
+}
+
+#[test]
+fn test_flatten() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xEEEEEE")]
+    struct Header {
+        #[serde(rename = "0xBBBBBB")]
+        id: i32,
+        #[serde(default, rename = "0xCCCCCC")]
+        name: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Message {
+        #[serde(flatten)]
+        header: Header,
+        #[serde(rename = "0xDDDDDD")]
+        flag: bool,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000030",
+        "BBBBBB02000000040000000100000000",
+        "CCCCCC07000000026869000000000000",
+        "DDDDDD06000000080000000000000001"
+    ))
+    .unwrap();
+
+    let r: Message = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.header.id, 1);
+    assert_eq!(r.header.name.as_deref(), Some("hi"));
+    assert!(r.flag);
+
+    // Optional fields of the flattened struct may be absent
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB02000000040000000200000000",
+        "DDDDDD06000000080000000000000000"
+    ))
+    .unwrap();
+
+    let r: Message = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.header.id, 2);
+    assert_eq!(r.header.name, None);
+    assert!(!r.flag);
+
+    // Required fields of the flattened struct may not
+    let ttlv_bytes = hex::decode("AAAAAA0100000010DDDDDD06000000080000000000000000").unwrap();
+    let err = from_slice::<Message>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(msg)) if msg.contains("0xBBBBBB"));
+}
+
+#[test]
+fn test_default_for_absent_items() {
+    use serde_derive::Deserialize;
+
+    fn default_count() -> i32 {
+        7
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(default = "default_count", rename = "0xBBBBBB")]
+        count: i32,
+        #[serde(rename = "0xCCCCCC")]
+        flag: bool,
+        #[serde(default, rename = "0xDDDDDD")]
+        names: Vec<String>,
+        #[serde(default, rename = "0xEEEEEE")]
+        id: i64,
+    }
+
+    // Only the required item is present
+    let ttlv_bytes = hex::decode("AAAAAA0100000010CCCCCC06000000080000000000000001").unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.count, 7);
+    assert!(r.flag);
+    assert!(r.names.is_empty());
+    assert_eq!(r.id, 0);
+
+    // Absent items in between present items
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000030",
+        "BBBBBB02000000040000000100000000",
+        "CCCCCC06000000080000000000000000",
+        "EEEEEE03000000080000000000000002"
+    ))
+    .unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.count, 1);
+    assert!(!r.flag);
+    assert!(r.names.is_empty());
+    assert_eq!(r.id, 2);
+
+    // A required item cannot be absent
+    let ttlv_bytes = hex::decode("AAAAAA0100000010EEEEEE03000000080000000000000002").unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(msg)) if msg.contains("0xCCCCCC"));
+}
+
+#[test]
+fn test_tuple_structs() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xCCCCCC")]
+    struct Version(i32, i32);
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root(String, Version, Option<bool>);
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000050",
+        "BBBBBB07000000026869000000000000",
+        "CCCCCC0100000020",
+        "DDDDDD02000000040000000100000000",
+        "EEEEEE02000000040000000200000000",
+        "FFFFFF06000000080000000000000001",
+        "FFFFFF06000000080000000000000000"
+    ))
+    .unwrap();
+
+    // Tuple structs are not supported by default
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::UnsupportedRustType("tuple struct"))
+    );
+
+    // Items are deserialized in order and surplus items are ignored
+    let config = Config::new().with_tuple_structs();
+    let r: Root = from_slice_with_config(&ttlv_bytes, &config).unwrap();
+    assert_eq!(r.0, "hi");
+    assert_eq!((r.1).0, 1);
+    assert_eq!((r.1).1, 2);
+    assert_eq!(r.2, Some(true));
+
+    // The tag of a tuple struct is checked
+    let mut wrong_tag_bytes = ttlv_bytes.clone();
+    wrong_tag_bytes[26] = 0xCD;
+    let err = from_slice_with_config::<Root>(&wrong_tag_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::UnexpectedTag { .. }));
+
+    // Too few items is an error
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB07000000026869000000000000").unwrap();
+    let err = from_slice_with_config::<Root>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(_)));
+}
+
+#[test]
+fn test_unit_structs() {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xBBBBBB")]
+    struct Marker;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xCCCCCC")]
+    struct Empty {}
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        marker: Marker,
+        #[serde(rename = "0xCCCCCC")]
+        empty: Empty,
+    }
+
+    let ttlv_bytes = crate::to_vec(&Marker).unwrap();
+    assert_eq!(from_slice::<Marker>(&ttlv_bytes).unwrap(), Marker);
+
+    let ttlv_bytes = crate::to_vec(&Empty {}).unwrap();
+    assert_eq!(from_slice::<Empty>(&ttlv_bytes).unwrap(), Empty {});
+
+    // Unit structs can be nested and any content is ignored
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB0100000010",
+        "DDDDDD02000000040000000100000000",
+        "CCCCCC0100000000"
+    ))
+    .unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.marker, Marker);
+    assert_eq!(r.empty, Empty {});
+
+    // The tag is checked
+    let err = from_slice::<Marker>(&hex::decode("CCCCCC0100000000").unwrap()).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::UnexpectedTag { .. }));
+}
+
+#[test]
+fn test_maps_keyed_by_tag() {
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
+    use std::convert::TryFrom;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Attributes(BTreeMap<TtlvTag, i32>);
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    let mut map = BTreeMap::new();
+    map.insert(tag(0xCCCCCC), 2);
+    map.insert(tag(0xBBBBBB), 1);
+    let attributes = Attributes(map);
+
+    let ttlv_bytes = crate::to_vec(&attributes).unwrap();
+    assert_eq!(
+        hex::encode_upper(&ttlv_bytes),
+        concat!(
+            "AAAAAA0100000020",
+            "BBBBBB02000000040000000100000000",
+            "CCCCCC02000000040000000200000000"
+        )
+    );
+    assert_eq!(from_slice::<Attributes>(&ttlv_bytes).unwrap(), attributes);
+
+    // Maps can be nested and keyed by numeric tag values
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Nested(HashMap<u32, HashMap<u32, String>>);
+
+    let mut inner = HashMap::new();
+    inner.insert(0xDDDDDD, "hi".to_string());
+    let mut outer = HashMap::new();
+    outer.insert(0xBBBBBB, inner);
+    let nested = Nested(outer);
+
+    let ttlv_bytes = crate::to_vec(&nested).unwrap();
+    assert_eq!(
+        hex::encode_upper(&ttlv_bytes),
+        concat!(
+            "AAAAAA0100000018",
+            "BBBBBB0100000010",
+            "DDDDDD07000000026869000000000000"
+        )
+    );
+    assert_eq!(from_slice::<Nested>(&ttlv_bytes).unwrap(), nested);
+
+    // The entries of a HashMap can be serialized in tag order regardless of iteration order
+    #[derive(Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Unordered(HashMap<TtlvTag, i32>);
+
+    let config = crate::ser::Config::new().with_canonical_order();
+    let unordered = Unordered((0..16).map(|i| (tag(0xBBBB00 + i), i as i32)).collect());
+    let ttlv_bytes = crate::to_vec_with_config(&unordered, &config).unwrap();
+    let ordered = Attributes((0..16).map(|i| (tag(0xBBBB00 + i), i as i32)).collect());
+    assert_eq!(ttlv_bytes, crate::to_vec(&ordered).unwrap());
+
+    // Map keys must be valid tags
+    let mut map = BTreeMap::new();
+    map.insert(0x1000000u32, 1);
+    #[derive(Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Invalid(BTreeMap<u32, i32>);
+    assert!(crate::to_vec(&Invalid(map)).is_err());
+}
+
+#[test]
+fn test_enumeration_into_u32() {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        op: u32,
+        #[serde(rename = "0xCCCCCC")]
+        payload: Payload,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xCCCCCC")]
+    enum Payload {
+        #[serde(rename = "if 0xBBBBBB==0x54000001")]
+        Vendor(VendorPayload),
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename = "0xCCCCCC")]
+    struct VendorPayload {
+        #[serde(rename = "0xDDDDDD")]
+        a: i32,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000028",
+        "BBBBBB05000000045400000100000000",
+        "CCCCCC0100000010",
+        "DDDDDD02000000040000000100000000"
+    ))
+    .unwrap();
+
+    // The raw enumeration value is available and can still be used to select an enum variant
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.op, 0x54000001);
+    assert_eq!(r.payload, Payload::Vendor(VendorPayload { a: 1 }));
+
+    // The value round-trips
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Passthrough(Op);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct Op(u32);
+
+    let value = Passthrough(Op(0x54000001));
+    let ttlv_bytes = crate::to_vec(&value).unwrap();
+    assert_eq!(
+        hex::encode_upper(&ttlv_bytes),
+        "AAAAAA0100000010BBBBBB05000000045400000100000000"
+    );
+
+    // Other types are rejected
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB02000000045400000100000000").unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::UnexpectedType { .. }));
+}
+
+#[test]
+fn test_interval() {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        secs: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct StrictRoot {
+        #[serde(rename = "0xBBBBBB")]
+        timeout: Timeout,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct Timeout(TtlvInterval);
+
+    // 10 days, as per the KMIP 1.0 spec section 9.1.2 Examples
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB0A00000004000D2F0000000000").unwrap();
+
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.secs, 864000);
+
+    let r: StrictRoot = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.timeout, Timeout(TtlvInterval(864000)));
+
+    // The value round-trips
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+
+    // A TtlvInterval field rejects other types
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB05000000040000000100000000").unwrap();
+    let err = from_slice::<StrictRoot>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::UnexpectedType {
+            expected: TtlvType::Interval,
+            actual: TtlvType::Enumeration
+        })
+    );
+}
+
+#[test]
+fn test_integer_into_narrower_types() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i8,
+        #[serde(rename = "0xCCCCCC")]
+        b: i16,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB0200000004FFFFFF8000000000",
+        "CCCCCC020000000400007FFF00000000"
+    ))
+    .unwrap();
+
+    // Values that fit are accepted
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, i8::MIN);
+    assert_eq!(r.b, i16::MAX);
+
+    // Values that don't fit are rejected
+    let mut too_big = ttlv_bytes.clone();
+    too_big[16..20].copy_from_slice(&128i32.to_be_bytes());
+    let err = from_slice::<Root>(&too_big).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::IntegerOutOfRange {
+            value: 128,
+            target: "i8"
+        })
+    );
+
+    let mut too_small = ttlv_bytes;
+    too_small[32..36].copy_from_slice(&(-32769i32).to_be_bytes());
+    let err = from_slice::<Root>(&too_small).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::IntegerOutOfRange {
+            value: -32769,
+            target: "i16"
+        })
+    );
+}
+
+#[test]
+fn test_big_integer_into_128_bit_integers() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i128,
+        #[serde(rename = "0xCCCCCC")]
+        b: u128,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000030",
+        "BBBBBB0400000008FFFFFFFFFFFFFFFE",
+        "CCCCCC0400000018000000000000000080000000000000000000000000000001"
+    ))
+    .unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, -2);
+    assert_eq!(r.b, (1u128 << 127) + 1);
+
+    // A value that is too big for an i128
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000030",
+        "BBBBBB0400000018000000000000000080000000000000000000000000000001",
+        "CCCCCC04000000080000000000000001"
+    ))
+    .unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::BigIntegerOutOfRange { target: "i128" })
+    );
+
+    // A negative value for a u128
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB04000000080000000000000001",
+        "CCCCCC0400000008FFFFFFFFFFFFFFFF"
+    ))
+    .unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::SerdeError(SerdeError::BigIntegerOutOfRange { target: "u128" })
+    );
+}
+
+#[test]
+fn test_max_big_integer_len() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i128,
+    }
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB0400000018000000000000000000000000000000000000000000000001"
+    ))
+    .unwrap();
+    assert_eq!(from_slice::<Root>(&ttlv_bytes).unwrap().a, 1);
+    validate(&ttlv_bytes, &Config::new()).unwrap();
+
+    let config = Config::new().with_max_big_integer_len(16);
+    let err = from_slice_with_config::<Root>(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ValueLengthExceedsLimit(24));
+    assert_eq!(err.location().tag(), Some(TtlvTag::from([0xBB, 0xBB, 0xBB])));
+    let err = validate(&ttlv_bytes, &config).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ValueLengthExceedsLimit(24));
+
+    // A huge length is rejected by default rather than allocating space for the value
+    let ttlv_bytes = hex::decode(concat!("AAAAAA0100000010", "BBBBBB04FFFFFFF80000000000000001")).unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::ValueLengthExceedsLimit(0xFFFFFFF8));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_datetime() {
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB", with = "crate::datetime")]
+        created: DateTime<Utc>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct WrappedRoot {
+        #[serde(rename = "0xBBBBBB")]
+        created: Created,
+        #[serde(rename = "0xCCCCCC", skip_serializing_if = "Option::is_none")]
+        activated: Option<Activated>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct Created(#[serde(with = "crate::datetime")] DateTime<Utc>);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xCCCCCC")]
+    struct Activated(#[serde(with = "crate::datetime")] DateTime<Utc>);
+
+    // Friday, March 14, 2008, 11:56:40 GMT, as per the KMIP 1.0 spec section 9.1.2 Examples
+    let created = Utc.timestamp_opt(0x47DA67F8, 0).unwrap();
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB09000000080000000047DA67F8").unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.created, created);
+
+    let r: WrappedRoot = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.created, Created(created));
+    assert_eq!(r.activated, None);
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB09000000080000000047DA67F8",
+        "CCCCCC09000000080000000047DA67F9"
+    ))
+    .unwrap();
+    let r: WrappedRoot = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.activated, Some(Activated(Utc.timestamp_opt(0x47DA67F9, 0).unwrap())));
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+
+    // Values out of range for chrono are rejected
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB09000000087FFFFFFFFFFFFFFF").unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(_)));
+
+    // Fractional seconds are discarded when serializing, unless using the strict variant
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct StrictRoot {
+        #[serde(rename = "0xBBBBBB")]
+        created: StrictCreated,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct StrictCreated(#[serde(with = "crate::datetime::strict")] DateTime<Utc>);
+
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB09000000080000000047DA67F8").unwrap();
+    let fractional = Utc.timestamp_opt(0x47DA67F8, 500_000_000).unwrap();
+    let r = WrappedRoot {
+        created: Created(fractional),
+        activated: None,
+    };
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+    let r = StrictRoot {
+        created: StrictCreated(fractional),
+    };
+    let err = crate::to_vec(&r).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(_)));
+    let r = StrictRoot {
+        created: StrictCreated(created),
+    };
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+    assert_eq!(from_slice::<StrictRoot>(&ttlv_bytes).unwrap(), r);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_offset_datetime() {
+    use serde_derive::{Deserialize, Serialize};
+    use time::{OffsetDateTime, UtcOffset};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        created: Created,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct Created(#[serde(with = "crate::offset_datetime")] OffsetDateTime);
+
+    // Friday, March 14, 2008, 11:56:40 GMT, as per the KMIP 1.0 spec section 9.1.2 Examples
+    let created = OffsetDateTime::from_unix_timestamp(0x47DA67F8).unwrap();
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB09000000080000000047DA67F8").unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.created, Created(created));
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+
+    // The UTC offset does not affect the serialized value
+    let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+    let r = Root {
+        created: Created(created.to_offset(offset)),
+    };
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+
+    // Values out of range for time are rejected
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB09000000087FFFFFFFFFFFFFFF").unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(_)));
+
+    // Fractional seconds are discarded when serializing, unless using the strict variant
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct StrictRoot {
+        #[serde(rename = "0xBBBBBB")]
+        created: StrictCreated,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct StrictCreated(#[serde(with = "crate::offset_datetime::strict")] OffsetDateTime);
+
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB09000000080000000047DA67F8").unwrap();
+    let fractional = created + time::Duration::milliseconds(500);
+    let r = Root {
+        created: Created(fractional),
+    };
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+    let r = StrictRoot {
+        created: StrictCreated(fractional),
+    };
+    let err = crate::to_vec(&r).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::SerdeError(SerdeError::Other(_)));
+    let r = StrictRoot {
+        created: StrictCreated(created),
+    };
+    assert_eq!(crate::to_vec(&r).unwrap(), ttlv_bytes);
+    assert_eq!(from_slice::<StrictRoot>(&ttlv_bytes).unwrap(), r);
+}
+
+#[test]
+fn test_deserialize_into_generic_consumers() {
+    use serde::de::IgnoredAny;
+    use serde_derive::Deserialize;
+
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000058",
+        "BBBBBB02000000040000000100000000",
+        "DDDDDD0100000030",
+        "EEEEEE07000000026869000000000000",
+        "FFFFFF05000000040000000300000000",
+        "11111106000000080000000000000001",
+        "CCCCCC09000000080000000000000002",
+    ))
+    .unwrap();
+
+    // A generic consumer such as serde_json::Value can be used to capture the whole input
+    let v: serde_json::Value = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(
+        v,
+        serde_json::json!({
+            "0xBBBBBB": 1,
+            "0xDDDDDD": { "0xEEEEEE": "hi", "0xFFFFFF": 3, "0x111111": true },
+            "0xCCCCCC": 2,
+        })
+    );
+
+    // Or a single item
+    let v: serde_json::Value = from_slice(&ttlv_bytes[8..24]).unwrap();
+    assert_eq!(v, serde_json::json!(1));
+
+    // The whole input can be skipped without knowing what it contains
+    let IgnoredAny = from_slice(&ttlv_bytes).unwrap();
+
+    // As can a structure within it
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+        #[serde(rename = "0xDDDDDD")]
+        _inner: IgnoredAny,
+        #[serde(rename = "0xCCCCCC")]
+        c: i64,
+    }
+
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, 1);
+    assert_eq!(r.c, 2);
+}
+
+#[test]
+fn test_unknown_items_are_skipped_without_parsing() {
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Root {
+        #[serde(rename = "0xBBBBBB")]
+        a: i32,
+    }
+
+    // The content of the unknown structure is not valid TTLV, which is fine as it is skipped using its length
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000038",
+        "BBBBBB02000000040000000100000000",
+        "CCCCCC0100000010",
+        "DDDDDD00000000000000000000000000",
+        "EEEEEE08000000030102030000000000",
+    ))
+    .unwrap();
+    let r: Root = from_slice(&ttlv_bytes).unwrap();
+    assert_eq!(r.a, 1);
+
+    // An unknown item that claims to be longer than the input is still an error
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000020",
+        "BBBBBB02000000040000000100000000",
+        "CCCCCC0100000100",
+        "DDDDDD0000000000",
+    ))
+    .unwrap();
+    let err = from_slice::<Root>(&ttlv_bytes).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::IoError(_));
+}
+
+#[test]
+fn test_tag_provider() {
+    use crate::{ser, to_vec_with_config};
+    use serde_derive::{Deserialize, Serialize};
+
+    fn tag_registry(name: &str) -> Option<TtlvTag> {
+        match name {
+            "Root" => Some(TtlvTag::from([0xAA, 0xAA, 0xAA])),
+            "Count" | "count" => Some(TtlvTag::from([0xBB, 0xBB, 0xBB])),
+            "Label" | "label" => Some(TtlvTag::from([0xCC, 0xCC, 0xCC])),
+            _ => None,
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Root {
+        count: Count,
+        label: Option<Label>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:Count")]
+    struct Count(i32);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:Label")]
+    struct Label(String);
+
+    let ttlv_bytes = hex::decode(
+        "AAAAAA 01 00000020 \
+         BBBBBB 02 00000004 00000001 00000000 \
+         CCCCCC 07 00000002 6869 000000000000"
+            .replace(" ", ""),
+    )
+    .unwrap();
+    let value = Root {
+        count: Count(1),
+        label: Some(Label("hi".to_string())),
+    };
+
+    // Without a tag provider the names are not tags
+    assert!(crate::to_vec(&value).is_err());
+    assert!(from_slice::<Root>(&ttlv_bytes).is_err());
+
+    let config = ser::Config::new().with_tag_provider(tag_registry);
+    assert_eq!(to_vec_with_config(&value, &config).unwrap(), ttlv_bytes);
+
+    let config = Config::new().with_tag_provider(tag_registry);
+    assert_eq!(from_slice_with_config::<Root>(&ttlv_bytes, &config).unwrap(), value);
+
+    // Absent optional fields are still recognized as such
+    let ttlv_bytes = hex::decode("AAAAAA 01 00000010 BBBBBB 02 00000004 00000001 00000000".replace(" ", "")).unwrap();
+    let value = Root {
+        count: Count(1),
+        label: None,
+    };
+    assert_eq!(from_slice_with_config::<Root>(&ttlv_bytes, &config).unwrap(), value);
+
+    // Names unknown to the tag provider are errors
+    #[derive(Debug, Deserialize)]
+    struct Unknown {
+        #[allow(dead_code)]
+        count: Count,
+    }
+    assert!(from_slice_with_config::<Unknown>(&ttlv_bytes, &config).is_err());
+}
+
+#[test]
+fn test_bytes_wrappers() {
+    use crate::{to_vec, Bytes, BytesRef};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Owned {
+        a: A,
+        b: Option<B>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct A(Bytes);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xCCCCCC")]
+    struct B(Bytes);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        a: ARef<'a>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct ARef<'a>(#[serde(borrow)] BytesRef<'a>);
+
+    let ttlv_bytes = hex::decode("AAAAAA 01 00000010 BBBBBB 08 00000003 010203 0000000000".replace(" ", "")).unwrap();
+
+    let owned = Owned {
+        a: A(Bytes(vec![1, 2, 3])),
+        b: None,
+    };
+    assert_eq!(to_vec(&owned).unwrap(), ttlv_bytes);
+    assert_eq!(from_slice::<Owned>(&ttlv_bytes).unwrap(), owned);
+
+    let borrowed = Borrowed {
+        a: ARef(BytesRef(&[1, 2, 3])),
+    };
+    assert_eq!(to_vec(&borrowed).unwrap(), ttlv_bytes);
+    let de = from_slice::<Borrowed>(&ttlv_bytes).unwrap();
+    assert_eq!(de, borrowed);
+    assert_eq!(de.a.0.as_ptr(), ttlv_bytes[16..].as_ptr());
+
+    // Other TTLV types are rejected rather than being treated as a sequence of bytes
+    let ttlv_bytes = hex::decode("AAAAAA 01 00000010 BBBBBB 02 00000004 00000001 00000000".replace(" ", "")).unwrap();
+    assert_matches!(
+        from_slice::<Owned>(&ttlv_bytes).unwrap_err().kind(),
+        ErrorKind::SerdeError(SerdeError::UnexpectedType {
+            expected: TtlvType::ByteString,
+            actual: TtlvType::Integer
+        })
+    );
+}
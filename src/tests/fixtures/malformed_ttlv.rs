@@ -48,7 +48,7 @@ pub(crate) fn ttlv_bytes_with_invalid_root_type() -> Vec<u8> {
 }
 
 pub(crate) fn ttlv_bytes_with_wrong_root_type() -> Vec<u8> {
-    let test_data = format!("AAAAAA  {:02X}  00000020", wrong_root_type() as u8);
+    let test_data = format!("AAAAAA  {:02X}  00000020", u8::from(wrong_root_type()));
     hex::decode(test_data.replace(" ", "")).unwrap()
 }
 
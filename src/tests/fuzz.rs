@@ -0,0 +1,68 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::fuzz::{try_parse_raw, try_serde_roundtrip};
+use crate::tests::fixtures;
+
+#[test]
+fn test_try_parse_raw() {
+    let ttlv_bytes = fixtures::kmip_10_create_destroy_use_case::ttlv_bytes();
+    assert!(try_parse_raw(&ttlv_bytes));
+
+    // Every truncation of valid TTLV is invalid and must not cause a panic
+    for len in 0..ttlv_bytes.len() {
+        assert!(!try_parse_raw(&ttlv_bytes[..len]));
+    }
+
+    // Nor should corrupting any single byte
+    for i in 0..ttlv_bytes.len() {
+        let mut corrupted = ttlv_bytes.clone();
+        corrupted[i] ^= 0xFF;
+        let _ = try_parse_raw(&corrupted);
+    }
+}
+
+#[test]
+fn test_try_serde_roundtrip() {
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct RoundTrip {
+        #[serde(rename = "0xBBBBBB")]
+        a: IntWrapper,
+        #[serde(rename = "0xCCCCCC")]
+        b: StrWrapper,
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct IntWrapper(i32);
+
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(rename = "Transparent:0xCCCCCC")]
+    struct StrWrapper(String);
+
+    assert!(try_serde_roundtrip::<RoundTrip>());
+
+    // None values are omitted and so deserialize back to None
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct OptionalRoundTrip {
+        #[serde(rename = "0xBBBBBB")]
+        a: Option<IntWrapper>,
+    }
+
+    assert!(try_serde_roundtrip::<OptionalRoundTrip>());
+
+    // Floating point values cannot be serialized
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(rename = "0xAAAAAA")]
+    struct NoRoundTrip {
+        #[serde(rename = "0xBBBBBB")]
+        a: FloatWrapper,
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    #[serde(rename = "Transparent:0xBBBBBB")]
+    struct FloatWrapper(f32);
+
+    assert!(!try_serde_roundtrip::<NoRoundTrip>());
+}
@@ -0,0 +1,80 @@
+use serde_derive::{Deserialize, Serialize};
+
+#[allow(unused_imports)]
+use pretty_assertions::{assert_eq, assert_ne};
+
+use crate::kmip_tags::{tag_for_name, tag_map, tag_name, TAGS};
+use crate::ser::to_vec_with_config;
+use crate::types::TtlvTag;
+use crate::{from_slice_with_config, Config, PrettyPrinter};
+
+#[test]
+fn test_registry() {
+    // Tag lookup relies on the registry being ordered by tag, and names must be unique for reverse lookup
+    assert!(TAGS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    for (tag, name) in TAGS {
+        assert_eq!(tag_name(*tag), Some(*name));
+        assert_eq!(tag_for_name(name), Some(*tag));
+    }
+
+    assert_eq!(tag_name(TtlvTag::new(0x420078)), Some("RequestMessage"));
+    assert_eq!(tag_name(TtlvTag::new(0x420094)), Some("UniqueIdentifier"));
+    assert_eq!(tag_name(TtlvTag::new(0x540000)), None);
+    assert_eq!(tag_for_name("ProtocolVersionMajor"), Some(TtlvTag::new(0x42006A)));
+    assert_eq!(tag_for_name("Protocol Version Major"), None);
+    assert_eq!(tag_map().len(), TAGS.len());
+}
+
+#[test]
+fn test_registry_as_tag_provider_and_resolver() {
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct ProtocolVersion {
+        #[serde(rename = "ProtocolVersionMajor")]
+        major: ProtocolVersionMajor,
+        #[serde(rename = "ProtocolVersionMinor")]
+        minor: ProtocolVersionMinor,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:ProtocolVersionMajor")]
+    struct ProtocolVersionMajor(i32);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "Transparent:ProtocolVersionMinor")]
+    struct ProtocolVersionMinor(i32);
+
+    let ttlv_bytes = hex::decode(
+        "420069 01 00000020 \
+         42006A 02 00000004 00000001 00000000 \
+         42006B 02 00000004 00000002 00000000"
+            .replace(" ", ""),
+    )
+    .unwrap();
+    let value = ProtocolVersion {
+        major: ProtocolVersionMajor(1),
+        minor: ProtocolVersionMinor(2),
+    };
+
+    let config = crate::ser::Config::new().with_tag_provider(tag_for_name);
+    assert_eq!(to_vec_with_config(&value, &config).unwrap(), ttlv_bytes);
+
+    let config = Config::new()
+        .with_tag_provider(tag_for_name)
+        .with_tag_name_resolver(tag_name);
+    assert_eq!(
+        from_slice_with_config::<ProtocolVersion>(&ttlv_bytes, &config).unwrap(),
+        value
+    );
+
+    // Change the type of the first integer item to a Long Integer
+    let mut bad_ttlv_bytes = ttlv_bytes.clone();
+    bad_ttlv_bytes[11] = 0x03;
+    let err = from_slice_with_config::<ProtocolVersion>(&bad_ttlv_bytes, &config).unwrap_err();
+    assert_eq!(err.location().tag_name(), Some("ProtocolVersionMajor"));
+
+    let mut pretty_printer = PrettyPrinter::new();
+    pretty_printer.with_tag_map(tag_map());
+    assert!(pretty_printer
+        .to_string(&ttlv_bytes)
+        .starts_with("Tag: ProtocolVersion (0x420069), Type: Structure (0x01)"));
+}
@@ -1,9 +1,22 @@
+#![allow(dead_code)]
+
+#[cfg(feature = "codec")]
+mod codec;
 #[cfg(feature = "high-level")]
 mod de;
 #[cfg(feature = "high-level")]
 mod fixtures;
+#[cfg(feature = "fuzz")]
+mod fuzz;
 #[cfg(feature = "high-level")]
 mod helpers;
+#[cfg(all(feature = "kmip-tags", feature = "high-level"))]
+mod kmip_tags;
+#[cfg(not(feature = "std"))]
+mod no_std;
+#[cfg(feature = "std")]
 mod types;
 #[cfg(feature = "high-level")]
 mod util;
+#[cfg(feature = "xml")]
+mod xml;
@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+
+use crate::types::{
+    Error, SerializableTtlvType, TtlvInteger, TtlvLength, TtlvTag, TtlvTextString, TtlvType, TtlvWrite,
+};
+
+use assert_matches::assert_matches;
+
+#[test]
+fn test_read_from_slice() {
+    let ttlv_wire = hex::decode("66000102000000040000000300000000").unwrap();
+    let mut src = ttlv_wire.as_slice();
+
+    assert_eq!(*TtlvTag::read(&mut src).unwrap(), 0x660001);
+    assert_eq!(TtlvType::read(&mut src).unwrap(), TtlvType::Integer);
+    assert_eq!(*TtlvInteger::read(&mut src).unwrap(), 3);
+    assert!(src.is_empty());
+
+    let mut src = &ttlv_wire[..6];
+    TtlvTag::read(&mut src).unwrap();
+    TtlvType::read(&mut src).unwrap();
+    assert_matches!(TtlvLength::read(&mut src), Err(Error::UnexpectedEof));
+}
+
+#[test]
+fn test_write_to_vec() {
+    let mut dst = Vec::new();
+    TtlvTag::from([0x66, 0x00, 0x01]).write(&mut dst).unwrap();
+    TtlvInteger(3).write(&mut dst).unwrap();
+    assert_eq!(dst, hex::decode("66000102000000040000000300000000").unwrap());
+}
+
+#[test]
+fn test_write_to_slice() {
+    let mut buf = [0u8; 21];
+    let mut dst = &mut buf[..];
+    TtlvTextString("Hello".into()).write(&mut dst).unwrap();
+    assert_eq!(dst.len(), 8);
+    dst.write_all(&[0xFF; 8]).unwrap();
+    assert_matches!(dst.write_all(&[0xFF]), Err(Error::WriteZero));
+    assert_eq!(
+        buf.as_ref(),
+        hex::decode("070000000548656C6C6F000000FFFFFFFFFFFFFFFF")
+            .unwrap()
+            .as_slice()
+    );
+}
@@ -4,7 +4,8 @@ use std::{convert::TryFrom, io::Cursor, str::FromStr};
 
 use crate::types::{
     Error, SerializableTtlvType, TtlvBigInteger, TtlvBoolean, TtlvByteString, TtlvDateTime, TtlvEnumeration,
-    TtlvInteger, TtlvLongInteger, TtlvTag, TtlvTextString, TtlvType,
+    TtlvExtensionType, TtlvInteger, TtlvInterval, TtlvLongInteger, TtlvStateMachine, TtlvStateMachineMode, TtlvTag,
+    TtlvTextString, TtlvType,
 };
 
 use assert_matches::assert_matches;
@@ -24,6 +25,8 @@ fn test_item_tag() {
     assert!(TtlvTag::from_str("").is_err());
     assert!(TtlvTag::from_str("    ").is_err());
     assert!(TtlvTag::from_str("XYZ").is_err());
+    assert_matches!(TtlvTag::from_str("0x1AABBCC"), Err(Error::InvalidTtlvTag(v)) if v == "0x1AABBCC");
+    assert_eq!(*TtlvTag::from_str("0xFFFFFF").unwrap(), 0xFFFFFF);
 
     #[allow(non_snake_case)]
     let ZERO_TAG = TtlvTag::from([0x00u8, 0x00u8, 0x00u8]);
@@ -78,14 +81,21 @@ fn test_item_type() {
     assert_matches!(TtlvType::try_from(0x07), Ok(TtlvType::TextString));
     assert_matches!(TtlvType::try_from(0x08), Ok(TtlvType::ByteString));
     assert_matches!(TtlvType::try_from(0x09), Ok(TtlvType::DateTime));
+    assert_matches!(TtlvType::try_from(0x0A), Ok(TtlvType::Interval));
+    assert_eq!(u8::from(TtlvType::Interval), 0x0A);
 
-    // Interval is not yet implemented
-    assert_matches!(TtlvType::try_from(0x0A), Err(Error::UnsupportedTtlvType(0x0A)));
+    // All other values are extension types, not defined by the KMIP specification
+    for i in 0x0B..=0xFF {
+        assert_matches!(TtlvType::try_from(i), Ok(TtlvType::Extension(n)) if *n == i);
+        assert_eq!(u8::from(TtlvType::try_from(i).unwrap()), i);
+    }
+    assert_eq!(TtlvType::try_from(0x0B).unwrap().to_string(), "Extension(0x0B)");
 
-    // All other values are invalid
-    for i in 0x0B..0xFF {
-        assert_matches!(TtlvType::try_from(i), Err(Error::InvalidTtlvType(n)) if n == i);
+    // An extension type cannot be given a type byte that is defined by the KMIP specification
+    for i in 0x00..=0x0A {
+        assert_matches!(TtlvExtensionType::try_from(i), Err(Error::InvalidTtlvType(n)) if n == i);
     }
+    assert_eq!(u8::from(TtlvExtensionType::try_from(0x0B).unwrap()), 0x0B);
 }
 
 fn spec_ttlv_to_vec_tlv(s: &str) -> Vec<u8> {
@@ -155,7 +165,7 @@ fn test_spec_ttlv_big_integer() {
     let mut readable_spec_lv_bytes = Cursor::new(&spec_tlv_bytes[1..]);
     let v = TtlvBigInteger::read(&mut readable_spec_lv_bytes);
     assert!(v.is_ok());
-    assert_eq!(big_int, num_bigint::BigInt::from_signed_bytes_be(&(*(v.unwrap()))));
+    assert_eq!(big_int, num_bigint::BigInt::from_signed_bytes_be(&v.unwrap()));
 }
 
 #[test]
@@ -201,6 +211,168 @@ fn test_spec_ttlv_byte_string() {
 }
 
 #[test]
+fn test_read_strict_padding() {
+    let mut bytes = spec_ttlv_to_vec_tlv("42 00 20 | 02 | 00 00 00 04 | 00 00 00 08 00 00 00 01");
+    bytes.remove(0); // remove the type
+
+    // Non-zero padding is only rejected when reading strictly
+    assert_eq!(*TtlvInteger::read(&mut Cursor::new(&bytes)).unwrap(), 8);
+    assert_matches!(
+        TtlvInteger::read_strict(&mut Cursor::new(&bytes)),
+        Err(Error::InvalidTtlvPadding(TtlvType::Integer))
+    );
+
+    bytes[11] = 0x00;
+    assert_eq!(*TtlvInteger::read_strict(&mut Cursor::new(&bytes)).unwrap(), 8);
+}
+
+#[test]
+fn test_big_integer_max_len() {
+    let mut bytes =
+        spec_ttlv_to_vec_tlv("42 00 20 | 04 | 00 00 00 10 | 00 00 00 00 03 FD 35 EB 6B C2 DF 46 18 08 00 00");
+    bytes.remove(0); // remove the type
+
+    assert_eq!(
+        TtlvBigInteger::read_with_max_len(&mut Cursor::new(&bytes), 16)
+            .unwrap()
+            .len(),
+        16
+    );
+    assert_matches!(
+        TtlvBigInteger::read_with_max_len(&mut Cursor::new(&bytes), 8),
+        Err(Error::TtlvValueLengthExceedsLimit {
+            length: 16,
+            r#type: TtlvType::BigInteger
+        })
+    );
+
+    // The default limit applies to the regular read functions, before any space is allocated for the value
+    let huge_len = (TtlvBigInteger::DEFAULT_MAX_LEN + 8).to_be_bytes();
+    assert_matches!(
+        TtlvBigInteger::read(&mut Cursor::new(&huge_len)),
+        Err(Error::TtlvValueLengthExceedsLimit { .. })
+    );
+}
+
+#[test]
+fn test_inner_type_conversions() {
+    assert_eq!(*TtlvInteger::from(42i32), 42);
+    assert_eq!(i32::from(TtlvInteger(42)), 42);
+    assert_eq!(*TtlvLongInteger::from(42i64), 42);
+    assert_eq!(i64::from(TtlvLongInteger(42)), 42);
+    assert_eq!(*TtlvEnumeration::from(42u32), 42);
+    assert_eq!(u32::from(TtlvEnumeration(42)), 42);
+    assert_eq!(*TtlvDateTime::from(42i64), 42);
+    assert_eq!(i64::from(TtlvDateTime(42)), 42);
+    assert_eq!(*TtlvBoolean::from(true), true);
+    assert_eq!(bool::from(TtlvBoolean(true)), true);
+    assert_eq!(*TtlvTextString::from("abc".to_string()), "abc");
+    assert_eq!(String::from(TtlvTextString("abc".to_string())), "abc");
+    assert_eq!(*TtlvByteString::from(vec![1u8, 2]), vec![1, 2]);
+    assert_eq!(Vec::<u8>::from(TtlvByteString(vec![1, 2])), vec![1, 2]);
+    assert_eq!(*TtlvBigInteger::from(vec![1u8, 2]), vec![1, 2]);
+    assert_eq!(Vec::<u8>::from(TtlvBigInteger(vec![1, 2])), vec![1, 2]);
+}
+
+#[test]
+fn test_big_integer_128_bit_conversions() {
+    fn big(hex: &str) -> TtlvBigInteger {
+        TtlvBigInteger(hex::decode(hex).unwrap())
+    }
+
+    assert_eq!(i128::try_from(big("0000000000000000")).unwrap(), 0);
+    assert_eq!(i128::try_from(big("FFFFFFFFFFFFFFFF")).unwrap(), -1);
+    assert_eq!(
+        i128::try_from(big("7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF")).unwrap(),
+        i128::MAX
+    );
+    assert_eq!(
+        i128::try_from(big("FFFFFFFFFFFFFFFF80000000000000000000000000000000")).unwrap(),
+        i128::MIN
+    );
+    assert!(i128::try_from(big("000000000000000080000000000000000000000000000000")).is_err());
+    assert!(i128::try_from(big("FFFFFFFFFFFFFFFF7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF")).is_err());
+
+    assert_eq!(u128::try_from(big("0000000000000000")).unwrap(), 0);
+    assert_eq!(
+        u128::try_from(big("0000000000000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF")).unwrap(),
+        u128::MAX
+    );
+    assert!(u128::try_from(big("FFFFFFFFFFFFFFFF")).is_err());
+    assert!(u128::try_from(big("000000000000000100000000000000000000000000000000")).is_err());
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn test_big_integer_num_bigint_conversions() {
+    use num_bigint::{BigInt, BigUint};
+
+    fn to_ttlv(v: i64) -> Vec<u8> {
+        TtlvBigInteger::from(BigInt::from(v)).0
+    }
+
+    // Zero and small values are sign-extended to a full eight bytes
+    assert_eq!(to_ttlv(0), vec![0x00; 8]);
+    assert_eq!(to_ttlv(1), vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    assert_eq!(to_ttlv(-1), vec![0xFF; 8]);
+    assert_eq!(to_ttlv(-129), vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F]);
+
+    // Values that are exactly eight bytes long are not padded
+    assert_eq!(to_ttlv(i64::MAX), i64::MAX.to_be_bytes().to_vec());
+    assert_eq!(to_ttlv(i64::MIN), i64::MIN.to_be_bytes().to_vec());
+
+    // Values just over eight bytes long are padded to sixteen bytes
+    let nine_bytes = BigInt::from(i64::MAX) + BigInt::from(1);
+    let mut expected = vec![0x00; 8];
+    expected.extend(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(TtlvBigInteger::from(nine_bytes.clone()).0, expected);
+    let minus_nine_bytes = -nine_bytes - BigInt::from(1);
+    let mut expected = vec![0xFF; 8];
+    expected.extend(&[0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(TtlvBigInteger::from(minus_nine_bytes).0, expected);
+
+    // TTLV to BigInt
+    assert_eq!(BigInt::from(TtlvBigInteger(vec![0xFF; 8])), BigInt::from(-1));
+    assert_eq!(BigInt::from(TtlvBigInteger(vec![0x00; 8])), BigInt::from(0));
+
+    // An unsigned value with the most significant bit set gains a leading zero byte so it stays positive
+    let unsigned = BigUint::from(u64::MAX);
+    let ttlv = TtlvBigInteger::from(unsigned.clone());
+    assert_eq!(ttlv.len(), 16);
+    assert_eq!(BigUint::try_from(ttlv).unwrap(), unsigned);
+
+    // Negative values cannot be converted to BigUint
+    assert_matches!(
+        BigUint::try_from(TtlvBigInteger::from(BigInt::from(-42))),
+        Err(Error::InvalidTtlvValue(TtlvType::BigInteger))
+    );
+
+    // Round trip through the TTLV wire format
+    //   - A Big Integer containing the decimal value 1234567890000000000000000000:
+    //     42 00 20 | 04 | 00 00 00 10 | 00 00 00 00 03 FD 35 EB 6B C2 DF 46 18 08 00 00
+    let spec_tlv_bytes =
+        spec_ttlv_to_vec_tlv("42 00 20 | 04 | 00 00 00 10 | 00 00 00 00 03 FD 35 EB 6B C2 DF 46 18 08 00 00");
+    for v in &[
+        BigInt::parse_bytes(b"1234567890000000000000000000", 10).unwrap(),
+        BigInt::parse_bytes(b"-1234567890000000000000000000", 10).unwrap(),
+        BigInt::from(0),
+        BigInt::from(i64::MIN),
+    ] {
+        let mut buf = Vec::new();
+        TtlvBigInteger::from(v.clone()).write(&mut buf).unwrap();
+        let read = TtlvBigInteger::read(&mut Cursor::new(&buf[1..])).unwrap();
+        assert_eq!(read.len() % 8, 0);
+        assert_eq!(&BigInt::from(read), v);
+    }
+    let mut buf = Vec::new();
+    TtlvBigInteger::from(BigInt::parse_bytes(b"1234567890000000000000000000", 10).unwrap())
+        .write(&mut buf)
+        .unwrap();
+    assert_eq!(spec_tlv_bytes, buf);
+}
+
+#[test]
+#[allow(deprecated)]
 fn test_spec_ttlv_date_time() {
     use chrono::TimeZone;
 
@@ -219,12 +391,21 @@ fn test_spec_ttlv_date_time() {
 }
 
 #[test]
-#[should_panic]
 fn test_spec_ttlv_interval() {
     //   - An Interval, containing the value for 10 days:
     //     42 00 20 | 0A | 00 00 00 04 | 00 0D 2F 00 00 00 00 00
-    // NOT IMPLEMENTED YET
-    todo!()
+    let spec_tlv_bytes = spec_ttlv_to_vec_tlv("42 00 20 | 0A | 00 00 00 04 | 00 0D 2F 00 00 00 00 00");
+
+    // Test serialization
+    let mut serialized_tlv_bytes = Vec::new();
+    assert!(TtlvInterval(10 * 24 * 60 * 60).write(&mut serialized_tlv_bytes).is_ok());
+    assert_eq!(spec_tlv_bytes, serialized_tlv_bytes);
+
+    // Test deserialization
+    let mut readable_spec_lv_bytes = Cursor::new(&spec_tlv_bytes[1..]);
+    let v = TtlvInterval::read(&mut readable_spec_lv_bytes);
+    assert!(v.is_ok());
+    assert_eq!(10 * 24 * 60 * 60, *(v.unwrap()));
 }
 
 #[test]
@@ -236,3 +417,700 @@ fn test_spec_ttlv_structure() {
     //     00 00 00 00 | 42 00 05 | 02 | 00 00 00 04 | 00 00 00 FF 00 00 00 00
     panic!("NOT IN SCOPE FOR THIS MODULE");
 }
+
+#[test]
+fn test_state_machine_depth() {
+    let mut sm = TtlvStateMachine::new(TtlvStateMachineMode::Serializing);
+    assert_eq!(sm.current_depth(), 0);
+    assert_matches!(sm.leave_structure(), Err(Error::InvalidStateMachineOperation));
+
+    sm.enter_structure();
+    sm.enter_structure();
+    assert_eq!(sm.current_depth(), 2);
+    sm.leave_structure().unwrap();
+    assert_eq!(sm.current_depth(), 1);
+
+    sm.reset();
+    assert_eq!(sm.current_depth(), 0);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_datetime_chrono_conversions() {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    let dt = Utc.timestamp_opt(0x47DA67F8, 0).unwrap();
+    assert_eq!(*TtlvDateTime::from(dt), 0x47DA67F8);
+    assert_eq!(DateTime::<Utc>::try_from(TtlvDateTime(0x47DA67F8)).unwrap(), dt);
+    assert_matches!(
+        DateTime::<Utc>::try_from(TtlvDateTime(i64::MAX)),
+        Err(Error::InvalidTtlvValue(TtlvType::DateTime))
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_datetime_time_conversions() {
+    use time::OffsetDateTime;
+
+    let dt = OffsetDateTime::from_unix_timestamp(0x47DA67F8).unwrap();
+    assert_eq!(*TtlvDateTime::from(dt), 0x47DA67F8);
+    assert_eq!(OffsetDateTime::try_from(TtlvDateTime(0x47DA67F8)).unwrap(), dt);
+    assert_matches!(
+        OffsetDateTime::try_from(TtlvDateTime(i64::MAX)),
+        Err(Error::InvalidTtlvValue(TtlvType::DateTime))
+    );
+}
+
+#[test]
+fn test_node_parse_and_encode() {
+    use crate::types::{TtlvNode, TtlvValue};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    // The Structure example from the KMIP specification, extended with a nested Structure and a Text String
+    let ttlv_wire = hex::decode(concat!(
+        "4200200100000040",
+        "4200040500000004000000FE00000000",
+        "4200050200000004000000FF00000000",
+        "4200060100000018",
+        "420007070000000B48656C6C6F20776F726C640000000000",
+    ))
+    .unwrap();
+
+    let node = TtlvNode::parse(&ttlv_wire).unwrap();
+    assert_eq!(
+        node,
+        TtlvNode::new(
+            tag(0x420020),
+            TtlvValue::Structure(vec![
+                TtlvNode::new(tag(0x420004), TtlvValue::Enumeration(0xFE)),
+                TtlvNode::new(tag(0x420005), TtlvValue::Integer(0xFF)),
+                TtlvNode::new(
+                    tag(0x420006),
+                    TtlvValue::Structure(vec![TtlvNode::new(
+                        tag(0x420007),
+                        TtlvValue::TextString("Hello world".to_string())
+                    )])
+                ),
+            ])
+        )
+    );
+    assert_eq!(node.encoded_len(), ttlv_wire.len());
+    assert_eq!(node.encode(), ttlv_wire);
+
+    // Values of every type survive a round trip
+    let node = TtlvNode::new(
+        tag(0x420001),
+        TtlvValue::Structure(vec![
+            TtlvNode::new(tag(0x420002), TtlvValue::LongInteger(-2)),
+            TtlvNode::new(tag(0x420003), TtlvValue::BigInteger(vec![0xFF; 8])),
+            TtlvNode::new(tag(0x420004), TtlvValue::Boolean(true)),
+            TtlvNode::new(tag(0x420005), TtlvValue::ByteString(vec![1, 2, 3])),
+            TtlvNode::new(tag(0x420006), TtlvValue::DateTime(0x47DA67F8)),
+            TtlvNode::new(tag(0x420007), TtlvValue::Interval(864000)),
+            TtlvNode::new(
+                tag(0x420008),
+                TtlvValue::Extension {
+                    type_byte: TtlvExtensionType::try_from(0x80).unwrap(),
+                    raw: vec![4, 5],
+                },
+            ),
+            TtlvNode::new(tag(0x420009), TtlvValue::Structure(vec![])),
+        ]),
+    );
+    assert_eq!(TtlvNode::parse(&node.encode()).unwrap(), node);
+
+    // The bytes must contain exactly one complete item
+    let mut trailing = ttlv_wire.clone();
+    trailing.push(0);
+    assert_matches!(TtlvNode::parse(&trailing), Err(Error::TrailingBytes(1)));
+    assert_matches!(
+        TtlvNode::parse(&ttlv_wire[..ttlv_wire.len() - 1]),
+        Err(Error::IoError(_))
+    );
+
+    // Invalid values are rejected
+    let invalid_bool = hex::decode("42000106000000080000000000000002").unwrap();
+    assert_matches!(
+        TtlvNode::parse(&invalid_bool),
+        Err(Error::InvalidTtlvValue(TtlvType::Boolean))
+    );
+
+    // Deeply nested Structures are rejected
+    let depth = TtlvNode::MAX_NESTING_DEPTH + 1;
+    let mut too_deep = Vec::new();
+    for level in 0..depth {
+        too_deep.extend(hex::decode("4200010100000000").unwrap());
+        let len = ((depth - level - 1) * 8) as u32;
+        too_deep[level * 8 + 4..level * 8 + 8].copy_from_slice(&len.to_be_bytes());
+    }
+    assert_matches!(
+        TtlvNode::parse(&too_deep),
+        Err(Error::NestingDepthExceedsLimit(d)) if d == depth
+    );
+    assert!(TtlvNode::parse(&too_deep[8..]).is_ok());
+}
+
+#[test]
+fn test_structure_builder() {
+    use crate::types::{StructureBuilder, TtlvNode, TtlvValue};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    // The Structure example from the KMIP specification
+    let ttlv_wire = StructureBuilder::new(tag(0x420020))
+        .enumeration(tag(0x420004), 0xFE)
+        .int(tag(0x420005), 0xFF)
+        .build();
+    assert_eq!(
+        hex::encode_upper(&ttlv_wire),
+        concat!(
+            "4200200100000020",
+            "4200040500000004000000FE00000000",
+            "4200050200000004000000FF00000000",
+        )
+    );
+
+    // Values are padded, nested Structures and pre-built items can be added
+    let ttlv_wire = StructureBuilder::new(tag(0x420001))
+        .text(tag(0x420002), "abc")
+        .bytes(tag(0x420003), vec![1u8, 2, 3, 4, 5, 6, 7, 8, 9])
+        .structure(
+            StructureBuilder::new(tag(0x420004))
+                .long(tag(0x420005), -1)
+                .boolean(tag(0x420006), true),
+        )
+        .big_int(tag(0x420007), -2i128)
+        .big_int(tag(0x420008), u128::MAX)
+        .date_time(tag(0x420009), 0x47DA67F8)
+        .interval(tag(0x42000A), 864000)
+        .node(TtlvNode::new(
+            tag(0x42000B),
+            TtlvValue::Extension {
+                type_byte: TtlvExtensionType::try_from(0x80).unwrap(),
+                raw: vec![0xAB],
+            },
+        ))
+        .build();
+    assert_eq!(
+        hex::encode_upper(&ttlv_wire),
+        concat!(
+            "42000101000000B0",
+            "42000207000000036162630000000000",
+            "420003080000000901020304050607080900000000000000",
+            "4200040100000020",
+            "4200050300000008FFFFFFFFFFFFFFFF",
+            "42000606000000080000000000000001",
+            "4200070400000008FFFFFFFFFFFFFFFE",
+            "42000804000000180000000000000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            "42000909000000080000000047DA67F8",
+            "42000A0A00000004000D2F0000000000",
+            "42000B8000000001AB00000000000000",
+        )
+    );
+    assert!(TtlvNode::parse(&ttlv_wire).is_ok());
+
+    // Big Integers are sign-extended to a multiple of eight bytes
+    let ttlv_wire = StructureBuilder::new(tag(0x420001))
+        .big_int(tag(0x420002), TtlvBigInteger(vec![0x80, 0x01]))
+        .big_int(tag(0x420003), TtlvBigInteger(vec![0x7F]))
+        .build();
+    assert_eq!(
+        hex::encode_upper(&ttlv_wire),
+        concat!(
+            "4200010100000020",
+            "4200020400000008FFFFFFFFFFFF8001",
+            "4200030400000008000000000000007F",
+        )
+    );
+}
+
+#[test]
+fn test_tokenizer() {
+    use crate::types::{Event, StructureBuilder, TtlvTokenizer, TtlvValueRef};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    let ttlv_wire = StructureBuilder::new(tag(0x420001))
+        .int(tag(0x420002), 3)
+        .structure(StructureBuilder::new(tag(0x420003)).text(tag(0x420004), "abc"))
+        .bytes(tag(0x420005), vec![1u8, 2])
+        .build();
+
+    let events = TtlvTokenizer::new(&ttlv_wire).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::StructStart {
+                offset: 0,
+                tag: tag(0x420001),
+                len: 56
+            },
+            Event::Primitive {
+                offset: 8,
+                tag: tag(0x420002),
+                value: TtlvValueRef::Integer(3)
+            },
+            Event::StructStart {
+                offset: 24,
+                tag: tag(0x420003),
+                len: 16
+            },
+            Event::Primitive {
+                offset: 32,
+                tag: tag(0x420004),
+                value: TtlvValueRef::TextString("abc")
+            },
+            Event::StructEnd {
+                offset: 48,
+                tag: tag(0x420003)
+            },
+            Event::Primitive {
+                offset: 48,
+                tag: tag(0x420005),
+                value: TtlvValueRef::ByteString(&[1, 2])
+            },
+            Event::StructEnd {
+                offset: 64,
+                tag: tag(0x420001)
+            },
+        ]
+    );
+
+    // Items of a type not defined by the KMIP specification keep their type byte
+    let extension = hex::decode("AAAAAA8000000002ABCD000000000000").unwrap();
+    assert_matches!(
+        TtlvTokenizer::new(&extension).next(),
+        Some(Ok(Event::Primitive {
+            value: TtlvValueRef::Extension {
+                type_byte,
+                raw: &[0xAB, 0xCD]
+            },
+            ..
+        })) if *type_byte == 0x80
+    );
+
+    // Back-to-back items are tokenized one after the other
+    let mut two = ttlv_wire.clone();
+    two.extend_from_slice(&ttlv_wire);
+    assert_eq!(TtlvTokenizer::new(&two).count(), 2 * events.len());
+
+    // Iteration stops after an item that extends beyond the end of its Structure
+    let mut overflow = ttlv_wire.clone();
+    overflow[31] = 0x08; // shrink the inner Structure so that its Text String no longer fits
+    let mut tokenizer = TtlvTokenizer::new(&overflow);
+    assert_eq!(tokenizer.by_ref().take(3).filter(Result::is_ok).count(), 3);
+    assert_eq!(tokenizer.depth(), 2);
+    assert_matches!(tokenizer.next(), Some(Err(Error::IoError(_))));
+    assert!(tokenizer.next().is_none());
+
+    // Invalid UTF-8 is rejected
+    let mut invalid_utf8 = ttlv_wire;
+    invalid_utf8[40] = 0xFF;
+    assert!(
+        TtlvTokenizer::new(&invalid_utf8).any(|res| matches!(res, Err(Error::InvalidTtlvValue(TtlvType::TextString))))
+    );
+}
+
+#[test]
+fn test_walk() {
+    use crate::types::{walk, StructureBuilder, TtlvValueRef, TtlvVisitor};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    #[derive(Default)]
+    struct Stats {
+        depth: usize,
+        max_depth: usize,
+        num_items: usize,
+        secrets: Vec<std::ops::Range<usize>>,
+        trace: String,
+    }
+
+    impl TtlvVisitor for Stats {
+        fn on_struct_start(&mut self, offset: usize, tag: TtlvTag, len: u32) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+            self.num_items += 1;
+            self.trace.push_str(&format!("{}:{:?}[{} ", offset, tag, len));
+        }
+
+        fn on_primitive(&mut self, offset: usize, tag: TtlvTag, value: TtlvValueRef<'_>) {
+            self.num_items += 1;
+            if let TtlvValueRef::ByteString(v) = value {
+                // The value follows the 8 byte tag, type and length header
+                self.secrets.push(offset + 8..offset + 8 + v.len());
+            }
+            self.trace.push_str(&format!("{}:{:?} ", offset, tag));
+        }
+
+        fn on_struct_end(&mut self, offset: usize, _tag: TtlvTag) {
+            self.depth -= 1;
+            self.trace.push_str(&format!("]{} ", offset));
+        }
+    }
+
+    let mut ttlv_wire = StructureBuilder::new(tag(0x420001))
+        .int(tag(0x420002), 3)
+        .structure(StructureBuilder::new(tag(0x420003)).bytes(tag(0x420004), vec![0xAAu8; 3]))
+        .build();
+
+    let mut stats = Stats::default();
+    walk(&ttlv_wire, &mut stats).unwrap();
+    assert_eq!(stats.num_items, 4);
+    assert_eq!(stats.max_depth, 2);
+    assert_eq!(stats.depth, 0);
+    assert_eq!(
+        stats.trace,
+        "0:0x420001[40 8:0x420002 24:0x420003[16 32:0x420004 ]48 ]48 "
+    );
+
+    // Redact the byte strings found
+    for range in stats.secrets {
+        ttlv_wire[range].iter_mut().for_each(|b| *b = 0);
+    }
+    assert_eq!(&ttlv_wire[40..43], &[0, 0, 0]);
+
+    // Items before a problem are visited before the error is returned
+    ttlv_wire[39] = 0x20; // the Byte String no longer fits in its Structure
+    let mut stats = Stats::default();
+    assert!(walk(&ttlv_wire, &mut stats).is_err());
+    assert_eq!(stats.num_items, 3);
+}
+
+#[test]
+fn test_edit_in_place() {
+    use crate::types::{append_item, insert_item, remove_item, replace_value, StructureBuilder, TtlvNode, TtlvValue};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    let build = |id: &str, extra: bool| {
+        let mut inner = StructureBuilder::new(tag(0x420003));
+        if extra {
+            inner = inner.int(tag(0x420005), 7);
+        }
+        StructureBuilder::new(tag(0x420001))
+            .int(tag(0x420002), 3)
+            .structure(inner.text(tag(0x420004), id))
+            .build()
+    };
+
+    // Replace the Text String nested two levels deep with a longer one
+    let mut buf = build("abc", false);
+    replace_value(&mut buf, 32, TtlvValue::TextString("0123456789".to_string())).unwrap();
+    assert_eq!(buf, build("0123456789", false));
+
+    // And back again
+    replace_value(&mut buf, 32, TtlvValue::TextString("abc".to_string())).unwrap();
+    assert_eq!(buf, build("abc", false));
+
+    // Insert an item before the Text String then remove it again
+    insert_item(&mut buf, 32, &TtlvNode::new(tag(0x420005), TtlvValue::Integer(7))).unwrap();
+    assert_eq!(buf, build("abc", true));
+    remove_item(&mut buf, 32).unwrap();
+    assert_eq!(buf, build("abc", false));
+
+    // Append an item after the last item of the inner and of the outer Structure
+    append_item(&mut buf, 24, &TtlvNode::new(tag(0x420005), TtlvValue::Integer(7))).unwrap();
+    let expected = StructureBuilder::new(tag(0x420001))
+        .int(tag(0x420002), 3)
+        .structure(
+            StructureBuilder::new(tag(0x420003))
+                .text(tag(0x420004), "abc")
+                .int(tag(0x420005), 7),
+        )
+        .build();
+    assert_eq!(buf, expected);
+    remove_item(&mut buf, 48).unwrap();
+    append_item(&mut buf, 0, &TtlvNode::new(tag(0x420005), TtlvValue::Integer(7))).unwrap();
+    let expected = StructureBuilder::new(tag(0x420001))
+        .int(tag(0x420002), 3)
+        .structure(StructureBuilder::new(tag(0x420003)).text(tag(0x420004), "abc"))
+        .int(tag(0x420005), 7)
+        .build();
+    assert_eq!(buf, expected);
+    remove_item(&mut buf, 48).unwrap();
+
+    // Remove a whole Structure
+    remove_item(&mut buf, 24).unwrap();
+    assert_eq!(buf, StructureBuilder::new(tag(0x420001)).int(tag(0x420002), 3).build());
+
+    // Append to an empty Structure
+    let mut buf = StructureBuilder::new(tag(0x420001)).build();
+    append_item(&mut buf, 0, &TtlvNode::new(tag(0x420002), TtlvValue::Integer(3))).unwrap();
+    assert_eq!(buf, StructureBuilder::new(tag(0x420001)).int(tag(0x420002), 3).build());
+    assert_matches!(
+        append_item(&mut buf, 8, &TtlvNode::new(tag(0x420005), TtlvValue::Integer(7))),
+        Err(Error::UnexpectedTtlvType { .. })
+    );
+
+    // The offset must be that of an item
+    let mut buf = build("abc", false);
+    assert_matches!(remove_item(&mut buf, 12), Err(Error::NoItemAtOffset(12)));
+    assert_matches!(
+        insert_item(&mut buf, 48, &TtlvNode::new(tag(0x420005), TtlvValue::Boolean(true))),
+        Err(Error::NoItemAtOffset(48))
+    );
+    assert_eq!(buf, build("abc", false));
+}
+
+#[test]
+fn test_find() {
+    use crate::types::{find, StructureBuilder, TtlvValueRef};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    let batch_item = |status: u32| {
+        StructureBuilder::new(tag(0x42000F))
+            .enumeration(tag(0x42005C), 0x1E)
+            .enumeration(tag(0x42007F), status)
+    };
+    let response = StructureBuilder::new(tag(0x42007B))
+        .structure(StructureBuilder::new(tag(0x42007A)).int(tag(0x42000D), 2))
+        .structure(batch_item(0))
+        .structure(batch_item(1))
+        .build();
+
+    let found = find(&response, "0x42007B/0x42000F[1]/0x42007F").unwrap().unwrap();
+    assert_eq!(found.tag, tag(0x42007F));
+    assert_eq!(found.r#type, TtlvType::Enumeration);
+    assert_eq!(found.range, 96..112);
+    assert_eq!(found.value, Some(TtlvValueRef::Enumeration(1)));
+    assert_eq!(&response[found.range][..3], &[0x42, 0x00, 0x7F]);
+
+    // Without an index the first item with the tag is selected
+    let found = find(&response, "0x42007B/0x42000F/0x42007F").unwrap().unwrap();
+    assert_eq!(found.value, Some(TtlvValueRef::Enumeration(0)));
+    let found = find(&response, "0x42007B/0x42007A/0x42000D").unwrap().unwrap();
+    assert_eq!(found.value, Some(TtlvValueRef::Integer(2)));
+
+    // Structures can be found too
+    let found = find(&response, "0x42007B/0x42000F[0]").unwrap().unwrap();
+    assert_eq!(found.r#type, TtlvType::Structure);
+    assert_eq!(found.range, 32..72);
+    assert_eq!(found.value, None);
+    assert_eq!(find(&response, "0x42007B").unwrap().unwrap().range, 0..response.len());
+
+    // Paths that do not lead to an item
+    assert_eq!(find(&response, "0x42007B/0x42000F[2]/0x42007F").unwrap(), None);
+    assert_eq!(find(&response, "0x42007B/0x42007A/0x42007F").unwrap(), None);
+    assert_eq!(find(&response, "0x42007B/0x42000D").unwrap(), None);
+    assert_eq!(find(&response, "0x42007B/0x42000F/0x42007F/0x42007F").unwrap(), None);
+    assert_eq!(find(&response, "0x42007A").unwrap(), None);
+
+    // Invalid paths
+    assert_matches!(find(&response, "0x42007B/0x42000F[x]"), Err(Error::InvalidTtlvTag(_)));
+    assert_matches!(find(&response, "0x42007B/0x42000F[1"), Err(Error::InvalidTtlvTag(_)));
+    assert_matches!(find(&response, "0x42007B//0x42000F"), Err(Error::InvalidTtlvTag(_)));
+}
+
+#[test]
+fn test_subtree() {
+    use crate::types::{subtree, subtree_by_tag, StructureBuilder};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    let payload = StructureBuilder::new(tag(0x420079))
+        .text(tag(0x420094), "1234")
+        .bytes(tag(0x420043), vec![0xAAu8; 16])
+        .build();
+    let request = StructureBuilder::new(tag(0x420078))
+        .structure(StructureBuilder::new(tag(0x420077)).int(tag(0x42000D), 1))
+        .structure(StructureBuilder::new(tag(0x42000F)).node(crate::types::TtlvNode::parse(&payload).unwrap()))
+        .build();
+
+    assert_eq!(
+        subtree(&request, "0x420078/0x42000F/0x420079").unwrap(),
+        Some(&payload[..])
+    );
+    assert_eq!(subtree(&request, "0x420078").unwrap(), Some(&request[..]));
+    assert_eq!(subtree(&request, "0x420078/0x420079").unwrap(), None);
+
+    assert_eq!(subtree_by_tag(&request, tag(0x420079)).unwrap(), Some(&payload[..]));
+    assert_eq!(subtree_by_tag(&request, tag(0x420094)).unwrap(), Some(&payload[8..24]));
+    assert_eq!(subtree_by_tag(&request, tag(0x420001)).unwrap(), None);
+}
+
+#[test]
+fn test_error_display_and_source() {
+    use std::error::Error as _;
+
+    assert_eq!(
+        Error::InvalidTtlvValue(TtlvType::Boolean).to_string(),
+        "Invalid value for TTLV type Boolean (0x06)"
+    );
+    assert_eq!(
+        Error::InvalidTtlvValueLength {
+            expected: 4,
+            actual: 8,
+            r#type: TtlvType::Integer
+        }
+        .to_string(),
+        "Invalid TTLV length 8 for type Integer (0x02), expected 4"
+    );
+    assert_eq!(Error::InvalidTtlvType(0).to_string(), "Invalid TTLV type 0x00");
+    assert!(Error::InvalidTtlvType(0).source().is_none());
+
+    // The underlying IO error is the source of an IO error
+    let err = TtlvInteger::read(&mut Cursor::new(&[0u8; 2])).unwrap_err();
+    assert!(err.to_string().starts_with("IO error: "));
+    let source = err.source().unwrap().downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(source.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // Errors can be converted to IO errors, e.g. for use in Read and Write implementations
+    let io_err = std::io::Error::from(err);
+    assert_eq!(io_err.kind(), std::io::ErrorKind::UnexpectedEof);
+    let io_err = std::io::Error::from(Error::TrailingBytes(2));
+    assert_eq!(io_err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(io_err.to_string(), "2 bytes remain after the end of the TTLV item");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_tag_type_and_length() {
+    use crate::types::TtlvLength;
+
+    let tag = TtlvTag::from_str("0x42000A").unwrap();
+    assert_eq!(serde_json::to_string(&tag).unwrap(), r#""0x42000A""#);
+    assert_eq!(serde_json::from_str::<TtlvTag>(r#""0x42000A""#).unwrap(), tag);
+    assert!(serde_json::from_str::<TtlvTag>(r#""0x1000000""#).is_err());
+
+    assert_eq!(serde_json::to_string(&TtlvType::TextString).unwrap(), r#""TextString""#);
+    assert_eq!(
+        serde_json::to_string(&TtlvType::try_from(0x80).unwrap()).unwrap(),
+        r#""0x80""#
+    );
+    assert_eq!(
+        serde_json::from_str::<TtlvType>(r#""TextString""#).unwrap(),
+        TtlvType::TextString
+    );
+    assert_eq!(
+        serde_json::from_str::<TtlvType>(r#""0x07""#).unwrap(),
+        TtlvType::TextString
+    );
+    assert_eq!(serde_json::from_str::<TtlvType>("7").unwrap(), TtlvType::TextString);
+    assert_eq!(
+        serde_json::from_str::<TtlvType>(r#""0x80""#).unwrap(),
+        TtlvType::try_from(0x80).unwrap()
+    );
+    assert!(serde_json::from_str::<TtlvType>(r#""Unknown""#).is_err());
+    assert!(serde_json::from_str::<TtlvType>("0").is_err());
+    assert!(serde_json::from_str::<TtlvType>("256").is_err());
+
+    let len = TtlvLength::new(16);
+    assert_eq!(serde_json::to_string(&len).unwrap(), "16");
+    assert_eq!(serde_json::from_str::<TtlvLength>("16").unwrap(), len);
+}
+
+#[test]
+fn test_ttlv_tag_new_and_macro() {
+    const TAG: TtlvTag = TtlvTag::new(0x42000A);
+    assert_eq!(TAG, TtlvTag::from_str("0x42000A").unwrap());
+    assert_eq!(crate::ttlv_tag!(0x42000A), TAG);
+    assert_eq!(crate::ttlv_tag!(0xFFFFFF), TtlvTag::try_from(0xFFFFFFu32).unwrap());
+    assert!(std::panic::catch_unwind(|| TtlvTag::new(0x1000000)).is_err());
+}
+
+#[test]
+fn test_repair_lengths() {
+    use crate::types::{repair_lengths, StructureBuilder, TtlvNode};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    // 0x420001 { 0x420002: Integer, 0x420003 { 0x420004: Text String } }, the inner Structure starts at offset 24
+    let good = StructureBuilder::new(tag(0x420001))
+        .int(tag(0x420002), 3)
+        .structure(StructureBuilder::new(tag(0x420003)).text(tag(0x420004), "abc"))
+        .build();
+    assert_eq!(repair_lengths(&good).unwrap(), good);
+
+    let with_lens = |root_len: u32, inner_len: u32| {
+        let mut buf = good.clone();
+        buf[4..8].copy_from_slice(&root_len.to_be_bytes());
+        buf[28..32].copy_from_slice(&inner_len.to_be_bytes());
+        buf
+    };
+
+    // The root length is ignored as the root spans all of the bytes
+    let bad = with_lens(0, 16);
+    assert!(TtlvNode::parse(&bad).is_err());
+    assert_eq!(repair_lengths(&bad).unwrap(), good);
+
+    // A length that excludes the padding of the last item, or that overruns the enclosing Structure
+    assert_eq!(repair_lengths(&with_lens(35, 11)).unwrap(), good);
+    assert_eq!(repair_lengths(&with_lens(40, 0xFFFF)).unwrap(), good);
+
+    // The lengths of other items are trusted
+    let mut bad = good.clone();
+    bad[39] = 0xFF;
+    assert!(repair_lengths(&bad).is_err());
+    assert!(repair_lengths(&good[..good.len() - 1]).is_err());
+}
+
+#[test]
+fn test_graft_encoded_items() {
+    use crate::types::{append_encoded, concat_structures, insert_encoded, subtree_by_tag, StructureBuilder};
+
+    let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+
+    // 0x420001 { 0x420002: Integer, 0x420003 { 0x420004: Text String } }, the inner Structure starts at offset 24
+    let build = |extra: bool| {
+        let mut inner = StructureBuilder::new(tag(0x420003)).text(tag(0x420004), "abc");
+        if extra {
+            inner = inner.int(tag(0x420005), 7);
+        }
+        StructureBuilder::new(tag(0x420001))
+            .int(tag(0x420002), 3)
+            .structure(inner)
+            .build()
+    };
+    let extra = StructureBuilder::new(tag(0x420099)).int(tag(0x420005), 7).build();
+    let item = subtree_by_tag(&extra, tag(0x420005)).unwrap().unwrap();
+
+    // Append an item taken from another message to the inner Structure
+    let mut buf = build(false);
+    append_encoded(&mut buf, 24, item).unwrap();
+    assert_eq!(buf, build(true));
+
+    // Insert it before the Text String instead
+    let mut buf = build(false);
+    insert_encoded(&mut buf, 32, item).unwrap();
+    let expected = StructureBuilder::new(tag(0x420001))
+        .int(tag(0x420002), 3)
+        .structure(
+            StructureBuilder::new(tag(0x420003))
+                .int(tag(0x420005), 7)
+                .text(tag(0x420004), "abc"),
+        )
+        .build();
+    assert_eq!(buf, expected);
+
+    // Items can only be appended to a Structure, and must themselves be complete
+    let mut buf = build(false);
+    assert_matches!(
+        append_encoded(&mut buf, 8, item),
+        Err(Error::UnexpectedTtlvType {
+            expected: TtlvType::Structure,
+            actual: TtlvType::Integer
+        })
+    );
+    assert!(append_encoded(&mut buf, 24, &item[..12]).is_err());
+    assert_eq!(buf, build(false));
+
+    // Concatenating Structures keeps the items in order under the new tag
+    let first = StructureBuilder::new(tag(0x420010)).int(tag(0x420002), 3).build();
+    let second = StructureBuilder::new(tag(0x420011))
+        .structure(StructureBuilder::new(tag(0x420003)).text(tag(0x420004), "abc"))
+        .build();
+    assert_eq!(
+        concat_structures(tag(0x420001), &[&first, &second]).unwrap(),
+        build(false)
+    );
+    assert_eq!(
+        concat_structures(tag(0x420001), &[]).unwrap(),
+        StructureBuilder::new(tag(0x420001)).build()
+    );
+    assert!(concat_structures(tag(0x420001), &[item]).is_err());
+    assert!(concat_structures(tag(0x420001), &[&build(false)[..40]]).is_err());
+}
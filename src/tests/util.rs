@@ -134,3 +134,69 @@ fn test_from_diag_string_with_tag_map() {
           Tag: Attribute Value (0x42000B), Type: Integer (0x02), Data: <redacted>"#;
     assert_eq!(expected_pretty_str, pretty_printer.from_diag_string(diag_str));
 }
+
+#[test]
+fn test_extension_type() {
+    let ttlv_bytes = hex::decode("AAAAAA0100000010BBBBBB0B000000030102030000000000").unwrap();
+    let pretty_printer = PrettyPrinter::default();
+
+    let expected_pretty_str =
+        "Tag: 0xAAAAAA, Type: Structure (0x01), Data:\n  Tag: 0xBBBBBB, Type: Extension(0x0B), Data: 010203\n";
+    assert_eq!(expected_pretty_str, pretty_printer.to_string(&ttlv_bytes));
+
+    let diag_str = pretty_printer.to_diag_string(&ttlv_bytes);
+    assert_eq!("AAAAAA[BBBBBBx0B:]", diag_str);
+
+    let expected_pretty_str =
+        "Tag: 0xAAAAAA, Type: Structure (0x01), Data: \n  Tag: 0xBBBBBB, Type: Extension(0x0B), Data: <redacted>";
+    assert_eq!(expected_pretty_str, pretty_printer.from_diag_string(&diag_str));
+}
+
+#[test]
+fn test_to_string_with_max_depth() {
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000028",
+        "BBBBBB02000000040000000100000000",
+        "CCCCCC0100000010",
+        "DDDDDD02000000040000000200000000"
+    ))
+    .unwrap();
+    let mut pretty_printer = PrettyPrinter::default();
+
+    pretty_printer.with_max_depth(0);
+    let expected_pretty_str = "Tag: 0xAAAAAA, Type: Structure (0x01), Data: <... 40 bytes of nested TTLV>\n";
+    assert_eq!(expected_pretty_str, pretty_printer.to_string(&ttlv_bytes));
+
+    pretty_printer.with_max_depth(1);
+    let expected_pretty_str = r#"Tag: 0xAAAAAA, Type: Structure (0x01), Data:
+  Tag: 0xBBBBBB, Type: Integer (0x02), Data: 0x000001 (1)
+  Tag: 0xCCCCCC, Type: Structure (0x01), Data: <... 16 bytes of nested TTLV>
+"#;
+    assert_eq!(expected_pretty_str, pretty_printer.to_string(&ttlv_bytes));
+
+    pretty_printer.with_max_depth(2);
+    assert_eq!(
+        PrettyPrinter::default().to_string(&ttlv_bytes),
+        pretty_printer.to_string(&ttlv_bytes)
+    );
+
+    // Suppressed items are still parsed
+    let mut broken_ttlv_bytes = ttlv_bytes;
+    broken_ttlv_bytes[35] = 0x00;
+    pretty_printer.with_max_depth(1);
+    assert!(pretty_printer.to_string(&broken_ttlv_bytes).contains("ERROR: "));
+}
+
+#[test]
+fn test_from_diag_string_with_many_structures() {
+    // Each structure used to be handled by recursing, which for long input could exhaust the call stack
+    let count = 100_000;
+    let diag_str = "AA[".to_string() + &"BB[CCi]".repeat(count) + "]";
+
+    let pretty_str = PrettyPrinter::default().from_diag_string(&diag_str);
+    assert_eq!(pretty_str.lines().count(), 1 + count * 2);
+    assert_eq!(
+        pretty_str.lines().last(),
+        Some("    Tag: 0x00CC, Type: Integer (0x02), Data: <redacted>")
+    );
+}
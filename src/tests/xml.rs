@@ -0,0 +1,211 @@
+use serde_derive::{Deserialize, Serialize};
+
+#[allow(unused_imports)]
+use pretty_assertions::{assert_eq, assert_ne};
+
+use assert_matches::assert_matches;
+
+use crate::error::{ErrorKind, MalformedTtlvError};
+use crate::tests::fixtures;
+use crate::types::{ByteOffset, TtlvType};
+use crate::xml::{ttlv_to_xml, xml_to_ttlv};
+use crate::{from_xml, to_xml};
+
+// Fields are wrapped in transparent newtypes so that the same type can be both serialized, which uses the type name
+// as the tag, and deserialized, which uses the field name as the tag.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "0xAAAAAA")]
+struct AllTypes {
+    #[serde(rename = "0xAAAA01")]
+    integer: Integer,
+    #[serde(rename = "0xAAAA02")]
+    long_integer: LongInteger,
+    #[serde(rename = "0xAAAA03")]
+    enumeration: Enumeration,
+    #[serde(rename = "0xAAAA04")]
+    boolean: Boolean,
+    #[serde(rename = "0xAAAA05")]
+    text: Text,
+    #[serde(rename = "0xAAAA06")]
+    bytes: Bytes,
+    #[serde(rename = "0xBBBBBB")]
+    inner: Inner,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Transparent:0xAAAA01")]
+struct Integer(i32);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Transparent:0xAAAA02")]
+struct LongInteger(i64);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "0xAAAA03")]
+enum Enumeration {
+    #[serde(rename = "0x80000001")]
+    Extension,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Transparent:0xAAAA04")]
+struct Boolean(bool);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Transparent:0xAAAA05")]
+struct Text(String);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Transparent:0xAAAA06")]
+struct Bytes(#[serde(with = "serde_bytes")] Vec<u8>);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "0xBBBBBB")]
+struct Inner {
+    #[serde(rename = "0xBBBB01")]
+    integer: InnerInteger,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "Transparent:0xBBBB01")]
+struct InnerInteger(i32);
+
+#[test]
+fn test_to_and_from_xml() {
+    let value = AllTypes {
+        integer: Integer(-1),
+        long_integer: LongInteger(1234567890123),
+        enumeration: Enumeration::Extension,
+        boolean: Boolean(true),
+        text: Text("<Hello & Goodbye>".into()),
+        bytes: Bytes(vec![0xDE, 0xAD, 0xBE, 0xEF]),
+        inner: Inner {
+            integer: InnerInteger(42),
+        },
+    };
+
+    let xml = to_xml(&value).unwrap();
+    let expected_xml = r#"<TTLV tag="0xAAAAAA">
+  <TTLV tag="0xAAAA01" type="Integer" value="-1"/>
+  <TTLV tag="0xAAAA02" type="LongInteger" value="1234567890123"/>
+  <TTLV tag="0xAAAA03" type="Enumeration" value="0x80000001"/>
+  <TTLV tag="0xAAAA04" type="Boolean" value="true"/>
+  <TTLV tag="0xAAAA05" type="TextString" value="&lt;Hello &amp; Goodbye&gt;"/>
+  <TTLV tag="0xAAAA06" type="ByteString" value="DEADBEEF"/>
+  <TTLV tag="0xBBBBBB">
+    <TTLV tag="0xBBBB01" type="Integer" value="42"/>
+  </TTLV>
+</TTLV>"#;
+    assert_eq!(xml, expected_xml);
+
+    let deserialized: AllTypes = from_xml(&xml).unwrap();
+    assert_eq!(deserialized, value);
+}
+
+#[test]
+fn test_binary_to_xml_to_binary_round_trip() {
+    // The KMIP 1.0 use case response contains Structure, Integer, DateTime, Enumeration and TextString items
+    let ttlv_bytes = fixtures::kmip_10_create_destroy_use_case::ttlv_bytes();
+    let xml = ttlv_to_xml(&ttlv_bytes).unwrap();
+    assert!(xml.starts_with("<ResponseMessage>\n  <ResponseHeader>\n    <ProtocolVersion>\n"));
+    assert!(xml.contains(r#"<ProtocolVersionMajor type="Integer" value="1"/>"#));
+    assert!(xml.contains(r#"<TimeStamp type="DateTime" value="1258022850"/>"#));
+    assert!(xml.ends_with("</ResponseMessage>"));
+    assert_eq!(xml_to_ttlv(&xml).unwrap(), ttlv_bytes);
+
+    // Big Integer, Boolean, Byte String and an empty Structure
+    let ttlv_bytes = hex::decode(concat!(
+        "AAAAAA0100000040",
+        "AAAA010400000010FFFFFFFFFFFFFFFF8000000000000000",
+        "AAAA020600000008000000000000000",
+        "0AAAA0308000000030102030000000000",
+        "AAAA040100000000",
+    ))
+    .unwrap();
+    let xml = ttlv_to_xml(&ttlv_bytes).unwrap();
+    assert!(xml.contains(r#"<TTLV tag="0xAAAA01" type="BigInteger" value="FFFFFFFFFFFFFFFF8000000000000000"/>"#));
+    assert!(xml.contains(r#"<TTLV tag="0xAAAA02" type="Boolean" value="false"/>"#));
+    assert!(xml.contains(r#"<TTLV tag="0xAAAA03" type="ByteString" value="010203"/>"#));
+    assert_eq!(xml_to_ttlv(&xml).unwrap(), ttlv_bytes);
+}
+
+#[test]
+fn test_xml_with_kmip_element_names() {
+    let xml = r#"<?xml version="1.0"?>
+<!-- elements are named after KMIP tags, Structures have no type attribute -->
+<ProtocolVersion>
+  <ProtocolVersionMajor type="Integer" value="1"></ProtocolVersionMajor>
+  <ProtocolVersionMinor type="Integer" value="2"/>
+</ProtocolVersion>"#;
+    let ttlv_bytes = hex::decode(concat!(
+        "4200690100000020",
+        "42006A02000000040000000100000000",
+        "42006B02000000040000000200000000",
+    ))
+    .unwrap();
+    assert_eq!(xml_to_ttlv(xml).unwrap(), ttlv_bytes);
+
+    // A tag attribute takes precedence over the element name
+    let xml = r#"<RootType tag="0xAAAAAA" type="Structure">
+  <A tag="0xBBBBBB" type="Integer" value="1"></A>
+  <B tag="0xCCCCCC" type="Integer" value="2"/>
+</RootType>"#;
+    assert_eq!(xml_to_ttlv(xml).unwrap(), fixtures::simple::ttlv_bytes());
+}
+
+#[test]
+fn test_malformed_xml() {
+    let err = xml_to_ttlv(r#"<TTLV tag="0xAAAAAA" type="Structure">"#).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedXml(_));
+
+    let err = xml_to_ttlv(r#"<TTLV tag="0xAAAAAA" type="Unknown"/>"#).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedXml(msg) if msg == "unknown type 'Unknown'");
+
+    let err = xml_to_ttlv(r#"<TTLV type="Integer" value="1"/>"#).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedXml(msg) if msg == "missing tag attribute");
+
+    let err = xml_to_ttlv(r#"<Unknown type="Integer" value="1"/>"#).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedXml(msg) if msg == "unknown tag name 'Unknown'");
+
+    let err = xml_to_ttlv(r#"<TTLV tag="0xAAAAAA" type="Integer"/>"#).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedXml(msg) if msg == "missing value attribute");
+
+    let err = xml_to_ttlv(
+        r#"<TTLV tag="0xAAAAAA" type="Structure"><TTLV tag="0xBBBBBB" type="Boolean" value="yes"/></TTLV>"#,
+    )
+    .unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidValue {
+            r#type: TtlvType::Boolean
+        })
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(38)));
+
+    // Big Integer values must be padded to a multiple of 8 bytes
+    let err = xml_to_ttlv(r#"<TTLV tag="0xAAAAAA" type="BigInteger" value="0102"/>"#).unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedTtlv(MalformedTtlvError::InvalidValue {
+            r#type: TtlvType::BigInteger
+        })
+    );
+    assert_eq!(
+        xml_to_ttlv(r#"<TTLV tag="0xAAAAAA" type="BigInteger" value="0000000000000102"/>"#).unwrap(),
+        hex::decode("AAAAAA04000000080000000000000102").unwrap()
+    );
+
+    let err =
+        xml_to_ttlv(r#"<TTLV tag="0xAAAAAA" type="Structure"/><TTLV tag="0xAAAAAA" type="Structure"/>"#).unwrap_err();
+    assert_matches!(err.kind(), ErrorKind::MalformedXml(msg) if msg == "more than one root element");
+
+    let err = xml_to_ttlv(
+        r#"<TTLV tag="0xAAAAAA" type="Integer" value="1"><TTLV tag="0xBBBBBB" type="Integer" value="2"/></TTLV>"#,
+    )
+    .unwrap_err();
+    assert_matches!(
+        err.kind(),
+        ErrorKind::MalformedXml(msg) if msg == "unexpected child element of a non-Structure element"
+    );
+    assert_eq!(err.location().offset(), Some(ByteOffset(46)));
+}
@@ -1,11 +1,14 @@
 //! Dynamic traits for sync or async use depending on the Cargo features used.
 //!
-//! By default the [AnySyncRead] trait is equivalent to `std::io::Read`.
+//! The [AnySyncRead] trait is the reader bound of `de::from_reader()`. With the `sync` feature enabled it is
+//! equivalent to `std::io::Read`.
 //!
-//! However, if this crate is built with either the `async-with-async-std` or `async-with-tokio` feature enabled then
-//! this trait instead becomes `async_std::io::ReadExt` or `tokio::io::AsyncReadExt` respectively.
+//! Otherwise, if this crate is built with the `async-with-tokio`, `async-with-async-std` or `async-with-futures`
+//! feature enabled then this trait instead becomes `tokio::io::AsyncReadExt`, `async_std::io::ReadExt` or
+//! `futures_util::io::AsyncReadExt` respectively, in that order of preference when more than one is enabled.
 //!
-//! This enables code that is otherwise identical to be re-used.
+//! Code that needs both the blocking and an async reader should instead use the functions in the
+//! `de::sync` and `de::asynch` modules which have fixed reader bounds.
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "sync")] {
@@ -20,5 +23,9 @@ cfg_if::cfg_if! {
         trait_set::trait_set! {
             pub trait AnySyncRead = async_std::io::ReadExt + std::marker::Unpin;
         }
+    } else if #[cfg(feature = "async-with-futures")] {
+        trait_set::trait_set! {
+            pub trait AnySyncRead = futures_util::io::AsyncReadExt + std::marker::Unpin;
+        }
     }
 }
@@ -18,13 +18,13 @@
 //! ttlv_wire.extend(b"\x00\x00\x00\x03"); // 4-byte big-endian integer value 3
 //! ttlv_wire.extend(b"\x00\x00\x00\x00"); // 4-byte padding
 //!
-//! // Create a cursor for "Read"ing from the buffer
-//! let mut cursor = std::io::Cursor::new(&ttlv_wire);
+//! // Create a slice for "Read"ing from the buffer, reading advances the slice past the bytes read
+//! let mut src = ttlv_wire.as_slice();
 //!
 //! // Deserialize the TTLV bytes
-//! let tag = TtlvTag::read(&mut cursor)?;
-//! let typ = TtlvType::read(&mut cursor)?;
-//! let val = TtlvInteger::read(&mut cursor)?; // reads the length and padding bytes as well
+//! let tag = TtlvTag::read(&mut src)?;
+//! let typ = TtlvType::read(&mut src)?;
+//! let val = TtlvInteger::read(&mut src)?; // reads the length and padding bytes as well
 //!
 //! // Verify the result
 //! assert_eq!(*tag, 0x660001);
@@ -41,11 +41,24 @@
 //! # Ok(())
 //! # }
 //! ```
-use std::{
+//!
+//! Rather than hand crafting TTLV bytes use a [StructureBuilder], which determines the lengths and padding for you, and
+//! to inspect or rewrite TTLV bytes without defining Rust types for them parse them into a [TtlvNode] tree. To scan
+//! TTLV bytes without allocating use a [TtlvTokenizer] or [walk()] them with a [TtlvVisitor]. To change individual
+//! items in existing TTLV bytes use [replace_value()], [insert_item()] and [remove_item()], to graft in already encoded
+//! items use [insert_encoded()] and [append_encoded()], and to merge TTLV Structures use [concat_structures()]. To
+//! pluck a single value out of TTLV bytes by its tag path use [find()], or to extract the bytes of a complete item use
+//! [subtree()] or [subtree_by_tag()]. To fix the TTLV Structure lengths written by a buggy encoder use
+//! [repair_lengths()].
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
     convert::TryFrom,
     fmt::{Debug, Display},
-    io::{Read, Write},
-    ops::Deref,
+    ops::{Deref, Range},
     str::FromStr,
 };
 
@@ -64,6 +77,7 @@ pub enum FieldType {
     TypeAndLengthAndValue, // used when serializing
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for FieldType {
     fn default() -> Self {
         Self::Tag
@@ -71,7 +85,7 @@ impl Default for FieldType {
 }
 
 impl Display for FieldType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             FieldType::Tag => f.write_str("Tag"),
             FieldType::Type => f.write_str("Type"),
@@ -89,7 +103,7 @@ impl Display for FieldType {
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ByteOffset(pub u64);
 
-impl std::ops::Deref for ByteOffset {
+impl core::ops::Deref for ByteOffset {
     type Target = u64;
 
     fn deref(&self) -> &Self::Target {
@@ -112,7 +126,7 @@ impl From<u64> for ByteOffset {
 impl TryFrom<usize> for ByteOffset {
     type Error = ();
 
-    fn try_from(value: usize) -> std::result::Result<Self, Self::Error> {
+    fn try_from(value: usize) -> core::result::Result<Self, Self::Error> {
         if value < (u64::MAX as usize) {
             Ok(ByteOffset(value as u64))
         } else {
@@ -121,12 +135,14 @@ impl TryFrom<usize> for ByteOffset {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> From<&std::io::Cursor<T>> for ByteOffset {
     fn from(cursor: &std::io::Cursor<T>) -> Self {
         ByteOffset(cursor.position())
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> From<std::io::Cursor<T>> for ByteOffset {
     fn from(cursor: std::io::Cursor<T>) -> Self {
         ByteOffset(cursor.position())
@@ -137,7 +153,14 @@ impl<T> From<std::io::Cursor<T>> for ByteOffset {
 #[derive(Debug)]
 #[allow(clippy::enum_variant_names)]
 pub enum Error {
+    #[cfg(feature = "std")]
     IoError(std::io::Error),
+    /// Not enough bytes remained in the source to read the next field (without the `std` feature only).
+    #[cfg(not(feature = "std"))]
+    UnexpectedEof,
+    /// Not enough space remained in the destination to write the next field (without the `std` feature only).
+    #[cfg(not(feature = "std"))]
+    WriteZero,
     InvalidTtlvTag(String),
     UnexpectedTtlvField {
         expected: FieldType,
@@ -151,16 +174,167 @@ pub enum Error {
         r#type: TtlvType,
     },
     InvalidTtlvValue(TtlvType),
+    /// The padding bytes following a value are not all zero (only reported by the `_strict` read functions).
+    InvalidTtlvPadding(TtlvType),
+    /// The length of the value exceeds the limit for its type, e.g. [TtlvBigInteger::DEFAULT_MAX_LEN].
+    TtlvValueLengthExceedsLimit {
+        length: u64,
+        r#type: TtlvType,
+    },
     InvalidStateMachineOperation,
+    /// The given number of bytes remain after the end of the TTLV item.
+    TrailingBytes(usize),
+    /// TTLV Structures are nested more deeply than the limit, e.g. [TtlvNode::MAX_NESTING_DEPTH].
+    NestingDepthExceedsLimit(usize),
+    /// No TTLV item starts at the given offset.
+    NoItemAtOffset(usize),
+    /// The TTLV item is not of the type required by the operation, e.g. [append_encoded()] requires a Structure.
+    UnexpectedTtlvType {
+        expected: TtlvType,
+        actual: TtlvType,
+    },
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
         Error::IoError(e)
     }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+/// Converts IO errors back to the original [std::io::Error] and other errors to one of kind
+/// [std::io::ErrorKind::InvalidData], e.g. for use in [std::io::Read] or [std::io::Write] implementations.
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::IoError(e) => e,
+            other => std::io::Error::new(std::io::ErrorKind::InvalidData, other),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Error::IoError(e) => write!(f, "IO error: {}", e),
+            #[cfg(not(feature = "std"))]
+            Error::UnexpectedEof => f.write_str("Unexpected end of input"),
+            #[cfg(not(feature = "std"))]
+            Error::WriteZero => f.write_str("Not enough space left in the output"),
+            Error::InvalidTtlvTag(v) => write!(f, "Invalid TTLV tag '{}'", v),
+            Error::UnexpectedTtlvField { expected, actual } => {
+                write!(f, "Expected TTLV field {} but found {}", expected, actual)
+            }
+            Error::UnsupportedTtlvType(v) => write!(f, "Unsupported TTLV type 0x{:02X}", v),
+            Error::InvalidTtlvType(v) => write!(f, "Invalid TTLV type 0x{:02X}", v),
+            Error::InvalidTtlvValueLength {
+                expected,
+                actual,
+                r#type,
+            } => write!(
+                f,
+                "Invalid TTLV length {} for type {}, expected {}",
+                actual, r#type, expected
+            ),
+            Error::InvalidTtlvValue(r#type) => write!(f, "Invalid value for TTLV type {}", r#type),
+            Error::InvalidTtlvPadding(r#type) => write!(f, "Non-zero padding bytes after TTLV {} value", r#type),
+            Error::TtlvValueLengthExceedsLimit { length, r#type } => {
+                write!(f, "TTLV {} value length {} exceeds the limit", r#type, length)
+            }
+            Error::InvalidStateMachineOperation => f.write_str("Invalid TTLV state machine operation"),
+            Error::TrailingBytes(n) => write!(f, "{} bytes remain after the end of the TTLV item", n),
+            Error::NestingDepthExceedsLimit(depth) => {
+                write!(f, "TTLV Structure nesting depth {} exceeds the limit", depth)
+            }
+            Error::NoItemAtOffset(offset) => write!(f, "No TTLV item starts at offset {}", offset),
+            Error::UnexpectedTtlvType { expected, actual } => {
+                write!(f, "Expected TTLV type {} but found {}", expected, actual)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+// --- TtlvRead & TtlvWrite -------------------------------------------------------------------------------------------
+
+/// A minimal source of bytes to deserialize TTLV from.
+///
+/// With the `std` feature enabled this is implemented for every type that implements [std::io::Read]. Without it it is
+/// instead implemented for `&[u8]`, which like [std::io::Read] for `&[u8]` advances the slice past the bytes that were
+/// read.
+pub trait TtlvRead {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+}
+
+/// A minimal sink of bytes to serialize TTLV to.
+///
+/// With the `std` feature enabled this is implemented for every type that implements [std::io::Write]. Without it it
+/// is instead implemented for `Vec<u8>` and for `&mut [u8]`, the latter advancing the slice past the bytes that were
+/// written.
+pub trait TtlvWrite {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Read + ?Sized> TtlvRead for T {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        std::io::Read::read_exact(self, buf).map_err(Error::IoError)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Write + ?Sized> TtlvWrite for T {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        std::io::Write::write_all(self, buf).map_err(Error::IoError)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl TtlvRead for &[u8] {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        if buf.len() > self.len() {
+            return Err(Error::UnexpectedEof);
+        }
+        let (head, tail) = self.split_at(buf.len());
+        buf.copy_from_slice(head);
+        *self = tail;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl TtlvWrite for &mut [u8] {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        if buf.len() > self.len() {
+            return Err(Error::WriteZero);
+        }
+        let (head, tail) = core::mem::take(self).split_at_mut(buf.len());
+        head.copy_from_slice(buf);
+        *self = tail;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl TtlvWrite for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
 
 // --- TtlvTag --------------------------------------------------------------------------------------------------------
 
@@ -168,24 +342,64 @@ pub type Result<T> = std::result::Result<T, Error>;
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.1 Item Tag](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_toc8560):
 /// > _An Item Tag is a three-byte binary unsigned integer, transmitted big endian, which contains a number that
-///   designates the specific Protocol Field or Object that the TTLV object represents._
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// > designates the specific Protocol Field or Object that the TTLV object represents._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TtlvTag(u32);
 
 impl TtlvTag {
-    pub fn read<T: Read>(src: &mut T) -> Result<Self> {
+    /// Create a tag from its numeric value, e.g. `TtlvTag::new(0x42000A)`.
+    ///
+    /// Use the [ttlv_tag!](crate::ttlv_tag) macro to have the value checked at compile time instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value does not fit in the three bytes of a TTLV tag, i.e. is greater than 0xFFFFFF. When
+    /// evaluated in a const context this is a compile time error. Use `TtlvTag::try_from(u32)` to handle such
+    /// values without panicking.
+    #[allow(clippy::no_effect, clippy::unnecessary_operation)]
+    pub const fn new(value: u32) -> Self {
+        // panic!() cannot be used in a const fn with our minimum supported Rust version so instead index out of
+        // bounds when the value is too large.
+        [()][(value > 0xFFFFFF) as usize];
+        TtlvTag(value)
+    }
+
+    pub fn read<T: TtlvRead>(src: &mut T) -> Result<Self> {
         let mut raw_item_tag = [0u8; 3];
         src.read_exact(&mut raw_item_tag)?;
         Ok(TtlvTag::from(raw_item_tag))
     }
 
-    pub fn write<T: Write>(&self, dst: &mut T) -> Result<()> {
-        dst.write_all(&<[u8; 3]>::from(self)).map_err(Error::IoError)
+    pub fn write<T: TtlvWrite>(&self, dst: &mut T) -> Result<()> {
+        dst.write_all(&<[u8; 3]>::from(self))
     }
 }
 
+/// Create a [TtlvTag](crate::types::TtlvTag) from a numeric value, checking at compile time that the value fits in
+/// the three bytes of a TTLV tag.
+///
+/// ```
+/// use kmip_ttlv::{ttlv_tag, types::TtlvTag};
+///
+/// const UNIQUE_IDENTIFIER: TtlvTag = ttlv_tag!(0x420094);
+/// assert_eq!(*UNIQUE_IDENTIFIER, 0x420094);
+/// ```
+///
+/// Values that are too large fail to compile:
+///
+/// ```compile_fail
+/// let tag = kmip_ttlv::ttlv_tag!(0x1000000);
+/// ```
+#[macro_export]
+macro_rules! ttlv_tag {
+    ($value:expr) => {{
+        const TAG: $crate::types::TtlvTag = $crate::types::TtlvTag::new($value);
+        TAG
+    }};
+}
+
 impl Debug for TtlvTag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("0x{:0X}", &self.0))
     }
 }
@@ -201,21 +415,24 @@ impl Deref for TtlvTag {
 impl FromStr for TtlvTag {
     type Err = Error;
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let v =
-            u32::from_str_radix(s.trim_start_matches("0x"), 16).map_err(|_| Error::InvalidTtlvTag(s.to_string()))?;
-        Ok(TtlvTag(v))
+    /// Parse a tag in hex form, with or without a `0x` prefix, e.g. `0x42000A`. Values that do not fit in three bytes
+    /// are rejected.
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        match u32::from_str_radix(s.trim_start_matches("0x"), 16) {
+            Ok(v) if v <= 0xFFFFFF => Ok(TtlvTag(v)),
+            _ => Err(Error::InvalidTtlvTag(s.to_string())),
+        }
     }
 }
 
-impl std::fmt::Display for TtlvTag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for TtlvTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "0x{:06X}", self)
     }
 }
 
-impl std::fmt::UpperHex for TtlvTag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::UpperHex for TtlvTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:X}", self.0)
     }
 }
@@ -233,6 +450,18 @@ impl From<&TtlvTag> for [u8; 3] {
     }
 }
 
+impl TryFrom<u32> for TtlvTag {
+    type Error = Error;
+
+    fn try_from(value: u32) -> core::result::Result<Self, Self::Error> {
+        if value <= 0xFFFFFF {
+            Ok(TtlvTag(value))
+        } else {
+            Err(Error::InvalidTtlvTag(alloc::format!("0x{:X}", value)))
+        }
+    }
+}
+
 impl From<[u8; 3]> for TtlvTag {
     fn from(b: [u8; 3]) -> Self {
         TtlvTag(u32::from_be_bytes([0x00u8, b[0], b[1], b[2]]))
@@ -247,39 +476,123 @@ impl From<&[u8; 3]> for TtlvTag {
 
 // --- TtlvType -------------------------------------------------------------------------------------------------------
 
+/// A TTLV type byte in the range 0x0B - 0xFF, i.e. one that is not defined by the KMIP specification.
+///
+/// Use `TtlvExtensionType::try_from(u8)` to create one. Type bytes defined by the KMIP specification, and 0x00, are
+/// rejected with [Error::InvalidTtlvType] so that a [TtlvType::Extension] can never be mistaken for a defined type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TtlvExtensionType(u8);
+
+impl Deref for TtlvExtensionType {
+    type Target = u8;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl core::fmt::UpperHex for TtlvExtensionType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl TryFrom<u8> for TtlvExtensionType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> core::result::Result<Self, Self::Error> {
+        match value {
+            0x00..=0x0A => Err(Error::InvalidTtlvType(value)),
+            _ => Ok(TtlvExtensionType(value)),
+        }
+    }
+}
+
+impl From<TtlvExtensionType> for u8 {
+    fn from(extension_type: TtlvExtensionType) -> Self {
+        extension_type.0
+    }
+}
+
 /// A type for (de)serializing a TTLV Type.
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.2 Item Type](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_toc8562):
 /// > _An Item Type is a byte containing a coded value that indicates the data type of the data object._
-#[repr(u8)]
+///
+/// Type bytes that are not defined by the KMIP specification, e.g. experimental or vendor defined types, are
+/// represented by [TtlvType::Extension] so that TTLV containing them can be inspected and passed through rather than
+/// rejected.
+///
+/// **Compatibility note:** this enum is `#[non_exhaustive]` as more variants may be added in future and since the
+/// addition of [TtlvType::Extension] it is no longer a fieldless `#[repr(u8)]` enum and so cannot be cast using
+/// `as u8`. Use `u8::from(ttlv_type)` instead.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum TtlvType {
-    Structure = 0x01,
-    Integer = 0x02,
-    LongInteger = 0x03,
-    BigInteger = 0x04,
-    Enumeration = 0x05,
-    Boolean = 0x06,
-    TextString = 0x07,
-    ByteString = 0x08,
-    DateTime = 0x09,
-    // Interval = 0x0A,
+    Structure,
+    Integer,
+    LongInteger,
+    BigInteger,
+    Enumeration,
+    Boolean,
+    TextString,
+    ByteString,
+    DateTime,
+    Interval,
+    /// A type byte which is not defined by the KMIP specification.
+    Extension(TtlvExtensionType),
 }
 
 impl TtlvType {
-    pub fn read<T: Read>(src: &mut T) -> Result<Self> {
+    pub fn read<T: TtlvRead>(src: &mut T) -> Result<Self> {
         let mut raw_item_type = [0u8; 1];
         src.read_exact(&mut raw_item_type)?;
         TtlvType::try_from(raw_item_type[0])
     }
 
-    pub fn write<T: Write>(&self, dst: &mut T) -> Result<()> {
-        dst.write_all(&[*self as u8]).map_err(Error::IoError)
+    pub fn write<T: TtlvWrite>(&self, dst: &mut T) -> Result<()> {
+        dst.write_all(&[u8::from(*self)])
+    }
+
+    /// The name of the type as used by the KMIP XML encoding, e.g. "TextString".
+    #[cfg(any(feature = "serde", feature = "xml"))]
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            TtlvType::Structure => "Structure",
+            TtlvType::Integer => "Integer",
+            TtlvType::LongInteger => "LongInteger",
+            TtlvType::BigInteger => "BigInteger",
+            TtlvType::Enumeration => "Enumeration",
+            TtlvType::Boolean => "Boolean",
+            TtlvType::TextString => "TextString",
+            TtlvType::ByteString => "ByteString",
+            TtlvType::DateTime => "DateTime",
+            TtlvType::Interval => "Interval",
+            TtlvType::Extension(_) => "Extension",
+        }
+    }
+
+    /// The inverse of [TtlvType::name], except that there is no name for [TtlvType::Extension].
+    #[cfg(any(feature = "serde", feature = "xml"))]
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Structure" => Some(TtlvType::Structure),
+            "Integer" => Some(TtlvType::Integer),
+            "LongInteger" => Some(TtlvType::LongInteger),
+            "BigInteger" => Some(TtlvType::BigInteger),
+            "Enumeration" => Some(TtlvType::Enumeration),
+            "Boolean" => Some(TtlvType::Boolean),
+            "TextString" => Some(TtlvType::TextString),
+            "ByteString" => Some(TtlvType::ByteString),
+            "DateTime" => Some(TtlvType::DateTime),
+            "Interval" => Some(TtlvType::Interval),
+            _ => None,
+        }
     }
 }
 
-impl std::fmt::Display for TtlvType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for TtlvType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             TtlvType::Structure => f.write_str("Structure (0x01)"),
             TtlvType::Integer => f.write_str("Integer (0x02)"),
@@ -290,6 +603,8 @@ impl std::fmt::Display for TtlvType {
             TtlvType::TextString => f.write_str("TextString (0x07)"),
             TtlvType::ByteString => f.write_str("ByteString (0x08)"),
             TtlvType::DateTime => f.write_str("DateTime (0x09)"),
+            TtlvType::Interval => f.write_str("Interval (0x0A)"),
+            TtlvType::Extension(v) => f.write_fmt(format_args!("Extension(0x{:02X})", v)),
         }
     }
 }
@@ -297,7 +612,7 @@ impl std::fmt::Display for TtlvType {
 impl TryFrom<u8> for TtlvType {
     type Error = Error;
 
-    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+    fn try_from(value: u8) -> core::result::Result<Self, Self::Error> {
         match value {
             0x01 => Ok(TtlvType::Structure),
             0x02 => Ok(TtlvType::Integer),
@@ -308,16 +623,33 @@ impl TryFrom<u8> for TtlvType {
             0x07 => Ok(TtlvType::TextString),
             0x08 => Ok(TtlvType::ByteString),
             0x09 => Ok(TtlvType::DateTime),
-            // 0x0A => Ok(TtlvType::Interval),
-            0x0A => Err(Error::UnsupportedTtlvType(0x0A)),
-            _ => Err(Error::InvalidTtlvType(value)),
+            0x0A => Ok(TtlvType::Interval),
+            _ => TtlvExtensionType::try_from(value).map(TtlvType::Extension),
+        }
+    }
+}
+
+impl From<TtlvType> for u8 {
+    fn from(item_type: TtlvType) -> Self {
+        match item_type {
+            TtlvType::Structure => 0x01,
+            TtlvType::Integer => 0x02,
+            TtlvType::LongInteger => 0x03,
+            TtlvType::BigInteger => 0x04,
+            TtlvType::Enumeration => 0x05,
+            TtlvType::Boolean => 0x06,
+            TtlvType::TextString => 0x07,
+            TtlvType::ByteString => 0x08,
+            TtlvType::DateTime => 0x09,
+            TtlvType::Interval => 0x0A,
+            TtlvType::Extension(v) => *v,
         }
     }
 }
 
 impl From<TtlvType> for [u8; 1] {
     fn from(item_type: TtlvType) -> Self {
-        [item_type as u8]
+        [u8::from(item_type)]
     }
 }
 
@@ -327,7 +659,7 @@ impl From<TtlvType> for [u8; 1] {
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.3 Item Length](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Toc236497868):
 /// > _An Item Length is a 32-bit binary integer, transmitted big-endian, containing the number of bytes in the Item
-///   Value._
+/// > Value._
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TtlvLength(u32);
 
@@ -336,19 +668,19 @@ impl TtlvLength {
         Self(value)
     }
 
-    pub fn read<T: Read>(src: &mut T) -> Result<Self> {
+    pub fn read<T: TtlvRead>(src: &mut T) -> Result<Self> {
         let mut value_length = [0u8; 4];
         src.read_exact(&mut value_length)?;
         Ok(Self(u32::from_be_bytes(value_length)))
     }
 
-    pub fn write<T: Write>(&self, dst: &mut T) -> Result<()> {
-        dst.write_all(&self.0.to_be_bytes()).map_err(Error::IoError)
+    pub fn write<T: TtlvWrite>(&self, dst: &mut T) -> Result<()> {
+        dst.write_all(&self.0.to_be_bytes())
     }
 }
 
 impl Debug for TtlvLength {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("0x{:0X}", &self.0))
     }
 }
@@ -361,14 +693,14 @@ impl Deref for TtlvLength {
     }
 }
 
-impl std::fmt::Display for TtlvLength {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for TtlvLength {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "0x{:08X}", self)
     }
 }
 
-impl std::fmt::UpperHex for TtlvLength {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::UpperHex for TtlvLength {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:X}", self.0)
     }
 }
@@ -429,16 +761,30 @@ pub trait SerializableTtlvType: Sized + Deref {
         }
     }
 
-    fn read_pad_bytes<T: Read>(src: &mut T, value_len: u32) -> Result<()> {
+    fn read_pad_bytes<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<()> {
+        let num_pad_bytes = Self::calc_pad_bytes(value_len) as usize;
+        if num_pad_bytes > 0 {
+            let mut dst = [0u8; 8];
+            src.read_exact(&mut dst[..num_pad_bytes])?;
+        }
+        Ok(())
+    }
+
+    /// Like [Self::read_pad_bytes] but fails with [Error::InvalidTtlvPadding] if any of the padding bytes is non-zero,
+    /// as required by the KMIP specification.
+    fn read_pad_bytes_strict<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<()> {
         let num_pad_bytes = Self::calc_pad_bytes(value_len) as usize;
         if num_pad_bytes > 0 {
             let mut dst = [0u8; 8];
             src.read_exact(&mut dst[..num_pad_bytes])?;
+            if dst.iter().any(|&b| b != 0) {
+                return Err(Error::InvalidTtlvPadding(Self::TTLV_TYPE));
+            }
         }
         Ok(())
     }
 
-    fn write_pad_bytes<T: Write>(dst: &mut T, value_len: u32) -> Result<()> {
+    fn write_pad_bytes<T: TtlvWrite>(dst: &mut T, value_len: u32) -> Result<()> {
         let num_pad_bytes = Self::calc_pad_bytes(value_len) as usize;
         if num_pad_bytes > 0 {
             const PADDING_BYTES: [u8; 8] = [0; 8];
@@ -447,7 +793,7 @@ pub trait SerializableTtlvType: Sized + Deref {
         Ok(())
     }
 
-    fn read<T: Read>(src: &mut T) -> Result<Self> {
+    fn read<T: TtlvRead>(src: &mut T) -> Result<Self> {
         // The TTLV T_ype has already been read by the caller in order to determine which Primitive struct to use so
         // we only have to read the L_ength and and the V_alue.
         let mut value_len = [0u8; 4];
@@ -458,18 +804,52 @@ pub trait SerializableTtlvType: Sized + Deref {
         Ok(v)
     }
 
+    /// Like [Self::read] but verifies that the padding bytes following the value are zero, see
+    /// [Self::read_pad_bytes_strict].
+    fn read_strict<T: TtlvRead>(src: &mut T) -> Result<Self> {
+        let mut value_len = [0u8; 4];
+        src.read_exact(&mut value_len)?; // read L_ength
+        let value_len = u32::from_be_bytes(value_len);
+        let v = Self::read_value(src, value_len)?; // read V_alue
+        Self::read_pad_bytes_strict(src, value_len)?; // read and verify 8-byte alignment padding bytes
+        Ok(v)
+    }
+
     // Writes the TLV part of TTLV, i.e. the type, length and value. It doesn't write the preceeding tag as that is
     // not part of the primitive value but is part of the callers context and only they can know which tag value to
     // write.
-    fn write<T: Write>(&self, dst: &mut T) -> Result<()> {
-        dst.write_all(&[Self::TTLV_TYPE as u8])?; // write T_ype
+    fn write<T: TtlvWrite>(&self, dst: &mut T) -> Result<()> {
+        self.write_returning_len(dst).map(|_| ())
+    }
+
+    /// Like [Self::write] but returns the length of the value written, excluding padding.
+    fn write_returning_len<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32> {
+        dst.write_all(&[u8::from(Self::TTLV_TYPE)])?; // write T_ype
         let value_len = self.write_length_and_value(dst)?; // write L_ength and V_alue
-        Self::write_pad_bytes(dst, value_len) // Write 8-byte alignment padding bytes
+        Self::write_pad_bytes(dst, value_len)?; // Write 8-byte alignment padding bytes
+        Ok(value_len)
     }
 
-    fn read_value<T: Read>(src: &mut T, value_len: u32) -> Result<Self>;
+    fn read_value<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<Self>;
+
+    fn write_length_and_value<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32>;
+}
 
-    fn write_length_and_value<T: Write>(&self, dst: &mut T) -> Result<u32>;
+// E.g. define_inner_type_conversions!(MyType, i32) would implement `From<i32> for MyType` and `From<MyType> for i32`
+// where MyType is a tuple struct wrapping a single public i32 value.
+macro_rules! define_inner_type_conversions {
+    ($NEW_TYPE_NAME:ident, $RUST_TYPE:ty) => {
+        impl From<$RUST_TYPE> for $NEW_TYPE_NAME {
+            fn from(v: $RUST_TYPE) -> Self {
+                $NEW_TYPE_NAME(v)
+            }
+        }
+        impl From<$NEW_TYPE_NAME> for $RUST_TYPE {
+            fn from(v: $NEW_TYPE_NAME) -> Self {
+                v.0
+            }
+        }
+    };
 }
 
 // E.g. simple_primitive!(MyType, ItemType::Integer, i32, 4) would define a new Rust struct called MyType which wraps an
@@ -490,10 +870,11 @@ macro_rules! define_fixed_value_length_serializable_ttlv_type {
                 &self.0
             }
         }
+        define_inner_type_conversions!($NEW_TYPE_NAME, $RUST_TYPE);
         impl SerializableTtlvType for $NEW_TYPE_NAME {
             const TTLV_TYPE: TtlvType = $TTLV_ITEM_TYPE;
 
-            fn read_value<T: Read>(src: &mut T, value_len: u32) -> Result<Self> {
+            fn read_value<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<Self> {
                 if value_len != Self::TTLV_FIXED_VALUE_LENGTH {
                     Err(Error::InvalidTtlvValueLength {
                         expected: Self::TTLV_FIXED_VALUE_LENGTH,
@@ -508,7 +889,7 @@ macro_rules! define_fixed_value_length_serializable_ttlv_type {
                 }
             }
 
-            fn write_length_and_value<T: Write>(&self, dst: &mut T) -> Result<u32> {
+            fn write_length_and_value<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32> {
                 dst.write_all(&Self::TTLV_FIXED_VALUE_LENGTH.to_be_bytes())?; // Write L_ength
                 dst.write_all(&self.0.to_be_bytes())?; // Write V_alue
                 Ok(Self::TTLV_FIXED_VALUE_LENGTH)
@@ -551,10 +932,13 @@ define_fixed_value_length_serializable_ttlv_type!(
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.4 Item Value](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Ref262577330):
 /// > _Big Integers are encoded as a sequence of eight-bit bytes, in two's complement notation,
-///   transmitted big-endian. If the length of the sequence is not a multiple of eight bytes, then Big
-///   Integers SHALL be padded with the minimal number of leading sign-extended bytes to make the
-///   length a multiple of eight bytes. These padding bytes are part of the Item Value and SHALL be
-///   counted in the Item Length._
+/// > transmitted big-endian. If the length of the sequence is not a multiple of eight bytes, then Big
+/// > Integers SHALL be padded with the minimal number of leading sign-extended bytes to make the
+/// > length a multiple of eight bytes. These padding bytes are part of the Item Value and SHALL be
+/// > counted in the Item Length._
+///
+/// With the `high-level` feature enabled this type can also be used as a Rust struct field to serialize a TTLV Big
+/// Integer, whereas a `Vec<u8>` field serializes to a TTLV Byte String.
 #[derive(Clone, Debug)]
 pub struct TtlvBigInteger(pub Vec<u8>);
 impl Deref for TtlvBigInteger {
@@ -564,16 +948,16 @@ impl Deref for TtlvBigInteger {
         &self.0
     }
 }
+define_inner_type_conversions!(TtlvBigInteger, Vec<u8>);
 impl SerializableTtlvType for TtlvBigInteger {
     const TTLV_TYPE: TtlvType = TtlvType::BigInteger;
 
-    fn read_value<T: Read>(src: &mut T, value_len: u32) -> Result<Self> {
-        let mut dst = vec![0; value_len as usize];
-        src.read_exact(&mut dst)?;
-        Ok(TtlvBigInteger(dst))
+    /// Reads the value, failing if it is longer than [Self::DEFAULT_MAX_LEN] bytes.
+    fn read_value<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<Self> {
+        Self::read_value_with_max_len(src, value_len, Self::DEFAULT_MAX_LEN)
     }
 
-    fn write_length_and_value<T: Write>(&self, dst: &mut T) -> Result<u32> {
+    fn write_length_and_value<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32> {
         let v = self.0.as_slice();
         let v_len = v.len() as u32;
         let num_pad_bytes = Self::calc_pad_bytes(v_len);
@@ -594,6 +978,178 @@ impl SerializableTtlvType for TtlvBigInteger {
     }
 }
 
+impl TtlvBigInteger {
+    /// The maximum length in bytes of a Big Integer value that will be read, unless specified otherwise.
+    ///
+    /// The value bytes are allocated before they are read, so without a limit a TTLV length field set by an attacker
+    /// could cause allocation of up to 4 GiB per Big Integer. Legitimate Big Integers are small, e.g. the modulus of an
+    /// 8192-bit RSA key occupies 1 KiB.
+    pub const DEFAULT_MAX_LEN: u32 = 4096;
+
+    /// Like [SerializableTtlvType::read_value] but with the given limit on the length of the value in bytes.
+    ///
+    /// Fails with [Error::TtlvValueLengthExceedsLimit] if the value is longer, without allocating space for it.
+    pub fn read_value_with_max_len<T: TtlvRead>(src: &mut T, value_len: u32, max_len: u32) -> Result<Self> {
+        if value_len > max_len {
+            return Err(Error::TtlvValueLengthExceedsLimit {
+                length: value_len as u64,
+                r#type: TtlvType::BigInteger,
+            });
+        }
+        let mut dst = vec![0; value_len as usize];
+        src.read_exact(&mut dst)?;
+        Ok(TtlvBigInteger(dst))
+    }
+
+    /// Like [SerializableTtlvType::read] but with the given limit on the length of the value in bytes.
+    pub fn read_with_max_len<T: TtlvRead>(src: &mut T, max_len: u32) -> Result<Self> {
+        let mut value_len = [0u8; 4];
+        src.read_exact(&mut value_len)?; // read L_ength
+        let value_len = u32::from_be_bytes(value_len);
+        let v = Self::read_value_with_max_len(src, value_len, max_len)?; // read V_alue
+        Self::read_pad_bytes(src, value_len)?; // read 8-byte alignment padding bytes
+        Ok(v)
+    }
+
+    /// Like [Self::read_with_max_len] but verifies that the padding bytes following the value, if any, are zero.
+    pub fn read_strict_with_max_len<T: TtlvRead>(src: &mut T, max_len: u32) -> Result<Self> {
+        let mut value_len = [0u8; 4];
+        src.read_exact(&mut value_len)?; // read L_ength
+        let value_len = u32::from_be_bytes(value_len);
+        let v = Self::read_value_with_max_len(src, value_len, max_len)?; // read V_alue
+        Self::read_pad_bytes_strict(src, value_len)?; // read and verify 8-byte alignment padding bytes
+        Ok(v)
+    }
+
+    fn is_negative(&self) -> bool {
+        matches!(self.0.first(), Some(b) if b & 0b1000_0000 == 0b1000_0000)
+    }
+
+    /// Get the value as 16 big-endian bytes, dropping leading bytes that have the value `pad_byte`. Fails if there
+    /// are more than 16 remaining bytes.
+    fn to_16_bytes(&self, pad_byte: u8) -> Option<[u8; 16]> {
+        let v = self.0.as_slice();
+        let num_pad_bytes = v.iter().take_while(|b| **b == pad_byte).count();
+        let v = &v[num_pad_bytes..];
+        if v.len() > 16 {
+            return None;
+        }
+        let mut bytes = [pad_byte; 16];
+        bytes[16 - v.len()..].copy_from_slice(v);
+        Some(bytes)
+    }
+}
+
+impl TryFrom<TtlvBigInteger> for i128 {
+    type Error = Error;
+
+    /// Fails with [Error::InvalidTtlvValue] if the TTLV Big Integer holds a value that does not fit in an `i128`.
+    fn try_from(v: TtlvBigInteger) -> Result<Self> {
+        let is_negative = v.is_negative();
+        let pad_byte = if is_negative { 0b1111_1111 } else { 0b0000_0000 };
+        match v.to_16_bytes(pad_byte).map(i128::from_be_bytes) {
+            // Dropping sign extension bytes must not change the sign, e.g. 0x00FF.. is positive but 0xFF.. is not.
+            Some(n) if (n < 0) == is_negative => Ok(n),
+            _ => Err(Error::InvalidTtlvValue(TtlvType::BigInteger)),
+        }
+    }
+}
+
+impl TryFrom<TtlvBigInteger> for u128 {
+    type Error = Error;
+
+    /// Fails with [Error::InvalidTtlvValue] if the TTLV Big Integer holds a negative value or a value that does not
+    /// fit in a `u128`.
+    fn try_from(v: TtlvBigInteger) -> Result<Self> {
+        if v.is_negative() {
+            return Err(Error::InvalidTtlvValue(TtlvType::BigInteger));
+        }
+        v.to_16_bytes(0b0000_0000)
+            .map(u128::from_be_bytes)
+            .ok_or(Error::InvalidTtlvValue(TtlvType::BigInteger))
+    }
+}
+
+impl From<i128> for TtlvBigInteger {
+    /// Converts to the shortest two's complement big-endian representation that is a multiple of eight bytes long.
+    fn from(v: i128) -> Self {
+        let bytes = v.to_be_bytes();
+        if i64::try_from(v).is_ok() {
+            TtlvBigInteger(bytes[8..].to_vec())
+        } else {
+            TtlvBigInteger(bytes.to_vec())
+        }
+    }
+}
+
+impl From<u128> for TtlvBigInteger {
+    /// Converts to the shortest two's complement big-endian representation that is a multiple of eight bytes long,
+    /// prefixing the value with eight zero bytes if needed to keep the most significant bit clear.
+    fn from(v: u128) -> Self {
+        let bytes = v.to_be_bytes();
+        if i64::try_from(v).is_ok() {
+            TtlvBigInteger(bytes[8..].to_vec())
+        } else if i128::try_from(v).is_ok() {
+            TtlvBigInteger(bytes.to_vec())
+        } else {
+            let mut padded = vec![0b0000_0000; 8];
+            padded.extend_from_slice(&bytes);
+            TtlvBigInteger(padded)
+        }
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<TtlvBigInteger> for num_bigint::BigInt {
+    fn from(v: TtlvBigInteger) -> Self {
+        num_bigint::BigInt::from_signed_bytes_be(&v.0)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl TryFrom<TtlvBigInteger> for num_bigint::BigUint {
+    type Error = Error;
+
+    /// Fails with [Error::InvalidTtlvValue] if the TTLV Big Integer holds a negative value.
+    fn try_from(v: TtlvBigInteger) -> Result<Self> {
+        num_bigint::BigInt::from(v)
+            .to_biguint()
+            .ok_or(Error::InvalidTtlvValue(TtlvType::BigInteger))
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<num_bigint::BigInt> for TtlvBigInteger {
+    /// Produces the two's complement big-endian byte sequence for the given value, padded with leading sign-extended
+    /// bytes to a multiple of eight bytes as required by the KMIP specification. Zero is encoded as eight zero bytes.
+    fn from(v: num_bigint::BigInt) -> Self {
+        let v_bytes = v.to_signed_bytes_be();
+        let num_pad_bytes = Self::calc_pad_bytes(v_bytes.len() as u32) as usize;
+        let pad_byte = if v.sign() == num_bigint::Sign::Minus {
+            0b1111_1111
+        } else {
+            0b0000_0000
+        };
+        let mut padded = Vec::with_capacity(num_pad_bytes + v_bytes.len());
+        padded.resize(num_pad_bytes, pad_byte);
+        padded.extend_from_slice(&v_bytes);
+        TtlvBigInteger(padded)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<num_bigint::BigUint> for TtlvBigInteger {
+    fn from(v: num_bigint::BigUint) -> Self {
+        // Go via BigInt so that a leading zero byte is added when the most significant bit is set, otherwise the value
+        // would be interpreted as negative when decoded as two's complement.
+        TtlvBigInteger::from(num_bigint::BigInt::from(v))
+    }
+}
+
+/// The Serde newtype struct name used by [TtlvBigInteger] to identify itself to the serializer.
+#[cfg(feature = "high-level")]
+pub(crate) const TTLV_BIG_INTEGER_NAME: &str = "BigInteger:TtlvBigInteger";
+
 // --- TtlvEnumeration ------------------------------------------------------------------------------------------------
 
 define_fixed_value_length_serializable_ttlv_type!(
@@ -609,14 +1165,19 @@ define_fixed_value_length_serializable_ttlv_type!(
     4
 );
 
+/// The Serde newtype struct name used by [crate::as_type::enumeration] to identify itself to the serializer.
+#[cfg(feature = "high-level")]
+pub(crate) const TTLV_ENUMERATION_NAME: &str = "Enumeration:TtlvEnumeration";
+
 // --- TtlvBoolean ----------------------------------------------------------------------------------------------------
 
 /// A type for (de)serializing a TTLV Boolean.
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.4 Item Value](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Ref262577330):
 /// > _Booleans are encoded as an eight-byte value that SHALL either contain the hex value
-///   0000000000000000, indicating the Boolean value False, or the hex value 0000000000000001,
-///   transmitted big-endian, indicating the Boolean value True._
+/// > 0000000000000000, indicating the Boolean value False, or the hex value 0000000000000001,
+/// > transmitted big-endian, indicating the Boolean value True._
+///
 /// Boolean cannot be implemented using the define_fixed_value_length_serializable_ttlv_type! macro because it has
 /// special value verification rules.
 #[derive(Clone, Debug)]
@@ -631,10 +1192,11 @@ impl Deref for TtlvBoolean {
         &self.0
     }
 }
+define_inner_type_conversions!(TtlvBoolean, bool);
 impl SerializableTtlvType for TtlvBoolean {
     const TTLV_TYPE: TtlvType = TtlvType::Boolean;
 
-    fn read_value<T: Read>(src: &mut T, value_len: u32) -> Result<Self> {
+    fn read_value<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<Self> {
         if value_len != Self::TTLV_FIXED_VALUE_LENGTH {
             Err(Error::InvalidTtlvValueLength {
                 expected: Self::TTLV_FIXED_VALUE_LENGTH,
@@ -652,7 +1214,7 @@ impl SerializableTtlvType for TtlvBoolean {
         }
     }
 
-    fn write_length_and_value<T: Write>(&self, dst: &mut T) -> Result<u32> {
+    fn write_length_and_value<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32> {
         let v = match self.0 {
             true => 1u64,
             false => 0u64,
@@ -672,7 +1234,7 @@ impl SerializableTtlvType for TtlvBoolean {
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.4 Item Value](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Ref262577330):
 /// > _Text Strings are sequences of bytes that encode character values according to the UTF-8
-///   encoding standard. There SHALL NOT be null-termination at the end of such strings._
+/// > encoding standard. There SHALL NOT be null-termination at the end of such strings._
 #[derive(Clone, Debug)]
 pub struct TtlvTextString(pub String);
 impl Deref for TtlvTextString {
@@ -682,10 +1244,11 @@ impl Deref for TtlvTextString {
         &self.0
     }
 }
+define_inner_type_conversions!(TtlvTextString, String);
 impl SerializableTtlvType for TtlvTextString {
     const TTLV_TYPE: TtlvType = TtlvType::TextString;
 
-    fn read_value<T: Read>(src: &mut T, value_len: u32) -> Result<Self> {
+    fn read_value<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<Self> {
         // AnySyncRead the UTF-8 bytes, without knowing if they are valid UTF-8
         let mut dst = vec![0; value_len as usize];
         src.read_exact(&mut dst)?;
@@ -697,7 +1260,7 @@ impl SerializableTtlvType for TtlvTextString {
         Ok(TtlvTextString(new_str))
     }
 
-    fn write_length_and_value<T: Write>(&self, dst: &mut T) -> Result<u32> {
+    fn write_length_and_value<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32> {
         let v = self.0.as_bytes();
         let v_len = v.len() as u32;
         dst.write_all(&v_len.to_be_bytes())?; // Write L_ength
@@ -715,7 +1278,7 @@ impl SerializableTtlvType for TtlvTextString {
 ///
 /// According to the [KMIP specification 1.0 section 9.1.1.4 Item Value](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Ref262577330):
 /// > _Byte Strings are sequences of bytes containing individual unspecified eight-bit binary values, and are interpreted
-///   in the same sequence order._
+/// > in the same sequence order._
 #[derive(Clone, Debug)]
 pub struct TtlvByteString(pub Vec<u8>);
 impl Deref for TtlvByteString {
@@ -725,17 +1288,18 @@ impl Deref for TtlvByteString {
         &self.0
     }
 }
+define_inner_type_conversions!(TtlvByteString, Vec<u8>);
 impl SerializableTtlvType for TtlvByteString {
     const TTLV_TYPE: TtlvType = TtlvType::ByteString;
 
-    fn read_value<T: Read>(src: &mut T, value_len: u32) -> Result<Self> {
+    fn read_value<T: TtlvRead>(src: &mut T, value_len: u32) -> Result<Self> {
         // AnySyncRead the UTF-8 bytes, without knowing if they are valid UTF-8
         let mut dst = vec![0; value_len as usize];
         src.read_exact(&mut dst)?;
         Ok(TtlvByteString(dst))
     }
 
-    fn write_length_and_value<T: Write>(&self, dst: &mut T) -> Result<u32> {
+    fn write_length_and_value<T: TtlvWrite>(&self, dst: &mut T) -> Result<u32> {
         let v = self.0.as_slice();
         let v_len = v.len() as u32;
         dst.write_all(&v_len.to_be_bytes())?; // Write L_ength
@@ -744,6 +1308,59 @@ impl SerializableTtlvType for TtlvByteString {
     }
 }
 
+// --- Bytes & BytesRef -----------------------------------------------------------------------------------------------
+
+/// An owned sequence of bytes that always (de)serializes as a TTLV Byte String.
+///
+/// Serde treats a `Vec<u8>` as a sequence of `u8` values, i.e. a sequence of TTLV Integers, unless the field is
+/// annotated with `#[serde(with = "serde_bytes")]`. A [Bytes] field needs no such annotation. The tag must be supplied
+/// by an enclosing `Transparent:0xNNNNNN` newtype.
+///
+/// ```ignore
+/// #[derive(Deserialize, Serialize)]
+/// #[serde(rename = "Transparent:0x420043")]
+/// struct KeyMaterial(Bytes);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bytes(pub Vec<u8>);
+impl Deref for Bytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+define_inner_type_conversions!(Bytes, Vec<u8>);
+impl From<&[u8]> for Bytes {
+    fn from(v: &[u8]) -> Self {
+        Bytes(v.to_vec())
+    }
+}
+
+/// A borrowed sequence of bytes that always (de)serializes as a TTLV Byte String.
+///
+/// Like [Bytes] but when deserializing borrows the value from the input rather than copying it. This requires
+/// deserializing with [crate::de::from_slice] and the input to outlive the deserialized value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BytesRef<'a>(pub &'a [u8]);
+impl<'a> Deref for BytesRef<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+impl<'a> From<&'a [u8]> for BytesRef<'a> {
+    fn from(v: &'a [u8]) -> Self {
+        BytesRef(v)
+    }
+}
+impl<'a> From<BytesRef<'a>> for Bytes {
+    fn from(v: BytesRef<'a>) -> Self {
+        Bytes(v.0.to_vec())
+    }
+}
+
 // --- TtlvDateTime ---------------------------------------------------------------------------------------------------
 
 define_fixed_value_length_serializable_ttlv_type!(
@@ -759,42 +1376,1225 @@ define_fixed_value_length_serializable_ttlv_type!(
     8
 );
 
-// --- TtlvInterval ---------------------------------------------------------------------------------------------------
+#[cfg(feature = "chrono")]
+impl TryFrom<TtlvDateTime> for chrono::DateTime<chrono::Utc> {
+    type Error = Error;
 
-/// A type for (de)serializing a TTLV Interval.
-///
-/// According to the [KMIP specification 1.0 section 9.1.1.4 Item Value](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Ref262577330):
-/// > _Intervals are encoded as four-byte long (32 bit) binary unsigned numbers, transmitted big-endian.
-///   They have a resolution of one second._
-#[allow(dead_code)]
-pub type TtlvInterval = TtlvEnumeration;
+    /// Fails with [Error::InvalidTtlvValue] if the TTLV Date-Time is outside the range supported by `chrono`.
+    fn try_from(v: TtlvDateTime) -> Result<Self> {
+        use chrono::TimeZone;
+        chrono::Utc
+            .timestamp_opt(v.0, 0)
+            .single()
+            .ok_or(Error::InvalidTtlvValue(TtlvType::DateTime))
+    }
+}
 
-// --- TtlvStateMachine ---------------------------------------------------------------------------------------------
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for TtlvDateTime {
+    /// TTLV Date-Time values have a resolution of one second, any fractional second is discarded.
+    fn from(v: chrono::DateTime<chrono::Utc>) -> Self {
+        TtlvDateTime(v.timestamp())
+    }
+}
 
-/// A flag used by [TtlvStateMachine] to know which rules to apply.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum TtlvStateMachineMode {
-    Deserializing,
-    Serializing,
+#[cfg(feature = "time")]
+impl TryFrom<TtlvDateTime> for time::OffsetDateTime {
+    type Error = Error;
+
+    /// Fails with [Error::InvalidTtlvValue] if the TTLV Date-Time is outside the range supported by `time`.
+    fn try_from(v: TtlvDateTime) -> Result<Self> {
+        time::OffsetDateTime::from_unix_timestamp(v.0).map_err(|_| Error::InvalidTtlvValue(TtlvType::DateTime))
+    }
 }
 
-/// A state machine for enforcing TTLV field order rules.
-pub struct TtlvStateMachine {
-    mode: TtlvStateMachineMode,
-    expected_next_field_type: FieldType,
-    ignore_next_tag: bool,
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for TtlvDateTime {
+    /// TTLV Date-Time values have a resolution of one second, any fractional second is discarded.
+    fn from(v: time::OffsetDateTime) -> Self {
+        TtlvDateTime(v.unix_timestamp())
+    }
 }
 
-impl TtlvStateMachine {
-    pub fn new(mode: TtlvStateMachineMode) -> Self {
-        Self {
-            mode,
-            expected_next_field_type: FieldType::default(),
+/// The Serde newtype struct name used by [crate::as_type::date_time] to identify itself to the serializer.
+#[cfg(feature = "high-level")]
+pub(crate) const TTLV_DATE_TIME_NAME: &str = "DateTime:TtlvDateTime";
+
+// --- TtlvInterval ---------------------------------------------------------------------------------------------------
+
+define_fixed_value_length_serializable_ttlv_type!(
+    /// A type for (de)serializing a TTLV Interval.
+    ///
+    /// According to the [KMIP specification 1.0 section 9.1.1.4 Item Value](http://docs.oasis-open.org/kmip/spec/v1.0/os/kmip-spec-1.0-os.html#_Ref262577330):
+    /// > _Intervals are encoded as four-byte long (32 bit) binary unsigned numbers, transmitted big-endian.
+    /// > They have a resolution of one second._
+    ///
+    /// With the `high-level` feature enabled this type can also be used as a Rust struct field to (de)serialize a TTLV
+    /// Interval, whereas a plain `u32` field serializes to a TTLV Enumeration.
+    #[derive(Copy, PartialEq, Eq)]
+    TtlvInterval,
+    TtlvType::Interval,
+    u32,
+    4
+);
+
+/// The Serde newtype struct name used by [TtlvInterval] to identify itself to the (de)serializer.
+#[cfg(feature = "high-level")]
+pub(crate) const TTLV_INTERVAL_NAME: &str = "Interval:TtlvInterval";
+
+// --- TtlvItem -------------------------------------------------------------------------------------------------------
+
+/// A complete TTLV item whose value is kept in raw byte form.
+///
+/// This can be used to capture and later reproduce TTLV items without knowing or interpreting their content, e.g. to
+/// pass vendor extension items through a gateway. The value of a Structure item is the raw bytes of its child items.
+/// The padding bytes that follow the value, if any, are not included.
+///
+/// With the `high-level` feature enabled this type can be deserialized into, see the `de` module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TtlvItem {
+    pub tag: TtlvTag,
+    pub r#type: TtlvType,
+    pub value: Vec<u8>,
+}
+
+impl TtlvItem {
+    pub fn new(tag: TtlvTag, r#type: TtlvType, value: Vec<u8>) -> Self {
+        Self { tag, r#type, value }
+    }
+
+    /// Write the tag, type, length, value and padding bytes of this item.
+    pub fn write<T: TtlvWrite>(&self, dst: &mut T) -> Result<()> {
+        let value_len = self.value.len() as u32;
+        self.tag.write(dst)?;
+        self.r#type.write(dst)?;
+        TtlvLength::new(value_len).write(dst)?;
+        dst.write_all(&self.value)?;
+        if self.r#type != TtlvType::Structure {
+            TtlvByteString::write_pad_bytes(dst, value_len)?;
+        }
+        Ok(())
+    }
+}
+
+// --- TtlvNode & TtlvValue -------------------------------------------------------------------------------------------
+
+/// A TTLV item parsed into its tag and typed value, i.e. a node in a tree of TTLV items.
+///
+/// This can be used to inspect or rewrite TTLV without defining Rust types for every message shape. Unlike
+/// [TtlvItem] the value of a Structure is itself parsed, into a [TtlvValue::Structure] holding the child nodes.
+///
+/// ```
+/// use kmip_ttlv::types::{TtlvNode, TtlvValue, TtlvTag};
+/// # fn main() -> kmip_ttlv::types::Result<()> {
+/// let ttlv_wire = b"\x66\x00\x01\x01\x00\x00\x00\x10\x66\x00\x02\x02\x00\x00\x00\x04\x00\x00\x00\x03\x00\x00\x00\x00";
+///
+/// let mut node = TtlvNode::parse(ttlv_wire)?;
+/// assert_eq!(*node.tag, 0x660001);
+/// if let TtlvValue::Structure(children) = &mut node.value {
+///     assert_eq!(children[0].value, TtlvValue::Integer(3));
+///     children[0].value = TtlvValue::Integer(4);
+/// }
+///
+/// assert_eq!(node.encode()[19], 4);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TtlvNode {
+    pub tag: TtlvTag,
+    pub value: TtlvValue,
+}
+
+/// The typed value of a [TtlvNode].
+///
+/// The value of an item whose type is not defined by the KMIP specification is kept in raw byte form.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TtlvValue {
+    Structure(Vec<TtlvNode>),
+    Integer(i32),
+    LongInteger(i64),
+    /// The big-endian two's complement bytes of the value, including any sign-extension bytes.
+    BigInteger(Vec<u8>),
+    Enumeration(u32),
+    Boolean(bool),
+    TextString(String),
+    ByteString(Vec<u8>),
+    DateTime(i64),
+    Interval(u32),
+    /// The value of an item of a type not defined by the KMIP specification, see [TtlvType::Extension].
+    Extension {
+        /// The byte that identified the type of the item.
+        type_byte: TtlvExtensionType,
+        /// The value bytes, excluding any padding.
+        raw: Vec<u8>,
+    },
+}
+
+impl TtlvValue {
+    /// The TTLV type of this value.
+    pub fn ttlv_type(&self) -> TtlvType {
+        match self {
+            TtlvValue::Structure(_) => TtlvType::Structure,
+            TtlvValue::Integer(_) => TtlvType::Integer,
+            TtlvValue::LongInteger(_) => TtlvType::LongInteger,
+            TtlvValue::BigInteger(_) => TtlvType::BigInteger,
+            TtlvValue::Enumeration(_) => TtlvType::Enumeration,
+            TtlvValue::Boolean(_) => TtlvType::Boolean,
+            TtlvValue::TextString(_) => TtlvType::TextString,
+            TtlvValue::ByteString(_) => TtlvType::ByteString,
+            TtlvValue::DateTime(_) => TtlvType::DateTime,
+            TtlvValue::Interval(_) => TtlvType::Interval,
+            TtlvValue::Extension { type_byte, .. } => TtlvType::Extension(*type_byte),
+        }
+    }
+
+    /// The length of this value in bytes as written in the TTLV length field, i.e. excluding any padding.
+    pub fn value_len(&self) -> usize {
+        match self {
+            TtlvValue::Structure(children) => children.iter().map(TtlvNode::encoded_len).sum(),
+            TtlvValue::Integer(_) | TtlvValue::Enumeration(_) | TtlvValue::Interval(_) => 4,
+            TtlvValue::LongInteger(_) | TtlvValue::Boolean(_) | TtlvValue::DateTime(_) => 8,
+            TtlvValue::TextString(v) => v.len(),
+            TtlvValue::BigInteger(v) | TtlvValue::ByteString(v) | TtlvValue::Extension { raw: v, .. } => v.len(),
+        }
+    }
+}
+
+impl TtlvNode {
+    /// The maximum depth to which [TtlvNode::parse()] descends into nested TTLV Structures.
+    pub const MAX_NESTING_DEPTH: usize = 128;
+
+    pub fn new(tag: TtlvTag, value: TtlvValue) -> Self {
+        Self { tag, value }
+    }
+
+    /// Parse the given bytes as a single complete TTLV item, e.g. a KMIP request or response message.
+    ///
+    /// Fails with [Error::TrailingBytes] if bytes remain after the item and with [Error::NestingDepthExceedsLimit] if
+    /// TTLV Structures are nested more deeply than [TtlvNode::MAX_NESTING_DEPTH]. The padding bytes following values
+    /// are not checked.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut src = bytes;
+        let node = Self::parse_next(&mut src, 0)?;
+        if !src.is_empty() {
+            return Err(Error::TrailingBytes(src.len()));
+        }
+        Ok(node)
+    }
+
+    /// Write this node, including the items of any nested Structures, as TTLV bytes.
+    pub fn write<T: TtlvWrite>(&self, dst: &mut T) -> Result<()> {
+        self.tag.write(dst)?;
+        self.value.ttlv_type().write(dst)?;
+        let value_len = self.value.value_len() as u32;
+        TtlvLength::new(value_len).write(dst)?;
+        match &self.value {
+            TtlvValue::Structure(children) => {
+                for child in children {
+                    child.write(dst)?;
+                }
+                return Ok(());
+            }
+            TtlvValue::Integer(v) => dst.write_all(&v.to_be_bytes())?,
+            TtlvValue::LongInteger(v) | TtlvValue::DateTime(v) => dst.write_all(&v.to_be_bytes())?,
+            TtlvValue::Enumeration(v) | TtlvValue::Interval(v) => dst.write_all(&v.to_be_bytes())?,
+            TtlvValue::Boolean(v) => dst.write_all(&(*v as u64).to_be_bytes())?,
+            TtlvValue::TextString(v) => dst.write_all(v.as_bytes())?,
+            TtlvValue::BigInteger(v) | TtlvValue::ByteString(v) | TtlvValue::Extension { raw: v, .. } => {
+                dst.write_all(v)?
+            }
+        }
+        TtlvByteString::write_pad_bytes(dst, value_len)
+    }
+
+    /// Encode this node, including the items of any nested Structures, as TTLV bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut dst = Vec::with_capacity(self.encoded_len());
+        // Writing to a Vec cannot fail
+        let _ = self.write(&mut dst);
+        dst
+    }
+
+    /// The number of bytes that [TtlvNode::encode()] produces for this node, including the padding bytes.
+    pub fn encoded_len(&self) -> usize {
+        let value_len = self.value.value_len();
+        let num_pad_bytes = match self.value {
+            TtlvValue::Structure(_) => 0,
+            _ => TtlvByteString::calc_pad_bytes(value_len as u32) as usize,
+        };
+        8 + value_len + num_pad_bytes
+    }
+
+    fn parse_next(src: &mut &[u8], depth: usize) -> Result<Self> {
+        let tag = TtlvTag::read(src)?;
+        let r#type = TtlvType::read(src)?;
+        let value_len = *TtlvLength::read(src)?;
+        let mut value_src = take(src, value_len as usize)?;
+        let value = match r#type {
+            TtlvType::Structure => {
+                if depth >= Self::MAX_NESTING_DEPTH {
+                    return Err(Error::NestingDepthExceedsLimit(depth + 1));
+                }
+                let mut children = Vec::new();
+                while !value_src.is_empty() {
+                    children.push(Self::parse_next(&mut value_src, depth + 1)?);
+                }
+                // A Structure length is a multiple of eight and so is never followed by padding.
+                return Ok(TtlvNode::new(tag, TtlvValue::Structure(children)));
+            }
+            TtlvType::Integer => TtlvValue::Integer(*TtlvInteger::read_value(&mut value_src, value_len)?),
+            TtlvType::LongInteger => TtlvValue::LongInteger(*TtlvLongInteger::read_value(&mut value_src, value_len)?),
+            TtlvType::BigInteger => TtlvValue::BigInteger(value_src.to_vec()),
+            TtlvType::Enumeration => TtlvValue::Enumeration(*TtlvEnumeration::read_value(&mut value_src, value_len)?),
+            TtlvType::Boolean => TtlvValue::Boolean(*TtlvBoolean::read_value(&mut value_src, value_len)?),
+            TtlvType::TextString => TtlvValue::TextString(TtlvTextString::read_value(&mut value_src, value_len)?.0),
+            TtlvType::ByteString => TtlvValue::ByteString(value_src.to_vec()),
+            TtlvType::DateTime => TtlvValue::DateTime(*TtlvDateTime::read_value(&mut value_src, value_len)?),
+            TtlvType::Interval => TtlvValue::Interval(*TtlvInterval::read_value(&mut value_src, value_len)?),
+            TtlvType::Extension(type_byte) => TtlvValue::Extension {
+                type_byte,
+                raw: value_src.to_vec(),
+            },
+        };
+        take(src, TtlvByteString::calc_pad_bytes(value_len) as usize)?;
+        Ok(TtlvNode::new(tag, value))
+    }
+}
+
+/// Split off and return the next `len` bytes of the given slice, failing if it is too short.
+fn take<'a>(src: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if len > src.len() {
+        #[cfg(feature = "std")]
+        return Err(Error::IoError(std::io::ErrorKind::UnexpectedEof.into()));
+        #[cfg(not(feature = "std"))]
+        return Err(Error::UnexpectedEof);
+    }
+    let (head, tail) = src.split_at(len);
+    *src = tail;
+    Ok(head)
+}
+
+// --- StructureBuilder -----------------------------------------------------------------------------------------------
+
+/// A builder for a TTLV Structure and the items it contains.
+///
+/// Each item is appended in the order given and the lengths and padding bytes are determined automatically:
+///
+/// ```
+/// use kmip_ttlv::types::{StructureBuilder, TtlvTag};
+/// # use std::convert::TryFrom;
+/// # fn main() -> kmip_ttlv::types::Result<()> {
+/// let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+///
+/// let ttlv_wire = StructureBuilder::new(tag(0x420078))
+///     .structure(
+///         StructureBuilder::new(tag(0x420077))
+///             .structure(StructureBuilder::new(tag(0x420069)).int(tag(0x42006A), 1).int(tag(0x42006B), 0))
+///             .int(tag(0x42000D), 1),
+///     )
+///     .structure(StructureBuilder::new(tag(0x42000F)).enumeration(tag(0x42005C), 0x1E))
+///     .build();
+///
+/// assert_eq!(ttlv_wire.len(), 96);
+/// assert_eq!(&ttlv_wire[..8], b"\x42\x00\x78\x01\x00\x00\x00\x58");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct StructureBuilder {
+    tag: TtlvTag,
+    items: Vec<TtlvNode>,
+}
+
+impl StructureBuilder {
+    pub fn new(tag: TtlvTag) -> Self {
+        Self { tag, items: Vec::new() }
+    }
+
+    /// Append a TTLV Integer.
+    pub fn int(self, tag: TtlvTag, v: i32) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::Integer(v)))
+    }
+
+    /// Append a TTLV Long Integer.
+    pub fn long(self, tag: TtlvTag, v: i64) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::LongInteger(v)))
+    }
+
+    /// Append a TTLV Big Integer, sign-extended to a multiple of eight bytes if needed.
+    pub fn big_int<T: Into<TtlvBigInteger>>(self, tag: TtlvTag, v: T) -> Self {
+        let v = v.into();
+        let pad_byte = if v.is_negative() { 0b1111_1111 } else { 0b0000_0000 };
+        let mut padded = vec![pad_byte; TtlvBigInteger::calc_pad_bytes(v.len() as u32) as usize];
+        padded.extend_from_slice(&v);
+        self.node(TtlvNode::new(tag, TtlvValue::BigInteger(padded)))
+    }
+
+    /// Append a TTLV Enumeration.
+    pub fn enumeration(self, tag: TtlvTag, v: u32) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::Enumeration(v)))
+    }
+
+    /// Append a TTLV Boolean.
+    pub fn boolean(self, tag: TtlvTag, v: bool) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::Boolean(v)))
+    }
+
+    /// Append a TTLV Text String.
+    pub fn text<T: Into<String>>(self, tag: TtlvTag, v: T) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::TextString(v.into())))
+    }
+
+    /// Append a TTLV Byte String.
+    pub fn bytes<T: Into<Vec<u8>>>(self, tag: TtlvTag, v: T) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::ByteString(v.into())))
+    }
+
+    /// Append a TTLV Date-Time given as the number of seconds since the Unix epoch.
+    pub fn date_time(self, tag: TtlvTag, v: i64) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::DateTime(v)))
+    }
+
+    /// Append a TTLV Interval given as a number of seconds.
+    pub fn interval(self, tag: TtlvTag, v: u32) -> Self {
+        self.node(TtlvNode::new(tag, TtlvValue::Interval(v)))
+    }
+
+    /// Append a nested TTLV Structure.
+    pub fn structure(self, builder: StructureBuilder) -> Self {
+        self.node(builder.into_node())
+    }
+
+    /// Append an item of any type, e.g. one parsed by [TtlvNode::parse()].
+    pub fn node(mut self, node: TtlvNode) -> Self {
+        self.items.push(node);
+        self
+    }
+
+    /// Finish building and return the Structure as a [TtlvNode].
+    pub fn into_node(self) -> TtlvNode {
+        TtlvNode::new(self.tag, TtlvValue::Structure(self.items))
+    }
+
+    /// Finish building and return the Structure as TTLV bytes.
+    pub fn build(self) -> Vec<u8> {
+        self.into_node().encode()
+    }
+}
+
+// --- TtlvTokenizer --------------------------------------------------------------------------------------------------
+
+/// An event produced by a [TtlvTokenizer].
+///
+/// Offsets are relative to the start of the bytes being tokenized.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event<'a> {
+    /// The start of a TTLV Structure at the given offset, whose items are produced next, followed by
+    /// [Event::StructEnd].
+    StructStart { offset: usize, tag: TtlvTag, len: u32 },
+    /// A TTLV item of a type other than Structure at the given offset.
+    Primitive {
+        offset: usize,
+        tag: TtlvTag,
+        value: TtlvValueRef<'a>,
+    },
+    /// The end of a TTLV Structure, the offset is that of the first byte after the Structure.
+    StructEnd { offset: usize, tag: TtlvTag },
+}
+
+/// The value of a TTLV item other than a Structure, borrowing variable length values from the bytes being tokenized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TtlvValueRef<'a> {
+    Integer(i32),
+    LongInteger(i64),
+    BigInteger(&'a [u8]),
+    Enumeration(u32),
+    Boolean(bool),
+    TextString(&'a str),
+    ByteString(&'a [u8]),
+    DateTime(i64),
+    Interval(u32),
+    /// The value of an item of a type not defined by the KMIP specification, see [TtlvType::Extension].
+    Extension {
+        /// The byte that identified the type of the item.
+        type_byte: TtlvExtensionType,
+        /// The value bytes, excluding any padding.
+        raw: &'a [u8],
+    },
+}
+
+impl<'a> TtlvValueRef<'a> {
+    /// The TTLV type of this value.
+    pub fn ttlv_type(&self) -> TtlvType {
+        match self {
+            TtlvValueRef::Integer(_) => TtlvType::Integer,
+            TtlvValueRef::LongInteger(_) => TtlvType::LongInteger,
+            TtlvValueRef::BigInteger(_) => TtlvType::BigInteger,
+            TtlvValueRef::Enumeration(_) => TtlvType::Enumeration,
+            TtlvValueRef::Boolean(_) => TtlvType::Boolean,
+            TtlvValueRef::TextString(_) => TtlvType::TextString,
+            TtlvValueRef::ByteString(_) => TtlvType::ByteString,
+            TtlvValueRef::DateTime(_) => TtlvType::DateTime,
+            TtlvValueRef::Interval(_) => TtlvType::Interval,
+            TtlvValueRef::Extension { type_byte, .. } => TtlvType::Extension(*type_byte),
+        }
+    }
+
+    /// The length of this value in bytes as written in the TTLV length field, i.e. excluding any padding.
+    pub fn value_len(&self) -> usize {
+        match self {
+            TtlvValueRef::Integer(_) | TtlvValueRef::Enumeration(_) | TtlvValueRef::Interval(_) => 4,
+            TtlvValueRef::LongInteger(_) | TtlvValueRef::Boolean(_) | TtlvValueRef::DateTime(_) => 8,
+            TtlvValueRef::TextString(v) => v.len(),
+            TtlvValueRef::BigInteger(v) | TtlvValueRef::ByteString(v) | TtlvValueRef::Extension { raw: v, .. } => {
+                v.len()
+            }
+        }
+    }
+
+    /// The number of bytes occupied by an item with this value, including the tag, type, length and padding bytes.
+    pub fn encoded_len(&self) -> usize {
+        let value_len = self.value_len();
+        8 + value_len + TtlvByteString::calc_pad_bytes(value_len as u32) as usize
+    }
+}
+
+impl<'a> From<TtlvValueRef<'a>> for TtlvValue {
+    fn from(v: TtlvValueRef<'a>) -> Self {
+        match v {
+            TtlvValueRef::Integer(v) => TtlvValue::Integer(v),
+            TtlvValueRef::LongInteger(v) => TtlvValue::LongInteger(v),
+            TtlvValueRef::BigInteger(v) => TtlvValue::BigInteger(v.to_vec()),
+            TtlvValueRef::Enumeration(v) => TtlvValue::Enumeration(v),
+            TtlvValueRef::Boolean(v) => TtlvValue::Boolean(v),
+            TtlvValueRef::TextString(v) => TtlvValue::TextString(v.to_string()),
+            TtlvValueRef::ByteString(v) => TtlvValue::ByteString(v.to_vec()),
+            TtlvValueRef::DateTime(v) => TtlvValue::DateTime(v),
+            TtlvValueRef::Interval(v) => TtlvValue::Interval(v),
+            TtlvValueRef::Extension { type_byte, raw } => TtlvValue::Extension {
+                type_byte,
+                raw: raw.to_vec(),
+            },
+        }
+    }
+}
+
+/// A pull parser that iterates over the TTLV items in a byte slice as a sequence of [Event]s.
+///
+/// Unlike [TtlvNode::parse()] nothing is allocated, Text String, Byte String and Big Integer values are borrowed from
+/// the slice. This makes it suitable for scanning or validating large amounts of TTLV. If the slice contains several
+/// back-to-back TTLV items, e.g. a series of KMIP messages, they are produced one after the other.
+///
+/// Iteration ends after the first error. This is [Error::NestingDepthExceedsLimit] if TTLV Structures are nested
+/// more deeply than [TtlvNode::MAX_NESTING_DEPTH]. The padding bytes following values are not checked.
+///
+/// ```
+/// use kmip_ttlv::types::{Event, TtlvTokenizer, TtlvValueRef};
+/// # fn main() -> kmip_ttlv::types::Result<()> {
+/// let ttlv_wire = b"\x66\x00\x01\x01\x00\x00\x00\x10\x66\x00\x02\x07\x00\x00\x00\x02hi\x00\x00\x00\x00\x00\x00";
+///
+/// for event in TtlvTokenizer::new(ttlv_wire) {
+///     if let Event::Primitive { value: TtlvValueRef::TextString(text), .. } = event? {
+///         assert_eq!(text, "hi");
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TtlvTokenizer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    open: [(usize, TtlvTag); TtlvNode::MAX_NESTING_DEPTH], // the end offset and tag of each enclosing Structure
+    depth: usize,
+    failed: bool,
+}
+
+impl<'a> TtlvTokenizer<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            pos: 0,
+            open: [(0, TtlvTag(0)); TtlvNode::MAX_NESTING_DEPTH],
+            depth: 0,
+            failed: false,
+        }
+    }
+
+    /// The number of TTLV Structures that enclose the next item.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event<'a>>> {
+        // Items may not extend beyond the end of the enclosing Structure, if any.
+        let limit = match self.depth.checked_sub(1).map(|i| self.open[i]) {
+            Some((end, tag)) if self.pos == end => {
+                self.depth -= 1;
+                return Ok(Some(Event::StructEnd { offset: end, tag }));
+            }
+            Some((end, _)) => end,
+            None if self.pos == self.bytes.len() => return Ok(None),
+            None => self.bytes.len(),
+        };
+
+        let offset = self.pos;
+        let mut src = &self.bytes[offset..limit];
+        let tag = TtlvTag::read(&mut src)?;
+        let r#type = TtlvType::read(&mut src)?;
+        let len = *TtlvLength::read(&mut src)?;
+        let mut value_src = take(&mut src, len as usize)?;
+        let value = match r#type {
+            TtlvType::Structure => {
+                if self.depth >= TtlvNode::MAX_NESTING_DEPTH {
+                    return Err(Error::NestingDepthExceedsLimit(self.depth + 1));
+                }
+                self.open[self.depth] = (limit - src.len(), tag);
+                self.depth += 1;
+                self.pos = offset + 8;
+                return Ok(Some(Event::StructStart { offset, tag, len }));
+            }
+            TtlvType::Integer => TtlvValueRef::Integer(*TtlvInteger::read_value(&mut value_src, len)?),
+            TtlvType::LongInteger => TtlvValueRef::LongInteger(*TtlvLongInteger::read_value(&mut value_src, len)?),
+            TtlvType::BigInteger => TtlvValueRef::BigInteger(value_src),
+            TtlvType::Enumeration => TtlvValueRef::Enumeration(*TtlvEnumeration::read_value(&mut value_src, len)?),
+            TtlvType::Boolean => TtlvValueRef::Boolean(*TtlvBoolean::read_value(&mut value_src, len)?),
+            TtlvType::TextString => TtlvValueRef::TextString(
+                core::str::from_utf8(value_src).map_err(|_| Error::InvalidTtlvValue(TtlvType::TextString))?,
+            ),
+            TtlvType::ByteString => TtlvValueRef::ByteString(value_src),
+            TtlvType::DateTime => TtlvValueRef::DateTime(*TtlvDateTime::read_value(&mut value_src, len)?),
+            TtlvType::Interval => TtlvValueRef::Interval(*TtlvInterval::read_value(&mut value_src, len)?),
+            TtlvType::Extension(type_byte) => TtlvValueRef::Extension {
+                type_byte,
+                raw: value_src,
+            },
+        };
+        take(&mut src, TtlvByteString::calc_pad_bytes(len) as usize)?;
+        self.pos = limit - src.len();
+        Ok(Some(Event::Primitive { offset, tag, value }))
+    }
+}
+
+impl<'a> Iterator for TtlvTokenizer<'a> {
+    type Item = Result<Event<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+// --- TtlvVisitor ----------------------------------------------------------------------------------------------------
+
+/// Callbacks invoked by [walk()] for each TTLV item, in the order in which the items occur.
+///
+/// Offsets are relative to the start of the bytes being walked. Each callback does nothing by default so only those
+/// of interest need to be implemented.
+pub trait TtlvVisitor {
+    /// Called for the header of the TTLV Structure at the given offset, before its items are visited.
+    fn on_struct_start(&mut self, _offset: usize, _tag: TtlvTag, _len: u32) {}
+
+    /// Called for the TTLV item at the given offset which is of a type other than Structure.
+    fn on_primitive(&mut self, _offset: usize, _tag: TtlvTag, _value: TtlvValueRef<'_>) {}
+
+    /// Called after the items of a TTLV Structure have been visited, the offset is that of the first byte after the
+    /// Structure.
+    fn on_struct_end(&mut self, _offset: usize, _tag: TtlvTag) {}
+}
+
+/// Visit every TTLV item in the given bytes without building a [TtlvNode] tree, e.g. to gather statistics, check
+/// policies or locate values to redact.
+///
+/// Items are visited as they are parsed by a [TtlvTokenizer], so if the bytes are not valid TTLV the items preceding
+/// the problem will have been visited by the time the error is returned.
+pub fn walk<V: TtlvVisitor + ?Sized>(bytes: &[u8], visitor: &mut V) -> Result<()> {
+    for event in TtlvTokenizer::new(bytes) {
+        match event? {
+            Event::StructStart { offset, tag, len } => visitor.on_struct_start(offset, tag, len),
+            Event::Primitive { offset, tag, value } => visitor.on_primitive(offset, tag, value),
+            Event::StructEnd { offset, tag } => visitor.on_struct_end(offset, tag),
+        }
+    }
+    Ok(())
+}
+
+// --- Querying -------------------------------------------------------------------------------------------------------
+
+/// A TTLV item found by [find()].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TtlvMatch<'a> {
+    pub tag: TtlvTag,
+    pub r#type: TtlvType,
+    /// The range of bytes occupied by the complete item, from its tag to the end of its padding bytes.
+    pub range: Range<usize>,
+    /// The value of the item, or `None` for a TTLV Structure.
+    pub value: Option<TtlvValueRef<'a>>,
+}
+
+/// Find the TTLV item at the given tag path in the given TTLV bytes, without decoding any other values.
+///
+/// The path consists of tags in hex form separated by `/`, starting with the tag of the outermost item. A tag can be
+/// followed by a zero-based index in square brackets to select a later item with that tag from its siblings rather
+/// than the first one, e.g. to find the Result Status of the second Batch Item of a KMIP response:
+///
+/// ```
+/// # use kmip_ttlv::types::{StructureBuilder, TtlvTag, TtlvType, TtlvValueRef};
+/// # use std::convert::TryFrom;
+/// # fn main() -> kmip_ttlv::types::Result<()> {
+/// # let tag = |v: u32| TtlvTag::try_from(v).unwrap();
+/// # let batch_item = |status: u32| StructureBuilder::new(tag(0x42000F)).enumeration(tag(0x42007F), status);
+/// # let response = StructureBuilder::new(tag(0x42007B))
+/// #     .structure(batch_item(0))
+/// #     .structure(batch_item(1))
+/// #     .build();
+/// let found = kmip_ttlv::types::find(&response, "0x42007B/0x42000F[1]/0x42007F")?.unwrap();
+/// assert_eq!(found.r#type, TtlvType::Enumeration);
+/// assert_eq!(found.value, Some(TtlvValueRef::Enumeration(1)));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Returns `Ok(None)` if there is no such item. Fails with [Error::InvalidTtlvTag] if the path is invalid and with
+/// any error encountered by a [TtlvTokenizer] before the item is found.
+pub fn find<'a>(bytes: &'a [u8], path: &str) -> Result<Option<TtlvMatch<'a>>> {
+    let segments = path
+        .split('/')
+        .map(|segment| {
+            let invalid = || Error::InvalidTtlvTag(segment.to_string());
+            match segment.find('[') {
+                Some(pos) if segment.ends_with(']') => {
+                    let index = segment[pos + 1..segment.len() - 1]
+                        .parse::<usize>()
+                        .map_err(|_| invalid())?;
+                    Ok((TtlvTag::from_str(&segment[..pos]).map_err(|_| invalid())?, index))
+                }
+                Some(_) => Err(invalid()),
+                None => Ok((TtlvTag::from_str(segment)?, 0)),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut depth = 0; // the number of Structures enclosing the current item
+    let mut matched = 0; // the number of path segments matched so far, by Structures enclosing the current item
+    let mut seen = 0; // the number of siblings of the current item with the tag of the next path segment
+    for event in TtlvTokenizer::new(bytes) {
+        let (offset, tag, r#type, end, value) = match event? {
+            Event::StructStart { offset, tag, len } => {
+                depth += 1;
+                if depth - 1 != matched {
+                    continue;
+                }
+                (offset, tag, TtlvType::Structure, offset + 8 + len as usize, None)
+            }
+            Event::Primitive { offset, tag, value } => {
+                if depth != matched {
+                    continue;
+                }
+                (
+                    offset,
+                    tag,
+                    value.ttlv_type(),
+                    offset + value.encoded_len(),
+                    Some(value),
+                )
+            }
+            Event::StructEnd { .. } => {
+                depth -= 1;
+                if depth < matched {
+                    // The Structure selected by the path does not contain the rest of the path
+                    return Ok(None);
+                }
+                continue;
+            }
+        };
+
+        let (wanted_tag, wanted_index) = segments[matched];
+        if tag != wanted_tag {
+            continue;
+        }
+        if seen < wanted_index {
+            seen += 1;
+            continue;
+        }
+        if matched + 1 == segments.len() {
+            return Ok(Some(TtlvMatch {
+                tag,
+                r#type,
+                range: offset..end,
+                value,
+            }));
+        }
+        if r#type != TtlvType::Structure {
+            return Ok(None);
+        }
+        matched += 1;
+        seen = 0;
+    }
+    Ok(None)
+}
+
+/// Get the complete TTLV bytes, including any nested items, of the item at the given tag path in the given TTLV bytes.
+///
+/// This can be used to forward or store part of a message verbatim, e.g. an opaque payload, without re-encoding it.
+/// See [find()] for the path syntax.
+pub fn subtree<'a>(bytes: &'a [u8], path: &str) -> Result<Option<&'a [u8]>> {
+    Ok(find(bytes, path)?.map(|found| &bytes[found.range]))
+}
+
+/// Get the complete TTLV bytes, including any nested items, of the first item with the given tag at any depth in the
+/// given TTLV bytes.
+///
+/// Items are searched in the order in which they occur, so a Structure is found before any items nested inside it.
+pub fn subtree_by_tag(bytes: &[u8], tag: TtlvTag) -> Result<Option<&[u8]>> {
+    for event in TtlvTokenizer::new(bytes) {
+        match event? {
+            Event::StructStart { offset, tag: t, len } if t == tag => {
+                return Ok(Some(&bytes[offset..offset + 8 + len as usize]));
+            }
+            Event::Primitive { offset, tag: t, value } if t == tag => {
+                return Ok(Some(&bytes[offset..offset + value.encoded_len()]));
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+// --- Editing --------------------------------------------------------------------------------------------------------
+
+/// Replace the value of the TTLV item that starts at the given offset in the given TTLV bytes, keeping its tag.
+///
+/// The lengths of the enclosing TTLV Structures are updated to match, the bytes are otherwise left untouched. This
+/// makes it possible to rewrite a single field, e.g. a Unique Identifier, without decoding and re-encoding the whole
+/// message. The offset of an item can be found using e.g. a [TtlvTokenizer] or [walk()].
+///
+/// Fails with [Error::NoItemAtOffset] if no item starts at the given offset. The bytes are not modified on failure.
+pub fn replace_value(buf: &mut Vec<u8>, offset: usize, value: TtlvValue) -> Result<()> {
+    let (ancestors, end) = locate_item(buf, offset)?;
+    let tag = TtlvTag::read(&mut &buf[offset..])?;
+    let item = TtlvNode::new(tag, value).encode();
+    splice_item(buf, &ancestors, offset..end, &item)
+}
+
+/// Insert the given TTLV item before the TTLV item that starts at the given offset in the given TTLV bytes.
+///
+/// The item becomes part of the same TTLV Structure, if any, as the item at the offset and the lengths of the
+/// enclosing TTLV Structures are updated to match. To add an item after the last item of a Structure use
+/// [append_item()]. See also [replace_value()].
+pub fn insert_item(buf: &mut Vec<u8>, offset: usize, item: &TtlvNode) -> Result<()> {
+    let (ancestors, _) = locate_item(buf, offset)?;
+    splice_item(buf, &ancestors, offset..offset, &item.encode())
+}
+
+/// Append the given TTLV item after the last item of the TTLV Structure that starts at the given offset in the given
+/// TTLV bytes.
+///
+/// Unlike [insert_item()] this can add an item to the end of a Structure, including one that is empty. The lengths of
+/// the Structure and of the Structures enclosing it are updated to match. Fails with [Error::UnexpectedTtlvType] if the
+/// item at the offset is not a Structure.
+pub fn append_item(buf: &mut Vec<u8>, offset: usize, item: &TtlvNode) -> Result<()> {
+    append_to_structure(buf, offset, &item.encode())
+}
+
+/// Remove the TTLV item, including any items nested inside it, that starts at the given offset in the given TTLV
+/// bytes.
+///
+/// The lengths of the enclosing TTLV Structures are updated to match. See also [replace_value()].
+pub fn remove_item(buf: &mut Vec<u8>, offset: usize) -> Result<()> {
+    let (ancestors, end) = locate_item(buf, offset)?;
+    splice_item(buf, &ancestors, offset..end, &[])
+}
+
+/// Insert the given already encoded TTLV items before the TTLV item that starts at the given offset in the given TTLV
+/// bytes.
+///
+/// Like [insert_item()] but for items that are already in TTLV form, e.g. extracted from another message using
+/// [subtree()], so that they can be grafted into TTLV bytes that are otherwise treated as opaque. The inserted bytes
+/// must consist of zero or more complete TTLV items.
+pub fn insert_encoded(buf: &mut Vec<u8>, offset: usize, items: &[u8]) -> Result<()> {
+    check_items(items)?;
+    let (ancestors, _) = locate_item(buf, offset)?;
+    splice_item(buf, &ancestors, offset..offset, items)
+}
+
+/// Append the given already encoded TTLV items after the last item of the TTLV Structure that starts at the given
+/// offset in the given TTLV bytes.
+///
+/// The lengths of the Structure and of the Structures enclosing it are updated to match. Fails with
+/// [Error::UnexpectedTtlvType] if the item at the offset is not a Structure. See also [insert_encoded()].
+pub fn append_encoded(buf: &mut Vec<u8>, offset: usize, items: &[u8]) -> Result<()> {
+    check_items(items)?;
+    append_to_structure(buf, offset, items)
+}
+
+/// Create a TTLV Structure with the given tag whose items are the items of each of the given TTLV Structures in turn.
+///
+/// Each of the given byte slices must consist of exactly one complete TTLV Structure, whose own tag is discarded.
+pub fn concat_structures(tag: TtlvTag, structures: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut content_len = 0usize;
+    for structure in structures {
+        let mut src = *structure;
+        TtlvTag::read(&mut src)?;
+        let r#type = TtlvType::read(&mut src)?;
+        if r#type != TtlvType::Structure {
+            return Err(Error::UnexpectedTtlvType {
+                expected: TtlvType::Structure,
+                actual: r#type,
+            });
+        }
+        let len = *TtlvLength::read(&mut src)? as usize;
+        check_items(take(&mut src, len)?)?;
+        if !src.is_empty() {
+            return Err(Error::TrailingBytes(src.len()));
+        }
+        content_len += len;
+    }
+    let len = u32::try_from(content_len).map_err(|_| Error::TtlvValueLengthExceedsLimit {
+        length: content_len as u64,
+        r#type: TtlvType::Structure,
+    })?;
+
+    let mut buf = Vec::with_capacity(8 + content_len);
+    buf.extend_from_slice(&<[u8; 3]>::from(tag));
+    buf.push(u8::from(TtlvType::Structure));
+    buf.extend_from_slice(&len.to_be_bytes());
+    for structure in structures {
+        buf.extend_from_slice(&structure[8..]);
+    }
+    Ok(buf)
+}
+
+/// Return a copy of the given TTLV bytes in which the length of every TTLV Structure is recomputed from the items it
+/// actually contains.
+///
+/// This repairs messages produced by encoders that write incorrect Structure lengths so that they can be passed to
+/// strict parsers such as [TtlvNode::parse()] or [crate::de::from_slice]. Only the Structure lengths are rewritten, the
+/// lengths of other items are trusted. As there is otherwise no way to know where a Structure ends, a Structure is
+/// taken to contain the items that start before the end given by its declared length, or by the end of the enclosing
+/// Structure if that is earlier, and the root item is taken to span all of the given bytes.
+///
+/// Fails if an item other than a Structure extends beyond the end of the bytes, or if TTLV Structures are nested more
+/// deeply than [TtlvNode::MAX_NESTING_DEPTH].
+pub fn repair_lengths(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut buf = bytes.to_vec();
+    // The start offset and content limit of each enclosing Structure, innermost last.
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut pos = 0;
+
+    loop {
+        // Close the Structures whose content has been fully read, which may have overrun their declared length
+        while let Some(&(start, limit)) = open.last() {
+            if pos < limit {
+                break;
+            }
+            open.pop();
+            let len = u32::try_from(pos - start - 8).map_err(|_| Error::TtlvValueLengthExceedsLimit {
+                length: (pos - start - 8) as u64,
+                r#type: TtlvType::Structure,
+            })?;
+            buf[start + 4..start + 8].copy_from_slice(&len.to_be_bytes());
+        }
+        if pos > 0 && open.is_empty() {
+            break;
+        }
+
+        let mut src = &bytes[pos..];
+        let _tag = TtlvTag::read(&mut src)?;
+        let r#type = TtlvType::read(&mut src)?;
+        let len = *TtlvLength::read(&mut src)?;
+        if r#type == TtlvType::Structure {
+            if open.len() >= TtlvNode::MAX_NESTING_DEPTH {
+                return Err(Error::NestingDepthExceedsLimit(open.len() + 1));
+            }
+            let limit = match open.last() {
+                Some(&(_, parent_limit)) => parent_limit.min(pos + 8 + len as usize),
+                None => bytes.len(),
+            };
+            open.push((pos, limit));
+            pos += 8;
+        } else {
+            take(&mut src, len as usize + TtlvByteString::calc_pad_bytes(len) as usize)?;
+            pos = bytes.len() - src.len();
+        }
+    }
+
+    if pos < bytes.len() {
+        return Err(Error::TrailingBytes(bytes.len() - pos));
+    }
+    Ok(buf)
+}
+
+/// Check that the given bytes consist of zero or more complete and valid TTLV items.
+fn check_items(bytes: &[u8]) -> Result<()> {
+    TtlvTokenizer::new(bytes).try_for_each(|event| event.map(|_| ()))
+}
+
+/// Find the TTLV item that starts at the given offset, returning the offsets of the enclosing TTLV Structures,
+/// outermost first, and the offset of the first byte after the item.
+fn locate_item(bytes: &[u8], offset: usize) -> Result<(Vec<usize>, usize)> {
+    let mut open = Vec::new();
+    for event in TtlvTokenizer::new(bytes) {
+        match event? {
+            Event::StructStart { offset: start, len, .. } if start == offset => {
+                return Ok((open, start + 8 + len as usize));
+            }
+            Event::Primitive {
+                offset: start, value, ..
+            } if start == offset => {
+                return Ok((open, start + value.encoded_len()));
+            }
+            Event::StructStart { offset: start, .. } | Event::Primitive { offset: start, .. } if start > offset => {
+                break
+            }
+            Event::StructStart { offset: start, .. } => open.push(start),
+            Event::Primitive { .. } => {}
+            Event::StructEnd { .. } => {
+                open.pop();
+            }
+        }
+    }
+    Err(Error::NoItemAtOffset(offset))
+}
+
+/// Append the given encoded TTLV items to the content of the TTLV Structure that starts at the given offset.
+fn append_to_structure(buf: &mut Vec<u8>, offset: usize, items: &[u8]) -> Result<()> {
+    let (mut ancestors, end) = locate_item(buf, offset)?;
+    let r#type = TtlvType::try_from(buf[offset + 3])?;
+    if r#type != TtlvType::Structure {
+        return Err(Error::UnexpectedTtlvType {
+            expected: TtlvType::Structure,
+            actual: r#type,
+        });
+    }
+    ancestors.push(offset);
+    splice_item(buf, &ancestors, end..end, items)
+}
+
+/// Replace the given range of bytes, which must lie inside each of the given enclosing TTLV Structures, and adjust
+/// the lengths of those Structures by the change in size.
+fn splice_item(buf: &mut Vec<u8>, ancestors: &[usize], range: Range<usize>, replacement: &[u8]) -> Result<()> {
+    let delta = replacement.len() as i64 - range.len() as i64;
+    let new_lens = ancestors
+        .iter()
+        .map(|&start| {
+            let len = u32::from_be_bytes([buf[start + 4], buf[start + 5], buf[start + 6], buf[start + 7]]);
+            let new_len = len as i64 + delta;
+            u32::try_from(new_len).map_err(|_| Error::TtlvValueLengthExceedsLimit {
+                length: new_len as u64,
+                r#type: TtlvType::Structure,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    buf.splice(range, replacement.iter().copied());
+    // The Structure headers precede the replaced bytes and so have not moved.
+    for (&start, len) in ancestors.iter().zip(new_lens) {
+        buf[start + 4..start + 8].copy_from_slice(&len.to_be_bytes());
+    }
+    Ok(())
+}
+
+// --- Serde ----------------------------------------------------------------------------------------------------------
+
+/// Serialize a TTLV tag in its hex form, e.g. "0x42000A".
+#[cfg(feature = "serde")]
+impl serde::Serialize for TtlvTag {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Deserialize a TTLV tag from its hex form, e.g. "0x42000A", or from its numeric value.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TtlvTag {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct TtlvTagVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TtlvTagVisitor {
+            type Value = TtlvTag;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a TTLV tag")
+            }
+
+            fn visit_str<E>(self, v: &str) -> core::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                TtlvTag::from_str(v).map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> core::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                u32::try_from(v)
+                    .ok()
+                    .and_then(|v| TtlvTag::try_from(v).ok())
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(TtlvTagVisitor)
+    }
+}
+
+/// Serialize a TTLV type by its KMIP XML name, e.g. "TextString", or for [TtlvType::Extension] by its hex type
+/// byte, e.g. "0x80".
+#[cfg(feature = "serde")]
+impl serde::Serialize for TtlvType {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            TtlvType::Extension(v) => serializer.serialize_str(&alloc::format!("0x{:02X}", v)),
+            _ => serializer.serialize_str(self.name()),
+        }
+    }
+}
+
+/// Deserialize a TTLV type from its KMIP XML name, e.g. "TextString", or from its type byte either in hex form, e.g.
+/// "0x07", or as a number.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TtlvType {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct TtlvTypeVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TtlvTypeVisitor {
+            type Value = TtlvType;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a TTLV type")
+            }
+
+            fn visit_str<E>(self, v: &str) -> core::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                TtlvType::from_name(v)
+                    .or_else(|| {
+                        let hex = v.strip_prefix("0x")?;
+                        u8::from_str_radix(hex, 16)
+                            .ok()
+                            .and_then(|v| TtlvType::try_from(v).ok())
+                    })
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> core::result::Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                u8::try_from(v)
+                    .ok()
+                    .and_then(|v| TtlvType::try_from(v).ok())
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(TtlvTypeVisitor)
+    }
+}
+
+/// Serialize a TTLV length as a number.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TtlvLength {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u32(self.0)
+    }
+}
+
+/// Deserialize a TTLV length from a number.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TtlvLength {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <u32 as serde::Deserialize>::deserialize(deserializer).map(TtlvLength)
+    }
+}
+
+// --- TtlvStateMachine ---------------------------------------------------------------------------------------------
+
+/// A flag used by [TtlvStateMachine] to know which rules to apply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TtlvStateMachineMode {
+    Deserializing,
+    Serializing,
+}
+
+/// A state machine for enforcing TTLV field order rules.
+///
+/// The state machine also tracks the current depth of TTLV Structure nesting. As the field types alone do not reveal
+/// where a TTLV Structure ends the user of the state machine must signal this by calling
+/// [TtlvStateMachine::enter_structure()] and [TtlvStateMachine::leave_structure()].
+pub struct TtlvStateMachine {
+    mode: TtlvStateMachineMode,
+    expected_next_field_type: FieldType,
+    ignore_next_tag: bool,
+    current_depth: usize,
+}
+
+impl TtlvStateMachine {
+    pub fn new(mode: TtlvStateMachineMode) -> Self {
+        Self {
+            mode,
+            expected_next_field_type: FieldType::default(),
             ignore_next_tag: false,
+            current_depth: 0,
         }
     }
 
-    pub fn advance(&mut self, next_field_type: FieldType) -> std::result::Result<bool, Error> {
+    /// The type of TTLV field that is expected to be (de)serialized next.
+    pub fn expected_next_field_type(&self) -> FieldType {
+        self.expected_next_field_type
+    }
+
+    /// The number of TTLV Structures that enclose the current position, i.e. 0 outside of any structure.
+    pub fn current_depth(&self) -> usize {
+        self.current_depth
+    }
+
+    /// Signal that the value of a TTLV Structure is about to be (de)serialized.
+    pub fn enter_structure(&mut self) {
+        self.current_depth += 1;
+    }
+
+    /// Signal that the value of the innermost TTLV Structure has been completely (de)serialized.
+    ///
+    /// Returns [Error::InvalidStateMachineOperation] if there is no TTLV Structure to leave.
+    pub fn leave_structure(&mut self) -> core::result::Result<(), Error> {
+        if self.current_depth > 0 {
+            self.current_depth -= 1;
+            Ok(())
+        } else {
+            Err(Error::InvalidStateMachineOperation)
+        }
+    }
+
+    pub fn advance(&mut self, next_field_type: FieldType) -> core::result::Result<bool, Error> {
         use TtlvStateMachineMode as Mode;
 
         let next_expected_next_field_type = match (self.mode, self.expected_next_field_type, next_field_type) {
@@ -837,7 +2637,7 @@ impl TtlvStateMachine {
         }
     }
 
-    pub fn ignore_next_tag(&mut self) -> std::result::Result<(), Error> {
+    pub fn ignore_next_tag(&mut self) -> core::result::Result<(), Error> {
         if matches!(self.mode, TtlvStateMachineMode::Serializing) {
             self.ignore_next_tag = true;
             Ok(())
@@ -846,8 +2646,17 @@ impl TtlvStateMachine {
         }
     }
 
+    /// Expect a tag next, abandoning the TTLV item currently being (de)serialized, if any.
+    ///
+    /// Unlike [TtlvStateMachine::reset()] the current TTLV Structure depth is retained.
+    pub fn restart_item(&mut self) {
+        self.expected_next_field_type = FieldType::default();
+        self.ignore_next_tag = false;
+    }
+
     pub fn reset(&mut self) {
         self.expected_next_field_type = FieldType::default();
         self.ignore_next_tag = false;
+        self.current_depth = 0;
     }
 }
@@ -0,0 +1,79 @@
+//! Serde `with` helpers for (de)serializing TTLV Integer and Long Integer values from/to `u32` and `u64` fields.
+//!
+//! By default a `u32` serializes to a TTLV Enumeration and a `u64` to a TTLV Date-Time. KMIP fields such as lengths
+//! and counts are conceptually unsigned however, and are better represented in Rust by an unsigned type. Annotating
+//! such a field with `#[serde(with = "kmip_ttlv::unsigned")]` instead (de)serializes a `u32` from/to a TTLV Integer
+//! and a `u64` from/to a TTLV Long Integer:
+//!
+//! ```ignore
+//! #[derive(Deserialize, Serialize)]
+//! #[serde(rename = "Transparent:0x42002A")]
+//! struct CryptographicLength(#[serde(with = "kmip_ttlv::unsigned")] u32);
+//! ```
+//!
+//! As TTLV Integer and Long Integer values are signed, serialization fails if the value is too large for the signed
+//! type and deserialization fails if the value is negative.
+
+use std::convert::TryFrom;
+
+use serde::{de::Unexpected, Deserialize, Deserializer, Serializer};
+
+/// An unsigned Rust integer type that can be (de)serialized from/to a signed TTLV integer type of the same width.
+pub trait Unsigned: Sized {
+    /// Serialize the value as a signed TTLV integer, failing if it is out of range.
+    fn serialize_signed<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize a signed TTLV integer as this type, failing if it is negative.
+    fn deserialize_signed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl Unsigned for u32 {
+    fn serialize_signed<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v = i32::try_from(*self)
+            .map_err(|_| serde::ser::Error::custom(format!("u32 value {} is too large for a TTLV Integer", self)))?;
+        serializer.serialize_i32(v)
+    }
+
+    fn deserialize_signed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = i32::deserialize(deserializer)?;
+        u32::try_from(v)
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Signed(v as i64), &"a non-negative TTLV Integer"))
+    }
+}
+
+impl Unsigned for u64 {
+    fn serialize_signed<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v = i64::try_from(*self).map_err(|_| {
+            serde::ser::Error::custom(format!("u64 value {} is too large for a TTLV Long Integer", self))
+        })?;
+        serializer.serialize_i64(v)
+    }
+
+    fn deserialize_signed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = i64::deserialize(deserializer)?;
+        u64::try_from(v)
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Signed(v), &"a non-negative TTLV Long Integer"))
+    }
+}
+
+/// Serialize a `u32` as a TTLV Integer or a `u64` as a TTLV Long Integer.
+///
+/// Fails with a [crate::error::SerdeError] if the value does not fit in the signed TTLV type.
+pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Unsigned,
+    S: Serializer,
+{
+    v.serialize_signed(serializer)
+}
+
+/// Deserialize a TTLV Integer into a `u32` or a TTLV Long Integer into a `u64`.
+///
+/// Fails with a [crate::error::SerdeError] if the value is negative.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Unsigned,
+    D: Deserializer<'de>,
+{
+    T::deserialize_signed(deserializer)
+}
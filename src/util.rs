@@ -1,6 +1,7 @@
 //! Useful functionality separate but related to (de)serialization.
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt::Write;
 use std::io::Cursor;
 use std::ops::Deref;
@@ -9,8 +10,9 @@ use std::str::FromStr;
 use crate::de::TtlvDeserializer;
 use crate::error::ErrorKind;
 use crate::types::{
-    SerializableTtlvType, TtlvBigInteger, TtlvBoolean, TtlvByteString, TtlvDateTime, TtlvEnumeration, TtlvInteger,
-    TtlvLongInteger, TtlvStateMachine, TtlvStateMachineMode, TtlvTag, TtlvTextString, TtlvType,
+    SerializableTtlvType, TtlvBigInteger, TtlvBoolean, TtlvByteString, TtlvDateTime, TtlvEnumeration,
+    TtlvExtensionType, TtlvInteger, TtlvInterval, TtlvLongInteger, TtlvStateMachine, TtlvStateMachineMode, TtlvTag,
+    TtlvTextString, TtlvType,
 };
 
 /// Facilities for pretty printing TTLV bytes to text format.
@@ -18,6 +20,7 @@ use crate::types::{
 pub struct PrettyPrinter {
     tag_prefix: String,
     tag_map: HashMap<TtlvTag, &'static str>,
+    max_depth: Option<usize>,
 }
 
 impl PrettyPrinter {
@@ -43,6 +46,17 @@ impl PrettyPrinter {
         self
     }
 
+    /// Set the maximum depth to which [PrettyPrinter::to_string()] expands TTLV Structures.
+    ///
+    /// The items of Structures nested more deeply than the given depth are not rendered, instead the Structure is
+    /// rendered with the placeholder `<... N bytes of nested TTLV>` as its data, where N is the length of the Structure
+    /// value in bytes. A depth of 0 renders only the outermost item, a depth of 1 additionally renders its immediate
+    /// children, and so on. The suppressed items are still parsed so any problem with them is still reported.
+    pub fn with_max_depth(&mut self, max_depth: usize) -> &Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
     /// Interpret the given byte slice as TTLV as much as possible and render it to a String in human readable form.
     ///
     /// An example string for a successful KMIP 1.0 create symmetric key response could look like this:
@@ -104,6 +118,7 @@ impl PrettyPrinter {
 
     fn internal_to_string(&self, bytes: &[u8], diagnostic_report: bool) -> String {
         let mut indent: usize = 0;
+        let mut depth: usize = 0;
         let mut report = String::new();
         let mut struct_ends = Vec::<u64>::new();
         let mut cur_struct_end = Option::<u64>::None;
@@ -112,10 +127,12 @@ impl PrettyPrinter {
 
         /// Given a read cursor into a byte stream, attempt to read the next TTLV item and render its metadata and value in
         /// humand readable form to a result string. The TTLV item to process should have the form:
+        ///
         ///   - T: 3 bytes of "tag"
         ///   - T: 1 byte of "type"
         ///   - L: 4 bytes of "length"
         ///   - V: L bytes of "value"
+        ///
         /// On success returns the human readable string representation of the parsed TTLV item and if it was a "Structure"
         /// header also returns the byte length of the structure that follows. If the bytes in the stream at the cursor
         /// position are not valid TTLV an error will be returned.
@@ -137,12 +154,14 @@ impl PrettyPrinter {
                 TtlvType::Structure   => { len = Some(TtlvDeserializer::read_length(cursor, Some(&mut sm))? as u64); EMPTY_STRING }
                 TtlvType::Integer     => { format!(" {data:#08X} ({data})", data = TtlvInteger::read(cursor)?.deref()) }
                 TtlvType::LongInteger => { format!(" {data:#08X} ({data})", data = TtlvLongInteger::read(cursor)?.deref()) }
-                TtlvType::BigInteger  => { format!(" {data}", data = hex::encode_upper(&TtlvBigInteger::read(cursor)?.deref())) }
+                TtlvType::BigInteger  => { format!(" {data}", data = hex::encode_upper(TtlvBigInteger::read(cursor)?.deref())) }
                 TtlvType::Enumeration => { format!(" {data:#08X} ({data})", data = TtlvEnumeration::read(cursor)?.deref()) }
                 TtlvType::Boolean     => { format!(" {data}", data = TtlvBoolean::read(cursor)?.deref()) }
                 TtlvType::TextString  => { format!(" {data}", data = TtlvTextString::read(cursor)?.deref()) }
-                TtlvType::ByteString  => { format!(" {data}", data = hex::encode_upper(&TtlvByteString::read(cursor)?.deref())) }
+                TtlvType::ByteString  => { format!(" {data}", data = hex::encode_upper(TtlvByteString::read(cursor)?.deref())) }
                 TtlvType::DateTime    => { format!(" {data:#08X}", data = TtlvDateTime::read(cursor)?.deref()) }
+                TtlvType::Interval    => { format!(" {data:#08X} ({data})", data = TtlvInterval::read(cursor)?.deref()) }
+                TtlvType::Extension(_) => { format!(" {data}", data = hex::encode_upper(TtlvByteString::read(cursor)?.deref())) }
             };
 
                 if let Some(tag_name) = tag_map.get(&tag) {
@@ -162,10 +181,12 @@ impl PrettyPrinter {
                 TtlvType::TextString  => { TtlvTextString::read(cursor)?; "t".to_string() }
                 TtlvType::ByteString  => { TtlvByteString::read(cursor)?; "o".to_string() }
                 TtlvType::DateTime    => { TtlvDateTime::read(cursor)?; "d".to_string() }
+                TtlvType::Interval    => { TtlvInterval::read(cursor)?; "n".to_string() }
+                TtlvType::Extension(v) => { TtlvByteString::read(cursor)?; format!("x{:02X}:", v) }
             };
 
                 let tag = format!("{:06X}", *tag);
-                let tag = tag.strip_prefix(&strip_tag_prefix).unwrap_or(&tag);
+                let tag = tag.strip_prefix(strip_tag_prefix).unwrap_or(&tag);
                 format!("{}{}", tag, data)
             };
 
@@ -189,6 +210,7 @@ impl PrettyPrinter {
                             } else {
                                 report.push(']');
                             }
+                            depth -= 1;
                             cur_struct_end = Some(end);
                         } else {
                             // No more parent structures, we have finished processing the TTLV bytes
@@ -215,12 +237,30 @@ impl PrettyPrinter {
 
             match res {
                 Ok((ttlv_string, possible_new_struct_len)) => {
+                    // Items nested more deeply than the maximum depth, if any, are parsed but not rendered. The
+                    // Structure at the maximum depth is rendered with a placeholder in place of its items.
+                    let (render, ttlv_string) = match self.max_depth {
+                        Some(max_depth) if !diagnostic_report => match (depth.cmp(&max_depth), possible_new_struct_len)
+                        {
+                            (Ordering::Greater, _) => (false, ttlv_string),
+                            (Ordering::Equal, Some(new_len)) => {
+                                let placeholder = format!(" <... {} bytes of nested TTLV>\n", new_len);
+                                (true, ttlv_string.trim_end().to_string() + &placeholder)
+                            }
+                            _ => (true, ttlv_string),
+                        },
+                        _ => (true, ttlv_string),
+                    };
+
                     // Add (with correct indentation) the human readable result of deserialization to the "report" built up
                     // so far.
-                    if !diagnostic_report {
+                    if !render {
+                        // Suppressed, see above
+                    } else if !diagnostic_report {
                         let _ = write!(
                             report,
-                            "{width:width$}{ttlv_string}",
+                            "{:width$}{ttlv_string}",
+                            "",
                             width = indent,
                             ttlv_string = &ttlv_string
                         );
@@ -235,6 +275,7 @@ impl PrettyPrinter {
                         } else {
                             report.push('[');
                         }
+                        depth += 1;
 
                         if let Some(cur_end) = cur_struct_end {
                             // We have started processing a new child structure, remember the end of the parent structure we
@@ -248,7 +289,7 @@ impl PrettyPrinter {
                             // an error before we were able to go back into the byte stream to rewrite the structure length
                             // once the length was known. Note: this can also be correct, it might actually be an empty
                             // structure, but we cannot distinguish between the two cases.
-                            if !diagnostic_report {
+                            if !diagnostic_report && render {
                                 report.push_str("WARNING: TTLV structure length is zero\n");
                             }
                             broken = true;
@@ -350,6 +391,17 @@ impl PrettyPrinter {
                     't' => Some((TtlvType::TextString, new_s)),
                     'o' => Some((TtlvType::ByteString, new_s)),
                     'd' => Some((TtlvType::DateTime, new_s)),
+                    'n' => Some((TtlvType::Interval, new_s)),
+                    'x' => {
+                        // the extension type byte follows as two hex digits terminated by a ':' character
+                        let type_byte = u8::from_str_radix(s.get(1..3)?, 16).ok()?;
+                        let type_byte = TtlvExtensionType::try_from(type_byte).ok()?;
+                        if s.get(3..4)? != ":" {
+                            return None;
+                        }
+                        let new_s = s.get(4..).filter(|rest| !rest.is_empty());
+                        Some((TtlvType::Extension(type_byte), new_s))
+                    }
                     _ => None,
                 }
             } else {
@@ -357,13 +409,7 @@ impl PrettyPrinter {
             }
         }
 
-        fn read_val<'a>(
-            indent: &str,
-            s: &'a str,
-            typ: TtlvType,
-            tag_map: &HashMap<TtlvTag, &'static str>,
-            tag_prefix: &str,
-        ) -> Option<(String, Option<&'a str>)> {
+        fn read_val(s: &str, typ: TtlvType) -> Option<(String, Option<&str>)> {
             // split_once isn't available until Rust 1.52
             pub fn split_once(s: &str, delimiter: char) -> Option<(&str, &str)> {
                 let (start, end) = s.split_at(s.find(delimiter)?);
@@ -371,16 +417,6 @@ impl PrettyPrinter {
             }
 
             match typ {
-                TtlvType::Structure => {
-                    // recurse
-                    let indent = format!("  {}", indent);
-                    let next = read_next(&indent, s, tag_map, tag_prefix);
-                    if next.trim().is_empty() {
-                        Some((String::new(), None))
-                    } else {
-                        Some((format!("\n{}", next), None))
-                    }
-                }
                 TtlvType::Enumeration => {
                     // split at the enumeration value terminator ':' character
                     match split_once(s, ':') {
@@ -396,15 +432,17 @@ impl PrettyPrinter {
             }
         }
 
-        fn read_next(in_indent: &str, s: &str, tag_map: &HashMap<TtlvTag, &'static str>, tag_prefix: &str) -> String {
+        // Structures are handled by adjusting the indentation rather than by recursing so that long or deeply nested
+        // input cannot exhaust the call stack.
+        fn read_next(s: &str, tag_map: &HashMap<TtlvTag, &'static str>, tag_prefix: &str) -> String {
             let mut out = String::new();
             let mut outer_s = s;
-            let mut indent = in_indent;
+            let mut indent = String::new();
 
             loop {
                 if let Some((opt_tag, opt_new_s)) = read_tag(outer_s, tag_prefix) {
                     if let Some(tag) = opt_tag {
-                        out.push_str(indent);
+                        out.push_str(&indent);
                         if let Some(tag_name) = tag_map.get(&tag) {
                             let _ = write!(out, "Tag: {} ({:#06X})", tag_name, *tag);
                         } else {
@@ -414,7 +452,13 @@ impl PrettyPrinter {
                             if let Some((typ, opt_new_s)) = read_typ(s) {
                                 let _ = write!(out, ", Type: {}", typ);
                                 if let Some(s) = opt_new_s {
-                                    if let Some((val, opt_new_s)) = read_val(indent, s, typ, tag_map, tag_prefix) {
+                                    if typ == TtlvType::Structure {
+                                        // descend into the structure
+                                        out.push_str(", Data: \n");
+                                        indent.push_str("  ");
+                                        outer_s = s;
+                                        continue;
+                                    } else if let Some((val, opt_new_s)) = read_val(s, typ) {
                                         let _ = writeln!(out, ", Data: {}", &val);
                                         if let Some(s) = opt_new_s {
                                             outer_s = s;
@@ -426,7 +470,7 @@ impl PrettyPrinter {
                         }
                     } else if let Some(s) = opt_new_s {
                         // this is this the end of a structure
-                        indent = &indent[2..];
+                        indent.truncate(indent.len().saturating_sub(2));
                         outer_s = s;
                         continue;
                     }
@@ -438,7 +482,7 @@ impl PrettyPrinter {
             out
         }
 
-        read_next("", diag_str, &self.tag_map, &self.tag_prefix)
+        read_next(diag_str, &self.tag_map, &self.tag_prefix)
             .trim_end()
             .to_string()
     }
@@ -0,0 +1,346 @@
+//! (De)serialization of Rust data types from/to the KMIP XML encoding of TTLV.
+//!
+//! The [KMIP Profiles Version 1.2](http://docs.oasis-open.org/kmip/profiles/v1.2/os/kmip-profiles-v1.2-os.html)
+//! specification defines an XML encoding in which the same logical tree of TTLV items that is otherwise encoded as
+//! binary TTLV is represented as nested XML elements. Each TTLV item becomes an XML element named after the TTLV
+//! tag whose `type` and `value` attributes hold the TTLV type and value respectively. TTLV Structure elements have no
+//! `value` attribute and instead contain the elements of the items within the structure. The `type` attribute is
+//! omitted for TTLV Structures as it is the default when no type is given.
+//!
+//! For example the binary TTLV for a structure with tag 0x420069 (ProtocolVersion) containing an Integer with tag
+//! 0x42006A (ProtocolVersionMajor) and value 1 is encoded as:
+//!
+//! ```xml
+//! <ProtocolVersion>
+//!   <ProtocolVersionMajor type="Integer" value="1"/>
+//! </ProtocolVersion>
+//! ```
+//!
+//! Element names are taken from the [kmip_tags](crate::kmip_tags) registry. Items with a tag that has no known name,
+//! e.g. extension tags in the 0x54XXXX range, become elements named `TTLV` with a `tag` attribute holding the tag in
+//! hexadecimal form, e.g. `<TTLV tag="0x540001" type="Integer" value="1"/>`.
+//!
+//! When reading XML an element named `TTLV` must have a `tag` attribute, and any other element must either be named
+//! after a known KMIP tag or have a `tag` attribute.
+//!
+//! Values are rendered as follows:
+//!
+//! | TTLV data type | XML `value` attribute                               |
+//! |----------------|-----------------------------------------------------|
+//! | Integer        | Decimal integer, e.g. `-1`                          |
+//! | Long Integer   | Decimal integer                                     |
+//! | Big Integer    | Uppercase hexadecimal two's complement bytes        |
+//! | Enumeration    | `0x` prefixed 8 digit uppercase hexadecimal integer |
+//! | Boolean        | `true` or `false`                                   |
+//! | Text String    | The string itself                                   |
+//! | Byte String    | Uppercase hexadecimal bytes                         |
+//! | Date Time      | Decimal integer seconds since the Unix epoch        |
+//!
+//! Big Integer values are padded to a multiple of 8 bytes as required by the KMIP XML encoding. Values whose
+//! hexadecimal form is not a multiple of 16 digits long are rejected when reading XML.
+//!
+//! The same `#[serde(rename = "0xNNNNNN")]` annotated Rust types that are used with [to_vec] and [from_slice] can be
+//! used with [to_xml] and [from_xml] without modification, as the XML is produced from and converted back to the
+//! binary TTLV form.
+use std::{io::Cursor, ops::Deref, str::FromStr};
+
+use quick_xml::{
+    events::{BytesEnd, BytesStart, Event},
+    Reader, Writer,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    de::{from_slice, validate, Config},
+    error::{Error, ErrorKind, ErrorLocation, Result, SerdeError},
+    kmip_tags::{tag_for_name, tag_name},
+    ser::to_vec,
+    types::{
+        self, ByteOffset, SerializableTtlvType, TtlvBigInteger, TtlvBoolean, TtlvByteString, TtlvDateTime,
+        TtlvEnumeration, TtlvInteger, TtlvInterval, TtlvLength, TtlvLongInteger, TtlvTag, TtlvTextString, TtlvType,
+    },
+};
+
+/// The name of the element used for items whose tag has no known name.
+const XML_ELEMENT_NAME: &str = "TTLV";
+
+// --- Public interface ------------------------------------------------------------------------------------------------
+
+/// Serialize the given Rust value to a KMIP XML string.
+pub fn to_xml<T>(value: &T) -> Result<String>
+where
+    T: Serialize,
+{
+    ttlv_to_xml(&to_vec(value)?)
+}
+
+/// Deserialize a Rust value from the given KMIP XML string.
+///
+/// Errors in the XML are reported with an [ErrorLocation] whose offset is a byte position in the XML string. Errors
+/// detected while deserializing the TTLV bytes represented by the XML are reported with a byte offset into those TTLV
+/// bytes.
+pub fn from_xml<T>(xml: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_slice(&xml_to_ttlv(xml)?)
+}
+
+/// Convert binary TTLV bytes to the equivalent KMIP XML string.
+///
+/// The bytes are first checked with [validate] so that malformed input is rejected before any XML is produced.
+pub fn ttlv_to_xml(bytes: &[u8]) -> Result<String> {
+    validate(bytes, &Config::default())?;
+
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    let mut cursor = Cursor::new(bytes);
+    let mut structure_ends: Vec<(u64, &'static str)> = Vec::new();
+
+    loop {
+        // Close the elements of any TTLV Structures whose content has been fully read
+        while let Some(&(structure_end, element_name)) = structure_ends.last() {
+            if cursor.position() < structure_end {
+                break;
+            }
+            structure_ends.pop();
+            write_event(&mut writer, Event::End(BytesEnd::new(element_name)))?;
+        }
+
+        if cursor.position() >= bytes.len() as u64 {
+            break;
+        }
+
+        let item_start = cursor.position();
+        let location = || ErrorLocation::at(ByteOffset(item_start));
+        let tag = TtlvTag::read(&mut cursor).map_err(|err| pinpoint!(err, location()))?;
+        let r#type = TtlvType::read(&mut cursor).map_err(|err| pinpoint!(err, location(), tag))?;
+        let location = || {
+            ErrorLocation::at(ByteOffset(item_start))
+                .with_tag(tag)
+                .with_type(r#type)
+        };
+
+        let element_name = tag_name(tag).unwrap_or(XML_ELEMENT_NAME);
+        let mut element = BytesStart::new(element_name);
+        if element_name == XML_ELEMENT_NAME {
+            element.push_attribute(("tag", tag.to_string().as_str()));
+        }
+
+        if r#type == TtlvType::Structure {
+            let len = TtlvLength::read(&mut cursor).map_err(|err| pinpoint!(err, location()))?;
+            structure_ends.push((cursor.position() + *len as u64, element_name));
+            write_event(&mut writer, Event::Start(element))?;
+        } else {
+            element.push_attribute(("type", r#type.name()));
+            let value = read_value(&mut cursor, r#type).map_err(|err| pinpoint!(err, location()))?;
+            element.push_attribute(("value", value.as_str()));
+            write_event(&mut writer, Event::Empty(element))?;
+        }
+    }
+
+    // The writer only ever writes valid UTF-8 as all written content comes from Rust strings
+    Ok(String::from_utf8(writer.into_inner()).unwrap())
+}
+
+/// Convert a KMIP XML string to the equivalent binary TTLV bytes.
+pub fn xml_to_ttlv(xml: &str) -> Result<Vec<u8>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut dst = Vec::new();
+
+    // For each XML element that has been started but not yet ended, the position in the destination buffer of the
+    // TTLV Structure length bytes to rewrite once the end of the element is reached, or None if the element is not a
+    // TTLV Structure.
+    let mut bookmarks: Vec<Option<usize>> = Vec::new();
+    let mut root_seen = false;
+
+    loop {
+        let pos = reader.buffer_position();
+        let event = reader
+            .read_event()
+            .map_err(|err| malformed_xml(err.to_string(), reader.error_position()))?;
+
+        match event {
+            Event::Start(_) | Event::Empty(_) if root_seen && bookmarks.is_empty() => {
+                return Err(malformed_xml("more than one root element", pos));
+            }
+            Event::Start(_) | Event::Empty(_) if bookmarks.last() == Some(&None) => {
+                return Err(malformed_xml(
+                    "unexpected child element of a non-Structure element",
+                    pos,
+                ));
+            }
+            Event::Start(element) => {
+                root_seen = true;
+                let (tag, r#type, value) = parse_element(&element, pos)?;
+                if r#type == TtlvType::Structure {
+                    write_tag_and_type(&mut dst, tag, r#type);
+                    bookmarks.push(Some(dst.len()));
+                    dst.extend_from_slice(&[0u8; 4]);
+                } else {
+                    write_item(&mut dst, tag, r#type, value, pos)?;
+                    bookmarks.push(None);
+                }
+            }
+            Event::Empty(element) => {
+                root_seen = true;
+                let (tag, r#type, value) = parse_element(&element, pos)?;
+                if r#type == TtlvType::Structure {
+                    write_tag_and_type(&mut dst, tag, r#type);
+                    dst.extend_from_slice(&[0u8; 4]);
+                } else {
+                    write_item(&mut dst, tag, r#type, value, pos)?;
+                }
+            }
+            Event::End(_) => match bookmarks.pop() {
+                Some(Some(len_pos)) => {
+                    let len = (dst.len() - len_pos - 4) as u32;
+                    dst[len_pos..len_pos + 4].copy_from_slice(&len.to_be_bytes());
+                }
+                Some(None) => {}
+                None => return Err(malformed_xml("unexpected end element", pos)),
+            },
+            Event::Text(text) if !text.is_empty() => {
+                return Err(malformed_xml("unexpected text content", pos));
+            }
+            Event::CData(_) => {
+                return Err(malformed_xml("unexpected CDATA content", pos));
+            }
+            Event::Eof => break,
+            _ => {
+                // Ignore the XML declaration, comments, processing instructions, etc.
+            }
+        }
+    }
+
+    if !bookmarks.is_empty() {
+        return Err(malformed_xml("unclosed element", reader.buffer_position()));
+    }
+    if !root_seen {
+        return Err(malformed_xml("no root element", reader.buffer_position()));
+    }
+
+    Ok(dst)
+}
+
+// --- Private implementation details ----------------------------------------------------------------------------------
+
+fn malformed_xml<S: Into<String>>(msg: S, pos: u64) -> Error {
+    Error::pinpoint(ErrorKind::MalformedXml(msg.into()), ErrorLocation::at(ByteOffset(pos)))
+}
+
+fn write_event(writer: &mut Writer<Vec<u8>>, event: Event) -> Result<()> {
+    writer
+        .write_event(event)
+        .map_err(|err| Error::pinpoint(err, ErrorLocation::unknown()))
+}
+
+/// Read the length and value of a primitive TTLV item and render the value in its KMIP XML form.
+fn read_value(cursor: &mut Cursor<&[u8]>, r#type: TtlvType) -> std::result::Result<String, types::Error> {
+    let value = match r#type {
+        TtlvType::Integer => TtlvInteger::read(cursor)?.to_string(),
+        TtlvType::LongInteger => TtlvLongInteger::read(cursor)?.to_string(),
+        TtlvType::BigInteger => hex::encode_upper(TtlvBigInteger::read(cursor)?.deref()),
+        TtlvType::Enumeration => format!("0x{:08X}", *TtlvEnumeration::read(cursor)?),
+        TtlvType::Boolean => TtlvBoolean::read(cursor)?.to_string(),
+        TtlvType::TextString => TtlvTextString::read(cursor)?.0,
+        TtlvType::ByteString => hex::encode_upper(TtlvByteString::read(cursor)?.deref()),
+        TtlvType::DateTime => TtlvDateTime::read(cursor)?.to_string(),
+        TtlvType::Interval => TtlvInterval::read(cursor)?.to_string(),
+        TtlvType::Structure => return Err(types::Error::InvalidStateMachineOperation),
+        TtlvType::Extension(type_byte) => return Err(types::Error::InvalidTtlvType(*type_byte)),
+    };
+    Ok(value)
+}
+
+/// Extract the TTLV tag, type and (for non-Structure types) value from the name and attributes of the given XML
+/// element.
+fn parse_element(element: &BytesStart, pos: u64) -> Result<(TtlvTag, TtlvType, Option<String>)> {
+    let mut tag = None;
+    let mut r#type = None;
+    let mut value = None;
+
+    for attr in element.attributes() {
+        let attr = attr.map_err(|err| malformed_xml(err.to_string(), pos))?;
+        let attr_value = attr
+            .unescape_value()
+            .map_err(|err| malformed_xml(err.to_string(), pos))?;
+        match attr.key.as_ref() {
+            b"tag" => tag = Some(attr_value.into_owned()),
+            b"type" => r#type = Some(attr_value.into_owned()),
+            b"value" => value = Some(attr_value.into_owned()),
+            _ => {
+                // Ignore unknown attributes
+            }
+        }
+    }
+
+    let element_name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+    let tag = match tag {
+        Some(tag) => match TtlvTag::from_str(&tag) {
+            Ok(parsed_tag) => parsed_tag,
+            Err(_) => {
+                return Err(Error::pinpoint(
+                    SerdeError::InvalidTag(tag),
+                    ErrorLocation::at(ByteOffset(pos)),
+                ))
+            }
+        },
+        None if element_name == XML_ELEMENT_NAME => return Err(malformed_xml("missing tag attribute", pos)),
+        None => tag_for_name(&element_name)
+            .ok_or_else(|| malformed_xml(format!("unknown tag name '{}'", element_name), pos))?,
+    };
+
+    // The KMIP XML encoding omits the type of TTLV Structures
+    let r#type = match r#type {
+        Some(type_name) => TtlvType::from_name(&type_name).ok_or_else(|| {
+            let error = ErrorKind::MalformedXml(format!("unknown type '{}'", type_name));
+            pinpoint!(error, ByteOffset(pos), tag)
+        })?,
+        None => TtlvType::Structure,
+    };
+
+    Ok((tag, r#type, value))
+}
+
+fn write_tag_and_type(dst: &mut Vec<u8>, tag: TtlvTag, r#type: TtlvType) {
+    dst.extend_from_slice(&<[u8; 3]>::from(tag));
+    dst.push(u8::from(r#type));
+}
+
+/// Write the binary TTLV form of a primitive item given its KMIP XML form value.
+fn write_item(dst: &mut Vec<u8>, tag: TtlvTag, r#type: TtlvType, value: Option<String>, pos: u64) -> Result<()> {
+    let value = value.ok_or_else(|| {
+        let error = ErrorKind::MalformedXml("missing value attribute".into());
+        pinpoint!(error, ByteOffset(pos), tag, r#type)
+    })?;
+    let invalid_value = || pinpoint!(types::Error::InvalidTtlvValue(r#type), ByteOffset(pos), tag, r#type);
+
+    dst.extend_from_slice(&<[u8; 3]>::from(tag));
+    let res = match r#type {
+        TtlvType::Integer => TtlvInteger(value.parse().map_err(|_| invalid_value())?).write(dst),
+        TtlvType::LongInteger => TtlvLongInteger(value.parse().map_err(|_| invalid_value())?).write(dst),
+        TtlvType::BigInteger => {
+            // The KMIP XML encoding pads Big Integer values to a multiple of 8 bytes, i.e. 16 hexadecimal digits
+            if value.len() % 16 != 0 {
+                return Err(invalid_value());
+            }
+            TtlvBigInteger(hex::decode(&value).map_err(|_| invalid_value())?).write(dst)
+        }
+        TtlvType::Enumeration => {
+            let v = match value.strip_prefix("0x") {
+                Some(hex_value) => u32::from_str_radix(hex_value, 16),
+                None => value.parse(),
+            };
+            TtlvEnumeration(v.map_err(|_| invalid_value())?).write(dst)
+        }
+        TtlvType::Boolean => TtlvBoolean(value.parse().map_err(|_| invalid_value())?).write(dst),
+        TtlvType::TextString => TtlvTextString(value).write(dst),
+        TtlvType::ByteString => TtlvByteString(hex::decode(&value).map_err(|_| invalid_value())?).write(dst),
+        TtlvType::DateTime => TtlvDateTime(value.parse().map_err(|_| invalid_value())?).write(dst),
+        TtlvType::Interval => TtlvInterval(value.parse().map_err(|_| invalid_value())?).write(dst),
+        TtlvType::Structure | TtlvType::Extension(_) => Err(types::Error::InvalidStateMachineOperation),
+    };
+    res.map_err(|err| pinpoint!(err, ByteOffset(pos), tag, r#type))
+}